@@ -1,6 +1,11 @@
 package app.tauri.iap
 
 import android.app.Activity
+import android.content.ActivityNotFoundException
+import android.content.Intent
+import android.content.pm.ApplicationInfo
+import android.net.Uri
+import android.os.Build
 import android.webkit.WebView
 import app.tauri.Logger
 import app.tauri.annotation.Command
@@ -12,9 +17,14 @@ import app.tauri.plugin.Invoke
 import com.android.billingclient.api.*
 import kotlinx.coroutines.CoroutineScope
 import kotlinx.coroutines.Dispatchers
+import kotlinx.coroutines.delay
 import kotlinx.coroutines.launch
 import kotlinx.coroutines.withContext
 import org.json.JSONArray
+import java.text.SimpleDateFormat
+import java.util.Date
+import java.util.Locale
+import java.util.TimeZone
 
 @InvokeArg
 class GetProductsArgs {
@@ -31,6 +41,7 @@ class PurchaseArgs {
     var obfuscatedProfileId: String? = null
     var oldProductId: String? = null
     var subscriptionReplacementMode: Int? = null
+    var quantity: Int? = null
 }
 
 @InvokeArg
@@ -57,18 +68,68 @@ class GetProductStatusArgs {
     var productType: String = "subs" // "subs" or "inapp"
 }
 
+@InvokeArg
+class GetAllProductStatusesArgs {
+    var productType: String = "subs" // "subs" or "inapp"
+}
+
+@InvokeArg
+class IsEligibleForIntroOfferArgs {
+    var productId: String = ""
+}
+
+@InvokeArg
+class CheckIntroductoryOfferEligibilityArgs {
+    var productIds: List<String> = emptyList()
+}
+
+@InvokeArg
+class GetSubscriptionStatusArgs {
+    var productId: String = ""
+}
+
+@InvokeArg
+class CurrentEntitlementArgs {
+    var productId: String = ""
+}
+
+@InvokeArg
+class GetReceiptArgs {
+    var transactionId: String = ""
+}
+
+// Keep in sync with IapConfig in src/config.rs
+@InvokeArg
+class IapConfigArgs {
+    var autoAcknowledge: Boolean = false
+    var autoAcknowledgeDelayMs: Long = 0
+}
+
 @TauriPlugin
 class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpdatedListener, BillingClientStateListener {
     private lateinit var billingClient: BillingClient
     private val coroutineScope = CoroutineScope(Dispatchers.Main)
     private var pendingPurchaseInvoke: Invoke? = null
+    private var pendingPurchaseProductId: String? = null
+    // Offer picked for the pending purchase; Play's Purchase doesn't report it
+    private var pendingPurchaseOffer: ProductDetails.SubscriptionOfferDetails? = null
     private val TAG = "IapPlugin"
+    private var config = IapConfigArgs()
     
     // Keep in sync with PurchaseState in guest-js/index.ts
     companion object {
         const val PURCHASE_STATE_PURCHASED = 0
         const val PURCHASE_STATE_CANCELED = 1
         const val PURCHASE_STATE_PENDING = 2
+        const val PURCHASE_STATE_FAILED = 3
+        const val PURCHASE_STATE_REFUNDED = 4
+        const val PURCHASE_STATE_DEFERRED = 5
+
+        const val PLAY_SUBSCRIPTIONS_URL = "https://play.google.com/store/account/subscriptions"
+        const val PLAY_STORE_PACKAGE = "com.android.vending"
+
+        // Offer tags that name an offer type; keep in sync with Purchase.offer_type in src/models.rs
+        val OFFER_TYPE_TAGS = setOf("introductory", "promotional", "code", "winBack")
 
         fun translatePurchaseState(state: Int): Int = when(state) {
             Purchase.PurchaseState.PURCHASED -> PURCHASE_STATE_PURCHASED
@@ -80,10 +141,47 @@ class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpda
             "inapp" -> BillingClient.ProductType.INAPP
             else -> BillingClient.ProductType.SUBS
         }
+
+        // Keep in sync with SubscriptionPeriod in src/models.rs
+        fun parseSubscriptionPeriod(billingPeriod: String): Pair<String, Int>? {
+            val match = Regex("P(\\d+)([DWMY])").matchEntire(billingPeriod) ?: return null
+            val value = match.groupValues[1].toIntOrNull() ?: return null
+            val unit = when (match.groupValues[2]) {
+                "D" -> "day"
+                "W" -> "week"
+                "M" -> "month"
+                else -> "year"
+            }
+            return Pair(unit, value)
+        }
+
+        // Keep in sync with PaymentMode in src/models.rs
+        fun paymentModeFor(recurrenceMode: Int, priceAmountMicros: Long): String = when {
+            priceAmountMicros == 0L -> "freeTrial"
+            recurrenceMode == ProductDetails.RecurrenceMode.NON_RECURRING -> "payUpFront"
+            else -> "payAsYouGo"
+        }
+
+        // Keep in sync with IapErrorCode in src/error.rs
+        fun billingErrorName(responseCode: Int): String = when (responseCode) {
+            BillingClient.BillingResponseCode.USER_CANCELED -> "userCancelled"
+            BillingClient.BillingResponseCode.BILLING_UNAVAILABLE -> "billingUnavailable"
+            BillingClient.BillingResponseCode.SERVICE_UNAVAILABLE -> "serviceUnavailable"
+            BillingClient.BillingResponseCode.SERVICE_DISCONNECTED -> "serviceDisconnected"
+            BillingClient.BillingResponseCode.FEATURE_NOT_SUPPORTED -> "featureNotSupported"
+            BillingClient.BillingResponseCode.ITEM_UNAVAILABLE -> "itemUnavailable"
+            BillingClient.BillingResponseCode.ITEM_ALREADY_OWNED -> "itemAlreadyOwned"
+            BillingClient.BillingResponseCode.ITEM_NOT_OWNED -> "itemNotOwned"
+            BillingClient.BillingResponseCode.DEVELOPER_ERROR -> "developerError"
+            BillingClient.BillingResponseCode.NETWORK_ERROR -> "networkError"
+            BillingClient.BillingResponseCode.ERROR -> "unknown"
+            else -> responseCode.toString()
+        }
     }
     
     override fun load(webView: WebView) {
         super.load(webView)
+        config = getConfig(IapConfigArgs::class.java)
         initializeBillingClient()
     }
     
@@ -158,6 +256,56 @@ class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpda
                                     }
                                 }
                                 put("subscriptionOfferDetails", JSONArray(offers))
+
+                                // Regular price and billing period = the infinitely recurring phase of the base plan
+                                val regularPhase = subscriptionOfferDetails
+                                    .flatMap { it.pricingPhases.pricingPhaseList }
+                                    .firstOrNull { it.recurrenceMode == ProductDetails.RecurrenceMode.INFINITE_RECURRING }
+                                regularPhase?.let {
+                                    put("formattedPrice", it.formattedPrice)
+                                    put("priceCurrencyCode", it.priceCurrencyCode)
+                                    put("priceAmountMicros", it.priceAmountMicros)
+                                    put("priceLocale", Locale.getDefault().toString())
+                                }
+                                regularPhase
+                                    ?.let { parseSubscriptionPeriod(it.billingPeriod) }
+                                    ?.let { (unit, value) ->
+                                        put("subscriptionPeriod", JSObject().apply {
+                                            put("unit", unit)
+                                            put("value", value)
+                                        })
+                                    }
+
+                                // Play has no introductory/promotional split: the first
+                                // developer-defined offer's discounted phase is reported as
+                                // introductory, any further ones as promotional.
+                                val discountOffers = subscriptionOfferDetails
+                                    .filter { it.offerId != null }
+                                    .mapNotNull { offer ->
+                                        val phase = offer.pricingPhases.pricingPhaseList.firstOrNull {
+                                            it.recurrenceMode != ProductDetails.RecurrenceMode.INFINITE_RECURRING
+                                        } ?: return@mapNotNull null
+                                        val (unit, value) = parseSubscriptionPeriod(phase.billingPeriod)
+                                            ?: return@mapNotNull null
+                                        JSObject().apply {
+                                            put("id", offer.offerId)
+                                            put("price", phase.formattedPrice)
+                                            put("priceLocale", phase.priceCurrencyCode)
+                                            put("period", JSObject().apply {
+                                                put("unit", unit)
+                                                put("value", value)
+                                            })
+                                            put("periodCount", maxOf(phase.billingCycleCount, 1))
+                                            put("paymentMode", paymentModeFor(phase.recurrenceMode, phase.priceAmountMicros))
+                                        }
+                                    }
+                                discountOffers.firstOrNull()?.let {
+                                    it.put("offerType", "introductory")
+                                    put("introductoryOffer", it)
+                                }
+                                put("promotionalOffers", JSONArray(discountOffers.drop(1).onEach {
+                                    it.put("offerType", "promotional")
+                                }))
                             }
                         } else {
                             // For one-time products
@@ -166,6 +314,8 @@ class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpda
                                 put("formattedPrice", oneTimePurchaseOfferDetails.formattedPrice)
                                 put("priceCurrencyCode", oneTimePurchaseOfferDetails.priceCurrencyCode)
                                 put("priceAmountMicros", oneTimePurchaseOfferDetails.priceAmountMicros)
+                                // Play formats prices for the device locale
+                                put("priceLocale", Locale.getDefault().toString())
                             }
                         }
                     }
@@ -173,7 +323,10 @@ class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpda
                 products.put("products", JSONArray(productsArray))
                 invoke.resolve(products)
             } else {
-                invoke.reject("Failed to fetch products: ${billingResult.debugMessage}")
+                invoke.reject(
+                    "Failed to fetch products: ${billingResult.debugMessage}",
+                    billingResult.responseCode.toString()
+                )
             }
         }
     }
@@ -181,6 +334,12 @@ class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpda
     @Command
     fun purchase(invoke: Invoke) {
         val args = invoke.parseArgs(PurchaseArgs::class.java)
+
+        if ((args.quantity ?: 1) > 1) {
+            // Play picks the quantity in its own purchase dialog for multi-quantity products
+            invoke.reject("Purchase quantities above 1 are not supported on Android", "notSupported")
+            return
+        }
         
         if (!billingClient.isReady) {
             invoke.reject("Billing client not ready")
@@ -188,6 +347,8 @@ class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpda
         }
         
         pendingPurchaseInvoke = invoke
+        pendingPurchaseProductId = args.productId
+        pendingPurchaseOffer = null
         
         val productType = translateProductType(args.productType)
         
@@ -220,6 +381,8 @@ class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpda
                     val offerToken = args.offerToken
                         ?: productDetails.subscriptionOfferDetails?.firstOrNull()?.offerToken
                     offerToken?.let { productDetailsParamsBuilder.setOfferToken(it) }
+                    pendingPurchaseOffer = productDetails.subscriptionOfferDetails
+                        ?.firstOrNull { it.offerToken == offerToken }
                 }
 
                 args.oldProductId?.let { oldId ->
@@ -248,12 +411,20 @@ class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpda
                 
                 val billingResult = billingClient.launchBillingFlow(activity, billingFlowParams)
                 
-                if (billingResult.responseCode != BillingClient.BillingResponseCode.OK) {
+                if (billingResult.responseCode == BillingClient.BillingResponseCode.OK) {
+                    emitTransactionEvent(args.productId, JSObject().apply { put("type", "purchasing") })
+                } else {
                     pendingPurchaseInvoke = null
-                    invoke.reject("Failed to launch billing flow: ${billingResult.debugMessage}")
+                    pendingPurchaseProductId = null
+                    emitFailed(args.productId, billingResult.responseCode)
+                    invoke.reject(
+                        "Failed to launch billing flow: ${billingResult.debugMessage}",
+                        billingResult.responseCode.toString()
+                    )
                 }
             } else {
                 pendingPurchaseInvoke = null
+                pendingPurchaseProductId = null
                 invoke.reject("Product not found")
             }
         }
@@ -276,34 +447,186 @@ class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpda
         
         billingClient.queryPurchasesAsync(params) { billingResult, purchases ->
             if (billingResult.responseCode == BillingClient.BillingResponseCode.OK) {
-                val purchasesArray = purchases.map { purchase ->
-                    JSObject().apply {
-                        put("orderId", purchase.orderId)
-                        put("packageName", purchase.packageName)
-                        put("productId", purchase.products.firstOrNull() ?: "")
-                        put("purchaseTime", purchase.purchaseTime)
-                        put("purchaseToken", purchase.purchaseToken)
-                        put("purchaseState", translatePurchaseState(purchase.purchaseState))
-                        put("isAutoRenewing", purchase.isAutoRenewing)
-                        put("isAcknowledged", purchase.isAcknowledged)
-                        put("originalJson", purchase.originalJson)
-                        put("signature", purchase.signature)
-                    }
+                val purchasesArray = purchases.map { purchaseToJSObject(it) }
+                purchases.forEach { purchase ->
+                    emitTransactionEvent(
+                        purchase.products.firstOrNull() ?: "",
+                        JSObject().apply {
+                            put("type", "restored")
+                            put("originalTransactionId", purchase.orderId ?: purchase.purchaseToken)
+                        }
+                    )
                 }
 
                 val result = JSObject()
                 result.put("purchases", JSONArray(purchasesArray))
                 invoke.resolve(result)
             } else {
-                invoke.reject("Failed to restore purchases: ${billingResult.debugMessage}")
+                invoke.reject(
+                    "Failed to restore purchases: ${billingResult.debugMessage}",
+                    billingResult.responseCode.toString()
+                )
             }
         }
     }
     
+    @Command
+    fun getCurrentEntitlements(invoke: Invoke) {
+        if (!billingClient.isReady) {
+            invoke.reject("Billing client not ready")
+            return
+        }
+
+        // Play Billing serves queryPurchasesAsync from its local cache, so like
+        // StoreKit's currentEntitlements this works offline without a sign-in prompt.
+        queryOwnedPurchases(BillingClient.ProductType.INAPP) { inappResult, inappPurchases ->
+            if (inappResult.responseCode != BillingClient.BillingResponseCode.OK) {
+                invoke.reject(
+                    "Failed to get current entitlements: ${inappResult.debugMessage}",
+                    inappResult.responseCode.toString()
+                )
+                return@queryOwnedPurchases
+            }
+            queryOwnedPurchases(BillingClient.ProductType.SUBS) { subsResult, subsPurchases ->
+                if (subsResult.responseCode != BillingClient.BillingResponseCode.OK) {
+                    invoke.reject(
+                        "Failed to get current entitlements: ${subsResult.debugMessage}",
+                        subsResult.responseCode.toString()
+                    )
+                    return@queryOwnedPurchases
+                }
+                val purchasesArray = (inappPurchases + subsPurchases)
+                    .filter { it.purchaseState == Purchase.PurchaseState.PURCHASED }
+                    .map { purchaseToJSObject(it) }
+
+                val result = JSObject()
+                result.put("purchases", JSONArray(purchasesArray))
+                invoke.resolve(result)
+            }
+        }
+    }
+
+    // Keep in sync with CurrentEntitlementResponse in src/models.rs
+    @Command
+    fun currentEntitlement(invoke: Invoke) {
+        val args = invoke.parseArgs(CurrentEntitlementArgs::class.java)
+
+        if (!billingClient.isReady) {
+            invoke.reject("Billing client not ready")
+            return
+        }
+
+        queryOwnedPurchases(BillingClient.ProductType.INAPP) { inappResult, inappPurchases ->
+            if (inappResult.responseCode != BillingClient.BillingResponseCode.OK) {
+                invoke.reject(
+                    "Failed to get current entitlement: ${inappResult.debugMessage}",
+                    inappResult.responseCode.toString()
+                )
+                return@queryOwnedPurchases
+            }
+            queryOwnedPurchases(BillingClient.ProductType.SUBS) { subsResult, subsPurchases ->
+                if (subsResult.responseCode != BillingClient.BillingResponseCode.OK) {
+                    invoke.reject(
+                        "Failed to get current entitlement: ${subsResult.debugMessage}",
+                        subsResult.responseCode.toString()
+                    )
+                    return@queryOwnedPurchases
+                }
+                val purchase = (inappPurchases + subsPurchases).firstOrNull {
+                    it.purchaseState == Purchase.PurchaseState.PURCHASED &&
+                        it.products.contains(args.productId)
+                }
+
+                val result = JSObject()
+                if (purchase != null) {
+                    result.put("purchase", purchaseToJSObject(purchase))
+                }
+                invoke.resolve(result)
+            }
+        }
+    }
+
+    @Command
+    fun getUnfinishedTransactions(invoke: Invoke) {
+        if (!billingClient.isReady) {
+            invoke.reject("Billing client not ready")
+            return
+        }
+
+        // Play refunds purchases left unacknowledged for three days, so these
+        // need handling before anything else on startup.
+        queryOwnedPurchases(BillingClient.ProductType.INAPP) { inappResult, inappPurchases ->
+            if (inappResult.responseCode != BillingClient.BillingResponseCode.OK) {
+                invoke.reject(
+                    "Failed to get unfinished transactions: ${inappResult.debugMessage}",
+                    inappResult.responseCode.toString()
+                )
+                return@queryOwnedPurchases
+            }
+            queryOwnedPurchases(BillingClient.ProductType.SUBS) { subsResult, subsPurchases ->
+                if (subsResult.responseCode != BillingClient.BillingResponseCode.OK) {
+                    invoke.reject(
+                        "Failed to get unfinished transactions: ${subsResult.debugMessage}",
+                        subsResult.responseCode.toString()
+                    )
+                    return@queryOwnedPurchases
+                }
+                val purchasesArray = (inappPurchases + subsPurchases)
+                    .filter { it.purchaseState == Purchase.PurchaseState.PURCHASED && !it.isAcknowledged }
+                    .map { purchaseToJSObject(it) }
+
+                val result = JSObject()
+                result.put("purchases", JSONArray(purchasesArray))
+                invoke.resolve(result)
+            }
+        }
+    }
+
+    private fun queryOwnedPurchases(productType: String, callback: (BillingResult, List<Purchase>) -> Unit) {
+        val params = QueryPurchasesParams.newBuilder()
+            .setProductType(productType)
+            .build()
+        billingClient.queryPurchasesAsync(params) { billingResult, purchases ->
+            callback(billingResult, purchases)
+        }
+    }
+
+    // Keep in sync with Timestamp in src/timestamp.rs: RFC 3339 in UTC with
+    // milliseconds, e.g. "2024-01-31T12:00:00.000Z". java.time needs API 26.
+    private fun rfc3339(epochMillis: Long): String {
+        val format = SimpleDateFormat("yyyy-MM-dd'T'HH:mm:ss.SSS'Z'", Locale.US)
+        format.timeZone = TimeZone.getTimeZone("UTC")
+        return format.format(Date(epochMillis))
+    }
+
+    private fun purchaseToJSObject(purchase: Purchase): JSObject = JSObject().apply {
+        put("orderId", purchase.orderId)
+        put("packageName", purchase.packageName)
+        put("productId", purchase.products.firstOrNull() ?: "")
+        put("purchaseTime", rfc3339(purchase.purchaseTime))
+        put("purchaseToken", purchase.purchaseToken)
+        put("purchaseState", translatePurchaseState(purchase.purchaseState))
+        put("isAutoRenewing", purchase.isAutoRenewing)
+        put("isAcknowledged", purchase.isAcknowledged)
+        put("originalJson", purchase.originalJson)
+        put("signature", purchase.signature)
+        // No "environment": Play Billing doesn't expose whether the buyer is a
+        // license tester, so test purchases look like production ones here.
+        // No "expirationDate"/"nextRenewalDate" either: subscription expiry is
+        // only available server-side through the Play Developer API.
+    }
+
     @Command
     fun getPurchaseHistory(invoke: Invoke) {
         invoke.reject("Purchase history is not supported")
     }
+
+    @Command
+    fun getPurchaseHistoryFiltered(invoke: Invoke) {
+        // queryPurchaseHistoryAsync was removed in Play Billing 8; history now
+        // lives only in the Play Developer API on the server
+        invoke.reject("Purchase history is not supported", "notSupported")
+    }
     
     @Command
     fun acknowledgePurchase(invoke: Invoke) {
@@ -327,7 +650,321 @@ class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpda
             if (billingResult.responseCode == BillingClient.BillingResponseCode.OK) {
                 invoke.resolve()
             } else {
-                invoke.reject("Failed to acknowledge purchase: ${billingResult.debugMessage}")
+                invoke.reject(
+                    "Failed to acknowledge purchase: ${billingResult.debugMessage}",
+                    billingResult.responseCode.toString()
+                )
+            }
+        }
+    }
+
+    @Command
+    fun finishTransaction(invoke: Invoke) {
+        // Google Play has no separate finish step; use acknowledgePurchase or consumePurchase
+        invoke.reject("finishTransaction is only available on iOS and macOS", "notSupported")
+    }
+
+    @Command
+    fun getAppTransaction(invoke: Invoke) {
+        // AppTransaction is StoreKit-only; Play has no original-app-purchase record
+        invoke.reject("getAppTransaction is only available on iOS and macOS", "notSupported")
+    }
+
+    @Command
+    fun getSubscriptionStatus(invoke: Invoke) {
+        val args = invoke.parseArgs(GetSubscriptionStatusArgs::class.java)
+
+        if (!billingClient.isReady) {
+            invoke.reject("Billing client not ready")
+            return
+        }
+
+        val params = QueryPurchasesParams.newBuilder()
+            .setProductType(BillingClient.ProductType.SUBS)
+            .build()
+
+        // queryPurchasesAsync only returns subscriptions the user currently has
+        // access to (including Play's grace period). Billing retry (account
+        // hold), expiry and revocation are only visible to the server-side
+        // Developer API, so a lapsed subscription yields no status at all.
+        billingClient.queryPurchasesAsync(params) { billingResult, purchases ->
+            if (billingResult.responseCode != BillingClient.BillingResponseCode.OK) {
+                invoke.reject(
+                    "Failed to query subscriptions: ${billingResult.debugMessage}",
+                    billingResult.responseCode.toString()
+                )
+                return@queryPurchasesAsync
+            }
+
+            // Keep in sync with SubscriptionStatus in src/models.rs
+            val statuses = purchases
+                .filter {
+                    it.products.contains(args.productId) &&
+                        it.purchaseState == Purchase.PurchaseState.PURCHASED
+                }
+                .map { purchase ->
+                    JSObject().apply {
+                        put("productId", args.productId)
+                        put("state", "subscribed")
+                        put("transactionId", purchase.orderId ?: purchase.purchaseToken)
+                        put("originalTransactionId", purchase.purchaseToken)
+                        put("isFamilyShared", false)
+                        put("willAutoRenew", purchase.isAutoRenewing)
+                    }
+                }
+            invoke.resolve(JSObject().apply { put("statuses", JSONArray(statuses)) })
+        }
+    }
+
+    @Command
+    fun showManageSubscriptions(invoke: Invoke) {
+        // Play has no in-app sheet; the deep link opens the Play Store's
+        // subscription center (or the browser without the Play Store)
+        val intent = Intent(Intent.ACTION_VIEW, Uri.parse(PLAY_SUBSCRIPTIONS_URL))
+        try {
+            activity.startActivity(intent)
+            invoke.resolve()
+        } catch (e: ActivityNotFoundException) {
+            invoke.reject("No app can open the subscription center", "notSupported")
+        }
+    }
+
+    @Command
+    fun presentOfferCodeRedemptionSheet(invoke: Invoke) {
+        // Play promo codes are redeemed in the Play Store or the purchase flow itself
+        invoke.reject("presentOfferCodeRedemptionSheet is only available on iOS and macOS", "notSupported")
+    }
+
+    // Play's alternative billing and external offers programs use separate APIs
+    @Command
+    fun canOpenExternalPurchaseLink(invoke: Invoke) {
+        invoke.reject("canOpenExternalPurchaseLink is only available on iOS and macOS", "notSupported")
+    }
+
+    @Command
+    fun openExternalPurchaseLink(invoke: Invoke) {
+        invoke.reject("openExternalPurchaseLink is only available on iOS and macOS", "notSupported")
+    }
+
+    @Command
+    fun displayPendingStoreMessages(invoke: Invoke) {
+        invoke.reject("displayPendingStoreMessages is only available on iOS", "notSupported")
+    }
+
+    @Command
+    fun refreshReceipt(invoke: Invoke) {
+        invoke.reject("refreshReceipt is only available on iOS and macOS", "notSupported")
+    }
+
+    @Command
+    fun getAppReceipt(invoke: Invoke) {
+        invoke.reject("getAppReceipt is only available on iOS and macOS", "notSupported")
+    }
+
+    // Play Billing doesn't report refunds to the client; use the Voided
+    // Purchases API from a server instead
+    @Command
+    fun getRevokedPurchases(invoke: Invoke) {
+        invoke.reject("getRevokedPurchases is only available on iOS and macOS", "notSupported")
+    }
+
+    @Command
+    fun finishUnfinishedTransactions(invoke: Invoke) {
+        invoke.reject("finishUnfinishedTransactions is only available on iOS and macOS", "notSupported")
+    }
+
+    // Keep in sync with CanMakePaymentsResponse in src/models.rs
+    @Command
+    fun canMakePayments(invoke: Invoke) {
+        val reason = when {
+            !billingClient.isReady -> "billingUnavailable"
+            // Every purchase goes through queryProductDetailsAsync
+            billingClient.isFeatureSupported(BillingClient.FeatureType.PRODUCT_DETAILS).responseCode !=
+                BillingClient.BillingResponseCode.OK -> "billingUnsupported"
+            else -> null
+        }
+        invoke.resolve(JSObject().apply {
+            put("canMakePayments", reason == null)
+            reason?.let { put("reason", it) }
+        })
+    }
+
+    /// Keep in sync with StorefrontInfo in src/models.rs
+    @Command
+    fun getStorefront(invoke: Invoke) {
+        if (!billingClient.isReady) {
+            invoke.reject("Billing client not ready")
+            return
+        }
+
+        val params = GetBillingConfigParams.newBuilder().build()
+        billingClient.getBillingConfigAsync(params) { billingResult, billingConfig ->
+            if (billingResult.responseCode == BillingClient.BillingResponseCode.OK && billingConfig != null) {
+                // Play has no storefront id; the country code identifies it
+                invoke.resolve(JSObject().apply {
+                    put("id", billingConfig.countryCode)
+                    put("countryCode", billingConfig.countryCode)
+                })
+            } else {
+                invoke.reject(
+                    "Failed to get billing config: ${billingResult.debugMessage}",
+                    billingResult.responseCode.toString()
+                )
+            }
+        }
+    }
+
+    @Command
+    fun beginRefundRequest(invoke: Invoke) {
+        // Play refunds are requested on play.google.com or issued by the developer
+        invoke.reject("beginRefundRequest is only available on iOS and macOS", "notSupported")
+    }
+
+    @Command
+    fun getWinBackOffers(invoke: Invoke) {
+        // Play models win-back as developer-determined offers in subscriptionOfferDetails
+        invoke.reject("getWinBackOffers is only available on iOS and macOS", "notSupported")
+    }
+
+    /// Keep in sync with IsEligibleForIntroOfferResponse in src/models.rs
+    @Command
+    fun isEligibleForIntroOffer(invoke: Invoke) {
+        val args = invoke.parseArgs(IsEligibleForIntroOfferArgs::class.java)
+
+        if (!billingClient.isReady) {
+            invoke.reject("Billing client not ready")
+            return
+        }
+
+        val params = QueryProductDetailsParams.newBuilder()
+            .setProductList(listOf(
+                QueryProductDetailsParams.Product.newBuilder()
+                    .setProductId(args.productId)
+                    .setProductType(BillingClient.ProductType.SUBS)
+                    .build()
+            ))
+            .build()
+
+        billingClient.queryProductDetailsAsync(params) { billingResult: BillingResult, productDetailsResult: QueryProductDetailsResult ->
+            if (billingResult.responseCode != BillingClient.BillingResponseCode.OK) {
+                invoke.reject(
+                    "Failed to query product details: ${billingResult.debugMessage}",
+                    billingResult.responseCode.toString()
+                )
+                return@queryProductDetailsAsync
+            }
+            val productDetails = productDetailsResult.productDetailsList.firstOrNull()
+            if (productDetails == null) {
+                invoke.reject("Product not found", "productNotFound")
+                return@queryProductDetailsAsync
+            }
+
+            // Its absence can also mean no trial is configured
+            invoke.resolve(JSObject().apply {
+                put("eligibility", if (hasFreeTrial(productDetails)) "eligible" else "unknown")
+            })
+        }
+    }
+
+    /// Keep in sync with CheckIntroductoryOfferEligibilityResponse in src/models.rs
+    @Command
+    fun checkIntroductoryOfferEligibility(invoke: Invoke) {
+        val args = invoke.parseArgs(CheckIntroductoryOfferEligibilityArgs::class.java)
+
+        if (!billingClient.isReady) {
+            invoke.reject("Billing client not ready")
+            return
+        }
+        if (args.productIds.isEmpty()) {
+            invoke.resolve(JSObject().apply { put("eligibility", JSObject()) })
+            return
+        }
+
+        val params = QueryProductDetailsParams.newBuilder()
+            .setProductList(args.productIds.map { productId ->
+                QueryProductDetailsParams.Product.newBuilder()
+                    .setProductId(productId)
+                    .setProductType(BillingClient.ProductType.SUBS)
+                    .build()
+            })
+            .build()
+
+        billingClient.queryProductDetailsAsync(params) { billingResult: BillingResult, productDetailsResult: QueryProductDetailsResult ->
+            if (billingResult.responseCode != BillingClient.BillingResponseCode.OK) {
+                invoke.reject(
+                    "Failed to query product details: ${billingResult.debugMessage}",
+                    billingResult.responseCode.toString()
+                )
+                return@queryProductDetailsAsync
+            }
+            val eligibility = JSObject()
+            for (productDetails in productDetailsResult.productDetailsList) {
+                eligibility.put(productDetails.productId, hasFreeTrial(productDetails))
+            }
+            invoke.resolve(JSObject().apply { put("eligibility", eligibility) })
+        }
+    }
+
+    // Play only lists offers the account is eligible for, so a free trial phase
+    // means eligible
+    private fun hasFreeTrial(productDetails: ProductDetails): Boolean =
+        productDetails.subscriptionOfferDetails.orEmpty()
+            .filter { it.offerId != null }
+            .any { offer -> offer.pricingPhases.pricingPhaseList.any { it.priceAmountMicros == 0L } }
+
+    /// Keep in sync with IapEnvironment in src/models.rs
+    @Command
+    fun getEnvironment(invoke: Invoke) {
+        // Play has no sandbox: license testers buy on the production store, so only
+        // debuggable builds and installs from outside the Play Store are detectable
+        val isDebuggable = (activity.applicationInfo.flags and ApplicationInfo.FLAG_DEBUGGABLE) != 0
+        val installer = runCatching {
+            if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.R) {
+                activity.packageManager.getInstallSourceInfo(activity.packageName).installingPackageName
+            } else {
+                @Suppress("DEPRECATION")
+                activity.packageManager.getInstallerPackageName(activity.packageName)
+            }
+        }.getOrNull()
+        val environment = if (isDebuggable || installer != PLAY_STORE_PACKAGE) "androidTest" else "production"
+        invoke.resolve(JSObject().apply {
+            put("environment", environment)
+        })
+    }
+
+    /// Keep in sync with PurchaseReceipt in src/models.rs
+    @Command
+    fun getReceipt(invoke: Invoke) {
+        val transactionId = invoke.parseArgs(GetReceiptArgs::class.java).transactionId
+
+        if (!billingClient.isReady) {
+            invoke.reject("Billing client not ready")
+            return
+        }
+
+        // Only owned purchases are queryable; consumed ones are gone from the cache
+        queryOwnedPurchases(BillingClient.ProductType.INAPP) { inappResult, inappPurchases ->
+            queryOwnedPurchases(BillingClient.ProductType.SUBS) { subsResult, subsPurchases ->
+                val failed = listOf(inappResult, subsResult)
+                    .firstOrNull { it.responseCode != BillingClient.BillingResponseCode.OK }
+                if (failed != null) {
+                    invoke.reject("Failed to get receipt: ${failed.debugMessage}", failed.responseCode.toString())
+                    return@queryOwnedPurchases
+                }
+                val purchase = (inappPurchases + subsPurchases).firstOrNull {
+                    it.orderId == transactionId || it.purchaseToken == transactionId
+                }
+                if (purchase == null) {
+                    invoke.reject("No owned purchase with id $transactionId", "transactionNotFound")
+                    return@queryOwnedPurchases
+                }
+                invoke.resolve(JSObject().apply {
+                    put("platform", "android")
+                    put("receiptData", purchase.originalJson)
+                    put("purchaseToken", purchase.purchaseToken)
+                    put("productId", purchase.products.firstOrNull() ?: "")
+                    put("transactionId", transactionId)
+                })
             }
         }
     }
@@ -354,7 +991,10 @@ class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpda
             if (billingResult.responseCode == BillingClient.BillingResponseCode.OK) {
                 invoke.resolve()
             } else {
-                invoke.reject("Failed to consume purchase: ${billingResult.debugMessage}")
+                invoke.reject(
+                    "Failed to consume purchase: ${billingResult.debugMessage}",
+                    billingResult.responseCode.toString()
+                )
             }
         }
     }
@@ -380,31 +1020,82 @@ class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpda
                     purchase.products.contains(args.productId)
                 }
                 
-                val statusResult = JSObject().apply {
-                    put("productId", args.productId)
-                    
-                    if (productPurchase != null) {
-                        put("isOwned", true)
-                        put("purchaseState", translatePurchaseState(productPurchase.purchaseState))
-                        put("purchaseTime", productPurchase.purchaseTime)
-                        put("isAutoRenewing", productPurchase.isAutoRenewing)
-                        put("isAcknowledged", productPurchase.isAcknowledged)
-                        put("purchaseToken", productPurchase.purchaseToken)
-                        
-                        // Note: Android doesn't provide expiration time directly for subscriptions
-                        // It would require additional Google Play Developer API calls
-                    } else {
-                        put("isOwned", false)
-                    }
-                }
+                val statusResult = productStatusToJSObject(args.productId, productPurchase, productType)
                 
                 invoke.resolve(statusResult)
             } else {
-                invoke.reject("Failed to get product status: ${billingResult.debugMessage}")
+                invoke.reject(
+                    "Failed to get product status: ${billingResult.debugMessage}",
+                    billingResult.responseCode.toString()
+                )
             }
         }
     }
     
+    @Command
+    fun getAllProductStatuses(invoke: Invoke) {
+        val args = invoke.parseArgs(GetAllProductStatusesArgs::class.java)
+
+        if (!billingClient.isReady) {
+            invoke.reject("Billing client not ready")
+            return
+        }
+
+        val productType = translateProductType(args.productType)
+
+        // One query covers every owned product of this type, instead of a
+        // getProductStatus round-trip per product.
+        queryOwnedPurchases(productType) { billingResult, purchases ->
+            if (billingResult.responseCode != BillingClient.BillingResponseCode.OK) {
+                invoke.reject(
+                    "Failed to get product statuses: ${billingResult.debugMessage}",
+                    billingResult.responseCode.toString()
+                )
+                return@queryOwnedPurchases
+            }
+            val statuses = purchases.flatMap { purchase ->
+                purchase.products.map { productId ->
+                    productStatusToJSObject(productId, purchase, productType)
+                }
+            }
+
+            val result = JSObject()
+            result.put("statuses", JSONArray(statuses))
+            invoke.resolve(result)
+        }
+    }
+
+    // Keep in sync with ProductStatus in src/models.rs
+    private fun productStatusToJSObject(productId: String, productPurchase: Purchase?, productType: String): JSObject {
+        return JSObject().apply {
+            put("productId", productId)
+
+            if (productPurchase != null) {
+                put("isOwned", true)
+                put("purchaseState", translatePurchaseState(productPurchase.purchaseState))
+                put("purchaseTime", rfc3339(productPurchase.purchaseTime))
+                put("isAutoRenewing", productPurchase.isAutoRenewing)
+                put("isAcknowledged", productPurchase.isAcknowledged)
+                put("purchaseToken", productPurchase.purchaseToken)
+                // Play only returns active purchases, so this is never
+                // an expired or refunded one
+                put("latestTransaction", purchaseToJSObject(productPurchase))
+
+                // Keep in sync with SubscriptionRenewalInfo in src/models.rs.
+                // Expiration, grace period and billing retry state require
+                // the Google Play Developer API.
+                if (productType == BillingClient.ProductType.SUBS) {
+                    put("renewalInfo", JSObject().apply {
+                        put("autoRenewEnabled", productPurchase.isAutoRenewing)
+                        put("billingRetryPeriod", false)
+                    })
+                }
+            } else {
+                put("isOwned", false)
+            }
+        }
+    }
+
     override fun onPurchasesUpdated(billingResult: BillingResult, purchases: List<Purchase>?) {
         when (billingResult.responseCode) {
             BillingClient.BillingResponseCode.OK -> {
@@ -415,39 +1106,125 @@ class IapPlugin(private val activity: Activity): Plugin(activity), PurchasesUpda
                 }
             }
             BillingClient.BillingResponseCode.USER_CANCELED -> {
-                pendingPurchaseInvoke?.reject("Purchase cancelled by user")
+                pendingPurchaseProductId?.let { emitFailed(it, billingResult.responseCode) }
+                pendingPurchaseInvoke?.reject(
+                    "Purchase cancelled by user",
+                    billingResult.responseCode.toString()
+                )
                 pendingPurchaseInvoke = null
+                pendingPurchaseProductId = null
             }
             else -> {
-                pendingPurchaseInvoke?.reject("Purchase failed: ${billingResult.debugMessage}")
+                pendingPurchaseProductId?.let { emitFailed(it, billingResult.responseCode) }
+                pendingPurchaseInvoke?.reject(
+                    "Purchase failed: ${billingResult.debugMessage}",
+                    billingResult.responseCode.toString()
+                )
                 pendingPurchaseInvoke = null
+                pendingPurchaseProductId = null
             }
         }
     }
     
     private fun handlePurchase(purchase: Purchase) {
+        val productId = purchase.products.firstOrNull() ?: ""
+        if (purchase.purchaseState == Purchase.PurchaseState.PENDING) {
+            // Completes later (e.g. cash payment); Play redelivers it via onPurchasesUpdated
+            emitTransactionEvent(productId, JSObject().apply { put("type", "deferred") })
+        }
         if (purchase.purchaseState == Purchase.PurchaseState.PURCHASED) {
+            emitTransactionEvent(
+                productId,
+                JSObject().apply {
+                    put("type", "purchased")
+                    put("transactionId", purchase.orderId ?: purchase.purchaseToken)
+                }
+            )
+
             val purchaseData = JSObject().apply {
                 put("orderId", purchase.orderId)
                 put("packageName", purchase.packageName)
                 put("productId", purchase.products.firstOrNull() ?: "")
-                put("purchaseTime", purchase.purchaseTime)
+                put("purchaseTime", rfc3339(purchase.purchaseTime))
                 put("purchaseToken", purchase.purchaseToken)
                 put("purchaseState", translatePurchaseState(purchase.purchaseState))
                 put("isAutoRenewing", purchase.isAutoRenewing)
                 put("isAcknowledged", purchase.isAcknowledged)
                 put("originalJson", purchase.originalJson)
                 put("signature", purchase.signature)
+                if (productId == pendingPurchaseProductId) {
+                    pendingPurchaseOffer?.let { putOfferFields(this, it) }
+                }
             }
 
             pendingPurchaseInvoke?.resolve(purchaseData)
             pendingPurchaseInvoke = null
+            pendingPurchaseProductId = null
+            pendingPurchaseOffer = null
             
             // Emit event for purchase state change
             trigger("purchaseUpdated", purchaseData)
+
+            if (config.autoAcknowledge && !purchase.isAcknowledged) {
+                autoAcknowledge(productId, purchase.purchaseToken)
+            }
+        }
+    }
+
+    // Runs after purchaseUpdated has gone out, so listeners see the purchase
+    // before it's acknowledged. A failure is only logged: the app can still
+    // acknowledge it, and Play redelivers unacknowledged purchases.
+    private fun autoAcknowledge(productId: String, purchaseToken: String) {
+        // Keep in sync with MAX_AUTO_ACKNOWLEDGE_DELAY_MS in src/config.rs
+        val delayMs = config.autoAcknowledgeDelayMs.coerceIn(0L, 2000L)
+        coroutineScope.launch {
+            delay(delayMs)
+            val params = AcknowledgePurchaseParams.newBuilder()
+                .setPurchaseToken(purchaseToken)
+                .build()
+            billingClient.acknowledgePurchase(params) { billingResult ->
+                if (billingResult.responseCode == BillingClient.BillingResponseCode.OK) {
+                    emitTransactionEvent(
+                        productId,
+                        JSObject().apply {
+                            put("type", "autoAcknowledged")
+                            put("purchaseToken", purchaseToken)
+                        }
+                    )
+                } else {
+                    Logger.error(TAG, "Auto-acknowledge failed: ${billingResult.debugMessage}", null)
+                }
+            }
         }
     }
     
+    // Base plans have no offer id. Play has no notion of offer type, so it's
+    // only reported when the offer is tagged with one in the Play Console.
+    private fun putOfferFields(target: JSObject, offer: ProductDetails.SubscriptionOfferDetails) {
+        val offerId = offer.offerId ?: return
+        target.put("offerId", offerId)
+        offer.offerTags.firstOrNull { it in OFFER_TYPE_TAGS }?.let { target.put("offerType", it) }
+    }
+
+    // Keep in sync with TransactionEvent in src/models.rs
+    private fun emitTransactionEvent(productId: String, state: JSObject) {
+        val event = JSObject()
+        event.put("productId", productId)
+        event.put("state", state)
+        event.put("timestamp", System.currentTimeMillis())
+        trigger("transactionUpdated", event)
+    }
+
+    private fun emitFailed(productId: String, responseCode: Int) {
+        emitTransactionEvent(
+            productId,
+            JSObject().apply {
+                put("type", "failed")
+                put("error", billingErrorName(responseCode))
+            }
+        )
+    }
+
     override fun onBillingSetupFinished(billingResult: BillingResult) {
         if (billingResult.responseCode == BillingClient.BillingResponseCode.OK) {
             Logger.info(TAG, "Billing setup finished successfully")
@@ -1,5 +1,7 @@
 package app.tauri.iap
 
+import com.android.billingclient.api.BillingClient
+import com.android.billingclient.api.ProductDetails
 import org.junit.Test
 import org.junit.Assert.*
 
@@ -112,11 +114,38 @@ class IapPluginTest {
         assertEquals("inapp", args.productType)
     }
 
+    @Test
+    fun testParseSubscriptionPeriod() {
+        assertEquals(Pair("month", 1), IapPlugin.parseSubscriptionPeriod("P1M"))
+        assertEquals(Pair("week", 2), IapPlugin.parseSubscriptionPeriod("P2W"))
+        assertEquals(Pair("year", 1), IapPlugin.parseSubscriptionPeriod("P1Y"))
+        assertNull(IapPlugin.parseSubscriptionPeriod("P1Y2M"))
+        assertNull(IapPlugin.parseSubscriptionPeriod(""))
+    }
+
+    @Test
+    fun testBillingErrorName() {
+        assertEquals("userCancelled", IapPlugin.billingErrorName(BillingClient.BillingResponseCode.USER_CANCELED))
+        assertEquals("itemAlreadyOwned", IapPlugin.billingErrorName(BillingClient.BillingResponseCode.ITEM_ALREADY_OWNED))
+        assertEquals("networkError", IapPlugin.billingErrorName(BillingClient.BillingResponseCode.NETWORK_ERROR))
+        assertEquals("42", IapPlugin.billingErrorName(42))
+    }
+
+    @Test
+    fun testPaymentModeFor() {
+        assertEquals("freeTrial", IapPlugin.paymentModeFor(ProductDetails.RecurrenceMode.FINITE_RECURRING, 0L))
+        assertEquals("payAsYouGo", IapPlugin.paymentModeFor(ProductDetails.RecurrenceMode.FINITE_RECURRING, 990_000L))
+        assertEquals("payUpFront", IapPlugin.paymentModeFor(ProductDetails.RecurrenceMode.NON_RECURRING, 2_990_000L))
+    }
+
     @Test
     fun testPurchaseStateConstants() {
         assertEquals(0, IapPlugin.PURCHASE_STATE_PURCHASED)
         assertEquals(1, IapPlugin.PURCHASE_STATE_CANCELED)
         assertEquals(2, IapPlugin.PURCHASE_STATE_PENDING)
+        assertEquals(3, IapPlugin.PURCHASE_STATE_FAILED)
+        assertEquals(4, IapPlugin.PURCHASE_STATE_REFUNDED)
+        assertEquals(5, IapPlugin.PURCHASE_STATE_DEFERRED)
     }
 
     @Test
@@ -124,8 +153,11 @@ class IapPluginTest {
         val states = setOf(
             IapPlugin.PURCHASE_STATE_PURCHASED,
             IapPlugin.PURCHASE_STATE_CANCELED,
-            IapPlugin.PURCHASE_STATE_PENDING
+            IapPlugin.PURCHASE_STATE_PENDING,
+            IapPlugin.PURCHASE_STATE_FAILED,
+            IapPlugin.PURCHASE_STATE_REFUNDED,
+            IapPlugin.PURCHASE_STATE_DEFERRED
         )
-        assertEquals(3, states.size)
+        assertEquals(6, states.size)
     }
 }
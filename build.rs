@@ -12,6 +12,33 @@ const COMMANDS: &[&str] = &[
     "acknowledge_purchase",
     "consume_purchase",
     "get_product_status",
+    "finish_transaction",
+    "get_app_transaction",
+    "get_current_entitlements",
+    "get_subscription_status",
+    "show_manage_subscriptions",
+    "present_offer_code_redemption_sheet",
+    "get_storefront",
+    "get_unfinished_transactions",
+    "begin_refund_request",
+    "request_refund",
+    "get_win_back_offers",
+    "present_code_redemption_sheet",
+    "is_eligible_for_intro_offer",
+    "get_environment",
+    "get_receipt",
+    "refresh_receipt",
+    "can_make_payments",
+    "check_introductory_offer_eligibility",
+    "get_purchase_history_filtered",
+    "is_supported",
+    "get_all_product_statuses",
+    "can_open_external_purchase_link",
+    "open_external_purchase_link",
+    "display_pending_store_messages",
+    "current_entitlement",
+    "get_app_receipt",
+    "get_revoked_purchases",
 ];
 
 fn main() {
@@ -27,6 +54,7 @@ fn main() {
             // Rebuild when target architecture or deployment target changes
             println!("cargo:rerun-if-env-changed=CARGO_CFG_TARGET_ARCH");
             println!("cargo:rerun-if-env-changed=MACOSX_DEPLOYMENT_TARGET");
+            warn_on_old_deployment_target();
 
             let bridges = vec!["src/macos.rs"];
             for path in &bridges {
@@ -37,6 +65,11 @@ fn main() {
 
             swift_bridge_build::parse_bridges(bridges)
                 .write_all_concatenated(swift_bridge_out_dir(), env!("CARGO_PKG_NAME"));
+            dedupe_this_params(
+                &swift_bridge_out_dir()
+                    .join(env!("CARGO_PKG_NAME"))
+                    .join(concat!(env!("CARGO_PKG_NAME"), ".swift")),
+            );
 
             compile_swift();
 
@@ -104,6 +137,93 @@ Stdout: {}
     );
 }
 
+/// `swift-bridge` emits a second `this` parameter in the glue for async
+/// `&self` methods, which Swift 6 rejects as "invalid redeclaration of
+/// 'this'". Rename every repeated `this` in a `func` parameter list to
+/// `_self2`, `_self3`, ...; the body only ever refers to the first one.
+#[cfg(target_os = "macos")]
+fn dedupe_this_params(path: &std::path::Path) {
+    let source = std::fs::read_to_string(path).expect("Generated Swift bridge must be readable");
+
+    let mut out = String::with_capacity(source.len());
+    let mut rest = source.as_str();
+    while let Some(func) = rest.find("func ") {
+        let Some(open) = rest[func..].find('(').map(|i| func + i) else {
+            break;
+        };
+        out.push_str(&rest[..=open]);
+        rest = &rest[open + 1..];
+
+        // Parameter list ends at the matching `)`; closure types nest parens.
+        let mut depth = 0usize;
+        let close = rest
+            .char_indices()
+            .find(|&(_, c)| match c {
+                '(' => {
+                    depth += 1;
+                    false
+                }
+                ')' if depth == 0 => true,
+                ')' => {
+                    depth -= 1;
+                    false
+                }
+                _ => false,
+            })
+            .map_or(rest.len(), |(i, _)| i);
+
+        let mut seen = 0;
+        let params = split_top_level(&rest[..close])
+            .into_iter()
+            .map(|param| {
+                let (name, ty) = param.split_once(':').unwrap_or((param, ""));
+                if name.split_whitespace().last() != Some("this") {
+                    return param.to_string();
+                }
+                seen += 1;
+                if seen == 1 {
+                    return param.to_string();
+                }
+                let renamed = name.trim_end().strip_suffix("this").unwrap_or(name);
+                format!("{renamed}_self{seen}:{ty}")
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        out.push_str(&params);
+        rest = &rest[close..];
+    }
+    out.push_str(rest);
+
+    if out != source {
+        std::fs::write(path, out).expect("Generated Swift bridge must be writable");
+    }
+}
+
+/// Splits a parameter list on commas that aren't nested in parens or generics.
+#[cfg(target_os = "macos")]
+fn split_top_level(params: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    let mut prev = ' ';
+    for (i, c) in params.char_indices() {
+        match c {
+            '(' | '<' | '[' => depth += 1,
+            // `->` in closure types is not a closing generic bracket
+            '>' if prev == '-' => {}
+            ')' | '>' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&params[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        prev = c;
+    }
+    parts.push(&params[start..]);
+    parts
+}
+
 #[cfg(target_os = "macos")]
 fn swift_bridge_out_dir() -> PathBuf {
     generated_code_dir()
@@ -162,6 +282,24 @@ fn macos_deployment_target() -> String {
     std::env::var("MACOSX_DEPLOYMENT_TARGET").unwrap_or_else(|_| "13.0".to_string())
 }
 
+/// The Swift package targets macOS 13, so a lower deployment target builds an
+/// app that older systems refuse to launch before the plugin's runtime
+/// `storeKitUnavailable` check can run.
+#[cfg(target_os = "macos")]
+fn warn_on_old_deployment_target() {
+    let target = macos_deployment_target();
+    let major = target
+        .split('.')
+        .next()
+        .and_then(|major| major.parse::<u32>().ok());
+    if major.is_some_and(|major| major < 13) {
+        println!(
+            "cargo:warning=MACOSX_DEPLOYMENT_TARGET is {target}, but tauri-plugin-iap needs \
+             macOS 13.0 for StoreKit 2. Set bundle.macOS.minimumSystemVersion to 13.0."
+        );
+    }
+}
+
 #[cfg(target_os = "macos")]
 fn swift_target_triple() -> String {
     format!("{}-apple-macosx{}", swift_arch(), macos_deployment_target())
@@ -2,13 +2,50 @@ import { describe, it, expect, vi, beforeEach, afterEach } from "vitest";
 import {
   initialize,
   getProducts,
+  priceInfo,
   purchase,
   restorePurchases,
   getPurchaseHistory,
+  getPurchaseHistoryFiltered,
+  getCurrentEntitlements,
+  currentEntitlement,
   acknowledgePurchase,
   consumePurchase,
+  finishTransaction,
+  beginRefundRequest,
+  requestRefund,
+  getAppTransaction,
+  getStorefront,
+  getUnfinishedTransactions,
+  getRevokedPurchases,
+  onStorefrontChanged,
+  getSubscriptionStatus,
+  getWinBackOffers,
+  isEligibleForIntroOffer,
+  checkIntroductoryOfferEligibility,
+  getEnvironment,
+  getReceipt,
+  refreshReceipt,
+  getAppReceipt,
+  canMakePayments,
+  isSupported,
+  showManageSubscriptions,
+  presentOfferCodeRedemptionSheet,
+  presentCodeRedemptionSheet,
+  onSubscriptionStatusChanged,
+  onPriceIncreasePending,
+  onRenewalPreferenceChanged,
+  onBillingIssueDetected,
   getProductStatus,
+  getAllProductStatuses,
+  canOpenExternalPurchaseLink,
+  openExternalPurchaseLink,
+  onStoreMessageReceived,
+  displayPendingStoreMessages,
   onPurchaseUpdated,
+  onPurchaseCompleted,
+  onPurchaseRevoked,
+  onTransactionUpdated,
   PurchaseState,
   type GetProductsResponse,
   type Purchase,
@@ -16,6 +53,13 @@ import {
   type GetPurchaseHistoryResponse,
   type ProductStatus,
   type PurchaseOptions,
+  type AppTransactionInfo,
+  type StorefrontInfo,
+  type GetSubscriptionStatusResponse,
+  type GetWinBackOffersResponse,
+  type IsEligibleForIntroOfferResponse,
+  type PurchaseReceipt,
+  type ExternalPurchaseLinkAvailability,
 } from "./index";
 
 // Mock Tauri API
@@ -44,6 +88,18 @@ describe("IAP Plugin", () => {
 
       await expect(initialize()).rejects.toThrow("deprecated");
     });
+
+    it("should return the finished transaction count", async () => {
+      vi.mocked(invoke).mockResolvedValue({
+        success: true,
+        finishedTransactions: 2,
+      });
+
+      const result = await initialize();
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|initialize");
+      expect(result.finishedTransactions).toBe(2);
+    });
   });
 
   describe("getProducts", () => {
@@ -88,6 +144,22 @@ describe("IAP Plugin", () => {
       });
     });
 
+    it("should pass forceRefresh through", async () => {
+      vi.mocked(invoke).mockResolvedValue({ products: [] });
+
+      await getProducts(["com.example.product"], "inapp", {
+        forceRefresh: true,
+      });
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_products", {
+        payload: {
+          productIds: ["com.example.product"],
+          productType: "inapp",
+          forceRefresh: true,
+        },
+      });
+    });
+
     it("should fetch in-app products", async () => {
       const mockProducts: GetProductsResponse = {
         products: [
@@ -117,13 +189,46 @@ describe("IAP Plugin", () => {
     });
   });
 
+  describe("priceInfo", () => {
+    it("should collect the price fields of a product", () => {
+      const info = priceInfo({
+        productId: "coins_jp",
+        title: "Coins",
+        description: "",
+        productType: "inapp",
+        formattedPrice: "¥120",
+        priceCurrencyCode: "JPY",
+        priceAmountMicros: 120000000,
+        priceLocale: "ja_JP",
+      });
+
+      expect(info).toEqual({
+        amount: 120,
+        currencyCode: "JPY",
+        formattedPrice: "¥120",
+        localeIdentifier: "ja_JP",
+      });
+    });
+
+    it("should return undefined without a price", () => {
+      expect(
+        priceInfo({
+          productId: "premium",
+          title: "Premium",
+          description: "",
+          productType: "subs",
+        }),
+      ).toBeUndefined();
+    });
+  });
+
   describe("purchase", () => {
     it("should initiate purchase with correct parameters", async () => {
       const mockPurchase: Purchase = {
         orderId: "ORDER123",
         packageName: "com.example.app",
         productId: "com.example.premium",
-        purchaseTime: Date.now(),
+        purchaseTime: new Date().toISOString(),
         purchaseToken: "TOKEN123",
         purchaseState: PurchaseState.PURCHASED,
         isAutoRenewing: true,
@@ -144,11 +249,32 @@ describe("IAP Plugin", () => {
       expect(result).toEqual(mockPurchase);
     });
 
+    it("should pass through the StoreKit verification result", async () => {
+      const mockPurchase: Purchase = {
+        orderId: "2000000123",
+        packageName: "com.example.app",
+        productId: "com.example.premium",
+        purchaseTime: new Date().toISOString(),
+        purchaseToken: "2000000123",
+        purchaseState: PurchaseState.PURCHASED,
+        isAutoRenewing: false,
+        isAcknowledged: true,
+        originalJson: "",
+        signature: "",
+        verification: "unverified",
+      };
+      vi.mocked(invoke).mockResolvedValue(mockPurchase);
+
+      const result = await purchase("com.example.premium", "inapp");
+
+      expect(result.verification).toBe("unverified");
+    });
+
     it("should include purchase options in payload", async () => {
       const mockPurchase: Purchase = {
         packageName: "com.example.app",
         productId: "com.example.premium",
-        purchaseTime: Date.now(),
+        purchaseTime: new Date().toISOString(),
         purchaseToken: "TOKEN123",
         purchaseState: PurchaseState.PURCHASED,
         isAutoRenewing: false,
@@ -181,7 +307,7 @@ describe("IAP Plugin", () => {
       const mockPurchase: Purchase = {
         packageName: "com.example.app",
         productId: "com.example.premium",
-        purchaseTime: Date.now(),
+        purchaseTime: new Date().toISOString(),
         purchaseToken: "TOKEN123",
         purchaseState: PurchaseState.PURCHASED,
         isAutoRenewing: true,
@@ -206,11 +332,33 @@ describe("IAP Plugin", () => {
       });
     });
 
+    it("should pass a promotional offer", async () => {
+      vi.mocked(invoke).mockResolvedValue({});
+
+      const promotionalOffer = {
+        offerId: "winback",
+        keyId: "ABC123",
+        nonce: "4f0a7c1e-2b3d-4e5f-8a9b-0c1d2e3f4a5b",
+        signature: "MEUCIQ==",
+        timestamp: 1700000000000,
+      };
+
+      await purchase("com.example.premium", "subs", { promotionalOffer });
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|purchase", {
+        payload: {
+          productId: "com.example.premium",
+          productType: "subs",
+          promotionalOffer,
+        },
+      });
+    });
+
     it("should handle pending purchase state", async () => {
       const mockPurchase: Purchase = {
         packageName: "com.example.app",
         productId: "com.example.premium",
-        purchaseTime: Date.now(),
+        purchaseTime: new Date().toISOString(),
         purchaseToken: "TOKEN123",
         purchaseState: PurchaseState.PENDING,
         isAutoRenewing: false,
@@ -234,7 +382,7 @@ describe("IAP Plugin", () => {
             orderId: "ORDER123",
             packageName: "com.example.app",
             productId: "com.example.premium",
-            purchaseTime: Date.now(),
+            purchaseTime: new Date().toISOString(),
             purchaseToken: "TOKEN123",
             purchaseState: PurchaseState.PURCHASED,
             isAutoRenewing: true,
@@ -256,6 +404,19 @@ describe("IAP Plugin", () => {
       expect(result).toEqual(mockResponse);
     });
 
+    it("should pass forceSync through", async () => {
+      vi.mocked(invoke).mockResolvedValue({ purchases: [] });
+
+      await restorePurchases("inapp", { forceSync: true });
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|restore_purchases", {
+        payload: {
+          productType: "inapp",
+          forceSync: true,
+        },
+      });
+    });
+
     it("should default to subs product type", async () => {
       const mockResponse: RestorePurchasesResponse = { purchases: [] };
       vi.mocked(invoke).mockResolvedValue(mockResponse);
@@ -277,6 +438,33 @@ describe("IAP Plugin", () => {
 
       expect(result.purchases).toHaveLength(0);
     });
+
+    it("should pass through subscription expiration dates", async () => {
+      const mockResponse: RestorePurchasesResponse = {
+        purchases: [
+          {
+            orderId: "2000000456",
+            packageName: "com.example.app",
+            productId: "com.example.monthly",
+            purchaseTime: "2023-11-14T22:13:20.000Z",
+            purchaseToken: "2000000456",
+            purchaseState: PurchaseState.PURCHASED,
+            isAutoRenewing: true,
+            isAcknowledged: true,
+            originalJson: "",
+            signature: "",
+            expirationDate: "2023-12-14T22:13:20.000Z",
+            nextRenewalDate: "2023-12-14T22:13:20.000Z",
+          },
+        ],
+      };
+      vi.mocked(invoke).mockResolvedValue(mockResponse);
+
+      const result = await restorePurchases("subs");
+
+      expect(result.purchases[0].expirationDate).toBe("2023-12-14T22:13:20.000Z");
+      expect(result.purchases[0].nextRenewalDate).toBe("2023-12-14T22:13:20.000Z");
+    });
   });
 
   describe("getPurchaseHistory", () => {
@@ -285,7 +473,7 @@ describe("IAP Plugin", () => {
         history: [
           {
             productId: "com.example.coins",
-            purchaseTime: Date.now(),
+            purchaseTime: new Date().toISOString(),
             purchaseToken: "TOKEN123",
             quantity: 1,
             originalJson: "{}",
@@ -311,6 +499,42 @@ describe("IAP Plugin", () => {
     });
   });
 
+  describe("getPurchaseHistoryFiltered", () => {
+    it("should pass filters as the payload", async () => {
+      const mockHistory: GetPurchaseHistoryResponse = { history: [] };
+      vi.mocked(invoke).mockResolvedValue(mockHistory);
+
+      const result = await getPurchaseHistoryFiltered({
+        productType: "inapp",
+        sinceTimestamp: 1700000000000,
+        limit: 20,
+      });
+
+      expect(invoke).toHaveBeenCalledWith(
+        "plugin:iap|get_purchase_history_filtered",
+        {
+          payload: {
+            productType: "inapp",
+            sinceTimestamp: 1700000000000,
+            limit: 20,
+          },
+        },
+      );
+      expect(result).toEqual(mockHistory);
+    });
+
+    it("should send an empty payload without filters", async () => {
+      vi.mocked(invoke).mockResolvedValue({ history: [] });
+
+      await getPurchaseHistoryFiltered();
+
+      expect(invoke).toHaveBeenCalledWith(
+        "plugin:iap|get_purchase_history_filtered",
+        { payload: {} },
+      );
+    });
+  });
+
   describe("acknowledgePurchase", () => {
     it("should acknowledge purchase with token", async () => {
       vi.mocked(invoke).mockResolvedValue(undefined);
@@ -357,138 +581,1029 @@ describe("IAP Plugin", () => {
     });
   });
 
-  describe("getProductStatus", () => {
-    it("should get product status with correct parameters", async () => {
-      const mockStatus: ProductStatus = {
-        productId: "com.example.premium",
-        isOwned: true,
+  describe("getCurrentEntitlements", () => {
+    it("should get current entitlements", async () => {
+      const mockResponse = {
+        purchases: [
+          {
+            orderId: "2000000123",
+            packageName: "com.example.app",
+            productId: "premium_lifetime",
+            purchaseTime: "2023-11-14T22:13:20.000Z",
+            purchaseToken: "2000000123",
+            purchaseState: PurchaseState.PURCHASED,
+            isAutoRenewing: false,
+            isAcknowledged: true,
+            originalJson: "",
+            signature: "",
+          },
+        ],
+      };
+      vi.mocked(invoke).mockResolvedValue(mockResponse);
+
+      const result = await getCurrentEntitlements();
+
+      expect(invoke).toHaveBeenCalledWith(
+        "plugin:iap|get_current_entitlements",
+      );
+      expect(result).toEqual(mockResponse);
+    });
+  });
+
+  describe("currentEntitlement", () => {
+    it("should return the entitlement for a product", async () => {
+      const mockPurchase = {
+        orderId: "2000000123",
+        packageName: "com.example.app",
+        productId: "premium_lifetime",
+        purchaseTime: "2023-11-14T22:13:20.000Z",
+        purchaseToken: "2000000123",
         purchaseState: PurchaseState.PURCHASED,
-        purchaseTime: Date.now(),
-        isAutoRenewing: true,
+        isAutoRenewing: false,
         isAcknowledged: true,
-        purchaseToken: "TOKEN123",
+        originalJson: "",
+        signature: "",
       };
-      vi.mocked(invoke).mockResolvedValue(mockStatus);
+      vi.mocked(invoke).mockResolvedValue(mockPurchase);
 
-      const result = await getProductStatus("com.example.premium", "subs");
+      const result = await currentEntitlement("premium_lifetime");
 
-      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_product_status", {
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|current_entitlement", {
         payload: {
-          productId: "com.example.premium",
-          productType: "subs",
+          productId: "premium_lifetime",
         },
       });
-      expect(result).toEqual(mockStatus);
+      expect(result).toEqual(mockPurchase);
     });
 
-    it("should default to subs product type", async () => {
-      const mockStatus: ProductStatus = {
-        productId: "com.example.premium",
-        isOwned: false,
-      };
-      vi.mocked(invoke).mockResolvedValue(mockStatus);
+    it("should return null when the product isn't owned", async () => {
+      vi.mocked(invoke).mockResolvedValue(null);
 
-      await getProductStatus("com.example.premium");
+      await expect(currentEntitlement("premium_lifetime")).resolves.toBeNull();
+    });
+  });
 
-      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_product_status", {
+  describe("finishTransaction", () => {
+    it("should finish transaction by id", async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await expect(finishTransaction("2000000123")).resolves.toBeUndefined();
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|finish_transaction", {
         payload: {
-          productId: "com.example.premium",
-          productType: "subs",
+          transactionId: "2000000123",
         },
       });
     });
 
-    it("should indicate product not owned", async () => {
-      const mockStatus: ProductStatus = {
-        productId: "com.example.premium",
-        isOwned: false,
-      };
-      vi.mocked(invoke).mockResolvedValue(mockStatus);
+    it("should propagate failure from invoke", async () => {
+      const error = new Error("No unfinished transaction with id 2000000123");
+      vi.mocked(invoke).mockRejectedValue(error);
 
-      const result = await getProductStatus("com.example.premium", "subs");
+      await expect(finishTransaction("2000000123")).rejects.toThrow(
+        "No unfinished transaction",
+      );
+    });
+  });
 
-      expect(result.isOwned).toBe(false);
-      expect(result.purchaseState).toBeUndefined();
+  describe("beginRefundRequest", () => {
+    it("should begin refund request by transaction id", async () => {
+      vi.mocked(invoke).mockResolvedValue("success");
+
+      const result = await beginRefundRequest("2000000123");
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|begin_refund_request", {
+        payload: {
+          transactionId: "2000000123",
+        },
+      });
+      expect(result).toBe("success");
     });
 
-    it("should include expiration time for subscriptions", async () => {
-      const now = Date.now();
-      const mockStatus: ProductStatus = {
-        productId: "com.example.premium",
-        isOwned: true,
-        purchaseState: PurchaseState.PURCHASED,
-        purchaseTime: now,
-        expirationTime: now + 30 * 24 * 60 * 60 * 1000, // 30 days
-        isAutoRenewing: true,
-      };
-      vi.mocked(invoke).mockResolvedValue(mockStatus);
+    it("should resolve with userCancelled when the sheet is dismissed", async () => {
+      vi.mocked(invoke).mockResolvedValue("userCancelled");
 
-      const result = await getProductStatus("com.example.premium", "subs");
+      await expect(beginRefundRequest("2000000123")).resolves.toBe(
+        "userCancelled",
+      );
+    });
+  });
 
-      expect(result.expirationTime).toBeDefined();
-      expect(result.expirationTime).toBeGreaterThan(now);
+  describe("requestRefund", () => {
+    it("should request refund by transaction id", async () => {
+      vi.mocked(invoke).mockResolvedValue("error");
+
+      const result = await requestRefund("2000000123");
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|request_refund", {
+        payload: {
+          transactionId: "2000000123",
+        },
+      });
+      expect(result).toBe("error");
     });
   });
 
-  describe("onPurchaseUpdated", () => {
-    const createMockPluginListener = (unregister: () => Promise<void>) => ({
-      plugin: "iap",
-      event: "purchaseUpdated",
-      channelId: 1,
-      unregister,
+  describe("getUnfinishedTransactions", () => {
+    it("should return unfinished purchases", async () => {
+      const mockPurchases: Purchase[] = [
+        {
+          orderId: undefined,
+          packageName: "com.example.app",
+          productId: "coins_100",
+          purchaseTime: "2023-11-14T22:13:20.000Z",
+          purchaseToken: "2000000123",
+          purchaseState: PurchaseState.PURCHASED,
+          isAutoRenewing: false,
+          isAcknowledged: false,
+          originalJson: "",
+          signature: "",
+        },
+      ];
+      vi.mocked(invoke).mockResolvedValue(mockPurchases);
+
+      const result = await getUnfinishedTransactions();
+
+      expect(invoke).toHaveBeenCalledWith(
+        "plugin:iap|get_unfinished_transactions",
+      );
+      expect(result).toEqual(mockPurchases);
     });
+  });
 
-    it("should register plugin listener and return PluginListener", async () => {
+  describe("getRevokedPurchases", () => {
+    it("should return revoked purchases", async () => {
+      const mockPurchases: Purchase[] = [
+        {
+          orderId: "2000000123",
+          packageName: "com.example.app",
+          productId: "premium",
+          purchaseTime: "2023-11-14T22:13:20.000Z",
+          purchaseToken: "2000000123",
+          purchaseState: PurchaseState.CANCELED,
+          isAutoRenewing: false,
+          isAcknowledged: true,
+          originalJson: "",
+          signature: "",
+          revocationTime: "2023-11-14T22:21:40.000Z",
+          revocationReason: "other",
+        },
+      ];
+      vi.mocked(invoke).mockResolvedValue(mockPurchases);
+
+      const result = await getRevokedPurchases();
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_revoked_purchases", {
+        payload: {},
+      });
+      expect(result).toEqual(mockPurchases);
+    });
+
+    it("should pass since", async () => {
+      vi.mocked(invoke).mockResolvedValue([]);
+
+      await getRevokedPurchases({ since: "2023-11-01T00:00:00.000Z" });
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_revoked_purchases", {
+        payload: { since: "2023-11-01T00:00:00.000Z" },
+      });
+    });
+  });
+
+  describe("getStorefront", () => {
+    it("should get the storefront", async () => {
+      const mockStorefront: StorefrontInfo = {
+        id: "143441",
+        countryCode: "USA",
+      };
+      vi.mocked(invoke).mockResolvedValue(mockStorefront);
+
+      const result = await getStorefront();
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_storefront");
+      expect(result).toEqual(mockStorefront);
+    });
+  });
+
+  describe("onStorefrontChanged", () => {
+    it("should register storefrontChanged plugin listener", async () => {
       const mockUnregister = vi
         .fn<() => Promise<void>>()
         .mockResolvedValue(undefined);
-      vi.mocked(addPluginListener).mockResolvedValue(
-        createMockPluginListener(mockUnregister),
-      );
+      vi.mocked(addPluginListener).mockResolvedValue({
+        plugin: "iap",
+        event: "storefrontChanged",
+        channelId: 4,
+        unregister: mockUnregister,
+      });
 
       const callback = vi.fn();
-      const listener = await onPurchaseUpdated(callback);
+      await onStorefrontChanged(callback);
 
       expect(addPluginListener).toHaveBeenCalledWith(
         "iap",
-        "purchaseUpdated",
+        "storefrontChanged",
         callback,
       );
-      expect(listener.unregister).toBe(mockUnregister);
     });
+  });
 
-    it("should pass callback directly to addPluginListener", async () => {
+  describe("onPurchaseCompleted", () => {
+    it("should register purchaseCompleted plugin listener", async () => {
       const mockUnregister = vi
         .fn<() => Promise<void>>()
         .mockResolvedValue(undefined);
-      vi.mocked(addPluginListener).mockResolvedValue(
-        createMockPluginListener(mockUnregister),
-      );
+      vi.mocked(addPluginListener).mockResolvedValue({
+        plugin: "iap",
+        event: "purchaseCompleted",
+        channelId: 5,
+        unregister: mockUnregister,
+      });
 
       const callback = vi.fn();
-      await onPurchaseUpdated(callback);
+      await onPurchaseCompleted(callback);
 
       expect(addPluginListener).toHaveBeenCalledWith(
         "iap",
-        "purchaseUpdated",
+        "purchaseCompleted",
         callback,
       );
     });
+  });
 
-    it("should unregister listener when unregister is called", async () => {
+  describe("onPurchaseRevoked", () => {
+    it("should register purchaseRevoked plugin listener", async () => {
       const mockUnregister = vi
         .fn<() => Promise<void>>()
         .mockResolvedValue(undefined);
-      vi.mocked(addPluginListener).mockResolvedValue(
-        createMockPluginListener(mockUnregister),
-      );
+      vi.mocked(addPluginListener).mockResolvedValue({
+        plugin: "iap",
+        event: "purchaseRevoked",
+        channelId: 5,
+        unregister: mockUnregister,
+      });
 
       const callback = vi.fn();
-      const listener = await onPurchaseUpdated(callback);
+      await onPurchaseRevoked(callback);
 
-      await listener.unregister();
+      expect(addPluginListener).toHaveBeenCalledWith(
+        "iap",
+        "purchaseRevoked",
+        callback,
+      );
+    });
+  });
 
-      expect(mockUnregister).toHaveBeenCalled();
+  describe("getAppTransaction", () => {
+    it("should get app transaction without refresh by default", async () => {
+      const mockInfo: AppTransactionInfo = {
+        bundleId: "com.example.app",
+        appVersion: "2.3",
+        originalAppVersion: "1.0",
+        originalPurchaseDate: "2020-09-13T12:26:40.000Z",
+        environment: "production",
+        jwsRepresentation: "eyJ...",
+      };
+      vi.mocked(invoke).mockResolvedValue(mockInfo);
+
+      const result = await getAppTransaction();
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_app_transaction", {
+        payload: {
+          forceRefresh: false,
+        },
+      });
+      expect(result).toEqual(mockInfo);
+    });
+
+    it("should pass forceRefresh", async () => {
+      vi.mocked(invoke).mockResolvedValue({});
+
+      await getAppTransaction(true);
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_app_transaction", {
+        payload: {
+          forceRefresh: true,
+        },
+      });
+    });
+  });
+
+  describe("getEnvironment", () => {
+    it("should invoke get_environment", async () => {
+      vi.mocked(invoke).mockResolvedValue("testFlight");
+
+      const result = await getEnvironment();
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_environment");
+      expect(result).toBe("testFlight");
+    });
+
+    it("should propagate notSupported from invoke", async () => {
+      vi.mocked(invoke).mockRejectedValue(
+        new Error("get_environment is only available on iOS, macOS and Android"),
+      );
+
+      await expect(getEnvironment()).rejects.toThrow("only available on");
+    });
+  });
+
+  describe("getReceipt", () => {
+    it("should get the receipt for a transaction", async () => {
+      const mockReceipt: PurchaseReceipt = {
+        platform: "android",
+        receiptData: '{"orderId":"GPA.1234"}',
+        purchaseToken: "token123",
+        productId: "coins",
+        transactionId: "GPA.1234",
+      };
+      vi.mocked(invoke).mockResolvedValue(mockReceipt);
+
+      const result = await getReceipt("GPA.1234");
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_receipt", {
+        payload: { transactionId: "GPA.1234" },
+      });
+      expect(result).toEqual(mockReceipt);
+    });
+  });
+
+  describe("refreshReceipt", () => {
+    it("should invoke refresh_receipt", async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await refreshReceipt();
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|refresh_receipt");
+    });
+
+    it("should propagate notSupported from invoke", async () => {
+      vi.mocked(invoke).mockRejectedValue(
+        new Error("refresh_receipt is only available on iOS and macOS"),
+      );
+
+      await expect(refreshReceipt()).rejects.toThrow("only available on");
+    });
+  });
+
+  describe("getAppReceipt", () => {
+    it("should invoke get_app_receipt without refreshing by default", async () => {
+      vi.mocked(invoke).mockResolvedValue("TUlJVDBR");
+
+      const result = await getAppReceipt();
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_app_receipt", {
+        payload: { refreshIfMissing: false },
+      });
+      expect(result).toBe("TUlJVDBR");
+    });
+
+    it("should pass refreshIfMissing", async () => {
+      vi.mocked(invoke).mockResolvedValue("TUlJVDBR");
+
+      await getAppReceipt({ refreshIfMissing: true });
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_app_receipt", {
+        payload: { refreshIfMissing: true },
+      });
+    });
+
+    it("should propagate receiptUnavailable from invoke", async () => {
+      vi.mocked(invoke).mockRejectedValue(
+        new Error("The app has no App Store receipt"),
+      );
+
+      await expect(getAppReceipt()).rejects.toThrow("no App Store receipt");
+    });
+  });
+
+  describe("isSupported", () => {
+    it("should invoke is_supported", async () => {
+      vi.mocked(invoke).mockResolvedValue({ isSupported: true });
+
+      const result = await isSupported();
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|is_supported");
+      expect(result.isSupported).toBe(true);
+      expect(result.reason).toBeUndefined();
+    });
+
+    it("should pass through why the plugin is unsupported", async () => {
+      vi.mocked(invoke).mockResolvedValue({
+        isSupported: false,
+        reason: "storeKitUnavailable",
+      });
+
+      const result = await isSupported();
+
+      expect(result).toEqual({
+        isSupported: false,
+        reason: "storeKitUnavailable",
+      });
+    });
+  });
+
+  describe("canMakePayments", () => {
+    it("should invoke can_make_payments", async () => {
+      vi.mocked(invoke).mockResolvedValue({ canMakePayments: true });
+
+      const result = await canMakePayments();
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|can_make_payments");
+      expect(result.canMakePayments).toBe(true);
+      expect(result.reason).toBeUndefined();
+    });
+
+    it("should pass through the reason purchasing is disabled", async () => {
+      vi.mocked(invoke).mockResolvedValue({
+        canMakePayments: false,
+        reason: "restricted",
+      });
+
+      const result = await canMakePayments();
+
+      expect(result).toEqual({ canMakePayments: false, reason: "restricted" });
+    });
+  });
+
+  describe("isEligibleForIntroOffer", () => {
+    it("should check intro offer eligibility for a product", async () => {
+      const mockResponse: IsEligibleForIntroOfferResponse = {
+        eligibility: "eligible",
+        subscriptionGroupId: "21345678",
+      };
+      vi.mocked(invoke).mockResolvedValue(mockResponse);
+
+      const result = await isEligibleForIntroOffer("com.example.premium");
+
+      expect(invoke).toHaveBeenCalledWith(
+        "plugin:iap|is_eligible_for_intro_offer",
+        {
+          payload: {
+            productId: "com.example.premium",
+          },
+        },
+      );
+      expect(result).toEqual(mockResponse);
+    });
+
+    it("should handle unknown eligibility without a group", async () => {
+      vi.mocked(invoke).mockResolvedValue({ eligibility: "unknown" });
+
+      const result = await isEligibleForIntroOffer("com.example.premium");
+
+      expect(result.eligibility).toBe("unknown");
+      expect(result.subscriptionGroupId).toBeUndefined();
+    });
+  });
+
+  describe("checkIntroductoryOfferEligibility", () => {
+    it("should check intro offer eligibility for several products", async () => {
+      const mockResponse = {
+        "com.example.monthly": true,
+        "com.example.yearly": false,
+      };
+      vi.mocked(invoke).mockResolvedValue(mockResponse);
+
+      const result = await checkIntroductoryOfferEligibility([
+        "com.example.monthly",
+        "com.example.yearly",
+        "com.example.coins",
+      ]);
+
+      expect(invoke).toHaveBeenCalledWith(
+        "plugin:iap|check_introductory_offer_eligibility",
+        {
+          payload: {
+            productIds: [
+              "com.example.monthly",
+              "com.example.yearly",
+              "com.example.coins",
+            ],
+          },
+        },
+      );
+      expect(result).toEqual(mockResponse);
+      expect(result["com.example.coins"]).toBeUndefined();
+    });
+  });
+
+  describe("getWinBackOffers", () => {
+    it("should get win-back offers for a product", async () => {
+      const mockResponse: GetWinBackOffersResponse = {
+        offers: [
+          {
+            id: "winback_50",
+            offerType: "winBack",
+            price: "$4.99",
+            priceLocale: "en_US",
+            period: { unit: "month", value: 1 },
+            periodCount: 3,
+            paymentMode: "payAsYouGo",
+            isEligible: true,
+          },
+        ],
+      };
+      vi.mocked(invoke).mockResolvedValue(mockResponse);
+
+      const result = await getWinBackOffers("com.example.premium");
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_win_back_offers", {
+        payload: {
+          productId: "com.example.premium",
+        },
+      });
+      expect(result).toEqual(mockResponse);
+    });
+
+    it("should pass winBackOfferId to purchase", async () => {
+      vi.mocked(invoke).mockResolvedValue({});
+
+      await purchase("com.example.premium", "subs", {
+        winBackOfferId: "winback_50",
+      });
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|purchase", {
+        payload: {
+          productId: "com.example.premium",
+          productType: "subs",
+          winBackOfferId: "winback_50",
+        },
+      });
+    });
+  });
+
+  describe("getSubscriptionStatus", () => {
+    it("should get subscription statuses for a product", async () => {
+      const mockResponse: GetSubscriptionStatusResponse = {
+        statuses: [
+          {
+            productId: "premium_monthly",
+            state: "subscribed",
+            transactionId: "2000000123",
+            originalTransactionId: "2000000001",
+            isFamilyShared: false,
+            subscriptionGroupId: "21345678",
+            willAutoRenew: true,
+            expirationDate: "2023-11-14T22:13:20.000Z",
+            autoRenewProductId: "premium_yearly",
+          },
+          {
+            productId: "premium_monthly",
+            state: "expired",
+            transactionId: "2000000124",
+            originalTransactionId: "2000000002",
+            isFamilyShared: true,
+            willAutoRenew: false,
+            expirationReason: "billingError",
+          },
+        ],
+      };
+      vi.mocked(invoke).mockResolvedValue(mockResponse);
+
+      const result = await getSubscriptionStatus("premium_monthly");
+
+      expect(invoke).toHaveBeenCalledWith(
+        "plugin:iap|get_subscription_status",
+        {
+          payload: {
+            productId: "premium_monthly",
+          },
+        },
+      );
+      expect(result).toEqual(mockResponse);
+    });
+
+    it("should propagate failure from invoke", async () => {
+      vi.mocked(invoke).mockRejectedValue(
+        new Error("coins is not an auto-renewable subscription"),
+      );
+
+      await expect(getSubscriptionStatus("coins")).rejects.toThrow(
+        "not an auto-renewable subscription",
+      );
+    });
+  });
+
+  describe("showManageSubscriptions", () => {
+    it("should invoke show_manage_subscriptions", async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await expect(showManageSubscriptions()).resolves.toBeUndefined();
+
+      expect(invoke).toHaveBeenCalledWith(
+        "plugin:iap|show_manage_subscriptions",
+      );
+    });
+
+    it("should propagate failure from invoke", async () => {
+      vi.mocked(invoke).mockRejectedValue(
+        new Error("showManageSubscriptions is only available on iOS and macOS"),
+      );
+
+      await expect(showManageSubscriptions()).rejects.toThrow(
+        "only available on iOS and macOS",
+      );
+    });
+  });
+
+  describe("presentOfferCodeRedemptionSheet", () => {
+    it("should invoke present_offer_code_redemption_sheet", async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await expect(presentOfferCodeRedemptionSheet()).resolves.toBeUndefined();
+
+      expect(invoke).toHaveBeenCalledWith(
+        "plugin:iap|present_offer_code_redemption_sheet",
+      );
+    });
+
+    it("should propagate failure from invoke", async () => {
+      vi.mocked(invoke).mockRejectedValue(
+        new Error("Offer code redemption requires macOS 15.0 or later"),
+      );
+
+      await expect(presentOfferCodeRedemptionSheet()).rejects.toThrow(
+        "requires macOS 15.0 or later",
+      );
+    });
+  });
+
+  describe("presentCodeRedemptionSheet", () => {
+    it("should invoke present_code_redemption_sheet", async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await expect(presentCodeRedemptionSheet()).resolves.toBeUndefined();
+
+      expect(invoke).toHaveBeenCalledWith(
+        "plugin:iap|present_code_redemption_sheet",
+      );
+    });
+  });
+
+  describe("onSubscriptionStatusChanged", () => {
+    it("should register subscriptionStatusChanged plugin listener", async () => {
+      const mockUnregister = vi
+        .fn<() => Promise<void>>()
+        .mockResolvedValue(undefined);
+      vi.mocked(addPluginListener).mockResolvedValue({
+        plugin: "iap",
+        event: "subscriptionStatusChanged",
+        channelId: 3,
+        unregister: mockUnregister,
+      });
+
+      const callback = vi.fn();
+      await onSubscriptionStatusChanged(callback);
+
+      expect(addPluginListener).toHaveBeenCalledWith(
+        "iap",
+        "subscriptionStatusChanged",
+        callback,
+      );
+    });
+  });
+
+  describe("onPriceIncreasePending", () => {
+    it("should register priceIncreasePending plugin listener", async () => {
+      const mockUnregister = vi
+        .fn<() => Promise<void>>()
+        .mockResolvedValue(undefined);
+      vi.mocked(addPluginListener).mockResolvedValue({
+        plugin: "iap",
+        event: "priceIncreasePending",
+        channelId: 6,
+        unregister: mockUnregister,
+      });
+
+      const callback = vi.fn();
+      await onPriceIncreasePending(callback);
+
+      expect(addPluginListener).toHaveBeenCalledWith(
+        "iap",
+        "priceIncreasePending",
+        callback,
+      );
+    });
+  });
+
+  describe("onRenewalPreferenceChanged", () => {
+    it("should register renewalPreferenceChanged plugin listener", async () => {
+      const mockUnregister = vi
+        .fn<() => Promise<void>>()
+        .mockResolvedValue(undefined);
+      vi.mocked(addPluginListener).mockResolvedValue({
+        plugin: "iap",
+        event: "renewalPreferenceChanged",
+        channelId: 7,
+        unregister: mockUnregister,
+      });
+
+      const callback = vi.fn();
+      await onRenewalPreferenceChanged(callback);
+
+      expect(addPluginListener).toHaveBeenCalledWith(
+        "iap",
+        "renewalPreferenceChanged",
+        callback,
+      );
+    });
+  });
+
+  describe("onBillingIssueDetected", () => {
+    it("should register billingIssueDetected plugin listener", async () => {
+      const mockUnregister = vi
+        .fn<() => Promise<void>>()
+        .mockResolvedValue(undefined);
+      vi.mocked(addPluginListener).mockResolvedValue({
+        plugin: "iap",
+        event: "billingIssueDetected",
+        channelId: 8,
+        unregister: mockUnregister,
+      });
+
+      const callback = vi.fn();
+      await onBillingIssueDetected(callback);
+
+      expect(addPluginListener).toHaveBeenCalledWith(
+        "iap",
+        "billingIssueDetected",
+        callback,
+      );
+    });
+  });
+
+  describe("getAllProductStatuses", () => {
+    it("should get all product statuses for a product type", async () => {
+      const mockStatuses: ProductStatus[] = [
+        {
+          productId: "com.example.premium",
+          isOwned: true,
+          purchaseState: PurchaseState.PURCHASED,
+          purchaseToken: "TOKEN123",
+        },
+      ];
+      vi.mocked(invoke).mockResolvedValue(mockStatuses);
+
+      const result = await getAllProductStatuses("inapp");
+
+      expect(invoke).toHaveBeenCalledWith(
+        "plugin:iap|get_all_product_statuses",
+        {
+          payload: {
+            productType: "inapp",
+          },
+        },
+      );
+      expect(result).toEqual(mockStatuses);
+    });
+
+    it("should default to subs product type", async () => {
+      vi.mocked(invoke).mockResolvedValue([]);
+
+      await getAllProductStatuses();
+
+      expect(invoke).toHaveBeenCalledWith(
+        "plugin:iap|get_all_product_statuses",
+        {
+          payload: {
+            productType: "subs",
+          },
+        },
+      );
+    });
+  });
+
+  describe("canOpenExternalPurchaseLink", () => {
+    it("should return the availability", async () => {
+      const mockAvailability: ExternalPurchaseLinkAvailability = {
+        eligible: true,
+        storefrontCountry: "NLD",
+      };
+      vi.mocked(invoke).mockResolvedValue(mockAvailability);
+
+      const result = await canOpenExternalPurchaseLink();
+
+      expect(invoke).toHaveBeenCalledWith(
+        "plugin:iap|can_open_external_purchase_link",
+      );
+      expect(result).toEqual(mockAvailability);
+    });
+
+    it("should propagate notSupported from invoke", async () => {
+      vi.mocked(invoke).mockRejectedValue(
+        new Error("External purchase links require iOS 17.4 or later"),
+      );
+
+      await expect(canOpenExternalPurchaseLink()).rejects.toThrow(
+        "External purchase links require iOS 17.4 or later",
+      );
+    });
+  });
+
+  describe("openExternalPurchaseLink", () => {
+    it("should invoke open_external_purchase_link", async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await expect(openExternalPurchaseLink()).resolves.toBeUndefined();
+
+      expect(invoke).toHaveBeenCalledWith(
+        "plugin:iap|open_external_purchase_link",
+      );
+    });
+  });
+
+  describe("onStoreMessageReceived", () => {
+    it("should register storeMessageReceived plugin listener", async () => {
+      const mockUnregister = vi
+        .fn<() => Promise<void>>()
+        .mockResolvedValue(undefined);
+      vi.mocked(addPluginListener).mockResolvedValue({
+        plugin: "iap",
+        event: "storeMessageReceived",
+        channelId: 9,
+        unregister: mockUnregister,
+      });
+
+      const callback = vi.fn();
+      await onStoreMessageReceived(callback);
+
+      expect(addPluginListener).toHaveBeenCalledWith(
+        "iap",
+        "storeMessageReceived",
+        callback,
+      );
+    });
+  });
+
+  describe("displayPendingStoreMessages", () => {
+    it("should return the number of messages shown", async () => {
+      vi.mocked(invoke).mockResolvedValue({ displayed: 2 });
+
+      await expect(displayPendingStoreMessages()).resolves.toBe(2);
+
+      expect(invoke).toHaveBeenCalledWith(
+        "plugin:iap|display_pending_store_messages",
+      );
+    });
+  });
+
+  describe("getProductStatus", () => {
+    it("should get product status with correct parameters", async () => {
+      const mockStatus: ProductStatus = {
+        productId: "com.example.premium",
+        isOwned: true,
+        purchaseState: PurchaseState.PURCHASED,
+        purchaseTime: new Date().toISOString(),
+        isAutoRenewing: true,
+        isAcknowledged: true,
+        purchaseToken: "TOKEN123",
+      };
+      vi.mocked(invoke).mockResolvedValue(mockStatus);
+
+      const result = await getProductStatus("com.example.premium", "subs");
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_product_status", {
+        payload: {
+          productId: "com.example.premium",
+          productType: "subs",
+        },
+      });
+      expect(result).toEqual(mockStatus);
+    });
+
+    it("should default to subs product type", async () => {
+      const mockStatus: ProductStatus = {
+        productId: "com.example.premium",
+        isOwned: false,
+      };
+      vi.mocked(invoke).mockResolvedValue(mockStatus);
+
+      await getProductStatus("com.example.premium");
+
+      expect(invoke).toHaveBeenCalledWith("plugin:iap|get_product_status", {
+        payload: {
+          productId: "com.example.premium",
+          productType: "subs",
+        },
+      });
+    });
+
+    it("should indicate product not owned", async () => {
+      const mockStatus: ProductStatus = {
+        productId: "com.example.premium",
+        isOwned: false,
+      };
+      vi.mocked(invoke).mockResolvedValue(mockStatus);
+
+      const result = await getProductStatus("com.example.premium", "subs");
+
+      expect(result.isOwned).toBe(false);
+      expect(result.purchaseState).toBeUndefined();
+    });
+
+    it("should include expiration time for subscriptions", async () => {
+      const now = Date.now();
+      const mockStatus: ProductStatus = {
+        productId: "com.example.premium",
+        isOwned: true,
+        purchaseState: PurchaseState.PURCHASED,
+        purchaseTime: new Date(now).toISOString(),
+        expirationTime: new Date(now + 30 * 24 * 60 * 60 * 1000).toISOString(), // 30 days
+        isAutoRenewing: true,
+      };
+      vi.mocked(invoke).mockResolvedValue(mockStatus);
+
+      const result = await getProductStatus("com.example.premium", "subs");
+
+      expect(result.expirationTime).toBeDefined();
+      expect(Date.parse(result.expirationTime ?? "")).toBeGreaterThan(now);
+    });
+  });
+
+  describe("onPurchaseUpdated", () => {
+    const createMockPluginListener = (unregister: () => Promise<void>) => ({
+      plugin: "iap",
+      event: "purchaseUpdated",
+      channelId: 1,
+      unregister,
+    });
+
+    it("should register plugin listener and return PluginListener", async () => {
+      const mockUnregister = vi
+        .fn<() => Promise<void>>()
+        .mockResolvedValue(undefined);
+      vi.mocked(addPluginListener).mockResolvedValue(
+        createMockPluginListener(mockUnregister),
+      );
+
+      const callback = vi.fn();
+      const listener = await onPurchaseUpdated(callback);
+
+      expect(addPluginListener).toHaveBeenCalledWith(
+        "iap",
+        "purchaseUpdated",
+        callback,
+      );
+      expect(listener.unregister).toBe(mockUnregister);
+    });
+
+    it("should pass callback directly to addPluginListener", async () => {
+      const mockUnregister = vi
+        .fn<() => Promise<void>>()
+        .mockResolvedValue(undefined);
+      vi.mocked(addPluginListener).mockResolvedValue(
+        createMockPluginListener(mockUnregister),
+      );
+
+      const callback = vi.fn();
+      await onPurchaseUpdated(callback);
+
+      expect(addPluginListener).toHaveBeenCalledWith(
+        "iap",
+        "purchaseUpdated",
+        callback,
+      );
+    });
+
+    it("should unregister listener when unregister is called", async () => {
+      const mockUnregister = vi
+        .fn<() => Promise<void>>()
+        .mockResolvedValue(undefined);
+      vi.mocked(addPluginListener).mockResolvedValue(
+        createMockPluginListener(mockUnregister),
+      );
+
+      const callback = vi.fn();
+      const listener = await onPurchaseUpdated(callback);
+
+      await listener.unregister();
+
+      expect(mockUnregister).toHaveBeenCalled();
+    });
+  });
+
+  describe("onTransactionUpdated", () => {
+    it("should register transactionUpdated plugin listener", async () => {
+      const mockUnregister = vi
+        .fn<() => Promise<void>>()
+        .mockResolvedValue(undefined);
+      vi.mocked(addPluginListener).mockResolvedValue({
+        plugin: "iap",
+        event: "transactionUpdated",
+        channelId: 2,
+        unregister: mockUnregister,
+      });
+
+      const callback = vi.fn();
+      const listener = await onTransactionUpdated(callback);
+
+      expect(addPluginListener).toHaveBeenCalledWith(
+        "iap",
+        "transactionUpdated",
+        callback,
+      );
+      expect(listener.unregister).toBe(mockUnregister);
     });
   });
 
@@ -497,6 +1612,9 @@ describe("IAP Plugin", () => {
       expect(PurchaseState.PURCHASED).toBe(0);
       expect(PurchaseState.CANCELED).toBe(1);
       expect(PurchaseState.PENDING).toBe(2);
+      expect(PurchaseState.FAILED).toBe(3);
+      expect(PurchaseState.REFUNDED).toBe(4);
+      expect(PurchaseState.DEFERRED).toBe(5);
     });
   });
 });
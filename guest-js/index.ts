@@ -9,6 +9,39 @@ import {
  */
 export interface InitializeResponse {
   success: boolean;
+  /**
+   * Unfinished transactions delivered as `purchaseCompleted` and finished,
+   * when `autoFinishUnfinished` is set.
+   */
+  finishedTransactions?: number;
+}
+
+/**
+ * Billing period of a subscription, e.g. `{ unit: "month", value: 3 }`
+ */
+export interface SubscriptionPeriod {
+  unit: "day" | "week" | "month" | "year";
+  value: number;
+}
+
+/**
+ * Introductory or promotional discount on a subscription.
+ *
+ * On Android, Google Play does not distinguish the two: the first
+ * developer-defined offer is reported as introductory, the rest as promotional.
+ */
+export interface SubscriptionOfferInfo {
+  /** Offer identifier; absent for StoreKit introductory offers */
+  id?: string;
+  offerType: "introductory" | "promotional" | "winBack";
+  /** Localized price of one offer period */
+  price: string;
+  /** Locale (iOS/macOS) or ISO 4217 currency code (Android/Windows) of the price */
+  priceLocale: string;
+  period: SubscriptionPeriod;
+  /** Number of periods the offer lasts */
+  periodCount: number;
+  paymentMode: "freeTrial" | "payAsYouGo" | "payUpFront";
 }
 
 /**
@@ -49,10 +82,40 @@ export interface Product {
   formattedPrice?: string;
   /** ISO 4217 currency code (e.g., "USD", "EUR") */
   priceCurrencyCode?: string;
-  /** Price in micros (price × 1,000,000). For example, $9.99 = 9990000 */
+  /** Price in micros (price × 1,000,000). For example, $9.99 = 9990000. Regular recurring price for subscriptions */
   priceAmountMicros?: number;
+  /**
+   * Locale `formattedPrice` is formatted in (e.g., "en_US"). The device
+   * locale on Android; absent on Windows.
+   */
+  priceLocale?: string;
   /** Subscription offer details including pricing phases. (Android only) */
   subscriptionOfferDetails?: SubscriptionOffer[];
+  /** Regular billing period of a subscription; absent for one-time products */
+  subscriptionPeriod?: SubscriptionPeriod;
+  /** Introductory offer the customer is eligible for, if any */
+  introductoryOffer?: SubscriptionOfferInfo;
+  /** Promotional offers configured for the subscription */
+  promotionalOffers?: SubscriptionOfferInfo[];
+  /** StoreKit subscription group id (iOS/macOS only) */
+  subscriptionGroupId?: string;
+  /** Whether Family Sharing members get the product too (iOS/macOS only) */
+  isFamilyShareable?: boolean;
+}
+
+/**
+ * A product's price with the data needed to format it.
+ * Keep in sync with PriceInfo in src/models.rs
+ */
+export interface PriceInfo {
+  /** Price in major units (e.g., 4.99). Use `priceAmountMicros` for exact arithmetic */
+  amount: number;
+  /** ISO 4217 currency code (e.g., "USD") */
+  currencyCode: string;
+  /** The store's localized price string (e.g., "$4.99") */
+  formattedPrice: string;
+  /** Locale the store formatted the price in (e.g., "en_US"); empty when unknown */
+  localeIdentifier: string;
 }
 
 /**
@@ -72,8 +135,8 @@ export interface Purchase {
   packageName: string;
   /** Product identifier that was purchased */
   productId: string;
-  /** Unix timestamp (milliseconds) when the purchase was made */
-  purchaseTime: number;
+  /** RFC 3339 time the purchase was made, e.g. `"2024-01-31T12:00:00.000Z"` */
+  purchaseTime: string;
   /** Token used to identify this purchase for acknowledgment and server-side verification */
   purchaseToken: string;
   /** Current state of the purchase. */
@@ -88,10 +151,70 @@ export interface Purchase {
   signature: string;
   /** Original transaction ID. Used to link renewals and restores to the original purchase. (iOS/macOS only) */
   originalId?: string;
+  /** StoreKit `originalTransactionId`, the key the App Store Server API uses for a subscription or purchase. (iOS/macOS only) */
+  originalTransactionId?: string;
+  /** StoreKit `webOrderLineItemId`, unique per subscription period across devices. (iOS/macOS subscriptions only) */
+  webOrderLineItemId?: string;
+  /** StoreKit subscription group, matching `Product.subscriptionGroupId`. Android has no subscription groups and leaves it unset. (iOS/macOS subscriptions only) */
+  subscriptionGroupId?: string;
   /** JWS representation of the signed transaction for server-side validation. (iOS/macOS only) */
   jwsRepresentation?: string;
+  /** Bought by this user or shared by a family member. (iOS/macOS only) */
+  ownershipType?: OwnershipType;
+  /** Initial purchase or automatic subscription renewal. (iOS 17+/macOS 14+ only) */
+  transactionReason?: TransactionReason;
+  /** RFC 3339 time the App Store revoked the transaction (iOS/macOS only) */
+  revocationTime?: string;
+  /** `"developerIssue"` or `"other"` when revoked (iOS/macOS only) */
+  revocationReason?: string;
+  /** `"production"`, `"sandbox"` or `"xcode"` (iOS/macOS only) */
+  environment?: string;
+  /** The purchase's `appAccountToken`, as a lowercase UUID (iOS/macOS only) */
+  appAccountToken?: string;
+  /** Units bought in this transaction (iOS/macOS only) */
+  quantity?: number;
+  /**
+   * RFC 3339 time at which subscription access ends (iOS/macOS only).
+   * Always absent on Android, where Play Billing doesn't expose expiry
+   * on-device; check it server-side with the Play Developer API.
+   */
+  expirationDate?: string;
+  /** RFC 3339 time of the next renewal, when known (iOS/macOS only) */
+  nextRenewalDate?: string;
+  /**
+   * Redeemed offer's identifier. Apple introductory offers have none; on
+   * Android it's only known for purchases made in the current session.
+   */
+  offerId?: string;
+  /**
+   * `"introductory"`, `"promotional"`, `"code"` or `"winBack"`. On Android
+   * only set when the offer is tagged with one of these names in the Play
+   * Console, and never on restored purchases.
+   */
+  offerType?: string;
+  /**
+   * Whether StoreKit verified the transaction's signature (iOS/macOS only).
+   * Always `"verified"` unless `allowUnverifiedTransactions` is set in the
+   * plugin config.
+   */
+  verification?: TransactionVerification;
 }
 
+/**
+ * Keep in sync with OwnershipType in src/models.rs
+ */
+export type OwnershipType = "purchased" | "familyShared" | "unknown";
+
+/**
+ * Keep in sync with TransactionReason in src/models.rs
+ */
+export type TransactionReason = "purchase" | "renewal" | "unknown";
+
+/**
+ * Keep in sync with TransactionVerification in src/models.rs
+ */
+export type TransactionVerification = "verified" | "unverified";
+
 /**
  * Response containing restored purchases
  */
@@ -104,11 +227,27 @@ export interface RestorePurchasesResponse {
  */
 export interface PurchaseHistoryRecord {
   productId: string;
-  purchaseTime: number;
+  /** RFC 3339 */
+  purchaseTime: string;
   purchaseToken: string;
   quantity: number;
   originalJson: string;
   signature: string;
+  /** StoreKit transaction id (iOS/macOS only) */
+  transactionId?: string;
+  /** StoreKit original transaction id (iOS/macOS only) */
+  originalTransactionId?: string;
+  /** StoreKit web order line item id (iOS/macOS subscriptions only) */
+  webOrderLineItemId?: string;
+  productType?: "subs" | "inapp";
+  /** Initial purchase or automatic subscription renewal. (iOS 17+/macOS 14+ only) */
+  transactionReason?: TransactionReason;
+  /** RFC 3339 time the App Store revoked the transaction (iOS/macOS only) */
+  revocationTime?: string;
+  /** `"developerIssue"` or `"other"` when revoked (iOS/macOS only) */
+  revocationReason?: string;
+  /** Same as `Purchase.verification` (iOS/macOS only) */
+  verification?: TransactionVerification;
 }
 
 /**
@@ -119,12 +258,18 @@ export interface GetPurchaseHistoryResponse {
 }
 
 /**
- * Purchase state enumeration
+ * Purchase state enumeration.
+ *
+ * Values outside this list may arrive from newer native backends; treat them
+ * as unknown rather than failing.
  */
 export enum PurchaseState {
   PURCHASED = 0,
   CANCELED = 1,
   PENDING = 2,
+  FAILED = 3,
+  REFUNDED = 4,
+  DEFERRED = 5,
 }
 
 /**
@@ -134,11 +279,48 @@ export interface ProductStatus {
   productId: string;
   isOwned: boolean;
   purchaseState?: PurchaseState;
-  purchaseTime?: number;
-  expirationTime?: number;
+  /** RFC 3339 */
+  purchaseTime?: string;
+  /** RFC 3339 */
+  expirationTime?: string;
   isAutoRenewing?: boolean;
   isAcknowledged?: boolean;
   purchaseToken?: string;
+  /** Renewal details for owned auto-renewable subscriptions */
+  renewalInfo?: SubscriptionRenewalInfo;
+  /**
+   * Most recent transaction for the product, kept when revoked or expired
+   * (iOS/macOS). Android only sets it for an active purchase.
+   */
+  latestTransaction?: Purchase;
+  /**
+   * (macOS) Answered from the offline entitlement cache because the App
+   * Store was unreachable; only `isOwned` and `expirationTime` are set.
+   * See `offlineEntitlementMaxAgeSecs`.
+   */
+  fromCache?: boolean;
+}
+
+/**
+ * Keep in sync with `PriceIncreaseStatus` in `src/models.rs`.
+ */
+export type PriceIncreaseStatus = "noIncreasePending" | "pending" | "agreed";
+
+/**
+ * Renewal state of an owned subscription. Android only reports
+ * `autoRenewEnabled`; the other fields come from StoreKit on iOS/macOS.
+ */
+export interface SubscriptionRenewalInfo {
+  autoRenewEnabled: boolean;
+  /** RFC 3339 time the current period ends */
+  expirationDate?: string;
+  /** Whether the store is retrying a failed renewal charge */
+  billingRetryPeriod: boolean;
+  /** RFC 3339 time the billing grace period ends */
+  gracePeriodExpiresDate?: string;
+  priceIncreaseStatus?: PriceIncreaseStatus;
+  /** Product the subscription renews into at the next billing date */
+  renewalProductId?: string;
 }
 
 /**
@@ -162,6 +344,24 @@ export enum SubscriptionReplacementMode {
   KEEP_EXISTING = 6,
 }
 
+/**
+ * A StoreKit promotional offer signed by your server with an App Store
+ * Connect subscription key. Every field must match what was signed.
+ * Keep in sync with `PromotionalOffer` in `src/models.rs`.
+ */
+export interface PromotionalOffer {
+  /** Offer identifier from App Store Connect */
+  offerId: string;
+  /** Identifier of the subscription key used to sign */
+  keyId: string;
+  /** Lowercase UUID included in the signature */
+  nonce: string;
+  /** Base64-encoded signature */
+  signature: string;
+  /** Unix timestamp (milliseconds) included in the signature */
+  timestamp: number;
+}
+
 /**
  * Optional parameters for purchase requests
  */
@@ -172,8 +372,16 @@ export interface PurchaseOptions {
   obfuscatedAccountId?: string;
   /** Obfuscated profile identifier for fraud prevention (Android only) */
   obfuscatedProfileId?: string;
-  /** App account token - must be a valid UUID string (iOS only) */
+  /** App account token - must be a valid UUID string (iOS/macOS only) */
   appAccountToken?: string;
+  /** Number of units to buy in one transaction, 1 to 10, consumables only (iOS/macOS only) */
+  quantity?: number;
+  /**
+   * Whether `purchase()` finishes the StoreKit transaction before returning
+   * (iOS/macOS only). Defaults to `true`. Set to `false` to validate the
+   * purchase on your server first, then call {@link finishTransaction}.
+   */
+  autoFinish?: boolean;
   /**
    * Product ID of the existing subscription to replace (Android only).
    * When set, the purchase becomes a subscription upgrade/downgrade via the
@@ -204,13 +412,29 @@ export interface PurchaseOptions {
    * identity-bind the purchase.
    */
   publisherUserId?: string;
+  /**
+   * Signed promotional offer to apply (iOS/macOS only, ignored elsewhere).
+   * A rejected signature fails with `invalidOfferSignature`; an unknown or
+   * ineligible offer with `invalidOffer`.
+   */
+  promotionalOffer?: PromotionalOffer;
+  /**
+   * Id of a win-back offer from {@link getWinBackOffers} to apply
+   * (iOS 18+/macOS 15+ only, ignored elsewhere). Older OS versions reject
+   * with `notSupported`.
+   */
+  winBackOfferId?: string;
 }
 
 /**
  * Initialize the IAP plugin.
  *
- * @deprecated This function is no longer needed. The billing client is now initialized automatically when the plugin loads. This function will be removed in the next major release.
- * @returns Promise resolving to `{ success: true }` for backward compatibility
+ * With `autoFinishUnfinished` set (iOS/macOS), delivers every unfinished
+ * transaction once through {@link onPurchaseCompleted} and finishes it, so
+ * register listeners first.
+ *
+ * @deprecated Not needed otherwise: the billing client is now initialized automatically when the plugin loads, and without `autoFinishUnfinished` this rejects. This function will be removed in the next major release.
+ * @returns Promise resolving to `{ success: true, finishedTransactions }`
  */
 export async function initialize(): Promise<InitializeResponse> {
   return await invoke<InitializeResponse>("plugin:iap|initialize");
@@ -221,6 +445,7 @@ export async function initialize(): Promise<InitializeResponse> {
  *
  * @param productIds - Array of product identifiers to fetch
  * @param productType - Type of products: "subs" for subscriptions, "inapp" for one-time purchases
+ * @param options - `forceRefresh` skips the macOS products cache
  * @returns Promise resolving to product information
  * @example
  * ```typescript
@@ -233,15 +458,58 @@ export async function initialize(): Promise<InitializeResponse> {
 export async function getProducts(
   productIds: string[],
   productType: "subs" | "inapp" = "subs",
+  options?: {
+    /**
+     * (macOS) Query StoreKit even if a cached response for these products
+     * is still fresh. See `productsCacheTtlSecs`.
+     */
+    forceRefresh?: boolean;
+  },
 ): Promise<GetProductsResponse> {
   return await invoke<GetProductsResponse>("plugin:iap|get_products", {
     payload: {
       productIds,
       productType,
+      ...options,
     },
   });
 }
 
+/**
+ * Collect a product's price fields into a {@link PriceInfo}.
+ *
+ * @param product - Product returned by {@link getProducts}
+ * @returns The price, or undefined when the store reported none
+ * @example
+ * ```typescript
+ * // Monthly equivalent of a yearly subscription
+ * const info = priceInfo(yearlyProduct);
+ * if (info) {
+ *   const locale = info.localeIdentifier.replace("_", "-") || undefined;
+ *   const perMonth = new Intl.NumberFormat(locale, {
+ *     style: "currency",
+ *     currency: info.currencyCode,
+ *   }).format(info.amount / 12);
+ * }
+ * ```
+ */
+export function priceInfo(product: Product): PriceInfo | undefined {
+  const { formattedPrice, priceCurrencyCode, priceAmountMicros } = product;
+  if (
+    formattedPrice === undefined ||
+    priceCurrencyCode === undefined ||
+    priceAmountMicros === undefined
+  ) {
+    return undefined;
+  }
+  return {
+    amount: priceAmountMicros / 1_000_000,
+    currencyCode: priceCurrencyCode,
+    formattedPrice,
+    localeIdentifier: product.priceLocale ?? "",
+  };
+}
+
 /**
  * Initiate a purchase for the specified product.
  *
@@ -308,6 +576,12 @@ export async function restorePurchases(
     serviceTicket?: string;
     /** See {@link PurchaseOptions.publisherUserId}. */
     publisherUserId?: string;
+    /**
+     * (iOS/macOS) Call `AppStore.sync()` first so purchases made on another
+     * device show up. May prompt for App Store credentials; dismissing the
+     * prompt rejects with `userCancelled`.
+     */
+    forceSync?: boolean;
   },
 ): Promise<RestorePurchasesResponse> {
   return await invoke<RestorePurchasesResponse>(
@@ -321,9 +595,67 @@ export async function restorePurchases(
   );
 }
 
+/**
+ * Response containing the user's current entitlements
+ */
+export interface GetCurrentEntitlementsResponse {
+  purchases: Purchase[];
+}
+
+/**
+ * Get everything the user currently owns: active subscriptions and
+ * non-consumables, excluding revoked and expired transactions.
+ *
+ * Reads the store's local cache (`Transaction.currentEntitlements` on
+ * iOS/macOS, `queryPurchasesAsync` for both product types on Android), so it
+ * works offline and never prompts for store credentials. Cheaper than
+ * {@link restorePurchases} and covers every product in one call, unlike
+ * {@link getProductStatus}.
+ *
+ * @returns Promise resolving to the owned purchases
+ * @example
+ * ```typescript
+ * const { purchases } = await getCurrentEntitlements();
+ * const owned = new Set(purchases.map(p => p.productId));
+ * ```
+ */
+export async function getCurrentEntitlements(): Promise<GetCurrentEntitlementsResponse> {
+  return await invoke<GetCurrentEntitlementsResponse>(
+    "plugin:iap|get_current_entitlements",
+  );
+}
+
+/**
+ * Get the user's current entitlement to a single product, or `null` if they
+ * don't own it.
+ *
+ * Like {@link getCurrentEntitlements} this reads the store's local cache
+ * (`Transaction.currentEntitlement(for:)` on iOS/macOS), so it's cheap enough
+ * to call every time the app comes to the foreground.
+ *
+ * @param productId - The product to look up
+ * @returns Promise resolving to the owned purchase, or `null`
+ * @example
+ * ```typescript
+ * const premium = (await currentEntitlement('premium_lifetime')) !== null;
+ * ```
+ */
+export async function currentEntitlement(
+  productId: string,
+): Promise<Purchase | null> {
+  return await invoke<Purchase | null>("plugin:iap|current_entitlement", {
+    payload: {
+      productId,
+    },
+  });
+}
+
 /**
  * Get the user's purchase history.
- * Note: Not supported on all platforms.
+ *
+ * On iOS and macOS this walks StoreKit's `Transaction.all`, so it includes
+ * expired subscriptions, finished consumables and revoked transactions.
+ * Android and Windows reject — neither store exposes past transactions here.
  *
  * @returns Promise resolving to purchase history
  * @example
@@ -340,6 +672,48 @@ export async function getPurchaseHistory(): Promise<GetPurchaseHistoryResponse>
   );
 }
 
+/**
+ * Filters for {@link getPurchaseHistoryFiltered}. Unset fields don't filter.
+ * Keep in sync with PurchaseHistoryRequest in src/models.rs
+ */
+export interface PurchaseHistoryRequest {
+  productType?: "subs" | "inapp";
+  /** Only purchases made at or after this Unix timestamp (milliseconds) */
+  sinceTimestamp?: number;
+  /** Keep at most this many records, newest first */
+  limit?: number;
+}
+
+/**
+ * Get the user's purchase history narrowed by product type and purchase date,
+ * newest first. Useful for accounts with long histories, where
+ * {@link getPurchaseHistory} returns more than the app needs.
+ *
+ * Filtering happens natively while walking StoreKit's `Transaction.all`.
+ * Android and Windows reject with `notSupported`, as for `getPurchaseHistory`.
+ *
+ * @param request - Filters to apply
+ * @returns Promise resolving to the matching purchase history
+ * @example
+ * ```typescript
+ * const { history } = await getPurchaseHistoryFiltered({
+ *   productType: 'inapp',
+ *   sinceTimestamp: Date.now() - 30 * 24 * 60 * 60 * 1000,
+ *   limit: 50,
+ * });
+ * ```
+ */
+export async function getPurchaseHistoryFiltered(
+  request: PurchaseHistoryRequest = {},
+): Promise<GetPurchaseHistoryResponse> {
+  return await invoke<GetPurchaseHistoryResponse>(
+    "plugin:iap|get_purchase_history_filtered",
+    {
+      payload: request,
+    },
+  );
+}
+
 /**
  * Acknowledge a non-consumable purchase (subscriptions, durable products).
  *
@@ -399,56 +773,1036 @@ export async function consumePurchase(purchaseToken: string): Promise<void> {
 }
 
 /**
- * Get the current status of a product for the user.
- * Checks if the product is owned, expired, or available for purchase.
+ * Finish a StoreKit transaction that was left open by calling `purchase()`
+ * with `autoFinish: false` (iOS/macOS only).
  *
- * @param productId - Product identifier to check
- * @param productType - Type of product: "subs" or "inapp"
- * @returns Promise resolving to product status
+ * Until a transaction is finished StoreKit keeps redelivering it, so call this
+ * once your server has validated the purchase. Other platforms reject with
+ * `notSupported`; use {@link acknowledgePurchase} or {@link consumePurchase}
+ * there instead.
+ *
+ * @param transactionId - The purchase's `purchaseToken` (the StoreKit transaction id)
+ * @throws Rejects with `transactionNotFound` if neither an unfinished
+ *   transaction nor a current entitlement has this id
  * @example
  * ```typescript
- * const status = await getProductStatus('com.example.premium', 'subs');
- * if (status.isOwned) {
- *   console.log('User owns this product');
- *   if (status.isAutoRenewing) {
- *     console.log('Subscription is auto-renewing');
- *   }
+ * const result = await purchase('com.example.premium', 'subs', { autoFinish: false });
+ * await verifyOnServer(result.jwsRepresentation);
+ * await finishTransaction(result.purchaseToken);
+ * ```
+ */
+export async function finishTransaction(transactionId: string): Promise<void> {
+  await invoke("plugin:iap|finish_transaction", {
+    payload: {
+      transactionId,
+    },
+  });
+}
+
+/**
+ * How the user left the refund request sheet.
+ * Keep in sync with `RefundRequestResult` in `src/models.rs`.
+ */
+export type RefundRequestResult = "success" | "userCancelled";
+
+/**
+ * Present StoreKit's refund request sheet for one of the user's transactions
+ * (iOS/macOS only).
+ *
+ * `"success"` means the request was submitted; Apple decides later and a
+ * granted refund arrives as a `revoked` transaction event.
+ *
+ * @param transactionId - The purchase's `purchaseToken` (the StoreKit transaction id)
+ * @throws Rejects with `transactionNotFound` if the id isn't one of the
+ *   current user's transactions, `duplicateRequest` if a refund was already
+ *   requested, and `notSupported` on Android, Windows and iOS < 15.
+ * @example
+ * ```typescript
+ * const result = await beginRefundRequest(purchase.purchaseToken);
+ * if (result === 'success') {
+ *   showToast('Refund requested');
  * }
  * ```
  */
-export async function getProductStatus(
-  productId: string,
-  productType: "subs" | "inapp" = "subs",
-): Promise<ProductStatus> {
-  return await invoke<ProductStatus>("plugin:iap|get_product_status", {
+export async function beginRefundRequest(
+  transactionId: string,
+): Promise<RefundRequestResult> {
+  return await invoke<RefundRequestResult>("plugin:iap|begin_refund_request", {
     payload: {
-      productId,
-      productType,
+      transactionId,
     },
   });
 }
 
 /**
- * Listen for purchase updates.
- * This event is triggered when a purchase state changes.
+ * Outcome of {@link requestRefund}.
+ * Keep in sync with `RefundRequestStatus` in `src/models.rs`.
+ */
+export type RefundRequestStatus = "success" | "userCancelled" | "error";
+
+/**
+ * Like {@link beginRefundRequest}, but a refund request StoreKit failed to
+ * submit (including a duplicate request) resolves with `"error"` instead of
+ * rejecting (iOS/macOS only).
  *
- * @param callback - Function to call when a purchase is updated
+ * @param transactionId - The purchase's `purchaseToken` (the StoreKit transaction id)
+ * @throws Rejects with `transactionNotFound` if the id isn't one of the
+ *   current user's transactions, and `notSupported` on Android and Windows.
+ * @example
+ * ```typescript
+ * const status = await requestRefund(purchase.purchaseToken);
+ * if (status === 'error') {
+ *   openSupportPage();
+ * }
+ * ```
+ */
+export async function requestRefund(
+  transactionId: string,
+): Promise<RefundRequestStatus> {
+  return await invoke<RefundRequestStatus>("plugin:iap|request_refund", {
+    payload: {
+      transactionId,
+    },
+  });
+}
+
+/**
+ * Original app purchase details from StoreKit's `AppTransaction`.
+ */
+export interface AppTransactionInfo {
+  bundleId: string;
+  /** Build version the app transaction was signed for */
+  appVersion: string;
+  /** Build version the user originally bought or downloaded */
+  originalAppVersion: string;
+  /** RFC 3339 time of the original app purchase */
+  originalPurchaseDate: string;
+  /** `"production"` or `"sandbox"`, which includes Xcode StoreKit testing */
+  environment: IapEnvironment;
+  /** Signed JWS of the app transaction for server-side verification */
+  jwsRepresentation: string;
+}
+
+/**
+ * Get purchases that completed in the store but were never finished by the
+ * app, e.g. because it crashed before delivering the content.
+ *
+ * On iOS/macOS these are StoreKit's `Transaction.unfinished`; pass each
+ * `purchaseToken` to `finishTransaction()` once handled. On Android these are
+ * purchased but unacknowledged purchases; Google Play refunds them after
+ * three days, so acknowledge or consume them promptly.
+ *
+ * @throws Rejects with `notSupported` on Windows.
+ * @example
+ * ```typescript
+ * for (const purchase of await getUnfinishedTransactions()) {
+ *   await deliver(purchase);
+ *   await finishTransaction(purchase.purchaseToken);
+ * }
+ * ```
+ */
+export async function getUnfinishedTransactions(): Promise<Purchase[]> {
+  return await invoke<Purchase[]>("plugin:iap|get_unfinished_transactions");
+}
+
+/**
+ * Transactions Apple refunded or removed from Family Sharing (iOS/macOS
+ * only), newest revocation first, with `revocationTime` and
+ * `revocationReason` set. Includes revocations that happened while the app
+ * wasn't running, so call it on launch alongside the `purchaseRevoked` event.
+ *
+ * @param options.since - Only revocations at or after this time, e.g. the
+ *   last time the app reconciled
+ * @returns Promise resolving to the revoked purchases
+ * @throws Rejects with `notSupported` on Android, Windows and Linux.
+ * @example
+ * ```typescript
+ * for (const purchase of await getRevokedPurchases({ since: lastCheck })) {
+ *   await revokeEntitlement(purchase.productId);
+ * }
+ * ```
+ */
+export async function getRevokedPurchases(options?: {
+  since?: string;
+}): Promise<Purchase[]> {
+  return await invoke<Purchase[]>("plugin:iap|get_revoked_purchases", {
+    payload: { ...options },
+  });
+}
+
+/**
+ * The App Store storefront (or Google Play country) the user is buying from.
+ */
+export interface StorefrontInfo {
+  /** StoreKit storefront identifier; on Android this is the country code */
+  id: string;
+  /** ISO 3166-1 alpha-3 on iOS/macOS ("USA"), alpha-2 on Android ("US") */
+  countryCode: string;
+}
+
+/**
+ * Get the user's storefront, e.g. to pick pricing or legal copy by country.
+ *
+ * Uses `Storefront.current` on iOS/macOS and the Play billing config on
+ * Android.
+ *
+ * @throws Rejects with `storefrontUnavailable` when StoreKit has no
+ *   storefront, and `notSupported` on Windows and Linux.
+ * @example
+ * ```typescript
+ * const { countryCode } = await getStorefront();
+ * ```
+ */
+export async function getStorefront(): Promise<StorefrontInfo> {
+  return await invoke<StorefrontInfo>("plugin:iap|get_storefront");
+}
+
+/**
+ * Listen for storefront changes (iOS/macOS only), e.g. when the user switches
+ * Apple IDs or App Store regions. Prices fetched earlier are stale once this
+ * fires, so re-run `getProducts()`.
+ *
+ * @param callback - Function to call with the new storefront
  * @returns Promise resolving to a PluginListener that can be used to stop listening
  * @example
  * ```typescript
- * const listener = await onPurchaseUpdated((purchase) => {
- *   console.log(`Purchase updated: ${purchase.productId}`);
- *   if (purchase.purchaseState === PurchaseState.PURCHASED) {
- *     // Handle successful purchase
- *   }
+ * const listener = await onStorefrontChanged(async () => {
+ *   products = await getProducts(productIds);
  * });
- *
- * // Later, stop listening
- * await listener.unregister();
  * ```
  */
-export async function onPurchaseUpdated(
-  callback: (purchase: Purchase) => void,
+export async function onStorefrontChanged(
+  callback: (storefront: StorefrontInfo) => void,
 ): Promise<PluginListener> {
-  return await addPluginListener("iap", "purchaseUpdated", callback);
+  return await addPluginListener("iap", "storefrontChanged", callback);
+}
+
+/**
+ * Get the original app purchase (iOS 16+/macOS only).
+ *
+ * Useful when migrating from a paid app to in-app purchases: compare
+ * `originalAppVersion` against the last paid version to grant existing users
+ * their entitlement.
+ *
+ * @param forceRefresh - Fetch a fresh copy from the App Store. This shows the
+ *   App Store sign-in prompt, so only pass `true` from an explicit user action.
+ * @throws Rejects with `appTransactionUnavailable` when StoreKit can't produce
+ *   the app transaction (e.g. an unsigned development build),
+ *   `verificationFailed` if its signature doesn't verify, and `notSupported`
+ *   on Android and Windows.
+ * @example
+ * ```typescript
+ * const appTx = await getAppTransaction();
+ * if (appTx.originalAppVersion < '2.0') {
+ *   unlockPremium();
+ * }
+ * ```
+ */
+export async function getAppTransaction(
+  forceRefresh = false,
+): Promise<AppTransactionInfo> {
+  return await invoke<AppTransactionInfo>("plugin:iap|get_app_transaction", {
+    payload: {
+      forceRefresh,
+    },
+  });
+}
+
+/**
+ * StoreKit renewal state of a subscription.
+ * Keep in sync with `SubscriptionState` in `src/models.rs`.
+ */
+export type SubscriptionState =
+  | "subscribed"
+  | "expired"
+  | "inBillingRetry"
+  | "inGracePeriod"
+  | "revoked"
+  | "unknown";
+
+/**
+ * Why a subscription expired.
+ * Keep in sync with `ExpirationReason` in `src/models.rs`.
+ */
+export type ExpirationReason =
+  | "autoRenewDisabled"
+  | "billingError"
+  | "didNotConsentToPriceIncrease"
+  | "productUnavailable"
+  | "unknown";
+
+/**
+ * One status of a subscription group, combining the latest transaction with
+ * its renewal info.
+ */
+export interface SubscriptionStatus {
+  /** Product of the latest transaction; may differ from the queried product after a plan change */
+  productId: string;
+  state: SubscriptionState;
+  transactionId: string;
+  originalTransactionId: string;
+  /** Whether access comes from a family member's purchase */
+  isFamilyShared: boolean;
+  /** Subscription group of `productId` (iOS/macOS only) */
+  subscriptionGroupId?: string;
+  willAutoRenew: boolean;
+  /** RFC 3339 time the current period ends */
+  expirationDate?: string;
+  /** RFC 3339 time the billing grace period ends */
+  gracePeriodExpirationDate?: string;
+  /**
+   * Whether the store is still retrying the renewal charge. Stays `true`
+   * through the grace period; absent when `false`.
+   */
+  billingRetryPeriod?: boolean;
+  /** Product the subscription will renew into */
+  autoRenewProductId?: string;
+  /** Only set once the subscription has expired */
+  expirationReason?: ExpirationReason;
+  /** `"pending"` while the user hasn't consented to a price increase */
+  priceIncreaseStatus?: PriceIncreaseStatus;
+  /** Price of the next renewal in micros. iOS 17+ and macOS 14+ only */
+  renewalPriceAmountMicros?: number;
+  /** ISO 4217 currency of `renewalPriceAmountMicros` */
+  renewalCurrency?: string;
+}
+
+export interface GetSubscriptionStatusResponse {
+  statuses: SubscriptionStatus[];
+}
+
+/**
+ * A StoreKit win-back offer and whether the current user can redeem it.
+ * Keep in sync with `WinBackOffer` in `src/models.rs`.
+ */
+export interface WinBackOffer extends SubscriptionOfferInfo {
+  isEligible: boolean;
+}
+
+export interface GetWinBackOffersResponse {
+  /** Empty before iOS 18/macOS 15 */
+  offers: WinBackOffer[];
+}
+
+/**
+ * Get the win-back offers of a subscription for lapsed subscribers
+ * (iOS/macOS only).
+ *
+ * Resolves with an empty list on OS versions without win-back offers
+ * (before iOS 18/macOS 15).
+ *
+ * @param productId - Identifier of an auto-renewable subscription
+ * @throws Rejects with `productNotFound` for unknown products,
+ *   `notSubscription` for products that aren't auto-renewable subscriptions,
+ *   and `notSupported` on Android and Windows.
+ * @example
+ * ```typescript
+ * const { offers } = await getWinBackOffers('com.example.premium');
+ * const offer = offers.find(o => o.isEligible);
+ * if (offer) {
+ *   await purchase('com.example.premium', 'subs', { winBackOfferId: offer.id });
+ * }
+ * ```
+ */
+export async function getWinBackOffers(
+  productId: string,
+): Promise<GetWinBackOffersResponse> {
+  return await invoke<GetWinBackOffersResponse>(
+    "plugin:iap|get_win_back_offers",
+    {
+      payload: {
+        productId,
+      },
+    },
+  );
+}
+
+/**
+ * Store environment the app runs against.
+ * Keep in sync with IapEnvironment in src/models.rs
+ */
+export type IapEnvironment =
+  | "production"
+  | "sandbox"
+  | "testFlight"
+  | "androidTest";
+
+/**
+ * Detect whether the app runs against the production store or a test setup,
+ * e.g. to tag logs or turn off analytics outside production.
+ *
+ * iOS/macOS read `AppTransaction.environment` (iOS falls back to the receipt
+ * URL before iOS 16); `testFlight` is only detected on iOS. Android reports
+ * `androidTest` for debuggable builds and installs from outside the Play Store.
+ *
+ * @returns Promise resolving to the current environment
+ * @throws Rejects with `appTransactionUnavailable` on macOS when StoreKit
+ *   can't load the app transaction, and `notSupported` on Windows.
+ */
+export async function getEnvironment(): Promise<IapEnvironment> {
+  return await invoke<IapEnvironment>("plugin:iap|get_environment");
+}
+
+/**
+ * Store platform a receipt came from.
+ * Keep in sync with Platform in src/models.rs
+ */
+export type Platform = "ios" | "macos" | "android" | "windows";
+
+/**
+ * What a backend needs to verify a purchase with the store.
+ * Keep in sync with PurchaseReceipt in src/models.rs
+ */
+export interface PurchaseReceipt {
+  platform: Platform;
+  /** Base64 App Store receipt on iOS/macOS, the purchase's `originalJson` on Android */
+  receiptData: string;
+  /** Play purchase token for the Play Developer API (Android only) */
+  purchaseToken?: string;
+  productId: string;
+  transactionId: string;
+}
+
+/**
+ * Get the receipt for a transaction, to send to your own backend for
+ * verification.
+ *
+ * @param transactionId - Transaction id on iOS/macOS, order id or purchase token on Android
+ * @returns Promise resolving to the receipt
+ * @throws Rejects with `transactionNotFound` when the transaction isn't
+ *   found, `receiptUnavailable` on iOS/macOS when the app has no App Store
+ *   receipt, and `notSupported` on Windows.
+ */
+export async function getReceipt(
+  transactionId: string,
+): Promise<PurchaseReceipt> {
+  return await invoke<PurchaseReceipt>("plugin:iap|get_receipt", {
+    payload: {
+      transactionId,
+    },
+  });
+}
+
+/**
+ * Re-download the App Store receipt (iOS/macOS only), e.g. when
+ * {@link getReceipt} rejects with `receiptUnavailable` on a fresh TestFlight
+ * install. May show the App Store sign-in prompt.
+ *
+ * @returns Promise that resolves once the receipt has been refreshed
+ * @throws Rejects with `receiptRefreshFailed` when StoreKit can't refresh it,
+ *   and `notSupported` on Android, Windows and Linux.
+ */
+export async function refreshReceipt(): Promise<void> {
+  return await invoke("plugin:iap|refresh_receipt");
+}
+
+/**
+ * Get the app's whole App Store receipt (iOS/macOS only), base64-encoded,
+ * for legacy `verifyReceipt` validation on your backend.
+ *
+ * @param options.refreshIfMissing - Re-download the receipt first when the
+ *   app has none. May show the App Store sign-in prompt.
+ * @returns Promise resolving to the base64-encoded receipt
+ * @throws Rejects with `receiptUnavailable` when there's no receipt and
+ *   refreshing wasn't requested, was declined or failed, and `notSupported`
+ *   on Android, Windows and Linux.
+ */
+export async function getAppReceipt(options?: {
+  refreshIfMissing?: boolean;
+}): Promise<string> {
+  return await invoke<string>("plugin:iap|get_app_receipt", {
+    payload: {
+      refreshIfMissing: options?.refreshIfMissing ?? false,
+    },
+  });
+}
+
+/**
+ * Whether purchasing is possible on this device.
+ * Keep in sync with CanMakePaymentsResponse in src/models.rs
+ */
+export interface CanMakePaymentsResponse {
+  canMakePayments: boolean;
+  /**
+   * Why purchasing is unavailable: `"restricted"` (Screen Time, parental
+   * controls or device management on iOS/macOS), `"billingUnavailable"`,
+   * `"billingUnsupported"` (outdated Play Store) or `"notSupported"`.
+   */
+  reason?: string;
+}
+
+/**
+ * Check whether the user can make purchases, so a paywall can disable its
+ * buy button up front instead of waiting for {@link purchase} to fail.
+ *
+ * @returns Promise resolving to the availability and, when unavailable, why.
+ *   Resolves with `canMakePayments: false` rather than rejecting on
+ *   platforms without a store.
+ */
+export async function canMakePayments(): Promise<CanMakePaymentsResponse> {
+  return await invoke("plugin:iap|can_make_payments");
+}
+
+/**
+ * Whether the plugin can reach a store on this platform.
+ * Keep in sync with IsSupportedResponse in src/models.rs
+ */
+export interface IsSupportedResponse {
+  isSupported: boolean;
+  /**
+   * Why the plugin is unusable: `"storeKitUnavailable"` (macOS older than
+   * StoreKit 2 requires) or `"notSupported"` (no store on this platform).
+   */
+  reason?: string;
+}
+
+/**
+ * Check whether in-app purchases work on this platform and OS version, so the
+ * app can hide its store UI instead of handling a rejection from every call.
+ *
+ * @returns Promise resolving to the support status and, when unsupported, why.
+ *   When `reason` is `storeKitUnavailable`, every other command rejects with
+ *   that code.
+ */
+export async function isSupported(): Promise<IsSupportedResponse> {
+  return await invoke("plugin:iap|is_supported");
+}
+
+/**
+ * Whether the user can still redeem a subscription's introductory offer.
+ * `unknown` means the store doesn't say (Android without a free trial offer).
+ * Keep in sync with IntroOfferEligibility in src/models.rs
+ */
+export type IntroOfferEligibility = "eligible" | "ineligible" | "unknown";
+
+/**
+ * Keep in sync with IsEligibleForIntroOfferResponse in src/models.rs
+ */
+export interface IsEligibleForIntroOfferResponse {
+  eligibility: IntroOfferEligibility;
+  /** StoreKit subscription group; absent on Android */
+  subscriptionGroupId?: string;
+}
+
+/**
+ * Check whether the user can still redeem a subscription's introductory offer,
+ * e.g. to choose between "Start free trial" and "Subscribe".
+ *
+ * On iOS/macOS this is `Product.SubscriptionInfo.isEligibleForIntroOffer`, which
+ * applies to the whole subscription group. Android reports `eligible` when Play
+ * offers the account a free trial and `unknown` otherwise.
+ *
+ * @param productId - Identifier of an auto-renewable subscription
+ * @throws Rejects with `productNotFound` for unknown products,
+ *   `notSubscription` for non-subscriptions, and `notSupported` on Windows.
+ * @example
+ * ```typescript
+ * const { eligibility } = await isEligibleForIntroOffer('com.example.premium');
+ * const label = eligibility === 'eligible' ? 'Start free trial' : 'Subscribe';
+ * ```
+ */
+export async function isEligibleForIntroOffer(
+  productId: string,
+): Promise<IsEligibleForIntroOfferResponse> {
+  return await invoke<IsEligibleForIntroOfferResponse>(
+    "plugin:iap|is_eligible_for_intro_offer",
+    {
+      payload: {
+        productId,
+      },
+    },
+  );
+}
+
+/**
+ * Batch form of {@link isEligibleForIntroOffer} for paywalls listing several plans.
+ *
+ * Unknown ids and products that aren't auto-renewable subscriptions are left
+ * out of the result. On Android `false` also covers subscriptions without a
+ * free trial offer.
+ *
+ * @param productIds - Identifiers of auto-renewable subscriptions
+ * @returns Eligibility keyed by product id
+ * @throws Rejects with `notSupported` on Windows.
+ * @example
+ * ```typescript
+ * const eligibility = await checkIntroductoryOfferEligibility([
+ *   'com.example.monthly',
+ *   'com.example.yearly',
+ * ]);
+ * const showTrial = eligibility['com.example.yearly'] ?? false;
+ * ```
+ */
+export async function checkIntroductoryOfferEligibility(
+  productIds: string[],
+): Promise<Record<string, boolean>> {
+  return await invoke<Record<string, boolean>>(
+    "plugin:iap|check_introductory_offer_eligibility",
+    {
+      payload: {
+        productIds,
+      },
+    },
+  );
+}
+
+/**
+ * Get the renewal status of a subscription.
+ *
+ * Returns every status in the product's subscription group; with Family
+ * Sharing there is one per purchaser. Android only sees subscriptions the
+ * user currently has access to, so it returns a single `subscribed` status
+ * or none at all.
+ *
+ * @param productId - Identifier of an auto-renewable subscription
+ * @throws Rejects with `productNotFound` for unknown products,
+ *   `notSubscription` for products that aren't auto-renewable subscriptions,
+ *   and `notSupported` on Windows.
+ * @example
+ * ```typescript
+ * const { statuses } = await getSubscriptionStatus('com.example.premium');
+ * if (statuses.some(s => s.state === 'inBillingRetry')) {
+ *   showUpdatePaymentBanner();
+ * }
+ * ```
+ */
+export async function getSubscriptionStatus(
+  productId: string,
+): Promise<GetSubscriptionStatusResponse> {
+  return await invoke<GetSubscriptionStatusResponse>(
+    "plugin:iap|get_subscription_status",
+    {
+      payload: {
+        productId,
+      },
+    },
+  );
+}
+
+/**
+ * Show the system UI for managing subscriptions.
+ *
+ * On iOS this presents StoreKit's manage subscriptions sheet and resolves once
+ * the user dismisses it; if anything changed, `subscriptionStatusChanged`
+ * fires afterwards. macOS has no native sheet, so the App Store's
+ * subscription page is opened instead and the promise resolves immediately.
+ * Android likewise opens the Play Store's subscription center.
+ *
+ * @throws Rejects with `notSupported` on Windows and iOS < 15.
+ * @example
+ * ```typescript
+ * await showManageSubscriptions();
+ * ```
+ */
+export async function showManageSubscriptions(): Promise<void> {
+  return await invoke("plugin:iap|show_manage_subscriptions");
+}
+
+/**
+ * Present the App Store's offer code redemption sheet (iOS 15+/macOS 15+).
+ *
+ * Resolves once the sheet is dismissed. On iOS 15 it falls back to StoreKit 1's
+ * `SKPaymentQueue.presentCodeRedemptionSheet()` and resolves as soon as the
+ * sheet is shown. A successfully redeemed code yields a transaction that is
+ * delivered through `onPurchaseUpdated` and `onTransactionUpdated`, just like
+ * a regular purchase.
+ *
+ * @throws Rejects with `notSupported` on older OS versions, Android and Windows.
+ * @example
+ * ```typescript
+ * try {
+ *   await presentOfferCodeRedemptionSheet();
+ * } catch (error) {
+ *   // hide the "Redeem code" button when the sheet isn't available
+ * }
+ * ```
+ */
+export async function presentOfferCodeRedemptionSheet(): Promise<void> {
+  return await invoke("plugin:iap|present_offer_code_redemption_sheet");
+}
+
+/**
+ * Alias of {@link presentOfferCodeRedemptionSheet}.
+ */
+export async function presentCodeRedemptionSheet(): Promise<void> {
+  return await invoke("plugin:iap|present_code_redemption_sheet");
+}
+
+/**
+ * Listen for subscription changes made in the manage subscriptions sheet
+ * (iOS only). The payload lists the statuses of every subscription group
+ * the user is entitled to.
+ *
+ * @param callback - Function to call with the updated statuses
+ * @returns Promise resolving to a PluginListener that can be used to stop listening
+ */
+export async function onSubscriptionStatusChanged(
+  callback: (response: GetSubscriptionStatusResponse) => void,
+): Promise<PluginListener> {
+  return await addPluginListener("iap", "subscriptionStatusChanged", callback);
+}
+
+/**
+ * Payload of the `priceIncreasePending` event.
+ * Keep in sync with PriceIncreasePendingEvent in src/models.rs
+ */
+export interface PriceIncreasePendingEvent {
+  productId: string;
+  originalTransactionId: string;
+}
+
+/**
+ * Listen for subscriptions waiting on price-increase consent (iOS/macOS only).
+ * Fires when a transaction update or {@link getSubscriptionStatus} finds a
+ * status whose `priceIncreaseStatus` is `"pending"`; the subscription lapses
+ * at its next renewal unless the user agrees to the new price.
+ *
+ * @param callback - Function to call with the affected subscription
+ * @returns Promise resolving to a PluginListener that can be used to stop listening
+ */
+export async function onPriceIncreasePending(
+  callback: (event: PriceIncreasePendingEvent) => void,
+): Promise<PluginListener> {
+  return await addPluginListener("iap", "priceIncreasePending", callback);
+}
+
+/**
+ * Payload of the `renewalPreferenceChanged` event.
+ * Keep in sync with RenewalPreferenceChangedEvent in src/models.rs
+ */
+export interface RenewalPreferenceChangedEvent {
+  productId: string;
+  originalTransactionId: string;
+  /** Plan the subscription renews into; differs from `productId` after a downgrade */
+  autoRenewProductId?: string;
+  willAutoRenew: boolean;
+}
+
+/**
+ * Listen for scheduled plan changes and auto-renew toggles (iOS/macOS only).
+ * A downgrade keeps the current plan active until renewal, so use
+ * `autoRenewProductId` together with the status's `expirationDate` to show
+ * when the new plan takes over. Changes are detected from StoreKit status
+ * updates (iOS 17+/macOS 14+), transaction updates,
+ * {@link getSubscriptionStatus} and {@link showManageSubscriptions}.
+ *
+ * @param callback - Function to call with the new renewal preference
+ * @returns Promise resolving to a PluginListener that can be used to stop listening
+ */
+export async function onRenewalPreferenceChanged(
+  callback: (event: RenewalPreferenceChangedEvent) => void,
+): Promise<PluginListener> {
+  return await addPluginListener("iap", "renewalPreferenceChanged", callback);
+}
+
+/**
+ * Payload of the `billingIssueDetected` event.
+ * Keep in sync with BillingIssueDetectedEvent in src/models.rs
+ */
+export interface BillingIssueDetectedEvent {
+  productId: string;
+  originalTransactionId: string;
+  state: "inGracePeriod" | "inBillingRetry";
+  /** RFC 3339 time the user loses access */
+  gracePeriodExpirationDate?: string;
+}
+
+/**
+ * Listen for failed subscription renewals (iOS/macOS only), e.g. to show an
+ * "Update your payment method" banner. Fires once when a subscription enters
+ * the grace period or billing retry; it fires again only after the
+ * subscription recovers and fails again.
+ *
+ * @param callback - Function to call with the affected subscription
+ * @returns Promise resolving to a PluginListener that can be used to stop listening
+ */
+export async function onBillingIssueDetected(
+  callback: (event: BillingIssueDetectedEvent) => void,
+): Promise<PluginListener> {
+  return await addPluginListener("iap", "billingIssueDetected", callback);
+}
+
+/**
+ * Get the current status of a product for the user.
+ * Checks if the product is owned, expired, or available for purchase.
+ *
+ * @param productId - Product identifier to check
+ * @param productType - Type of product: "subs" or "inapp"
+ * @returns Promise resolving to product status
+ * @example
+ * ```typescript
+ * const status = await getProductStatus('com.example.premium', 'subs');
+ * if (status.isOwned) {
+ *   console.log('User owns this product');
+ *   if (status.isAutoRenewing) {
+ *     console.log('Subscription is auto-renewing');
+ *   }
+ * }
+ * ```
+ */
+export async function getProductStatus(
+  productId: string,
+  productType: "subs" | "inapp" = "subs",
+): Promise<ProductStatus> {
+  return await invoke<ProductStatus>("plugin:iap|get_product_status", {
+    payload: {
+      productId,
+      productType,
+    },
+  });
+}
+
+/**
+ * Get the status of every product of one type the user currently owns, with a
+ * single store query instead of one `getProductStatus` call per product.
+ * Products the user doesn't own are left out.
+ *
+ * Supported on iOS, macOS and Android.
+ *
+ * @param productType - Type of product: "subs" or "inapp"
+ * @returns Promise resolving to one status per owned product
+ * @example
+ * ```typescript
+ * const statuses = await getAllProductStatuses('subs');
+ * const active = statuses.filter((status) => status.isOwned);
+ * ```
+ */
+export async function getAllProductStatuses(
+  productType: "subs" | "inapp" = "subs",
+): Promise<ProductStatus[]> {
+  return await invoke<ProductStatus[]>(
+    "plugin:iap|get_all_product_statuses",
+    {
+      payload: {
+        productType,
+      },
+    },
+  );
+}
+
+/**
+ * Whether the app may link out to its own website for purchases.
+ * Keep in sync with `ExternalPurchaseLinkAvailability` in `src/models.rs`.
+ */
+export interface ExternalPurchaseLinkAvailability {
+  /** The app has the external purchase link entitlement and the storefront qualifies */
+  eligible: boolean;
+  /** ISO 3166-1 alpha-3 code of the current storefront, when known */
+  storefrontCountry?: string;
+}
+
+/**
+ * Check whether StoreKit's `ExternalPurchaseLink` can be opened, e.g. to show
+ * a "Buy on our website" button in EU storefronts.
+ *
+ * @throws Rejects with `notSupported` before iOS 17.4 and macOS 15.4, and on
+ *   Android, Windows and Linux.
+ */
+export async function canOpenExternalPurchaseLink(): Promise<ExternalPurchaseLinkAvailability> {
+  return await invoke<ExternalPurchaseLinkAvailability>(
+    "plugin:iap|can_open_external_purchase_link",
+  );
+}
+
+/**
+ * Show the App Store's external purchase notice and, if the user continues,
+ * open the link from the app's `SKExternalPurchaseLink` Info.plist entry.
+ *
+ * @returns Promise that resolves once the notice flow completes
+ * @throws Rejects with `notEligible` when {@link canOpenExternalPurchaseLink}
+ *   reports the app as not eligible, and `notSupported` where it is unavailable.
+ */
+export async function openExternalPurchaseLink(): Promise<void> {
+  return await invoke("plugin:iap|open_external_purchase_link");
+}
+
+/**
+ * StoreKit `Message.Reason`.
+ * Keep in sync with `StoreMessageReason` in `src/models.rs`.
+ */
+export type StoreMessageReason =
+  | "generic"
+  | "priceIncreaseConsent"
+  | "billingIssue"
+  | "winBackOffer"
+  | "unknown";
+
+/**
+ * Payload of the `storeMessageReceived` event.
+ * Keep in sync with StoreMessageEvent in src/models.rs
+ */
+export interface StoreMessageEvent {
+  reason: StoreMessageReason;
+  /** Held for {@link displayPendingStoreMessages} because `deferStoreMessages` is on */
+  deferred: boolean;
+}
+
+/**
+ * Listen for App Store messages (iOS 16+ only), such as price increase
+ * consent or billing issue notices. With `deferStoreMessages` set in the
+ * plugin config, the messages are held until
+ * {@link displayPendingStoreMessages}; otherwise they are shown right away.
+ *
+ * @param callback - Function to call with each message's reason
+ * @returns Promise resolving to a PluginListener that can be used to stop listening
+ */
+export async function onStoreMessageReceived(
+  callback: (event: StoreMessageEvent) => void,
+): Promise<PluginListener> {
+  return await addPluginListener("iap", "storeMessageReceived", callback);
+}
+
+/**
+ * Show the App Store messages held back by the `deferStoreMessages` plugin
+ * option, oldest first, e.g. once a checkout or game level is over.
+ *
+ * @returns Promise resolving to the number of messages shown
+ * @throws Rejects with `notSupported` before iOS 16 and on every other platform.
+ */
+export async function displayPendingStoreMessages(): Promise<number> {
+  const response = await invoke<{ displayed: number }>(
+    "plugin:iap|display_pending_store_messages",
+  );
+  return response.displayed;
+}
+
+/**
+ * Listen for purchase updates.
+ * This event is triggered when a purchase state changes.
+ *
+ * On iOS/macOS the delivered transaction is left unfinished unless the
+ * `autoFinishTransactionUpdates` plugin option is set: call
+ * `finishTransaction(purchase.purchaseToken)` once it has been validated, or
+ * StoreKit delivers it again on the next launch.
+ *
+ * @param callback - Function to call when a purchase is updated
+ * @returns Promise resolving to a PluginListener that can be used to stop listening
+ * @example
+ * ```typescript
+ * const listener = await onPurchaseUpdated((purchase) => {
+ *   console.log(`Purchase updated: ${purchase.productId}`);
+ *   if (purchase.purchaseState === PurchaseState.PURCHASED) {
+ *     // Handle successful purchase
+ *   }
+ * });
+ *
+ * // Later, stop listening
+ * await listener.unregister();
+ * ```
+ */
+export async function onPurchaseUpdated(
+  callback: (purchase: Purchase) => void,
+): Promise<PluginListener> {
+  return await addPluginListener("iap", "purchaseUpdated", callback);
+}
+
+/**
+ * Payload of the `purchaseRevoked` event.
+ * Keep in sync with PurchaseRevokedEvent in src/models.rs
+ */
+export interface PurchaseRevokedEvent {
+  productId: string;
+  transactionId: string;
+  /** RFC 3339 */
+  revocationTime: string;
+  /** `"developerIssue"` or `"other"` */
+  revocationReason?: string;
+}
+
+/**
+ * Listen for purchases completing after `purchase()` returned them with
+ * `PurchaseState.DEFERRED` (iOS/macOS only), e.g. once a parent approves an
+ * Ask to Buy request. Declined requests produce no event: StoreKit never
+ * reports them to the device.
+ *
+ * Like `onPurchaseUpdated` payloads, the purchase must be finished with
+ * `finishTransaction()` unless `autoFinishTransactionUpdates` is set.
+ *
+ * With the `autoFinishUnfinished` plugin option set, `initialize()` also
+ * delivers each stale unfinished transaction here once, then finishes it
+ * itself.
+ *
+ * @param callback - Function to call with the completed purchase
+ * @returns Promise resolving to a PluginListener that can be used to stop listening
+ * @example
+ * ```typescript
+ * const listener = await onPurchaseCompleted((purchase) => {
+ *   grantEntitlement(purchase.productId);
+ * });
+ * ```
+ */
+export async function onPurchaseCompleted(
+  callback: (purchase: Purchase) => void,
+): Promise<PluginListener> {
+  return await addPluginListener("iap", "purchaseCompleted", callback);
+}
+
+/**
+ * Listen for revoked transactions (iOS/macOS only): refunds and Family Sharing
+ * removals, e.g. when the family organizer stops sharing a purchase.
+ *
+ * @param callback - Function to call with the revoked transaction
+ * @returns Promise resolving to a PluginListener that can be used to stop listening
+ * @example
+ * ```typescript
+ * const listener = await onPurchaseRevoked((event) => {
+ *   revokeEntitlement(event.productId);
+ * });
+ * ```
+ */
+export async function onPurchaseRevoked(
+  callback: (event: PurchaseRevokedEvent) => void,
+): Promise<PluginListener> {
+  return await addPluginListener("iap", "purchaseRevoked", callback);
+}
+
+/**
+ * Lifecycle stage of a transaction, discriminated by `type`. For `failed`,
+ * `error` is an `IapErrorCode` name such as `userCancelled` or `networkError`.
+ * Keep in sync with `TransactionState` in `src/models.rs`.
+ */
+export type TransactionState =
+  | { type: "purchasing" }
+  | { type: "purchased"; transactionId: string }
+  | { type: "failed"; error: string }
+  | { type: "restored"; originalTransactionId: string }
+  | { type: "deferred" }
+  | { type: "revoked" }
+  /** Android, with `autoAcknowledge` enabled in the plugin config */
+  | { type: "autoAcknowledged"; purchaseToken: string };
+
+/**
+ * Payload of the `transactionUpdated` event.
+ */
+export interface TransactionEvent {
+  productId: string;
+  state: TransactionState;
+  /** Unix timestamp (milliseconds) when the platform observed the change */
+  timestamp: number;
+  /** Set on iOS/macOS for `purchased` and `revoked` states */
+  verification?: TransactionVerification;
+}
+
+/**
+ * Listen for transaction lifecycle changes.
+ *
+ * Unlike {@link onPurchaseUpdated}, which only fires with completed purchases,
+ * this reports every stage (purchasing, purchased, failed, restored,
+ * deferred, revoked) with the same payload shape on every platform.
+ *
+ * @param callback - Function to call for each transaction event
+ * @returns Promise resolving to a PluginListener that can be used to stop listening
+ * @example
+ * ```typescript
+ * const listener = await onTransactionUpdated((event) => {
+ *   if (event.state.type === 'failed' && event.state.error !== 'userCancelled') {
+ *     showError(event.productId);
+ *   }
+ * });
+ * ```
+ */
+export async function onTransactionUpdated(
+  callback: (event: TransactionEvent) => void,
+): Promise<PluginListener> {
+  return await addPluginListener("iap", "transactionUpdated", callback);
 }
@@ -13,14 +13,34 @@ class PurchaseArgs: Decodable {
     let productType: String?
     let offerToken: String?
     let appAccountToken: String?
+    let quantity: Int?
+    let autoFinish: Bool?
+    let promotionalOffer: PromotionalOfferArgs?
+    let winBackOfferId: String?
+}
+
+/// Keep in sync with PromotionalOffer in src/models.rs
+class PromotionalOfferArgs: Decodable {
+    let offerId: String
+    let keyId: String
+    let nonce: String
+    let signature: String
+    let timestamp: Int
 }
 
 class RestorePurchasesArgs: Decodable {
     let productType: String?
+    let forceSync: Bool?
 }
 
 class GetPurchaseHistoryArgs: Decodable {}
 
+class GetPurchaseHistoryFilteredArgs: Decodable {
+    let productType: String?
+    let sinceTimestamp: UInt64?
+    let limit: Int?
+}
+
 class AcknowledgePurchaseArgs: Decodable {
     let purchaseToken: String
 }
@@ -29,35 +49,186 @@ class ConsumePurchaseArgs: Decodable {
     let purchaseToken: String
 }
 
+class FinishTransactionArgs: Decodable {
+    let transactionId: String
+}
+
+class RefundRequestArgs: Decodable {
+    let transactionId: String
+}
+
+class GetAppTransactionArgs: Decodable {
+    let forceRefresh: Bool?
+}
+
 class GetProductStatusArgs: Decodable {
     let productId: String
     let productType: String?
 }
 
+class GetAllProductStatusesArgs: Decodable {
+    let productType: String?
+}
+
+class GetWinBackOffersArgs: Decodable {
+    let productId: String
+}
+
+class IsEligibleForIntroOfferArgs: Decodable {
+    let productId: String
+}
+
+class CheckIntroductoryOfferEligibilityArgs: Decodable {
+    let productIds: [String]
+}
+
+class GetSubscriptionStatusArgs: Decodable {
+    let productId: String
+}
+
+class CurrentEntitlementArgs: Decodable {
+    let productId: String
+}
+
+class GetReceiptArgs: Decodable {
+    let transactionId: String
+}
+
+class GetAppReceiptArgs: Decodable {
+    let refreshIfMissing: Bool?
+}
+
+class GetRevokedPurchasesArgs: Decodable {
+    let since: String?
+}
+
+/// Keep in sync with IapConfig in src/config.rs
+class IapConfigArgs: Decodable {
+    let allowUnverifiedTransactions: Bool?
+    let deferStoreMessages: Bool?
+    let autoFinishTransactionUpdates: Bool?
+}
+
 /// Keep in sync with PurchaseState in guest-js/index.ts
 enum PurchaseStateValue: Int {
     case purchased = 0
     case canceled = 1
     case pending = 2
+    case failed = 3
+    case refunded = 4
+    case deferred = 5
+}
+
+/// Bridges `SKReceiptRefreshRequest`'s delegate callbacks to async/await.
+/// The request only holds its delegate weakly, so callers keep this alive
+/// by awaiting `refresh()` on it.
+final class ReceiptRefreshDelegate: NSObject, SKRequestDelegate {
+    private var continuation: CheckedContinuation<Void, Error>?
+    private var request: SKReceiptRefreshRequest?
+
+    func refresh() async throws {
+        try await withCheckedThrowingContinuation { (continuation: CheckedContinuation<Void, Error>) in
+            self.continuation = continuation
+            let request = SKReceiptRefreshRequest()
+            request.delegate = self
+            self.request = request
+            request.start()
+        }
+    }
+
+    func requestDidFinish(_ request: SKRequest) {
+        continuation?.resume()
+        continuation = nil
+        self.request = nil
+    }
+
+    func request(_ request: SKRequest, didFailWithError error: Error) {
+        continuation?.resume(throwing: error)
+        continuation = nil
+        self.request = nil
+    }
 }
 
 @available(iOS 15.0, *)
 class IapPlugin: Plugin {
     private var updateListenerTask: Task<Void, Error>?
+    private var storefrontListenerTask: Task<Void, Never>?
+    private var statusListenerTask: Task<Void, Never>?
+    private var messageListenerTask: Task<Void, Never>?
+    /// Last seen auto-renew preference per original transaction id, to tell
+    /// renewal preference changes apart from unrelated status updates.
+    private var renewalPreferences: [String: JsonObject] = [:]
+    private let renewalPreferencesLock = NSLock()
+    /// Original transaction ids currently in grace period or billing retry,
+    /// so `billingIssueDetected` fires once per failed renewal.
+    private var billingIssues = Set<String>()
+    private let billingIssuesLock = NSLock()
+    /// Product ids whose purchase is waiting on Ask to Buy approval, so the
+    /// approved transaction can be reported as `purchaseCompleted`.
+    private var pendingApprovals = Set<String>()
+    private let pendingApprovalsLock = NSLock()
+    private var allowUnverifiedTransactions = false
+    private var deferStoreMessages = false
+    private var autoFinishTransactionUpdates = false
+    /// StoreKit messages held back by `deferStoreMessages`, oldest first.
+    /// Typed as `Any` because `Message` needs iOS 16; only touched on the
+    /// main actor.
+    private var pendingStoreMessages: [Any] = []
     
     public override func load(webview: WKWebView) {
         super.load(webview: webview)
 
+        // Read before the listeners start so no update slips through unchecked
+        let config = try? parseConfig(IapConfigArgs.self)
+        allowUnverifiedTransactions = config?.allowUnverifiedTransactions ?? false
+        deferStoreMessages = config?.deferStoreMessages ?? false
+        autoFinishTransactionUpdates = config?.autoFinishTransactionUpdates ?? false
+
         // Start listening for transaction updates
         updateListenerTask = Task {
             for await update in Transaction.updates {
                 await self.handleTransactionUpdate(update)
             }
         }
+
+        // Storefront changes (Apple ID switch, region change) make cached prices stale
+        storefrontListenerTask = Task {
+            for await storefront in Storefront.updates {
+                self.trigger("storefrontChanged", data: self.storefrontObject(storefront))
+            }
+        }
+
+        // Downgrades and auto-renew toggles create no transaction, so only
+        // status updates reveal them
+        statusListenerTask = Task {
+            let snapshot = await self.subscriptionStatusSnapshot()
+            self.emitRenewalPreferenceChanges(snapshot)
+            self.emitBillingIssues(snapshot)
+            if #available(iOS 17.0, *) {
+                for await status in Product.SubscriptionInfo.Status.updates {
+                    let objects = [status].compactMap(self.subscriptionStatusObject)
+                    self.emitRenewalPreferenceChanges(objects)
+                    self.emitBillingIssues(objects)
+                }
+            }
+        }
+
+        // Listening takes over from StoreKit's automatic display, so every
+        // message is either shown here or queued
+        if #available(iOS 16.0, *) {
+            messageListenerTask = Task { @MainActor in
+                for await message in Message.messages {
+                    await self.handleStoreMessage(message)
+                }
+            }
+        }
     }
     
     deinit {
         updateListenerTask?.cancel()
+        storefrontListenerTask?.cancel()
+        statusListenerTask?.cancel()
+        messageListenerTask?.cancel()
     }
 
     @objc public func getProducts(_ invoke: Invoke) async throws {
@@ -78,6 +249,9 @@ class IapPlugin: Plugin {
                 // Add pricing information
                 productDict["formattedPrice"] = product.displayPrice
                 productDict["priceCurrencyCode"] = getCurrencyCode(for: product)
+                productDict["priceAmountMicros"] = priceAmountMicros(product.price)
+                productDict["priceLocale"] = getPriceLocale(for: product)
+                productDict["isFamilyShareable"] = product.isFamilyShareable
                 
                 // Handle subscription-specific information
                 if product.type == .autoRenewable || product.type == .nonRenewable {
@@ -119,10 +293,15 @@ class IapPlugin: Plugin {
                         subscriptionOffers.append(regularOffer)
 
                         productDict["subscriptionOfferDetails"] = subscriptionOffers
+                        productDict["subscriptionGroupId"] = subscription.subscriptionGroupID
+                        productDict["subscriptionPeriod"] = subscriptionPeriodObject(subscription.subscriptionPeriod)
+                        if let introOffer = subscription.introductoryOffer {
+                            productDict["introductoryOffer"] = subscriptionOfferObject(introOffer, for: product)
+                        }
+                        productDict["promotionalOffers"] = subscription.promotionalOffers.map {
+                            subscriptionOfferObject($0, for: product)
+                        }
                     }
-                } else {
-                    // One-time purchase
-                    productDict["priceAmountMicros"] = priceAmountMicros(product.price)
                 }
                 
                 productsArray.append(productDict)
@@ -155,36 +334,81 @@ class IapPlugin: Plugin {
                 }
                 purchaseOptions.insert(.appAccountToken(uuid))
             }
+
+            if let quantity = args.quantity, quantity > 1 {
+                // The range is checked in Rust; StoreKit would sell a non-consumable once
+                guard product.type == .consumable else {
+                    invoke.reject("Only consumables can be bought with quantity \(quantity)", code: "developerError")
+                    return
+                }
+                purchaseOptions.insert(.quantity(quantity))
+            }
+
+            if let offer = args.promotionalOffer {
+                guard let nonce = UUID(uuidString: offer.nonce),
+                      let signature = Data(base64Encoded: offer.signature) else {
+                    invoke.reject("Invalid promotional offer: nonce must be a UUID and signature base64", code: "invalidOffer")
+                    return
+                }
+                purchaseOptions.insert(.promotionalOffer(offerID: offer.offerId, keyID: offer.keyId, nonce: nonce, signature: signature, timestamp: offer.timestamp))
+            }
+
+            if let winBackOfferId = args.winBackOfferId {
+                guard #available(iOS 18.0, *) else {
+                    invoke.reject("Win-back offers require iOS 18.0 or later", code: "notSupported")
+                    return
+                }
+                guard let offer = product.subscription?.winBackOffers.first(where: { $0.id == winBackOfferId }) else {
+                    invoke.reject("Unknown win-back offer \(winBackOfferId)", code: "invalidOffer")
+                    return
+                }
+                purchaseOptions.insert(.winBackOffer(offer))
+            }
             
             // Initiate purchase with options
+            emitTransactionEvent(productId: args.productId, state: ["type": "purchasing"])
             let result = purchaseOptions.isEmpty 
                 ? try await product.purchase()
                 : try await product.purchase(options: purchaseOptions)
             
             switch result {
             case .success(let verification):
-                switch verification {
-                case .verified(let transaction):
-                    // Finish the transaction
-                    await transaction.finish()
-
-                    let purchase = try await createPurchaseObject(from: verification, product: product)
-                    invoke.resolve(purchase)
+                guard let transaction = acceptedTransaction(verification) else {
+                    emitTransactionEvent(productId: args.productId, state: ["type": "failed", "error": "verificationFailed"])
+                    invoke.reject("Transaction verification failed", code: "verificationFailed")
+                    return
+                }
 
-                case .unverified(_, _):
-                    invoke.reject("Transaction verification failed")
+                // Finish the transaction unless the caller wants to validate it first
+                if args.autoFinish ?? true {
+                    await transaction.finish()
                 }
+
+                emitTransactionEvent(productId: args.productId, state: ["type": "purchased", "transactionId": String(transaction.id)], verification: verification)
+                let purchase = try await createPurchaseObject(from: verification, product: product)
+                invoke.resolve(purchase)
                 
             case .userCancelled:
-                invoke.reject("Purchase cancelled by user")
+                emitTransactionEvent(productId: args.productId, state: ["type": "failed", "error": "userCancelled"])
+                invoke.reject("Purchase cancelled by user", code: "userCancelled")
                 
             case .pending:
-                invoke.reject("Purchase is pending")
+                // Ask to Buy / SCA; the outcome arrives later via Transaction.updates
+                emitTransactionEvent(productId: args.productId, state: ["type": "deferred"])
+                pendingApprovalsLock.lock()
+                pendingApprovals.insert(args.productId)
+                pendingApprovalsLock.unlock()
+                invoke.resolve(pendingPurchaseObject(productId: args.productId))
                 
             @unknown default:
                 invoke.reject("Unknown purchase result")
             }
+        } catch let error as Product.PurchaseError {
+            let code = offerErrorCode(error)
+            emitTransactionEvent(productId: args.productId, state: ["type": "failed", "error": code])
+            invoke.reject("Purchase failed: \(error.localizedDescription)", code: code)
         } catch {
+            emitTransactionEvent(productId: args.productId, state: ["type": "failed", "error": "unknown"])
             invoke.reject("Purchase failed: \(error.localizedDescription)")
         }
     }
@@ -193,11 +417,25 @@ class IapPlugin: Plugin {
         let args = try? invoke.parseArgs(RestorePurchasesArgs.self)
         var purchases: [JsonObject] = []
         
+        if args?.forceSync == true {
+            // Pulls transactions made on other devices into the local cache.
+            // May show the App Store sign-in prompt.
+            do {
+                try await AppStore.sync()
+            } catch StoreKitError.userCancelled {
+                invoke.reject("App Store sign-in cancelled by user", code: "userCancelled")
+                return
+            } catch {
+                invoke.reject("Failed to sync with the App Store: \(error.localizedDescription)", code: "syncFailed")
+                return
+            }
+        }
+
         do {
             // Get all current entitlements
             for await result in Transaction.currentEntitlements {
-                switch result {
-                case .verified(let transaction):
+                switch acceptedTransaction(result) {
+                case .some(let transaction):
                     if let product = try? await Product.products(for: [transaction.productID]).first {
                         // Filter by product type if specified
                         if let requestedType = args?.productType {
@@ -214,14 +452,16 @@ class IapPlugin: Plugin {
                             if productTypeMatches {
                                 let purchase = try await createPurchaseObject(from: result, product: product)
                                 purchases.append(purchase)
+                                emitRestored(transaction)
                             }
                         } else {
                             // No filter, include all
                             let purchase = try await createPurchaseObject(from: result, product: product)
                             purchases.append(purchase)
+                            emitRestored(transaction)
                         }
                     }
-                case .unverified(_, _):
+                case .none:
                     // Skip unverified transactions
                     continue
                 }
@@ -233,231 +473,1478 @@ class IapPlugin: Plugin {
         }
     }
 
-    @objc public func getPurchaseHistory(_ invoke: Invoke) async throws {
-        var history: [JsonObject] = []
-        
+    @objc public func getCurrentEntitlements(_ invoke: Invoke) async throws {
+        var purchases: [JsonObject] = []
+
         do {
-            // Get all transactions (including expired ones)
-            for await result in Transaction.all {
-                switch result {
-                case .verified(let transaction):
-                    let record: JsonObject = [
-                        "productId": transaction.productID,
-                        "purchaseTime": Int(transaction.purchaseDate.timeIntervalSince1970 * 1000),
-                        "purchaseToken": String(transaction.id),
-                        "quantity": transaction.purchasedQuantity,
-                        "originalJson": "",  // Not available in StoreKit 2
-                        "signature": ""      // Not available in StoreKit 2
-                    ]
-                    history.append(record)
-                case .unverified(_, _):
+            // currentEntitlements reads StoreKit's local cache, so this works
+            // offline and never prompts for App Store credentials.
+            for await result in Transaction.currentEntitlements {
+                guard let transaction = acceptedTransaction(result) else {
+                    continue
+                }
+                if transaction.revocationDate != nil {
+                    continue
+                }
+                if let expirationDate = transaction.expirationDate, expirationDate < Date() {
                     continue
                 }
+
+                // Product metadata only refines isAutoRenewing; skip it when offline
+                let product = try? await Product.products(for: [transaction.productID]).first
+                purchases.append(try await createPurchaseObject(from: result, product: product))
             }
-            
-            invoke.resolve(["history": history])
+
+            invoke.resolve(["purchases": purchases])
         } catch {
-            invoke.reject("Failed to get purchase history: \(error.localizedDescription)")
+            invoke.reject("Failed to get current entitlements: \(error.localizedDescription)")
         }
     }
-    
-    /// No-op: `purchase()` already calls `transaction.finish()` after verification.
-    /// Args are still parsed so a missing/invalid `purchaseToken` rejects here too,
-    /// matching Android/Windows behavior.
-    @objc public func acknowledgePurchase(_ invoke: Invoke) throws {
-        _ = try invoke.parseArgs(AcknowledgePurchaseArgs.self)
-        invoke.resolve()
-    }
 
-    /// No-op: StoreKit auto-allows re-purchase of consumables once the transaction
-    /// has been finished, which `purchase()` already does. Args are still parsed
-    /// for cross-platform consistency.
-    @objc public func consumePurchase(_ invoke: Invoke) throws {
-        _ = try invoke.parseArgs(ConsumePurchaseArgs.self)
-        invoke.resolve()
+    /// Keep in sync with CurrentEntitlementResponse in src/models.rs
+    @objc public func currentEntitlement(_ invoke: Invoke) async throws {
+        let args = try invoke.parseArgs(CurrentEntitlementArgs.self)
+
+        // Local cache only, and no product lookup, so this never touches the
+        // network and can run on every app focus.
+        guard let result = await Transaction.currentEntitlement(for: args.productId),
+              acceptedTransaction(result) != nil else {
+            invoke.resolve([:])
+            return
+        }
+
+        do {
+            invoke.resolve(["purchase": try await createPurchaseObject(from: result, product: nil)])
+        } catch {
+            invoke.reject("Failed to get current entitlement: \(error.localizedDescription)")
+        }
     }
 
-    @objc public func getProductStatus(_ invoke: Invoke) async throws {
-        let args = try invoke.parseArgs(GetProductStatusArgs.self)
+    @objc public func getWinBackOffers(_ invoke: Invoke) async throws {
+        let args = try invoke.parseArgs(GetWinBackOffersArgs.self)
 
-        var statusResult: JsonObject = [
-            "productId": args.productId,
-            "isOwned": false
-        ]
-        
-        // Check current entitlements for the specific product
-        for await result in Transaction.currentEntitlements {
-            switch result {
-            case .verified(let transaction):
-                if transaction.productID == args.productId {
-                    statusResult["isOwned"] = true
-                    statusResult["purchaseTime"] = Int(transaction.purchaseDate.timeIntervalSince1970 * 1000)
-                    statusResult["purchaseToken"] = String(transaction.id)
-                    statusResult["isAcknowledged"] = true  // Always true on iOS
-                    
-                    // Check if expired/revoked
-                    if let revocationDate = transaction.revocationDate {
-                        statusResult["purchaseState"] = PurchaseStateValue.canceled.rawValue
-                        statusResult["isOwned"] = false
-                        statusResult["expirationTime"] = Int(revocationDate.timeIntervalSince1970 * 1000)
-                    } else if let expirationDate = transaction.expirationDate {
-                        if expirationDate < Date() {
-                            statusResult["purchaseState"] = PurchaseStateValue.canceled.rawValue
-                            statusResult["isOwned"] = false
-                        } else {
-                            statusResult["purchaseState"] = PurchaseStateValue.purchased.rawValue
-                        }
-                        statusResult["expirationTime"] = Int(expirationDate.timeIntervalSince1970 * 1000)
-                    } else {
-                        statusResult["purchaseState"] = PurchaseStateValue.purchased.rawValue
-                    }
-                    
-                    // Check subscription renewal status if it's a subscription
-                    if let product = try? await Product.products(for: [args.productId]).first {
-                        if product.type == .autoRenewable {
-                            // Check subscription status
-                            if let statuses = try? await product.subscription?.status {
-                                for status in statuses {
-                                    if status.state == .subscribed {
-                                        // `.subscribed` only means the subscription is still active;
-                                        // it does NOT imply auto-renew is on. A subscription that the
-                                        // user cancelled (but hasn't expired yet) is also `.subscribed`.
-                                        // The actual renewal intent lives in renewalInfo.willAutoRenew.
-                                        if case .verified(let renewalInfo) = status.renewalInfo {
-                                            statusResult["isAutoRenewing"] = renewalInfo.willAutoRenew
-                                        } else {
-                                            statusResult["isAutoRenewing"] = true
-                                        }
-                                    } else if status.state == .expired {
-                                        statusResult["isAutoRenewing"] = false
-                                        statusResult["purchaseState"] = PurchaseStateValue.canceled.rawValue
-                                        statusResult["isOwned"] = false
-                                    } else if status.state == .inGracePeriod {
-                                        statusResult["isAutoRenewing"] = true
-                                        statusResult["purchaseState"] = PurchaseStateValue.purchased.rawValue
-                                    } else {
-                                        statusResult["isAutoRenewing"] = false
-                                    }
-                                    break
-                                }
-                            }
-                        }
-                    }
-                    
-                    break
-                }
-            case .unverified(_, _):
-                // Skip unverified transactions
-                continue
+        guard #available(iOS 18.0, *) else {
+            invoke.resolve(["offers": [JsonObject]()])
+            return
+        }
+
+        let product: Product?
+        do {
+            product = try await Product.products(for: [args.productId]).first
+        } catch {
+            invoke.reject("Failed to fetch product: \(error.localizedDescription)")
+            return
+        }
+        guard let product = product else {
+            invoke.reject("Product not found", code: "productNotFound")
+            return
+        }
+        guard let subscription = product.subscription else {
+            invoke.reject("\(args.productId) is not an auto-renewable subscription", code: "notSubscription")
+            return
+        }
+
+        // Eligibility is per purchaser, so merge it across the group's statuses
+        var eligibleIds = Set<String>()
+        for status in (try? await subscription.status) ?? [] {
+            if case .verified(let renewalInfo) = status.renewalInfo {
+                eligibleIds.formUnion(renewalInfo.eligibleWinBackOfferIDs)
             }
         }
-        
-        invoke.resolve(statusResult)
+
+        // Keep in sync with WinBackOffer in src/models.rs
+        let offers: [JsonObject] = subscription.winBackOffers.map { offer in
+            var object = subscriptionOfferObject(offer, for: product)
+            object["isEligible"] = offer.id.map(eligibleIds.contains) ?? false
+            return object
+        }
+        invoke.resolve(["offers": offers])
     }
-    
-    private func handleTransactionUpdate(_ result: VerificationResult<Transaction>) async {
-        switch result {
-        case .verified(let transaction):
-            // Get product details
-            if let product = try? await Product.products(for: [transaction.productID]).first {
-                if let purchase = try? await createPurchaseObject(from: result, product: product) {
-                    // Emit event - convert to JSObject-compatible format
-                    trigger("purchaseUpdated", data: purchase as! JSObject)
-                }
-            }
 
-            // Always finish transactions
-            await transaction.finish()
+    @objc public func getEnvironment(_ invoke: Invoke) async throws {
+        let hasSandboxReceipt = Bundle.main.appStoreReceiptURL?.lastPathComponent == "sandboxReceipt"
+        var isSandbox = hasSandboxReceipt
+        if #available(iOS 16.0, *), let result = try? await AppTransaction.shared {
+            // Only the environment is read, which doesn't depend on the signature check
+            isSandbox = result.unsafePayloadValue.environment != .production
+        }
 
-        case .unverified(_, _):
-            // Handle unverified transaction
-            break
+        // Keep in sync with IapEnvironment in src/models.rs
+        guard isSandbox else {
+            invoke.resolve(["environment": "production"])
+            return
         }
+        // TestFlight builds are App Store signed and ship without a provisioning profile
+        let isTestFlight = hasSandboxReceipt
+            && Bundle.main.path(forResource: "embedded", ofType: "mobileprovision") == nil
+        invoke.resolve(["environment": isTestFlight ? "testFlight" : "sandbox"])
     }
-    
-    private func createPurchaseObject(from verificationResult: VerificationResult<Transaction>, product: Product) async throws -> JsonObject {
-        guard case .verified(let transaction) = verificationResult else {
-            throw NSError(domain: "IapPlugin", code: -1, userInfo: [NSLocalizedDescriptionKey: "Transaction not verified"])
-        }
 
-        var isAutoRenewing = false
+    @objc public func getReceipt(_ invoke: Invoke) async throws {
+        let args = try invoke.parseArgs(GetReceiptArgs.self)
 
-        // Check if it's an auto-renewable subscription
-        if product.type == .autoRenewable {
-            // Check subscription status
-            if let statuses = try? await product.subscription?.status {
-                for status in statuses {
-                    if status.state == .subscribed {
-                        // `.subscribed` means the subscription is currently active, but a cancelled
-                        // (yet unexpired) subscription also has this state. Use willAutoRenew.
-                        if case .verified(let renewalInfo) = status.renewalInfo {
-                            isAutoRenewing = renewalInfo.willAutoRenew
-                        } else {
-                            isAutoRenewing = true
-                        }
-                        break
-                    }
-                }
+        var match: Transaction?
+        for await result in Transaction.all {
+            if case .verified(let transaction) = result, String(transaction.id) == args.transactionId {
+                match = transaction
+                break
             }
         }
+        guard let transaction = match else {
+            invoke.reject("No transaction with id \(args.transactionId) for the current user", code: "transactionNotFound")
+            return
+        }
+        guard let url = Bundle.main.appStoreReceiptURL, let receipt = try? Data(contentsOf: url) else {
+            invoke.reject("The app has no App Store receipt", code: "receiptUnavailable")
+            return
+        }
 
-        return [
-            "orderId": String(transaction.id),
-            "originalId": String(transaction.originalID),
-            "jwsRepresentation": verificationResult.jwsRepresentation,
-            "packageName": Bundle.main.bundleIdentifier ?? "",
+        // Keep in sync with PurchaseReceipt in src/models.rs
+        invoke.resolve([
+            "platform": "ios",
+            "receiptData": receipt.base64EncodedString(),
             "productId": transaction.productID,
-            "purchaseTime": Int(transaction.purchaseDate.timeIntervalSince1970 * 1000),
-            "purchaseToken": String(transaction.id),
-            "purchaseState": transaction.revocationDate == nil ? PurchaseStateValue.purchased.rawValue : PurchaseStateValue.canceled.rawValue,
-            "isAutoRenewing": isAutoRenewing,
-            "isAcknowledged": true,  // Always true on iOS
-            "originalJson": "",      // Not available in StoreKit 2
-            "signature": ""          // Not available in StoreKit 2
-        ]
+            "transactionId": args.transactionId
+        ])
     }
-    
-    private func formatSubscriptionPeriod(_ period: Product.SubscriptionPeriod) -> String {
-        switch period.unit {
-        case .day:
-            return "P\(period.value)D"
-        case .week:
-            return "P\(period.value)W"
-        case .month:
-            return "P\(period.value)M"
-        case .year:
-            return "P\(period.value)Y"
-        @unknown default:
-            return "P1M"
+
+    @objc public func refreshReceipt(_ invoke: Invoke) async throws {
+        do {
+            // May show the App Store sign-in prompt
+            try await ReceiptRefreshDelegate().refresh()
+        } catch {
+            invoke.reject("Failed to refresh receipt: \(error.localizedDescription)", code: "receiptRefreshFailed")
+            return
         }
+        invoke.resolve()
     }
-    
-    private func getCurrencyCode(for product: Product) -> String {
-        if #available(iOS 16.0, *) {
-            return product.priceFormatStyle.locale.currency?.identifier ?? ""
+
+    /// Keep in sync with GetAppReceiptResponse in src/models.rs
+    @objc public func getAppReceipt(_ invoke: Invoke) async throws {
+        let args = try invoke.parseArgs(GetAppReceiptArgs.self)
+        let readReceipt = { Bundle.main.appStoreReceiptURL.flatMap { try? Data(contentsOf: $0) } }
+
+        if readReceipt() == nil {
+            guard args.refreshIfMissing ?? false else {
+                invoke.reject("The app has no App Store receipt", code: "receiptUnavailable")
+                return
+            }
+            do {
+                // May show the App Store sign-in prompt
+                try await ReceiptRefreshDelegate().refresh()
+            } catch {
+                invoke.reject("The app has no App Store receipt and refreshing it failed: \(error.localizedDescription)", code: "receiptUnavailable")
+                return
+            }
+        }
+        guard let receipt = readReceipt() else {
+            invoke.reject("The App Store returned no receipt", code: "receiptUnavailable")
+            return
+        }
+        invoke.resolve(["receiptData": receipt.base64EncodedString()])
+    }
+
+    /// Keep in sync with CanMakePaymentsResponse in src/models.rs
+    @objc public func canMakePayments(_ invoke: Invoke) async throws {
+        if AppStore.canMakePayments {
+            invoke.resolve(["canMakePayments": true])
         } else {
-            // Fallback for iOS 15: currency code not directly available
-            return ""
+            // Screen Time, parental controls or an MDM profile
+            invoke.resolve(["canMakePayments": false, "reason": "restricted"])
         }
     }
 
-    private func priceAmountMicros(_ decimal: Decimal) -> Int64 {
-        return NSDecimalNumber(decimal: decimal * 1_000_000).int64Value
+    @objc public func isEligibleForIntroOffer(_ invoke: Invoke) async throws {
+        let args = try invoke.parseArgs(IsEligibleForIntroOfferArgs.self)
+
+        let product: Product?
+        do {
+            product = try await Product.products(for: [args.productId]).first
+        } catch {
+            invoke.reject("Failed to fetch product: \(error.localizedDescription)")
+            return
+        }
+        guard let product = product else {
+            invoke.reject("Product not found", code: "productNotFound")
+            return
+        }
+        guard let subscription = product.subscription else {
+            invoke.reject("\(args.productId) is not an auto-renewable subscription", code: "notSubscription")
+            return
+        }
+
+        // Keep in sync with IsEligibleForIntroOfferResponse in src/models.rs
+        let isEligible = await subscription.isEligibleForIntroOffer
+        invoke.resolve([
+            "eligibility": isEligible ? "eligible" : "ineligible",
+            "subscriptionGroupId": subscription.subscriptionGroupID,
+        ])
     }
-}
 
-@_cdecl("init_plugin_iap")
-func initPlugin() -> Plugin {
-    if #available(iOS 15.0, *) {
-        return IapPlugin()
-    } else {
-        // Return a dummy plugin for older iOS versions
-        class DummyPlugin: Plugin {
-            @objc func getProducts(_ invoke: Invoke) {
-                invoke.reject("IAP requires iOS 15.0 or later")
-            }
-            @objc func purchase(_ invoke: Invoke) {
-                invoke.reject("IAP requires iOS 15.0 or later")
+    @objc public func checkIntroductoryOfferEligibility(_ invoke: Invoke) async throws {
+        let args = try invoke.parseArgs(CheckIntroductoryOfferEligibilityArgs.self)
+
+        let products: [Product]
+        do {
+            products = try await Product.products(for: args.productIds)
+        } catch {
+            invoke.reject("Failed to fetch products: \(error.localizedDescription)")
+            return
+        }
+
+        // Keep in sync with CheckIntroductoryOfferEligibilityResponse in src/models.rs
+        var eligibility: [String: Bool] = [:]
+        for product in products {
+            guard let subscription = product.subscription else { continue }
+            eligibility[product.id] = await subscription.isEligibleForIntroOffer
+        }
+        invoke.resolve(["eligibility": eligibility])
+    }
+
+    @objc public func getSubscriptionStatus(_ invoke: Invoke) async throws {
+        let args = try invoke.parseArgs(GetSubscriptionStatusArgs.self)
+
+        do {
+            guard let product = try await Product.products(for: [args.productId]).first else {
+                invoke.reject("Product not found", code: "productNotFound")
+                return
+            }
+            guard let subscription = product.subscription else {
+                invoke.reject("\(args.productId) is not an auto-renewable subscription", code: "notSubscription")
+                return
+            }
+
+            // One status per purchaser in the subscription group (Family Sharing)
+            let statuses = try await subscription.status
+            let objects = statuses.compactMap(subscriptionStatusObject)
+            emitPriceIncreasePending(objects)
+            emitRenewalPreferenceChanges(objects)
+            emitBillingIssues(objects)
+            invoke.resolve(["statuses": objects])
+        } catch {
+            invoke.reject("Failed to get subscription status: \(error.localizedDescription)")
+        }
+    }
+
+    @objc public func showManageSubscriptions(_ invoke: Invoke) async throws {
+        guard let scene = await activeWindowScene() else {
+            invoke.reject("No active window scene to present the sheet in", code: "windowError")
+            return
+        }
+
+        let before = await subscriptionStatusSnapshot()
+        do {
+            // Returns once the user dismisses the sheet
+            try await AppStore.showManageSubscriptions(in: scene)
+        } catch {
+            invoke.reject("Failed to show manage subscriptions: \(error.localizedDescription)")
+            return
+        }
+
+        let after = await subscriptionStatusSnapshot()
+        emitRenewalPreferenceChanges(after)
+        emitBillingIssues(after)
+        if !NSArray(array: before).isEqual(to: after) {
+            let statuses: JSArray = after.map { $0 as! JSObject }
+            trigger("subscriptionStatusChanged", data: ["statuses": statuses])
+        }
+        invoke.resolve()
+    }
+
+    @objc public func presentOfferCodeRedemptionSheet(_ invoke: Invoke) async throws {
+        guard #available(iOS 16.0, *) else {
+            // StoreKit 1 sheet; returns immediately and redemptions arrive via Transaction.updates
+            await MainActor.run {
+                SKPaymentQueue.default().presentCodeRedemptionSheet()
+            }
+            invoke.resolve()
+            return
+        }
+        guard let scene = await activeWindowScene() else {
+            invoke.reject("No active window scene to present the sheet in", code: "windowError")
+            return
+        }
+
+        do {
+            // Redeemed offers are delivered through Transaction.updates
+            try await AppStore.presentOfferCodeRedemptionSheet(in: scene)
+        } catch {
+            invoke.reject("Failed to present offer code redemption sheet: \(error.localizedDescription)")
+            return
+        }
+        invoke.resolve()
+    }
+
+    @objc public func canOpenExternalPurchaseLink(_ invoke: Invoke) async throws {
+        guard #available(iOS 17.4, *) else {
+            invoke.reject("External purchase links require iOS 17.4 or later", code: "notSupported")
+            return
+        }
+        // False without the external purchase link entitlement or outside a qualifying storefront
+        let eligible = await ExternalPurchaseLink.canOpen
+
+        // Keep in sync with ExternalPurchaseLinkAvailability in src/models.rs
+        var availability: JsonObject = ["eligible": eligible]
+        if let storefront = await Storefront.current {
+            availability["storefrontCountry"] = storefront.countryCode
+        }
+        invoke.resolve(availability)
+    }
+
+    @objc public func openExternalPurchaseLink(_ invoke: Invoke) async throws {
+        guard #available(iOS 17.4, *) else {
+            invoke.reject("External purchase links require iOS 17.4 or later", code: "notSupported")
+            return
+        }
+        guard await ExternalPurchaseLink.canOpen else {
+            invoke.reject("External purchase links aren't available for this app or storefront", code: "notEligible")
+            return
+        }
+
+        do {
+            // Shows the system disclosure sheet before leaving the app
+            try await ExternalPurchaseLink.open()
+        } catch {
+            invoke.reject("Failed to open external purchase link: \(error.localizedDescription)")
+            return
+        }
+        invoke.resolve()
+    }
+
+    @objc public func displayPendingStoreMessages(_ invoke: Invoke) async throws {
+        guard #available(iOS 16.0, *) else {
+            invoke.reject("StoreKit messages require iOS 16.0 or later", code: "notSupported")
+            return
+        }
+        guard let scene = await activeWindowScene() else {
+            invoke.reject("No active window scene to present the messages in", code: "windowError")
+            return
+        }
+
+        let displayed = await MainActor.run { () -> Int in
+            let messages = pendingStoreMessages.compactMap { $0 as? Message }
+            pendingStoreMessages.removeAll()
+            var displayed = 0
+            for message in messages where (try? message.display(in: scene)) != nil {
+                displayed += 1
+            }
+            return displayed
+        }
+        // Keep in sync with DisplayPendingStoreMessagesResponse in src/models.rs
+        invoke.resolve(["displayed": displayed])
+    }
+
+    @objc public func getStorefront(_ invoke: Invoke) async throws {
+        if let storefront = await Storefront.current {
+            invoke.resolve(storefrontObject(storefront))
+            return
+        }
+        // StoreKit 1 can still know the storefront when StoreKit 2 hasn't loaded it yet
+        guard let storefront = SKPaymentQueue.default().storefront else {
+            invoke.reject("App Store storefront unavailable", code: "storefrontUnavailable")
+            return
+        }
+        invoke.resolve(["id": storefront.identifier, "countryCode": storefront.countryCode])
+    }
+
+    @objc public func getUnfinishedTransactions(_ invoke: Invoke) async throws {
+        var purchases: [JsonObject] = []
+
+        do {
+            // Transactions the app never finished, e.g. because it crashed mid-purchase
+            for await result in Transaction.unfinished {
+                guard let transaction = acceptedTransaction(result) else {
+                    continue
+                }
+                let product = try? await Product.products(for: [transaction.productID]).first
+                purchases.append(try await createPurchaseObject(from: result, product: product))
+            }
+
+            invoke.resolve(["purchases": purchases])
+        } catch {
+            invoke.reject("Failed to get unfinished transactions: \(error.localizedDescription)")
+        }
+    }
+
+    /// Keep in sync with FinishUnfinishedTransactionsResponse in src/models.rs
+    @objc public func finishUnfinishedTransactions(_ invoke: Invoke) async throws {
+        var finished = 0
+
+        // Left behind by an app version that never finished them; each would
+        // otherwise be redelivered on every launch
+        for await result in Transaction.unfinished {
+            // One bad transaction stays unfinished rather than aborting the flush
+            guard let transaction = acceptedTransaction(result) else {
+                continue
+            }
+            let product = try? await Product.products(for: [transaction.productID]).first
+            guard let purchase = try? await createPurchaseObject(from: result, product: product) else {
+                continue
+            }
+            trigger("purchaseCompleted", data: purchase as! JSObject)
+            await transaction.finish()
+            finished += 1
+        }
+
+        invoke.resolve(["finished": finished])
+    }
+
+    @objc public func getRevokedPurchases(_ invoke: Invoke) async throws {
+        let args = try invoke.parseArgs(GetRevokedPurchasesArgs.self)
+        // Timestamp always serializes with milliseconds
+        let formatter = ISO8601DateFormatter()
+        formatter.formatOptions = [.withInternetDateTime, .withFractionalSeconds]
+        let since = args.since.flatMap { formatter.date(from: $0) }
+        var revoked: [(date: Date, purchase: JsonObject)] = []
+
+        do {
+            // Refunds and Family Sharing removals, including those that happened
+            // while the app wasn't running
+            for await result in Transaction.all {
+                guard let transaction = acceptedTransaction(result),
+                      let revocationDate = transaction.revocationDate else {
+                    continue
+                }
+                if let since, revocationDate < since {
+                    continue
+                }
+                // Product metadata would only refine isAutoRenewing, which a
+                // revoked transaction never is
+                var purchase = try await createPurchaseObject(from: result, product: nil)
+                purchase["isAutoRenewing"] = false
+                revoked.append((revocationDate, purchase))
+            }
+        } catch {
+            invoke.reject("Failed to get revoked purchases: \(error.localizedDescription)")
+            return
+        }
+
+        // Transaction.all has no documented order
+        revoked.sort { $0.date > $1.date }
+        invoke.resolve(["purchases": revoked.map(\.purchase)])
+    }
+
+    @objc public func beginRefundRequest(_ invoke: Invoke) async throws {
+        let args = try invoke.parseArgs(RefundRequestArgs.self)
+
+        var match: Transaction?
+        for await result in Transaction.all {
+            if case .verified(let transaction) = result, String(transaction.id) == args.transactionId {
+                match = transaction
+                break
+            }
+        }
+        guard let transaction = match else {
+            invoke.reject("No transaction with id \(args.transactionId) for the current user", code: "transactionNotFound")
+            return
+        }
+        guard let scene = await activeWindowScene() else {
+            invoke.reject("No active window scene to present the sheet in", code: "windowError")
+            return
+        }
+
+        let status: Transaction.RefundRequestStatus
+        do {
+            status = try await transaction.beginRefundRequest(in: scene)
+        } catch Transaction.RefundRequestError.duplicateRequest {
+            invoke.reject("A refund was already requested for this transaction", code: "duplicateRequest")
+            return
+        } catch {
+            invoke.reject("Refund request failed: \(error.localizedDescription)", code: "refundRequestFailed")
+            return
+        }
+
+        // Keep in sync with RefundRequestResult in src/models.rs
+        switch status {
+        case .userCancelled:
+            invoke.resolve(["result": "userCancelled"])
+        default:
+            invoke.resolve(["result": "success"])
+        }
+    }
+
+    @objc public func getPurchaseHistory(_ invoke: Invoke) async throws {
+        var history: [JsonObject] = []
+        
+        do {
+            // Get all transactions (including expired ones)
+            for await result in Transaction.all {
+                guard let transaction = acceptedTransaction(result) else {
+                    continue
+                }
+                history.append(createHistoryRecord(from: transaction, verification: result))
+            }
+            
+            invoke.resolve(["history": history])
+        } catch {
+            invoke.reject("Failed to get purchase history: \(error.localizedDescription)")
+        }
+    }
+
+    @objc public func getPurchaseHistoryFiltered(_ invoke: Invoke) async throws {
+        let args = try invoke.parseArgs(GetPurchaseHistoryFilteredArgs.self)
+        let since = args.sinceTimestamp.map { Date(timeIntervalSince1970: Double($0) / 1000) }
+
+        var transactions: [(transaction: Transaction, result: VerificationResult<Transaction>)] = []
+        for await result in Transaction.all {
+            guard let transaction = acceptedTransaction(result) else {
+                continue
+            }
+            if let since, transaction.purchaseDate < since {
+                continue
+            }
+            if let requestedType = args.productType,
+               !productTypeMatches(transaction.productType, requested: requestedType) {
+                continue
+            }
+            transactions.append((transaction, result))
+        }
+
+        // Transaction.all has no documented order
+        transactions.sort { $0.transaction.purchaseDate > $1.transaction.purchaseDate }
+        if let limit = args.limit {
+            transactions = Array(transactions.prefix(max(limit, 0)))
+        }
+        invoke.resolve(["history": transactions.map {
+            createHistoryRecord(from: $0.transaction, verification: $0.result)
+        }])
+    }
+    
+    /// No-op: `purchase()` already calls `transaction.finish()` after verification.
+    /// Args are still parsed so a missing/invalid `purchaseToken` rejects here too,
+    /// matching Android/Windows behavior.
+    @objc public func acknowledgePurchase(_ invoke: Invoke) throws {
+        _ = try invoke.parseArgs(AcknowledgePurchaseArgs.self)
+        invoke.resolve()
+    }
+
+    /// No-op: StoreKit auto-allows re-purchase of consumables once the transaction
+    /// has been finished, which `purchase()` already does. Args are still parsed
+    /// for cross-platform consistency.
+    @objc public func consumePurchase(_ invoke: Invoke) throws {
+        _ = try invoke.parseArgs(ConsumePurchaseArgs.self)
+        invoke.resolve()
+    }
+
+    @objc public func finishTransaction(_ invoke: Invoke) async throws {
+        let args = try invoke.parseArgs(FinishTransactionArgs.self)
+
+        guard let transaction = await findTransaction(id: args.transactionId) else {
+            invoke.reject(
+                "No unfinished transaction with id \(args.transactionId)",
+                code: "transactionNotFound")
+            return
+        }
+        await transaction.finish()
+        invoke.resolve()
+    }
+
+    /// Looks `id` up among unfinished transactions (including those delivered
+    /// through Transaction.updates), then current entitlements. finish() is
+    /// idempotent, so finishing an entitlement that was already finished is
+    /// harmless.
+    private func findTransaction(id: String) async -> Transaction? {
+        for await result in Transaction.unfinished {
+            if case .verified(let transaction) = result, String(transaction.id) == id {
+                return transaction
+            }
+        }
+        for await result in Transaction.currentEntitlements {
+            if case .verified(let transaction) = result, String(transaction.id) == id {
+                return transaction
+            }
+        }
+        return nil
+    }
+
+    @objc public func getProductStatus(_ invoke: Invoke) async throws {
+        let args = try invoke.parseArgs(GetProductStatusArgs.self)
+
+        var statusResult: JsonObject = [
+            "productId": args.productId,
+            "isOwned": false
+        ]
+        
+        // Check current entitlements for the specific product
+        for await result in Transaction.currentEntitlements {
+            switch result {
+            case .verified(let transaction):
+                if transaction.productID == args.productId {
+                    let product = try? await Product.products(for: [args.productId]).first
+                    statusResult = await entitlementStatus(transaction, product: product)
+                    break
+                }
+            case .unverified(_, _):
+                // Skip unverified transactions
+                continue
+            }
+        }
+        
+        // Product.latestTransaction also covers revoked and expired purchases,
+        // so the UI can say when access ended.
+        if let product = try? await Product.products(for: [args.productId]).first,
+           let latest = await product.latestTransaction,
+           let purchase = try? await createPurchaseObject(from: latest, product: product) {
+            statusResult["latestTransaction"] = purchase
+        }
+        
+        invoke.resolve(statusResult)
+    }
+    
+    @objc public func getAllProductStatuses(_ invoke: Invoke) async throws {
+        let args = try invoke.parseArgs(GetAllProductStatusesArgs.self)
+        let requestedType = args.productType ?? "subs"
+
+        // One pass over currentEntitlements and one product lookup, instead
+        // of a getProductStatus round-trip per product.
+        var transactions: [Transaction] = []
+        for await result in Transaction.currentEntitlements {
+            if case .verified(let transaction) = result,
+               productTypeMatches(transaction.productType, requested: requestedType) {
+                transactions.append(transaction)
+            }
+        }
+
+        let products = (try? await Product.products(for: Set(transactions.map(\.productID)))) ?? []
+        let productsById = Dictionary(products.map { ($0.id, $0) }) { first, _ in first }
+
+        var statuses: [JsonObject] = []
+        for transaction in transactions {
+            let product = productsById[transaction.productID]
+            var status = await entitlementStatus(transaction, product: product)
+            if let product,
+               let latest = await product.latestTransaction,
+               let purchase = try? await createPurchaseObject(from: latest, product: product) {
+                status["latestTransaction"] = purchase
+            }
+            statuses.append(status)
+        }
+
+        invoke.resolve(["statuses": statuses])
+    }
+
+    /// Keep in sync with ProductStatus in src/models.rs
+    /// The status of a verified current entitlement, without latestTransaction.
+    private func entitlementStatus(_ transaction: Transaction, product: Product?) async -> JsonObject {
+        var statusResult: JsonObject = [
+            "productId": transaction.productID,
+            "isOwned": true,
+            "purchaseTime": rfc3339(transaction.purchaseDate),
+            "purchaseToken": String(transaction.id),
+            "isAcknowledged": true  // Always true on iOS
+        ]
+
+        // Check if expired/revoked
+        if let revocationDate = transaction.revocationDate {
+            statusResult["purchaseState"] = PurchaseStateValue.canceled.rawValue
+            statusResult["isOwned"] = false
+            statusResult["expirationTime"] = rfc3339(revocationDate)
+        } else if let expirationDate = transaction.expirationDate {
+            if expirationDate < Date() {
+                statusResult["purchaseState"] = PurchaseStateValue.canceled.rawValue
+                statusResult["isOwned"] = false
+            } else {
+                statusResult["purchaseState"] = PurchaseStateValue.purchased.rawValue
+            }
+            statusResult["expirationTime"] = rfc3339(expirationDate)
+        } else {
+            statusResult["purchaseState"] = PurchaseStateValue.purchased.rawValue
+        }
+
+        // StoreKit never sets expirationDate on a non-renewing subscription
+        // and keeps it in currentEntitlements for good; the app decides
+        // when the purchased period ends, starting from purchaseTime.
+        if transaction.productType == .nonRenewable {
+            statusResult["isAutoRenewing"] = false
+        }
+
+        // Check subscription renewal status if it's a subscription
+        guard let product, product.type == .autoRenewable,
+              let statuses = try? await product.subscription?.status else {
+            return statusResult
+        }
+        // Family Sharing can yield several statuses; prefer
+        // the one for this transaction's purchaser.
+        let ownStatus = statuses.first { status in
+            guard case .verified(let statusTransaction) = status.transaction else {
+                return false
+            }
+            return statusTransaction.originalID == transaction.originalID
+        } ?? statuses.first
+        guard let status = ownStatus else {
+            return statusResult
+        }
+        statusResult["renewalInfo"] = renewalInfoObject(status, expirationDate: transaction.expirationDate)
+
+        if status.state == .subscribed {
+            // `.subscribed` only means the subscription is still active;
+            // it does NOT imply auto-renew is on. A subscription that the
+            // user cancelled (but hasn't expired yet) is also `.subscribed`.
+            // The actual renewal intent lives in renewalInfo.willAutoRenew.
+            if case .verified(let renewalInfo) = status.renewalInfo {
+                statusResult["isAutoRenewing"] = renewalInfo.willAutoRenew
+            } else {
+                statusResult["isAutoRenewing"] = true
+            }
+        } else if status.state == .expired {
+            statusResult["isAutoRenewing"] = false
+            statusResult["purchaseState"] = PurchaseStateValue.canceled.rawValue
+            statusResult["isOwned"] = false
+        } else if status.state == .inGracePeriod {
+            // The renewal charge failed, but the user keeps
+            // access until the grace period ends even though
+            // expirationDate has passed
+            statusResult["isAutoRenewing"] = true
+            statusResult["purchaseState"] = PurchaseStateValue.purchased.rawValue
+            statusResult["isOwned"] = true
+        } else {
+            statusResult["isAutoRenewing"] = false
+        }
+        return statusResult
+    }
+    
+    @objc public func getAppTransaction(_ invoke: Invoke) async throws {
+        let args = try invoke.parseArgs(GetAppTransactionArgs.self)
+
+        guard #available(iOS 16.0, *) else {
+            invoke.reject("AppTransaction requires iOS 16.0 or later", code: "notSupported")
+            return
+        }
+
+        let result: VerificationResult<AppTransaction>
+        do {
+            // refresh() shows the App Store sign-in prompt
+            result = args.forceRefresh ?? false
+                ? try await AppTransaction.refresh()
+                : try await AppTransaction.shared
+        } catch {
+            invoke.reject("Failed to get app transaction: \(error.localizedDescription)", code: "appTransactionUnavailable")
+            return
+        }
+
+        guard case .verified(let appTransaction) = result else {
+            invoke.reject("App transaction verification failed", code: "verificationFailed")
+            return
+        }
+
+        // Keep in sync with AppTransactionInfo in src/models.rs
+        invoke.resolve([
+            "bundleId": appTransaction.bundleID,
+            "appVersion": appTransaction.appVersion,
+            "originalAppVersion": appTransaction.originalAppVersion,
+            "originalPurchaseDate": rfc3339(appTransaction.originalPurchaseDate),
+            "environment": appTransaction.environment == .production ? "production" : "sandbox",
+            "jwsRepresentation": result.jwsRepresentation
+        ])
+    }
+
+    private func handleTransactionUpdate(_ result: VerificationResult<Transaction>) async {
+        // Unverified transactions are never delivered unless the app opted in
+        switch acceptedTransaction(result) {
+        case .some(let transaction):
+            if transaction.revocationDate != nil {
+                emitTransactionEvent(productId: transaction.productID, state: ["type": "revoked"], verification: result)
+
+                // Keep in sync with PurchaseRevokedEvent in src/models.rs
+                var revoked: JsonObject = [
+                    "productId": transaction.productID,
+                    "transactionId": String(transaction.id)
+                ]
+                revoked.merge(revocationFields(transaction)) { $1 }
+                trigger("purchaseRevoked", data: revoked as! JSObject)
+            } else {
+                emitTransactionEvent(productId: transaction.productID, state: ["type": "purchased", "transactionId": String(transaction.id)], verification: result)
+            }
+
+            // Get product details
+            if let product = try? await Product.products(for: [transaction.productID]).first {
+                if let purchase = try? await createPurchaseObject(from: result, product: product) {
+                    // Emit event - convert to JSObject-compatible format
+                    trigger("purchaseUpdated", data: purchase as! JSObject)
+                    if takePendingApproval(transaction) {
+                        trigger("purchaseCompleted", data: purchase as! JSObject)
+                    }
+                }
+                if let statuses = try? await product.subscription?.status {
+                    let objects = statuses.compactMap(subscriptionStatusObject)
+                    emitPriceIncreasePending(objects)
+                    emitRenewalPreferenceChanges(objects)
+                    emitBillingIssues(objects)
+                }
+            }
+
+            // Left open by default so the app can validate the JWS first and
+            // call finishTransaction; StoreKit redelivers it until then
+            if autoFinishTransactionUpdates {
+                await transaction.finish()
+            }
+
+        case .none:
+            break
+        }
+    }
+    
+    /// Keep in sync with PriceIncreasePendingEvent in src/models.rs
+    private func emitPriceIncreasePending(_ statuses: [JsonObject]) {
+        for status in statuses where status["priceIncreaseStatus"] as? String == "pending" {
+            trigger("priceIncreasePending", data: [
+                "productId": status["productId"] as? String ?? "",
+                "originalTransactionId": status["originalTransactionId"] as? String ?? ""
+            ])
+        }
+    }
+
+    /// Keep in sync with RenewalPreferenceChangedEvent in src/models.rs
+    ///
+    /// The first status seen for a subscription only records its preference,
+    /// so launching the app doesn't report every subscription as changed.
+    private func emitRenewalPreferenceChanges(_ statuses: [JsonObject]) {
+        for status in statuses {
+            guard let originalTransactionId = status["originalTransactionId"] as? String else {
+                continue
+            }
+            var preference: JsonObject = ["willAutoRenew": status["willAutoRenew"] as? Bool ?? false]
+            if let autoRenewProductId = status["autoRenewProductId"] as? String {
+                preference["autoRenewProductId"] = autoRenewProductId
+            }
+
+            renewalPreferencesLock.lock()
+            let previous = renewalPreferences.updateValue(preference, forKey: originalTransactionId)
+            renewalPreferencesLock.unlock()
+
+            guard let previous, !NSDictionary(dictionary: previous).isEqual(to: preference) else {
+                continue
+            }
+            var event = preference
+            event["productId"] = status["productId"] as? String ?? ""
+            event["originalTransactionId"] = originalTransactionId
+            trigger("renewalPreferenceChanged", data: event as! JSObject)
+        }
+    }
+
+    /// Keep in sync with BillingIssueDetectedEvent in src/models.rs
+    private func emitBillingIssues(_ statuses: [JsonObject]) {
+        for status in statuses {
+            guard let originalTransactionId = status["originalTransactionId"] as? String,
+                  let state = status["state"] as? String else {
+                continue
+            }
+            let hasIssue = state == "inGracePeriod" || state == "inBillingRetry"
+
+            // Grace period turning into billing retry is the same issue
+            billingIssuesLock.lock()
+            let isNew: Bool
+            if hasIssue {
+                isNew = billingIssues.insert(originalTransactionId).inserted
+            } else {
+                billingIssues.remove(originalTransactionId)
+                isNew = false
+            }
+            billingIssuesLock.unlock()
+
+            guard isNew else { continue }
+            var event: JSObject = [
+                "productId": status["productId"] as? String ?? "",
+                "originalTransactionId": originalTransactionId,
+                "state": state
+            ]
+            if let gracePeriodExpirationDate = status["gracePeriodExpirationDate"] as? String {
+                event["gracePeriodExpirationDate"] = gracePeriodExpirationDate
+            }
+            trigger("billingIssueDetected", data: event)
+        }
+    }
+
+    /// Keep in sync with TransactionEvent in src/models.rs
+    private func emitTransactionEvent(productId: String, state: JSObject, verification: VerificationResult<Transaction>? = nil) {
+        var event: JSObject = [
+            "productId": productId,
+            "state": state,
+            "timestamp": Int(Date().timeIntervalSince1970 * 1000)
+        ]
+        if let verification {
+            event["verification"] = verificationName(verification)
+        }
+        trigger("transactionUpdated", data: event)
+    }
+
+    /// Keep in sync with IapErrorCode in src/error.rs
+    private func offerErrorCode(_ error: Product.PurchaseError) -> String {
+        switch error {
+        case .invalidOfferSignature:
+            return "invalidOfferSignature"
+        case .invalidOfferIdentifier, .invalidOfferPrice, .missingOfferParameters, .ineligibleForOffer:
+            return "invalidOffer"
+        case .purchaseNotAllowed:
+            return "paymentNotAllowed"
+        case .productUnavailable:
+            return "itemUnavailable"
+        default:
+            return "unknown"
+        }
+    }
+
+    /// Foreground window scene, for presenting StoreKit sheets.
+    private func activeWindowScene() async -> UIWindowScene? {
+        return await MainActor.run {
+            UIApplication.shared.connectedScenes
+                .first { $0.activationState == .foregroundActive } as? UIWindowScene
+        }
+    }
+
+    /// Keep in sync with StoreMessageEvent in src/models.rs
+    @available(iOS 16.0, *)
+    @MainActor
+    private func handleStoreMessage(_ message: Message) async {
+        trigger("storeMessageReceived", data: [
+            "reason": storeMessageReasonName(message.reason),
+            "deferred": deferStoreMessages
+        ])
+        if deferStoreMessages {
+            pendingStoreMessages.append(message)
+            return
+        }
+        guard let scene = await activeWindowScene() else {
+            // Nothing to show it in now; keep it for displayPendingStoreMessages
+            pendingStoreMessages.append(message)
+            return
+        }
+        try? message.display(in: scene)
+    }
+
+    /// Keep in sync with StoreMessageReason in src/models.rs
+    @available(iOS 16.0, *)
+    private func storeMessageReasonName(_ reason: Message.Reason) -> String {
+        if reason == .priceIncreaseConsent {
+            return "priceIncreaseConsent"
+        }
+        if #available(iOS 16.4, *), reason == .billingIssue {
+            return "billingIssue"
+        }
+        if #available(iOS 18.0, *), reason == .winBackOffer {
+            return "winBackOffer"
+        }
+        return reason == .generic ? "generic" : "unknown"
+    }
+
+    /// Keep in sync with StorefrontInfo in src/models.rs
+    private func storefrontObject(_ storefront: Storefront) -> JSObject {
+        return ["id": storefront.id, "countryCode": storefront.countryCode]
+    }
+
+    private func emitRestored(_ transaction: Transaction) {
+        emitTransactionEvent(productId: transaction.productID, state: ["type": "restored", "originalTransactionId": String(transaction.originalID)])
+    }
+
+    private func createPurchaseObject(from verificationResult: VerificationResult<Transaction>, product: Product?) async throws -> JsonObject {
+        guard let transaction = acceptedTransaction(verificationResult) else {
+            throw NSError(domain: "IapPlugin", code: -1, userInfo: [NSLocalizedDescriptionKey: "Transaction verification failed"])
+        }
+
+        // Without product metadata (e.g. offline) assume an auto-renewable
+        // entitlement is still renewing.
+        var isAutoRenewing = product == nil && transaction.productType == .autoRenewable
+        var nextRenewalDate: Date?
+
+        // Check if it's an auto-renewable subscription
+        if let product, product.type == .autoRenewable {
+            // Check subscription status
+            if let statuses = try? await product.subscription?.status {
+                for status in statuses {
+                    if status.state == .subscribed {
+                        // `.subscribed` means the subscription is currently active, but a cancelled
+                        // (yet unexpired) subscription also has this state. Use willAutoRenew.
+                        if case .verified(let renewalInfo) = status.renewalInfo {
+                            isAutoRenewing = renewalInfo.willAutoRenew
+                            if renewalInfo.willAutoRenew {
+                                nextRenewalDate = renewalDate(renewalInfo, expirationDate: transaction.expirationDate)
+                            }
+                        } else {
+                            isAutoRenewing = true
+                        }
+                        break
+                    }
+                }
+            }
+        }
+
+        var purchase: JsonObject = [
+            "orderId": String(transaction.id),
+            "originalId": String(transaction.originalID),
+            "originalTransactionId": String(transaction.originalID),
+            "jwsRepresentation": verificationResult.jwsRepresentation,
+            "packageName": Bundle.main.bundleIdentifier ?? "",
+            "productId": transaction.productID,
+            "purchaseTime": rfc3339(transaction.purchaseDate),
+            "purchaseToken": String(transaction.id),
+            "purchaseState": transaction.revocationDate == nil ? PurchaseStateValue.purchased.rawValue : PurchaseStateValue.canceled.rawValue,
+            "isAutoRenewing": isAutoRenewing,
+            "isAcknowledged": true,  // Always true on iOS
+            "originalJson": "",      // Not available in StoreKit 2
+            "signature": "",         // Not available in StoreKit 2
+            "ownershipType": ownershipTypeName(transaction.ownershipType),
+            "environment": environmentName(transaction),
+            "verification": verificationName(verificationResult)
+        ]
+        if let webOrderLineItemID = transaction.webOrderLineItemID {
+            purchase["webOrderLineItemId"] = webOrderLineItemID
+        }
+        if let subscriptionGroupID = transaction.subscriptionGroupID {
+            purchase["subscriptionGroupId"] = subscriptionGroupID
+        }
+        purchase.merge(revocationFields(transaction)) { $1 }
+        purchase.merge(offerFields(transaction)) { $1 }
+        if let transactionReason = transactionReasonName(transaction) {
+            purchase["transactionReason"] = transactionReason
+        }
+        if let appAccountToken = transaction.appAccountToken {
+            purchase["appAccountToken"] = appAccountToken.uuidString.lowercased()
+        }
+        purchase["quantity"] = transaction.purchasedQuantity
+        if let expirationDate = transaction.expirationDate {
+            purchase["expirationDate"] = rfc3339(expirationDate)
+        }
+        if let nextRenewalDate {
+            purchase["nextRenewalDate"] = rfc3339(nextRenewalDate)
+        }
+        return purchase
+    }
+
+    /// `RenewalInfo.renewalDate` where available; older systems renew when the
+    /// current period expires.
+    private func renewalDate(_ renewalInfo: Product.SubscriptionInfo.RenewalInfo, expirationDate: Date?) -> Date? {
+        if #available(iOS 17.0, *) {
+            return renewalInfo.renewalDate ?? expirationDate
+        }
+        return expirationDate
+    }
+
+    /// Keep in sync with Timestamp in src/timestamp.rs
+    /// Dates in payloads are RFC 3339 strings with milliseconds, e.g.
+    /// "2024-01-31T12:00:00.000Z".
+    private func rfc3339(_ date: Date) -> String {
+        let formatter = ISO8601DateFormatter()
+        formatter.formatOptions = [.withInternetDateTime, .withFractionalSeconds]
+        return formatter.string(from: date)
+    }
+
+    /// Keep in sync with PurchaseHistoryRecord in src/models.rs
+    private func createHistoryRecord(
+        from transaction: Transaction, verification: VerificationResult<Transaction>
+    ) -> JsonObject {
+        var record: JsonObject = [
+            "productId": transaction.productID,
+            "purchaseTime": rfc3339(transaction.purchaseDate),
+            "purchaseToken": String(transaction.id),
+            "transactionId": String(transaction.id),
+            "originalTransactionId": String(transaction.originalID),
+            "productType": productTypeName(transaction.productType),
+            "quantity": transaction.purchasedQuantity,
+            "originalJson": "",  // Not available in StoreKit 2
+            "signature": "",     // Not available in StoreKit 2
+            "verification": verificationName(verification)
+        ]
+        if let webOrderLineItemID = transaction.webOrderLineItemID {
+            record["webOrderLineItemId"] = webOrderLineItemID
+        }
+        if let transactionReason = transactionReasonName(transaction) {
+            record["transactionReason"] = transactionReason
+        }
+        record.merge(revocationFields(transaction)) { $1 }
+        return record
+    }
+
+    /// Keep in sync with Purchase in src/models.rs. Stands in for a purchase
+    /// StoreKit reported as pending: there is no transaction yet, so only the
+    /// product id and state are meaningful.
+    private func pendingPurchaseObject(productId: String) -> JsonObject {
+        return [
+            "packageName": Bundle.main.bundleIdentifier ?? "",
+            "productId": productId,
+            "purchaseTime": rfc3339(Date()),
+            "purchaseToken": "",
+            "purchaseState": PurchaseStateValue.deferred.rawValue,
+            "isAutoRenewing": false,
+            "isAcknowledged": false,
+            "originalJson": "",
+            "signature": ""
+        ]
+    }
+
+    /// Whether `transaction` completes a purchase `purchase()` reported as
+    /// pending. Clears the pending entry so it completes only once.
+    private func takePendingApproval(_ transaction: Transaction) -> Bool {
+        guard transaction.revocationDate == nil else {
+            return false
+        }
+        pendingApprovalsLock.lock()
+        defer { pendingApprovalsLock.unlock() }
+        return pendingApprovals.remove(transaction.productID) != nil
+    }
+
+    /// The transaction to deliver: verified ones always, unverified ones only
+    /// when `allowUnverifiedTransactions` is set.
+    private func acceptedTransaction(_ result: VerificationResult<Transaction>) -> Transaction? {
+        switch result {
+        case .verified(let transaction):
+            return transaction
+        case .unverified(let transaction, _):
+            return allowUnverifiedTransactions ? transaction : nil
+        }
+    }
+
+    /// Keep in sync with TransactionVerification in src/models.rs
+    private func verificationName(_ result: VerificationResult<Transaction>) -> String {
+        if case .verified = result {
+            return "verified"
+        }
+        return "unverified"
+    }
+
+    /// `revocationTime`/`revocationReason` shared by Purchase, PurchaseHistoryRecord
+    /// and PurchaseRevokedEvent in src/models.rs; empty unless revoked.
+    private func revocationFields(_ transaction: Transaction) -> JsonObject {
+        var fields: JsonObject = [:]
+        if let revocationDate = transaction.revocationDate {
+            fields["revocationTime"] = rfc3339(revocationDate)
+        }
+        if let revocationReason = transaction.revocationReason {
+            fields["revocationReason"] = revocationReason == .developerIssue ? "developerIssue" : "other"
+        }
+        return fields
+    }
+
+    /// `offerId`/`offerType` on Purchase in src/models.rs; empty unless the
+    /// transaction redeemed an offer.
+    private func offerFields(_ transaction: Transaction) -> JsonObject {
+        var fields: JsonObject = [:]
+        let offerType: Transaction.OfferType?
+        let offerId: String?
+        if #available(iOS 17.2, *) {
+            offerType = transaction.offer?.type
+            offerId = transaction.offer?.id
+        } else {
+            offerType = transaction.offerType
+            offerId = transaction.offerID
+        }
+        if let offerType {
+            fields["offerType"] = transactionOfferTypeName(offerType)
+        }
+        if let offerId {
+            fields["offerId"] = offerId
+        }
+        return fields
+    }
+
+    /// "introductory", "promotional", "code" or "winBack"
+    private func transactionOfferTypeName(_ type: Transaction.OfferType) -> String? {
+        if #available(iOS 18.0, *), type == .winBack {
+            return "winBack"
+        }
+        switch type {
+        case .introductory:
+            return "introductory"
+        case .promotional:
+            return "promotional"
+        case .code:
+            return "code"
+        default:
+            return nil
+        }
+    }
+
+    /// Keep in sync with OwnershipType in src/models.rs
+    private func ownershipTypeName(_ type: Transaction.OwnershipType) -> String {
+        return type == .familyShared ? "familyShared" : "purchased"
+    }
+
+    /// Keep in sync with TransactionReason in src/models.rs. `Transaction.reason`
+    /// needs iOS 17.0; older systems send no reason.
+    private func transactionReasonName(_ transaction: Transaction) -> String? {
+        guard #available(iOS 17.0, *) else {
+            return nil
+        }
+        switch transaction.reason {
+        case .purchase:
+            return "purchase"
+        case .renewal:
+            return "renewal"
+        default:
+            return "unknown"
+        }
+    }
+
+    /// "production", "sandbox" or "xcode"
+    private func environmentName(_ transaction: Transaction) -> String {
+        if #available(iOS 16.0, *) {
+            return transaction.environment.rawValue.lowercased()
+        }
+        return transaction.environmentStringRepresentation.lowercased()
+    }
+    
+    /// Keep in sync with ProductType in src/models.rs
+    /// Statuses of every subscription group the user is entitled to, used to
+    /// detect changes made in the manage subscriptions sheet.
+    private func subscriptionStatusSnapshot() async -> [JsonObject] {
+        var seenGroups = Set<String>()
+        var statuses: [JsonObject] = []
+        for await result in Transaction.currentEntitlements {
+            guard case .verified(let transaction) = result,
+                  transaction.productType == .autoRenewable,
+                  let product = try? await Product.products(for: [transaction.productID]).first,
+                  let subscription = product.subscription,
+                  seenGroups.insert(subscription.subscriptionGroupID).inserted,
+                  let groupStatuses = try? await subscription.status else {
+                continue
+            }
+            statuses.append(contentsOf: groupStatuses.compactMap(subscriptionStatusObject))
+        }
+        return statuses.sorted {
+            ($0["transactionId"] as? String ?? "") < ($1["transactionId"] as? String ?? "")
+        }
+    }
+
+    /// Keep in sync with SubscriptionRenewalInfo in src/models.rs
+    private func renewalInfoObject(_ status: Product.SubscriptionInfo.Status, expirationDate: Date?) -> JsonObject? {
+        guard case .verified(let renewalInfo) = status.renewalInfo else {
+            return nil
+        }
+
+        var object: JsonObject = [
+            "autoRenewEnabled": renewalInfo.willAutoRenew,
+            "billingRetryPeriod": renewalInfo.isInBillingRetry
+        ]
+        if let expirationDate = expirationDate {
+            object["expirationDate"] = rfc3339(expirationDate)
+        }
+        if let gracePeriodExpirationDate = renewalInfo.gracePeriodExpirationDate {
+            object["gracePeriodExpiresDate"] = rfc3339(gracePeriodExpirationDate)
+        }
+        if let autoRenewPreference = renewalInfo.autoRenewPreference {
+            object["renewalProductId"] = autoRenewPreference
+        }
+        object["priceIncreaseStatus"] = priceIncreaseStatusName(renewalInfo.priceIncreaseStatus)
+        return object
+    }
+
+    /// Keep in sync with PriceIncreaseStatus in src/models.rs
+    private func priceIncreaseStatusName(
+        _ status: Product.SubscriptionInfo.RenewalInfo.PriceIncreaseStatus
+    ) -> String? {
+        switch status {
+        case .noIncreasePending:
+            return "noIncreasePending"
+        case .pending:
+            return "pending"
+        case .agreed:
+            return "agreed"
+        @unknown default:
+            return nil
+        }
+    }
+
+    /// Keep in sync with SubscriptionStatus in src/models.rs
+    private func subscriptionStatusObject(_ status: Product.SubscriptionInfo.Status) -> JsonObject? {
+        guard case .verified(let transaction) = status.transaction else {
+            return nil
+        }
+
+        let state: String
+        switch status.state {
+        case .subscribed:
+            state = "subscribed"
+        case .expired:
+            state = "expired"
+        case .inBillingRetryPeriod:
+            state = "inBillingRetry"
+        case .inGracePeriod:
+            state = "inGracePeriod"
+        case .revoked:
+            state = "revoked"
+        default:
+            state = "unknown"
+        }
+
+        var object: JsonObject = [
+            "productId": transaction.productID,
+            "state": state,
+            "transactionId": String(transaction.id),
+            "originalTransactionId": String(transaction.originalID),
+            "isFamilyShared": transaction.ownershipType == .familyShared,
+            "willAutoRenew": false
+        ]
+        if let subscriptionGroupID = transaction.subscriptionGroupID {
+            object["subscriptionGroupId"] = subscriptionGroupID
+        }
+        if let expirationDate = transaction.expirationDate {
+            object["expirationDate"] = rfc3339(expirationDate)
+        }
+
+        if case .verified(let renewalInfo) = status.renewalInfo {
+            object["willAutoRenew"] = renewalInfo.willAutoRenew
+            object["billingRetryPeriod"] = renewalInfo.isInBillingRetry
+            if let autoRenewPreference = renewalInfo.autoRenewPreference {
+                object["autoRenewProductId"] = autoRenewPreference
+            }
+            if let gracePeriodExpirationDate = renewalInfo.gracePeriodExpirationDate {
+                object["gracePeriodExpirationDate"] = rfc3339(gracePeriodExpirationDate)
+            }
+            if let expirationReason = renewalInfo.expirationReason {
+                object["expirationReason"] = expirationReasonName(expirationReason)
+            }
+            object["priceIncreaseStatus"] = priceIncreaseStatusName(renewalInfo.priceIncreaseStatus)
+            if #available(iOS 17.0, *),
+               let renewalPrice = renewalInfo.renewalPrice,
+               let currency = renewalInfo.currency {
+                object["renewalPriceAmountMicros"] = priceAmountMicros(renewalPrice)
+                object["renewalCurrency"] = currency.identifier
+            }
+        }
+        return object
+    }
+
+    private func expirationReasonName(
+        _ reason: Product.SubscriptionInfo.RenewalInfo.ExpirationReason
+    ) -> String {
+        switch reason {
+        case .autoRenewDisabled:
+            return "autoRenewDisabled"
+        case .billingError:
+            return "billingError"
+        case .didNotConsentToPriceIncrease:
+            return "didNotConsentToPriceIncrease"
+        case .productUnavailable:
+            return "productUnavailable"
+        default:
+            return "unknown"
+        }
+    }
+
+    /// Matches the `"subs"`/`"inapp"` wire value of ProductType in src/models.rs.
+    private func productTypeMatches(_ type: Product.ProductType, requested: String) -> Bool {
+        switch requested {
+        case "subs":
+            return type == .autoRenewable || type == .nonRenewable
+        case "inapp":
+            return type == .consumable || type == .nonConsumable
+        default:
+            return true
+        }
+    }
+
+    private func productTypeName(_ type: Product.ProductType) -> String {
+        switch type {
+        case .consumable:
+            return "consumable"
+        case .autoRenewable:
+            return "autoRenewableSubscription"
+        case .nonRenewable:
+            return "nonRenewingSubscription"
+        default:
+            return "nonConsumable"
+        }
+    }
+
+    private func formatSubscriptionPeriod(_ period: Product.SubscriptionPeriod) -> String {
+        switch period.unit {
+        case .day:
+            return "P\(period.value)D"
+        case .week:
+            return "P\(period.value)W"
+        case .month:
+            return "P\(period.value)M"
+        case .year:
+            return "P\(period.value)Y"
+        @unknown default:
+            return "P1M"
+        }
+    }
+    
+    /// Keep in sync with SubscriptionPeriod in src/models.rs
+    private func subscriptionPeriodObject(_ period: Product.SubscriptionPeriod) -> JsonObject {
+        let unit: String
+        switch period.unit {
+        case .day:
+            unit = "day"
+        case .week:
+            unit = "week"
+        case .year:
+            unit = "year"
+        default:
+            unit = "month"
+        }
+        return ["unit": unit, "value": period.value]
+    }
+
+    /// Keep in sync with SubscriptionOfferType in src/models.rs
+    private func offerTypeName(_ type: Product.SubscriptionOffer.OfferType) -> String {
+        if #available(iOS 18.0, *), type == .winBack {
+            return "winBack"
+        }
+        return type == .introductory ? "introductory" : "promotional"
+    }
+
+    /// Keep in sync with SubscriptionOfferInfo in src/models.rs
+    private func subscriptionOfferObject(_ offer: Product.SubscriptionOffer, for product: Product) -> JsonObject {
+        let paymentMode: String
+        switch offer.paymentMode {
+        case .freeTrial:
+            paymentMode = "freeTrial"
+        case .payUpFront:
+            paymentMode = "payUpFront"
+        default:
+            paymentMode = "payAsYouGo"
+        }
+
+        var offerDict: JsonObject = [
+            "offerType": offerTypeName(offer.type),
+            "price": offer.displayPrice,
+            "priceLocale": getPriceLocale(for: product),
+            "period": subscriptionPeriodObject(offer.period),
+            "periodCount": offer.periodCount,
+            "paymentMode": paymentMode
+        ]
+        if let id = offer.id {
+            offerDict["id"] = id
+        }
+        return offerDict
+    }
+
+    private func getPriceLocale(for product: Product) -> String {
+        if #available(iOS 16.0, *) {
+            return product.priceFormatStyle.locale.identifier
+        } else {
+            // Fallback for iOS 15: price locale not directly available
+            return ""
+        }
+    }
+
+    private func getCurrencyCode(for product: Product) -> String {
+        // Unlike `locale.currency`, the format style's ISO 4217 code needs no newer OS
+        return product.priceFormatStyle.currencyCode
+    }
+
+    private func priceAmountMicros(_ decimal: Decimal) -> Int64 {
+        // Round before converting: `int64Value` misreads fractional decimals
+        var micros = decimal * 1_000_000
+        var rounded = Decimal()
+        NSDecimalRound(&rounded, &micros, 0, .plain)
+        return NSDecimalNumber(decimal: rounded).int64Value
+    }
+}
+
+@_cdecl("init_plugin_iap")
+func initPlugin() -> Plugin {
+    if #available(iOS 15.0, *) {
+        return IapPlugin()
+    } else {
+        // Return a dummy plugin for older iOS versions
+        class DummyPlugin: Plugin {
+            @objc func getProducts(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func purchase(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
             }
             @objc func restorePurchases(_ invoke: Invoke) {
                 invoke.reject("IAP requires iOS 15.0 or later")
@@ -465,12 +1952,87 @@ func initPlugin() -> Plugin {
             @objc func getPurchaseHistory(_ invoke: Invoke) {
                 invoke.reject("IAP requires iOS 15.0 or later")
             }
+            @objc func getPurchaseHistoryFiltered(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
             @objc func acknowledgePurchase(_ invoke: Invoke) {
                 invoke.reject("IAP requires iOS 15.0 or later")
             }
             @objc func getProductStatus(_ invoke: Invoke) {
                 invoke.reject("IAP requires iOS 15.0 or later")
             }
+            @objc func getAllProductStatuses(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func finishTransaction(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func getAppTransaction(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func getCurrentEntitlements(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func getSubscriptionStatus(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func showManageSubscriptions(_ invoke: Invoke) {
+                invoke.reject("Managing subscriptions requires iOS 15.0 or later", code: "notSupported")
+            }
+            @objc func presentOfferCodeRedemptionSheet(_ invoke: Invoke) {
+                invoke.reject("Offer code redemption requires iOS 15.0 or later", code: "notSupported")
+            }
+            @objc func getStorefront(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func getUnfinishedTransactions(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func beginRefundRequest(_ invoke: Invoke) {
+                invoke.reject("Refund requests require iOS 15.0 or later", code: "notSupported")
+            }
+            @objc func getWinBackOffers(_ invoke: Invoke) {
+                invoke.resolve(["offers": [JsonObject]()])
+            }
+            @objc func isEligibleForIntroOffer(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func getEnvironment(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func getReceipt(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func refreshReceipt(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func canMakePayments(_ invoke: Invoke) {
+                invoke.resolve(["canMakePayments": false, "reason": "notSupported"])
+            }
+            @objc func checkIntroductoryOfferEligibility(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func canOpenExternalPurchaseLink(_ invoke: Invoke) {
+                invoke.reject("External purchase links require iOS 17.4 or later", code: "notSupported")
+            }
+            @objc func openExternalPurchaseLink(_ invoke: Invoke) {
+                invoke.reject("External purchase links require iOS 17.4 or later", code: "notSupported")
+            }
+            @objc func displayPendingStoreMessages(_ invoke: Invoke) {
+                invoke.reject("StoreKit messages require iOS 16.0 or later", code: "notSupported")
+            }
+            @objc func currentEntitlement(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func getAppReceipt(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func getRevokedPurchases(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
+            @objc func finishUnfinishedTransactions(_ invoke: Invoke) {
+                invoke.reject("IAP requires iOS 15.0 or later")
+            }
         }
         return DummyPlugin()
     }
@@ -298,7 +298,10 @@ final class PurchaseStateValueTests: XCTestCase {
         XCTAssertEqual(PurchaseStateValue(rawValue: 0), .purchased)
         XCTAssertEqual(PurchaseStateValue(rawValue: 1), .canceled)
         XCTAssertEqual(PurchaseStateValue(rawValue: 2), .pending)
-        XCTAssertNil(PurchaseStateValue(rawValue: 3))
+        XCTAssertEqual(PurchaseStateValue(rawValue: 3), .failed)
+        XCTAssertEqual(PurchaseStateValue(rawValue: 4), .refunded)
+        XCTAssertEqual(PurchaseStateValue(rawValue: 5), .deferred)
+        XCTAssertNil(PurchaseStateValue(rawValue: 6))
         XCTAssertNil(PurchaseStateValue(rawValue: -1))
     }
 }
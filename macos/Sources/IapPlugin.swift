@@ -1,7 +1,22 @@
+import AppKit
+import Security
 import StoreKit
 
 extension FFIResult: Error {}
 
+extension FFIResult {
+    /// Error carrying a stable code; Rust decodes it into `ErrorResponse`.
+    static func coded(_ code: String, _ message: String) -> FFIResult {
+        let body = ["code": code, "message": message]
+        guard let data = try? JSONSerialization.data(withJSONObject: body),
+            let json = String(data: data, encoding: .utf8)
+        else {
+            return FFIResult.Err(RustString(message))
+        }
+        return FFIResult.Err(RustString(json))
+    }
+}
+
 typealias JsonObject = [String: Any]
 
 /// Keep in sync with PurchaseState in guest-js/index.ts
@@ -9,22 +24,111 @@ enum PurchaseStateValue: Int {
     case purchased = 0
     case canceled = 1
     case pending = 2
+    case failed = 3
+    case refunded = 4
+    case deferred = 5
+}
+
+/// Keep in sync with PromotionalOffer in src/models.rs
+struct PromotionalOfferArgs: Decodable {
+    let offerId: String
+    let keyId: String
+    let nonce: String
+    let signature: String
+    let timestamp: Int
+}
+
+/// Bridges `SKReceiptRefreshRequest`'s delegate callbacks to async/await.
+/// The request only holds its delegate weakly, so callers keep this alive
+/// by awaiting `refresh()` on it.
+final class ReceiptRefreshDelegate: NSObject, SKRequestDelegate {
+    private var continuation: CheckedContinuation<Void, Error>?
+    private var request: SKReceiptRefreshRequest?
+
+    func refresh() async throws {
+        try await withCheckedThrowingContinuation { (continuation: CheckedContinuation<Void, Error>) in
+            self.continuation = continuation
+            let request = SKReceiptRefreshRequest()
+            request.delegate = self
+            self.request = request
+            request.start()
+        }
+    }
+
+    func requestDidFinish(_ request: SKRequest) {
+        continuation?.resume()
+        continuation = nil
+        self.request = nil
+    }
+
+    func request(_ request: SKRequest, didFailWithError error: Error) {
+        continuation?.resume(throwing: error)
+        continuation = nil
+        self.request = nil
+    }
 }
 
 class IapPlugin {
     private var updateListenerTask: Task<Void, Error>?
+    private var storefrontListenerTask: Task<Void, Never>?
+    private var statusListenerTask: Task<Void, Never>?
+    /// Last seen auto-renew preference per original transaction id, to tell
+    /// renewal preference changes apart from unrelated status updates.
+    private var renewalPreferences: [String: JsonObject] = [:]
+    private let renewalPreferencesLock = NSLock()
+    /// Original transaction ids currently in grace period or billing retry,
+    /// so `billingIssueDetected` fires once per failed renewal.
+    private var billingIssues = Set<String>()
+    private let billingIssuesLock = NSLock()
+    /// Product ids whose purchase is waiting on Ask to Buy approval, so the
+    /// approved transaction can be reported as `purchaseCompleted`.
+    private var pendingApprovals = Set<String>()
+    private let pendingApprovalsLock = NSLock()
+    /// `allowUnverifiedTransactions` from IapConfig in src/config.rs
+    private let allowUnverifiedTransactions: Bool
+    /// `autoFinishTransactionUpdates` from IapConfig in src/config.rs
+    private let autoFinishTransactionUpdates: Bool
+
+    init(allowUnverifiedTransactions: Bool, autoFinishTransactionUpdates: Bool) {
+        self.allowUnverifiedTransactions = allowUnverifiedTransactions
+        self.autoFinishTransactionUpdates = autoFinishTransactionUpdates
 
-    init() {
         // Start listening for transaction updates
         updateListenerTask = Task {
             for await update in Transaction.updates {
                 await self.handleTransactionUpdate(update)
             }
         }
+
+        // Storefront changes (Apple ID switch, region change) make cached prices stale
+        storefrontListenerTask = Task {
+            for await storefront in Storefront.updates {
+                if let jsonString = try? serializeToJSON(self.storefrontObject(storefront)) {
+                    try? trigger("storefrontChanged", jsonString)
+                }
+            }
+        }
+
+        // Downgrades and auto-renew toggles create no transaction, so only
+        // status updates reveal them
+        statusListenerTask = Task {
+            let snapshot = await self.subscriptionStatusSnapshot()
+            self.emitRenewalPreferenceChanges(snapshot)
+            self.emitBillingIssues(snapshot)
+            if #available(macOS 14.0, *) {
+                for await status in Product.SubscriptionInfo.Status.updates {
+                    let objects = [status].compactMap(self.subscriptionStatusObject)
+                    self.emitRenewalPreferenceChanges(objects)
+                    self.emitBillingIssues(objects)
+                }
+            }
+        }
     }
 
     deinit {
         updateListenerTask?.cancel()
+        storefrontListenerTask?.cancel()
+        statusListenerTask?.cancel()
     }
 
     public func getProducts(productIds: RustVec<RustString>, productType: RustString)
@@ -51,6 +155,9 @@ class IapPlugin {
             // Add pricing information
             productDict["formattedPrice"] = product.displayPrice
             productDict["priceCurrencyCode"] = getCurrencyCode(for: product)
+            productDict["priceAmountMicros"] = priceAmountMicros(product.price)
+            productDict["priceLocale"] = getPriceLocale(for: product)
+            productDict["isFamilyShareable"] = product.isFamilyShareable
 
             // Handle subscription-specific information
             if product.type == .autoRenewable || product.type == .nonRenewable {
@@ -97,10 +204,17 @@ class IapPlugin {
                     subscriptionOffers.append(regularOffer)
 
                     productDict["subscriptionOfferDetails"] = subscriptionOffers
+                    productDict["subscriptionGroupId"] = subscription.subscriptionGroupID
+                    productDict["subscriptionPeriod"] = subscriptionPeriodObject(
+                        subscription.subscriptionPeriod)
+                    if let introOffer = subscription.introductoryOffer {
+                        productDict["introductoryOffer"] = subscriptionOfferObject(
+                            introOffer, for: product)
+                    }
+                    productDict["promotionalOffers"] = subscription.promotionalOffers.map {
+                        subscriptionOfferObject($0, for: product)
+                    }
                 }
-            } else {
-                // One-time purchase
-                productDict["priceAmountMicros"] = priceAmountMicros(product.price)
             }
 
             productsArray.append(productDict)
@@ -109,7 +223,11 @@ class IapPlugin {
         return try serializeToJSON(["products": productsArray])
     }
 
-    public func purchase(productId: RustString, productType: RustString, offerToken: RustString?)
+    public func purchase(
+        productId: RustString, productType: RustString, offerToken: RustString?, quantity: UInt32,
+        autoFinish: Bool, promotionalOffer: RustString?, winBackOfferId: RustString?,
+        appAccountToken: RustString?
+    )
         async throws(FFIResult) -> String
     {
         let id = productId.as_str().toString()
@@ -126,47 +244,97 @@ class IapPlugin {
             throw FFIResult.Err(RustString("Product not found"))
         }
 
+        var options: Set<Product.PurchaseOption> = []
+        if quantity > 1 {
+            // The range is checked in Rust; StoreKit would sell a non-consumable once
+            guard product.type == .consumable else {
+                throw FFIResult.coded(
+                    "developerError", "Only consumables can be bought with quantity \(quantity)")
+            }
+            options.insert(.quantity(Int(quantity)))
+        }
+        if let promotionalOffer = promotionalOffer {
+            options.insert(try promotionalOfferOption(promotionalOffer.as_str().toString()))
+        }
+        if let winBackOfferId = winBackOfferId {
+            options.insert(try winBackOfferOption(winBackOfferId.as_str().toString(), for: product))
+        }
+        if let appAccountToken = appAccountToken {
+            // Already validated in Rust; the guard only covers direct callers
+            guard let uuid = UUID(uuidString: appAccountToken.as_str().toString()) else {
+                throw FFIResult.coded(
+                    "developerError", "Invalid appAccountToken: must be a valid UUID string")
+            }
+            options.insert(.appAccountToken(uuid))
+        }
+
         // Initiate purchase
+        emitTransactionEvent(productId: id, state: ["type": "purchasing"])
         let result: Product.PurchaseResult
         do {
-            result = try await product.purchase()
+            result =
+                options.isEmpty
+                ? try await product.purchase()
+                : try await product.purchase(options: options)
+        } catch let error as Product.PurchaseError {
+            let code = offerErrorCode(error)
+            emitTransactionEvent(productId: id, state: ["type": "failed", "error": code])
+            throw FFIResult.coded(code, "Purchase failed: \(error.localizedDescription)")
         } catch {
+            emitTransactionEvent(productId: id, state: ["type": "failed", "error": "unknown"])
             throw FFIResult.Err(RustString("Purchase failed: \(error.localizedDescription)"))
         }
 
         switch result {
         case .success(let verification):
-            switch verification {
-            case .verified(let transaction):
-                // Finish the transaction
-                await transaction.finish()
-
-                let purchase = try await createPurchaseObject(from: verification, product: product)
-                return try serializeToJSON(purchase)
+            guard let transaction = acceptedTransaction(verification) else {
+                emitTransactionEvent(
+                    productId: id, state: ["type": "failed", "error": "verificationFailed"])
+                throw FFIResult.coded("verificationFailed", "Transaction verification failed")
+            }
 
-            case .unverified(_, _):
-                throw FFIResult.Err(RustString("Transaction verification failed"))
+            // Finish the transaction unless the caller wants to validate it first
+            if autoFinish {
+                await transaction.finish()
             }
 
+            emitTransactionEvent(
+                productId: id,
+                state: ["type": "purchased", "transactionId": String(transaction.id)],
+                verification: verification)
+            let purchase = try await createPurchaseObject(from: verification, product: product)
+            return try serializeToJSON(purchase)
+
         case .userCancelled:
-            throw FFIResult.Err(RustString("Purchase cancelled by user"))
+            emitTransactionEvent(
+                productId: id, state: ["type": "failed", "error": "userCancelled"])
+            throw FFIResult.coded("userCancelled", "Purchase cancelled by user")
 
         case .pending:
-            throw FFIResult.Err(RustString("Purchase is pending"))
+            // Ask to Buy / SCA; the outcome arrives later via Transaction.updates
+            emitTransactionEvent(productId: id, state: ["type": "deferred"])
+            pendingApprovalsLock.lock()
+            pendingApprovals.insert(id)
+            pendingApprovalsLock.unlock()
+            return try serializeToJSON(pendingPurchaseObject(productId: id))
 
         @unknown default:
             throw FFIResult.Err(RustString("Unknown purchase result"))
         }
     }
 
-    public func restorePurchases(productType: RustString) async throws(FFIResult) -> String {
+    public func restorePurchases(productType: RustString, forceSync: Bool) async throws(FFIResult) -> String {
         var purchases: [JsonObject] = []
         let requestedType = productType.as_str().toString()
 
+        if forceSync {
+            try await syncWithAppStore()
+        }
+
         // Get all current entitlements
         for await result in Transaction.currentEntitlements {
-            switch result {
-            case .verified(let transaction):
+            switch acceptedTransaction(result) {
+            case .some(let transaction):
                 if let product = try? await Product.products(for: [transaction.productID]).first {
                     // Filter by product type if specified
                     if !requestedType.isEmpty {
@@ -185,14 +353,16 @@ class IapPlugin {
                         if productTypeMatches {
                             let purchase = try await createPurchaseObject(from: result, product: product)
                             purchases.append(purchase)
+                            emitRestored(transaction)
                         }
                     } else {
                         // No filter, include all
                         let purchase = try await createPurchaseObject(from: result, product: product)
                         purchases.append(purchase)
+                        emitRestored(transaction)
                     }
                 }
-            case .unverified(_, _):
+            case .none:
                 // Skip unverified transactions
                 continue
             }
@@ -201,185 +371,1377 @@ class IapPlugin {
         return try serializeToJSON(["purchases": purchases])
     }
 
-    public func getProductStatus(productId: RustString, productType: RustString)
+    /// `AppStore.sync()` pulls transactions made on other devices into the
+    /// local cache. It may show the App Store sign-in prompt.
+    private func syncWithAppStore() async throws(FFIResult) {
+        do {
+            try await AppStore.sync()
+        } catch StoreKitError.userCancelled {
+            throw FFIResult.coded("userCancelled", "App Store sign-in cancelled by user")
+        } catch {
+            throw FFIResult.coded(
+                "syncFailed", "Failed to sync with the App Store: \(error.localizedDescription)")
+        }
+    }
+
+    public func getPurchaseHistory() async throws(FFIResult) -> String {
+        var history: [JsonObject] = []
+
+        // Unlike currentEntitlements, Transaction.all also yields expired
+        // subscriptions and finished consumables. Iterating it here keeps the
+        // work on the cooperative pool rather than the main thread.
+        for await result in Transaction.all {
+            guard let transaction = acceptedTransaction(result) else {
+                continue
+            }
+            history.append(createHistoryRecord(from: transaction, verification: result))
+        }
+
+        return try serializeToJSON(["history": history])
+    }
+
+    public func getPurchaseHistoryFiltered(
+        productType: RustString?, sinceTimestamp: UInt64?, limit: UInt32?
+    )
         async throws(FFIResult) -> String
     {
-        let id = productId.as_str().toString()
+        let requestedType = productType?.as_str().toString()
+        let since = sinceTimestamp.map { Date(timeIntervalSince1970: Double($0) / 1000) }
 
-        var statusResult: JsonObject = [
-            "productId": id,
-            "isOwned": false,
-        ]
+        var transactions: [(transaction: Transaction, result: VerificationResult<Transaction>)] = []
+        for await result in Transaction.all {
+            guard let transaction = acceptedTransaction(result) else {
+                continue
+            }
+            if let since, transaction.purchaseDate < since {
+                continue
+            }
+            if let requestedType,
+                !productTypeMatches(transaction.productType, requested: requestedType)
+            {
+                continue
+            }
+            transactions.append((transaction, result))
+        }
 
-        // Check current entitlements for the specific product
-        for await result in Transaction.currentEntitlements {
-            switch result {
-            case .verified(let transaction):
-                if transaction.productID == id {
-                    statusResult["isOwned"] = true
-                    statusResult["purchaseTime"] = Int(
-                        transaction.purchaseDate.timeIntervalSince1970 * 1000)
-                    statusResult["purchaseToken"] = String(transaction.id)
-                    statusResult["isAcknowledged"] = true  // Always true on macOS
-
-                    // Check if expired/revoked
-                    if let revocationDate = transaction.revocationDate {
-                        statusResult["purchaseState"] = PurchaseStateValue.canceled.rawValue
-                        statusResult["isOwned"] = false
-                        statusResult["expirationTime"] = Int(
-                            revocationDate.timeIntervalSince1970 * 1000)
-                    } else if let expirationDate = transaction.expirationDate {
-                        if expirationDate < Date() {
-                            statusResult["purchaseState"] = PurchaseStateValue.canceled.rawValue
-                            statusResult["isOwned"] = false
-                        } else {
-                            statusResult["purchaseState"] = PurchaseStateValue.purchased.rawValue
-                        }
-                        statusResult["expirationTime"] = Int(
-                            expirationDate.timeIntervalSince1970 * 1000)
-                    } else {
-                        statusResult["purchaseState"] = PurchaseStateValue.purchased.rawValue
-                    }
+        // Transaction.all has no documented order
+        transactions.sort { $0.transaction.purchaseDate > $1.transaction.purchaseDate }
+        if let limit {
+            transactions = Array(transactions.prefix(Int(limit)))
+        }
+        let history = transactions.map {
+            createHistoryRecord(from: $0.transaction, verification: $0.result)
+        }
+        return try serializeToJSON(["history": history])
+    }
 
-                    // Check subscription renewal status if it's a subscription
-                    if let product = try? await Product.products(for: [id]).first {
-                        if product.type == .autoRenewable {
-                            // Check subscription status
-                            if let statuses = try? await product.subscription?.status {
-                                for status in statuses {
-                                    if status.state == .subscribed {
-                                        // `.subscribed` only means the subscription is still active;
-                                        // it does NOT imply auto-renew is on. A subscription that the
-                                        // user cancelled (but hasn't expired yet) is also `.subscribed`.
-                                        // The actual renewal intent lives in renewalInfo.willAutoRenew.
-                                        if case .verified(let renewalInfo) = status.renewalInfo {
-                                            statusResult["isAutoRenewing"] = renewalInfo.willAutoRenew
-                                        } else {
-                                            statusResult["isAutoRenewing"] = true
-                                        }
-                                    } else if status.state == .expired {
-                                        statusResult["isAutoRenewing"] = false
-                                        statusResult["purchaseState"] =
-                                            PurchaseStateValue.canceled.rawValue
-                                        statusResult["isOwned"] = false
-                                    } else if status.state == .inGracePeriod {
-                                        statusResult["isAutoRenewing"] = true
-                                        statusResult["purchaseState"] =
-                                            PurchaseStateValue.purchased.rawValue
-                                    } else {
-                                        statusResult["isAutoRenewing"] = false
-                                    }
-                                    break
-                                }
-                            }
-                        }
-                    }
+    public func getCurrentEntitlements() async throws(FFIResult) -> String {
+        var purchases: [JsonObject] = []
 
-                    break
-                }
-            case .unverified(_, _):
-                // Skip unverified transactions
+        // currentEntitlements reads StoreKit's local cache, so this works
+        // offline and never prompts for App Store credentials.
+        for await result in Transaction.currentEntitlements {
+            guard let transaction = acceptedTransaction(result) else {
+                continue
+            }
+            if transaction.revocationDate != nil {
                 continue
             }
+            if let expirationDate = transaction.expirationDate, expirationDate < Date() {
+                continue
+            }
+
+            // Product metadata only refines isAutoRenewing; skip it when offline
+            let product = try? await Product.products(for: [transaction.productID]).first
+            purchases.append(try await createPurchaseObject(from: result, product: product))
         }
 
-        return try serializeToJSON(statusResult)
+        return try serializeToJSON(["purchases": purchases])
     }
 
-    // MARK: - Helper Functions
+    /// Keep in sync with CurrentEntitlementResponse in src/models.rs
+    public func currentEntitlement(productId: RustString) async throws(FFIResult) -> String {
+        // Local cache only, and no product lookup, so this never touches the
+        // network and can run on every app focus.
+        guard let result = await Transaction.currentEntitlement(for: productId.as_str().toString()),
+              acceptedTransaction(result) != nil else {
+            return try serializeToJSON([:])
+        }
+        return try serializeToJSON(["purchase": try await createPurchaseObject(from: result, product: nil)])
+    }
 
-    private func handleTransactionUpdate(_ result: VerificationResult<Transaction>) async {
-        switch result {
-        case .verified(let transaction):
-            // Get product details
-            if let product = try? await Product.products(for: [transaction.productID]).first {
-                if let purchase = try? await createPurchaseObject(from: result, product: product),
-                   let jsonString = try? serializeToJSON(purchase) {
-                    try? trigger("purchaseUpdated", jsonString)
-                }
+    public func getUnfinishedTransactions() async throws(FFIResult) -> String {
+        var purchases: [JsonObject] = []
+
+        // Transactions the app never finished, e.g. because it quit mid-purchase
+        for await result in Transaction.unfinished {
+            guard let transaction = acceptedTransaction(result) else {
+                continue
             }
+            let product = try? await Product.products(for: [transaction.productID]).first
+            purchases.append(try await createPurchaseObject(from: result, product: product))
+        }
+
+        return try serializeToJSON(["purchases": purchases])
+    }
 
-            // Always finish transactions
+    /// Keep in sync with FinishUnfinishedTransactionsResponse in src/models.rs
+    public func finishUnfinishedTransactions() async throws(FFIResult) -> String {
+        var finished: UInt32 = 0
+
+        // Left behind by an app version that never finished them; each would
+        // otherwise be redelivered on every launch
+        for await result in Transaction.unfinished {
+            // One bad transaction stays unfinished rather than aborting the flush
+            guard let transaction = acceptedTransaction(result) else {
+                continue
+            }
+            let product = try? await Product.products(for: [transaction.productID]).first
+            guard let purchase = try? await createPurchaseObject(from: result, product: product)
+            else {
+                continue
+            }
+            if let jsonString = try? serializeToJSON(purchase) {
+                try? trigger("purchaseCompleted", jsonString)
+            }
             await transaction.finish()
+            finished += 1
+        }
 
-        case .unverified(_, _):
-            // Handle unverified transaction
-            break
+        return try serializeToJSON(["finished": finished])
+    }
+
+    public func getRevokedPurchases(sinceMillis: Int64?) async throws(FFIResult) -> String {
+        let since = sinceMillis.map { Date(timeIntervalSince1970: Double($0) / 1000) }
+        var revoked: [(date: Date, purchase: JsonObject)] = []
+
+        // Refunds and Family Sharing removals, including those that happened
+        // while the app wasn't running
+        for await result in Transaction.all {
+            guard let transaction = acceptedTransaction(result),
+                let revocationDate = transaction.revocationDate
+            else {
+                continue
+            }
+            if let since, revocationDate < since {
+                continue
+            }
+            // Product metadata would only refine isAutoRenewing, which a
+            // revoked transaction never is
+            var purchase = try await createPurchaseObject(from: result, product: nil)
+            purchase["isAutoRenewing"] = false
+            revoked.append((revocationDate, purchase))
         }
+
+        // Transaction.all has no documented order
+        revoked.sort { $0.date > $1.date }
+        return try serializeToJSON(["purchases": revoked.map(\.purchase)])
     }
 
-    private func serializeToJSON(_ object: JsonObject) throws(FFIResult) -> String {
-        guard let data = try? JSONSerialization.data(withJSONObject: object),
-            let jsonString = String(data: data, encoding: .utf8)
+    public func getWinBackOffers(productId: RustString) async throws(FFIResult) -> String {
+        guard #available(macOS 15.0, *) else {
+            return try serializeToJSON(["offers": [JsonObject]()])
+        }
+        let id = productId.as_str().toString()
+
+        let product: Product?
+        do {
+            product = try await Product.products(for: [id]).first
+        } catch {
+            throw FFIResult.Err(
+                RustString("Failed to fetch product: \(error.localizedDescription)"))
+        }
+        guard let product = product else {
+            throw FFIResult.coded("productNotFound", "Product not found")
+        }
+        guard let subscription = product.subscription else {
+            throw FFIResult.coded(
+                "notSubscription", "\(id) is not an auto-renewable subscription")
+        }
+
+        // Eligibility is per purchaser, so merge it across the group's statuses
+        var eligibleIds = Set<String>()
+        for status in (try? await subscription.status) ?? [] {
+            if case .verified(let renewalInfo) = status.renewalInfo {
+                eligibleIds.formUnion(renewalInfo.eligibleWinBackOfferIDs)
+            }
+        }
+
+        // Keep in sync with WinBackOffer in src/models.rs
+        let offers: [JsonObject] = subscription.winBackOffers.map { offer in
+            var object = subscriptionOfferObject(offer, for: product)
+            object["isEligible"] = offer.id.map(eligibleIds.contains) ?? false
+            return object
+        }
+        return try serializeToJSON(["offers": offers])
+    }
+
+    public func getEnvironment() async throws(FFIResult) -> String {
+        let result: VerificationResult<AppTransaction>
+        do {
+            result = try await AppTransaction.shared
+        } catch {
+            throw FFIResult.coded(
+                "appTransactionUnavailable",
+                "Failed to get app transaction: \(error.localizedDescription)")
+        }
+        // Only the environment is read, which doesn't depend on the signature check.
+        // Keep in sync with IapEnvironment in src/models.rs
+        let environment =
+            result.unsafePayloadValue.environment == .production ? "production" : "sandbox"
+        return try serializeToJSON(["environment": environment])
+    }
+
+    public func getReceipt(transactionId: RustString) async throws(FFIResult) -> String {
+        let id = transactionId.as_str().toString()
+
+        var match: Transaction?
+        for await result in Transaction.all {
+            if case .verified(let transaction) = result, String(transaction.id) == id {
+                match = transaction
+                break
+            }
+        }
+        guard let transaction = match else {
+            throw FFIResult.coded(
+                "transactionNotFound", "No transaction with id \(id) for the current user")
+        }
+        guard let url = Bundle.main.appStoreReceiptURL,
+            let receipt = try? Data(contentsOf: url)
         else {
-            throw FFIResult.Err(RustString("Failed to serialize JSON"))
+            throw FFIResult.coded(
+                "receiptUnavailable", "The app has no App Store receipt")
         }
-        return jsonString
+
+        // Keep in sync with PurchaseReceipt in src/models.rs
+        return try serializeToJSON([
+            "platform": "macos",
+            "receiptData": receipt.base64EncodedString(),
+            "productId": transaction.productID,
+            "transactionId": id,
+        ])
     }
 
-    private func formatSubscriptionPeriod(_ period: Product.SubscriptionPeriod) -> String {
-        switch period.unit {
-        case .day:
-            return "P\(period.value)D"
-        case .week:
-            return "P\(period.value)W"
-        case .month:
-            return "P\(period.value)M"
-        case .year:
-            return "P\(period.value)Y"
-        @unknown default:
-            return "P1M"
+    public func refreshReceipt() async throws(FFIResult) -> String {
+        do {
+            // May show the App Store sign-in prompt
+            try await ReceiptRefreshDelegate().refresh()
+        } catch {
+            throw FFIResult.coded(
+                "receiptRefreshFailed", "Failed to refresh receipt: \(error.localizedDescription)")
         }
+        return "{}"
     }
 
-    private func getCurrencyCode(for product: Product) -> String {
-        if #available(macOS 13.0, *) {
-            return product.priceFormatStyle.locale.currency?.identifier ?? ""
-        } else {
-            // Fallback for macOS 12: currency code not directly available
-            return ""
+    /// Keep in sync with GetAppReceiptResponse in src/models.rs
+    public func getAppReceipt(refreshIfMissing: Bool) async throws(FFIResult) -> String {
+        if let receipt = readAppReceipt() {
+            return try serializeToJSON(["receiptData": receipt.base64EncodedString()])
+        }
+        guard refreshIfMissing else {
+            throw FFIResult.coded(
+                "receiptUnavailable", "The app has no App Store receipt")
+        }
+        do {
+            // May show the App Store sign-in prompt
+            try await ReceiptRefreshDelegate().refresh()
+        } catch {
+            throw FFIResult.coded(
+                "receiptUnavailable",
+                "The app has no App Store receipt and refreshing it failed: \(error.localizedDescription)")
+        }
+        guard let receipt = readAppReceipt() else {
+            throw FFIResult.coded(
+                "receiptUnavailable", "The App Store returned no receipt")
         }
+        return try serializeToJSON(["receiptData": receipt.base64EncodedString()])
     }
 
-    private func priceAmountMicros(_ decimal: Decimal) -> Int64 {
-        return NSDecimalNumber(decimal: decimal * 1_000_000).int64Value
+    private func readAppReceipt() -> Data? {
+        Bundle.main.appStoreReceiptURL.flatMap { try? Data(contentsOf: $0) }
     }
 
-    private func createPurchaseObject(from verificationResult: VerificationResult<Transaction>, product: Product) async throws(FFIResult)
-        -> JsonObject
+    /// Keep in sync with SigningInfo in src/models.rs
+    public func getSigningInfo() async throws(FFIResult) -> String {
+        var code: SecCode?
+        var status = SecCodeCopySelf(SecCSFlags(), &code)
+        guard status == errSecSuccess, let code else {
+            throw FFIResult.Err(RustString("Failed to get own code object: OSStatus \(status)"))
+        }
+        var staticCode: SecStaticCode?
+        status = SecCodeCopyStaticCode(code, SecCSFlags(), &staticCode)
+        guard status == errSecSuccess, let staticCode else {
+            throw FFIResult.Err(RustString("Failed to get static code object: OSStatus \(status)"))
+        }
+        var information: CFDictionary?
+        status = SecCodeCopySigningInformation(
+            staticCode, SecCSFlags(rawValue: kSecCSSigningInformation), &information)
+        guard status == errSecSuccess, let info = information as? [String: Any] else {
+            throw FFIResult.Err(RustString("Failed to read signing information: OSStatus \(status)"))
+        }
+
+        // The leaf certificate comes first; unsigned binaries have none
+        let certificates = info[kSecCodeInfoCertificates as String] as? [SecCertificate]
+        let subjectCn = certificates?.first.flatMap { SecCertificateCopySubjectSummary($0) as String? }
+        var result: JsonObject = ["distributionType": distributionType(subjectCn: subjectCn)]
+        if let teamId = info[kSecCodeInfoTeamIdentifier as String] as? String {
+            result["teamId"] = teamId
+        }
+        if let subjectCn {
+            result["subjectCn"] = subjectCn
+        }
+        return try serializeToJSON(result)
+    }
+
+    public func canMakePayments() async throws(FFIResult) -> String {
+        // Keep in sync with CanMakePaymentsResponse in src/models.rs
+        if AppStore.canMakePayments {
+            return try serializeToJSON(["canMakePayments": true])
+        }
+        // Screen Time, parental controls or an MDM profile
+        return try serializeToJSON(["canMakePayments": false, "reason": "restricted"])
+    }
+
+    public func isEligibleForIntroOffer(productId: RustString) async throws(FFIResult) -> String {
+        let id = productId.as_str().toString()
+
+        let product: Product?
+        do {
+            product = try await Product.products(for: [id]).first
+        } catch {
+            throw FFIResult.Err(
+                RustString("Failed to fetch product: \(error.localizedDescription)"))
+        }
+        guard let product = product else {
+            throw FFIResult.coded("productNotFound", "Product not found")
+        }
+        guard let subscription = product.subscription else {
+            throw FFIResult.coded(
+                "notSubscription", "\(id) is not an auto-renewable subscription")
+        }
+
+        // Keep in sync with IsEligibleForIntroOfferResponse in src/models.rs
+        let isEligible = await subscription.isEligibleForIntroOffer
+        return try serializeToJSON([
+            "eligibility": isEligible ? "eligible" : "ineligible",
+            "subscriptionGroupId": subscription.subscriptionGroupID,
+        ])
+    }
+
+    public func checkIntroductoryOfferEligibility(productIds: RustVec<RustString>)
+        async throws(FFIResult) -> String
     {
-        guard case .verified(let transaction) = verificationResult else {
-            throw FFIResult.Err(RustString("Transaction not verified"))
+        let ids: [String] = productIds.map { $0.as_str().toString() }
+        let products: [Product]
+        do {
+            products = try await Product.products(for: ids)
+        } catch {
+            throw FFIResult.Err(
+                RustString("Failed to fetch products: \(error.localizedDescription)"))
         }
 
-        var isAutoRenewing = false
+        // Keep in sync with CheckIntroductoryOfferEligibilityResponse in src/models.rs
+        var eligibility: [String: Bool] = [:]
+        for product in products {
+            guard let subscription = product.subscription else { continue }
+            eligibility[product.id] = await subscription.isEligibleForIntroOffer
+        }
+        return try serializeToJSON(["eligibility": eligibility])
+    }
 
-        // Check if it's an auto-renewable subscription
-        if product.type == .autoRenewable {
-            // Check subscription status
-            if let statuses = try? await product.subscription?.status {
-                for status in statuses {
-                    if status.state == .subscribed {
-                        // `.subscribed` means the subscription is currently active, but a cancelled
-                        // (yet unexpired) subscription also has this state. Use willAutoRenew.
-                        if case .verified(let renewalInfo) = status.renewalInfo {
-                            isAutoRenewing = renewalInfo.willAutoRenew
-                        } else {
-                            isAutoRenewing = true
-                        }
-                        break
-                    }
-                }
-            }
+    public func getSubscriptionStatus(productId: RustString) async throws(FFIResult) -> String {
+        let id = productId.as_str().toString()
+
+        let product: Product?
+        do {
+            product = try await Product.products(for: [id]).first
+        } catch {
+            throw FFIResult.Err(
+                RustString("Failed to fetch product: \(error.localizedDescription)"))
+        }
+        guard let product = product else {
+            throw FFIResult.coded("productNotFound", "Product not found")
+        }
+        guard let subscription = product.subscription else {
+            throw FFIResult.coded(
+                "notSubscription", "\(id) is not an auto-renewable subscription")
         }
 
-        return [
-            "orderId": String(transaction.id),
-            "originalId": String(transaction.originalID),
-            "jwsRepresentation": verificationResult.jwsRepresentation,
+        // One status per purchaser in the subscription group (Family Sharing)
+        let statuses: [Product.SubscriptionInfo.Status]
+        do {
+            statuses = try await subscription.status
+        } catch {
+            throw FFIResult.Err(
+                RustString("Failed to get subscription status: \(error.localizedDescription)"))
+        }
+
+        let objects = statuses.compactMap(subscriptionStatusObject)
+        emitPriceIncreasePending(objects)
+        emitRenewalPreferenceChanges(objects)
+        emitBillingIssues(objects)
+        return try serializeToJSON(["statuses": objects])
+    }
+
+    public func showManageSubscriptions() async throws(FFIResult) -> String {
+        // AppStore.showManageSubscriptions(in:) has no native macOS variant,
+        // so hand off to the App Store's subscription management page.
+        guard let url = URL(string: "macappstores://apps.apple.com/account/subscriptions") else {
+            throw FFIResult.coded("notSupported", "Subscription management URL unavailable")
+        }
+        let opened = await MainActor.run { NSWorkspace.shared.open(url) }
+        guard opened else {
+            throw FFIResult.coded("notSupported", "Could not open the App Store")
+        }
+        return "{}"
+    }
+
+    public func presentOfferCodeRedemptionSheet() async throws(FFIResult) -> String {
+        guard #available(macOS 15.0, *) else {
+            throw FFIResult.coded(
+                "notSupported", "Offer code redemption requires macOS 15.0 or later")
+        }
+        guard let controller = await presentingViewController() else {
+            throw FFIResult.coded("windowError", "No window to present the sheet from")
+        }
+        do {
+            // Redeemed offers are delivered through Transaction.updates, which
+            // emits purchaseUpdated/transactionUpdated like any other purchase
+            try await AppStore.presentOfferCodeRedemptionSheet(from: controller)
+        } catch {
+            throw FFIResult.Err(
+                RustString(
+                    "Failed to present offer code redemption sheet: \(error.localizedDescription)"))
+        }
+        return "{}"
+    }
+
+    public func canOpenExternalPurchaseLink() async throws(FFIResult) -> String {
+        guard #available(macOS 15.4, *) else {
+            throw FFIResult.coded(
+                "notSupported", "External purchase links require macOS 15.4 or later")
+        }
+        // False without the external purchase link entitlement or outside a
+        // qualifying storefront
+        let eligible = await ExternalPurchaseLink.canOpen
+
+        // Keep in sync with ExternalPurchaseLinkAvailability in src/models.rs
+        var availability: JsonObject = ["eligible": eligible]
+        if let storefront = await Storefront.current {
+            availability["storefrontCountry"] = storefront.countryCode
+        }
+        return try serializeToJSON(availability)
+    }
+
+    public func openExternalPurchaseLink() async throws(FFIResult) -> String {
+        guard #available(macOS 15.4, *) else {
+            throw FFIResult.coded(
+                "notSupported", "External purchase links require macOS 15.4 or later")
+        }
+        guard await ExternalPurchaseLink.canOpen else {
+            throw FFIResult.coded(
+                "notEligible", "External purchase links aren't available for this app or storefront")
+        }
+        do {
+            // Shows the system disclosure sheet before leaving the app
+            try await ExternalPurchaseLink.open()
+        } catch {
+            throw FFIResult.Err(
+                RustString("Failed to open external purchase link: \(error.localizedDescription)"))
+        }
+        return "{}"
+    }
+
+    public func beginRefundRequest(transactionId: RustString) async throws(FFIResult) -> String {
+        let id = transactionId.as_str().toString()
+
+        var match: Transaction?
+        for await result in Transaction.all {
+            if case .verified(let transaction) = result, String(transaction.id) == id {
+                match = transaction
+                break
+            }
+        }
+        guard let transaction = match else {
+            throw FFIResult.coded(
+                "transactionNotFound", "No transaction with id \(id) for the current user")
+        }
+        guard let controller = await presentingViewController() else {
+            throw FFIResult.coded("windowError", "No window to present the sheet from")
+        }
+
+        let status: Transaction.RefundRequestStatus
+        do {
+            status = try await transaction.beginRefundRequest(in: controller)
+        } catch let error as Transaction.RefundRequestError {
+            switch error {
+            case .duplicateRequest:
+                throw FFIResult.coded(
+                    "duplicateRequest", "A refund was already requested for this transaction")
+            default:
+                throw FFIResult.coded(
+                    "refundRequestFailed", "Refund request failed: \(error.localizedDescription)")
+            }
+        } catch {
+            throw FFIResult.coded(
+                "refundRequestFailed", "Refund request failed: \(error.localizedDescription)")
+        }
+
+        // Keep in sync with RefundRequestResult in src/models.rs
+        switch status {
+        case .userCancelled:
+            return try serializeToJSON(["result": "userCancelled"])
+        default:
+            return try serializeToJSON(["result": "success"])
+        }
+    }
+
+    public func getStorefront() async throws(FFIResult) -> String {
+        if let storefront = await Storefront.current {
+            return try serializeToJSON(storefrontObject(storefront))
+        }
+        // StoreKit 1 can still know the storefront when StoreKit 2 hasn't loaded it yet
+        guard let storefront = SKPaymentQueue.default().storefront else {
+            throw FFIResult.coded("storefrontUnavailable", "App Store storefront unavailable")
+        }
+        return try serializeToJSON(["id": storefront.identifier, "countryCode": storefront.countryCode])
+    }
+
+    public func getProductStatus(productId: RustString, productType: RustString)
+        async throws(FFIResult) -> String
+    {
+        let id = productId.as_str().toString()
+
+        var statusResult: JsonObject = [
+            "productId": id,
+            "isOwned": false,
+        ]
+
+        // Check current entitlements for the specific product
+        for await result in Transaction.currentEntitlements {
+            switch result {
+            case .verified(let transaction):
+                if transaction.productID == id {
+                    let product = try? await Product.products(for: [id]).first
+                    statusResult = await entitlementStatus(transaction, product: product)
+                    break
+                }
+            case .unverified(_, _):
+                // Skip unverified transactions
+                continue
+            }
+        }
+
+        // Product.latestTransaction also covers revoked and expired purchases,
+        // so the UI can say when access ended.
+        if let product = try? await Product.products(for: [id]).first,
+            let latest = await product.latestTransaction,
+            let purchase = try? await createPurchaseObject(from: latest, product: product)
+        {
+            statusResult["latestTransaction"] = purchase
+        }
+
+        return try serializeToJSON(statusResult)
+    }
+
+    public func getAllProductStatuses(productType: RustString) async throws(FFIResult) -> String {
+        let requestedType = productType.as_str().toString()
+
+        // One pass over currentEntitlements and one product lookup, instead
+        // of a getProductStatus round-trip per product.
+        var transactions: [Transaction] = []
+        for await result in Transaction.currentEntitlements {
+            if case .verified(let transaction) = result,
+                productTypeMatches(transaction.productType, requested: requestedType)
+            {
+                transactions.append(transaction)
+            }
+        }
+
+        let products = (try? await Product.products(for: Set(transactions.map(\.productID)))) ?? []
+        let productsById = Dictionary(products.map { ($0.id, $0) }) { first, _ in first }
+
+        var statuses: [JsonObject] = []
+        for transaction in transactions {
+            let product = productsById[transaction.productID]
+            var status = await entitlementStatus(transaction, product: product)
+            if let product,
+                let latest = await product.latestTransaction,
+                let purchase = try? await createPurchaseObject(from: latest, product: product)
+            {
+                status["latestTransaction"] = purchase
+            }
+            statuses.append(status)
+        }
+
+        return try serializeToJSON(["statuses": statuses])
+    }
+
+    /// Keep in sync with ProductStatus in src/models.rs
+    /// The status of a verified current entitlement, without latestTransaction.
+    private func entitlementStatus(_ transaction: Transaction, product: Product?) async -> JsonObject {
+        var statusResult: JsonObject = [
+            "productId": transaction.productID,
+            "isOwned": true,
+            "purchaseTime": rfc3339(transaction.purchaseDate),
+            "purchaseToken": String(transaction.id),
+            "isAcknowledged": true,  // Always true on macOS
+        ]
+
+        // Check if expired/revoked
+        if let revocationDate = transaction.revocationDate {
+            statusResult["purchaseState"] = PurchaseStateValue.canceled.rawValue
+            statusResult["isOwned"] = false
+            statusResult["expirationTime"] = rfc3339(revocationDate)
+        } else if let expirationDate = transaction.expirationDate {
+            if expirationDate < Date() {
+                statusResult["purchaseState"] = PurchaseStateValue.canceled.rawValue
+                statusResult["isOwned"] = false
+            } else {
+                statusResult["purchaseState"] = PurchaseStateValue.purchased.rawValue
+            }
+            statusResult["expirationTime"] = rfc3339(expirationDate)
+        } else {
+            statusResult["purchaseState"] = PurchaseStateValue.purchased.rawValue
+        }
+
+        // StoreKit never sets expirationDate on a non-renewing subscription
+        // and keeps it in currentEntitlements for good; the app decides
+        // when the purchased period ends, starting from purchaseTime.
+        if transaction.productType == .nonRenewable {
+            statusResult["isAutoRenewing"] = false
+        }
+
+        // Check subscription renewal status if it's a subscription
+        guard let product, product.type == .autoRenewable,
+            let statuses = try? await product.subscription?.status
+        else {
+            return statusResult
+        }
+        // Family Sharing can yield several statuses; prefer
+        // the one for this transaction's purchaser.
+        let ownStatus =
+            statuses.first { status in
+                guard case .verified(let statusTransaction) = status.transaction else {
+                    return false
+                }
+                return statusTransaction.originalID == transaction.originalID
+            } ?? statuses.first
+        guard let status = ownStatus else {
+            return statusResult
+        }
+        statusResult["renewalInfo"] = renewalInfoObject(
+            status, expirationDate: transaction.expirationDate)
+
+        if status.state == .subscribed {
+            // `.subscribed` only means the subscription is still active;
+            // it does NOT imply auto-renew is on. A subscription that the
+            // user cancelled (but hasn't expired yet) is also `.subscribed`.
+            // The actual renewal intent lives in renewalInfo.willAutoRenew.
+            if case .verified(let renewalInfo) = status.renewalInfo {
+                statusResult["isAutoRenewing"] = renewalInfo.willAutoRenew
+            } else {
+                statusResult["isAutoRenewing"] = true
+            }
+        } else if status.state == .expired {
+            statusResult["isAutoRenewing"] = false
+            statusResult["purchaseState"] = PurchaseStateValue.canceled.rawValue
+            statusResult["isOwned"] = false
+        } else if status.state == .inGracePeriod {
+            // The renewal charge failed, but the user keeps
+            // access until the grace period ends even though
+            // expirationDate has passed
+            statusResult["isAutoRenewing"] = true
+            statusResult["purchaseState"] = PurchaseStateValue.purchased.rawValue
+            statusResult["isOwned"] = true
+        } else {
+            statusResult["isAutoRenewing"] = false
+        }
+        return statusResult
+    }
+
+    public func finishTransaction(transactionId: RustString) async throws(FFIResult) -> String {
+        let id = transactionId.as_str().toString()
+
+        guard let transaction = await findTransaction(id: id) else {
+            throw FFIResult.coded(
+                "transactionNotFound", "No unfinished transaction with id \(id)")
+        }
+        await transaction.finish()
+        return "{}"
+    }
+
+    /// Looks `id` up among unfinished transactions (including those delivered
+    /// through Transaction.updates), then current entitlements. finish() is
+    /// idempotent, so finishing an entitlement that was already finished is
+    /// harmless.
+    private func findTransaction(id: String) async -> Transaction? {
+        for await result in Transaction.unfinished {
+            if case .verified(let transaction) = result, String(transaction.id) == id {
+                return transaction
+            }
+        }
+        for await result in Transaction.currentEntitlements {
+            if case .verified(let transaction) = result, String(transaction.id) == id {
+                return transaction
+            }
+        }
+        return nil
+    }
+
+    public func getAppTransaction(forceRefresh: Bool) async throws(FFIResult) -> String {
+        let result: VerificationResult<AppTransaction>
+        do {
+            // refresh() shows the App Store sign-in prompt
+            result =
+                forceRefresh
+                ? try await AppTransaction.refresh() : try await AppTransaction.shared
+        } catch {
+            throw FFIResult.coded(
+                "appTransactionUnavailable",
+                "Failed to get app transaction: \(error.localizedDescription)")
+        }
+
+        guard case .verified(let appTransaction) = result else {
+            throw FFIResult.coded("verificationFailed", "App transaction verification failed")
+        }
+
+        // Keep in sync with AppTransactionInfo in src/models.rs
+        return try serializeToJSON([
+            "bundleId": appTransaction.bundleID,
+            "appVersion": appTransaction.appVersion,
+            "originalAppVersion": appTransaction.originalAppVersion,
+            "originalPurchaseDate": rfc3339(appTransaction.originalPurchaseDate),
+            "environment": appTransaction.environment == .production ? "production" : "sandbox",
+            "jwsRepresentation": result.jwsRepresentation,
+        ])
+    }
+
+    // MARK: - Helper Functions
+
+    private func handleTransactionUpdate(_ result: VerificationResult<Transaction>) async {
+        // Unverified transactions are never delivered unless the app opted in
+        switch acceptedTransaction(result) {
+        case .some(let transaction):
+            if transaction.revocationDate != nil {
+                emitTransactionEvent(
+                    productId: transaction.productID, state: ["type": "revoked"],
+                    verification: result)
+
+                // Keep in sync with PurchaseRevokedEvent in src/models.rs
+                var revoked: JsonObject = [
+                    "productId": transaction.productID,
+                    "transactionId": String(transaction.id),
+                ]
+                revoked.merge(revocationFields(transaction)) { $1 }
+                if let jsonString = try? serializeToJSON(revoked) {
+                    try? trigger("purchaseRevoked", jsonString)
+                }
+            } else {
+                emitTransactionEvent(
+                    productId: transaction.productID,
+                    state: ["type": "purchased", "transactionId": String(transaction.id)],
+                    verification: result)
+            }
+
+            // Get product details
+            if let product = try? await Product.products(for: [transaction.productID]).first {
+                if let purchase = try? await createPurchaseObject(from: result, product: product),
+                   let jsonString = try? serializeToJSON(purchase) {
+                    try? trigger("purchaseUpdated", jsonString)
+                    if takePendingApproval(transaction) {
+                        try? trigger("purchaseCompleted", jsonString)
+                    }
+                }
+                if let statuses = try? await product.subscription?.status {
+                    let objects = statuses.compactMap(subscriptionStatusObject)
+                    emitPriceIncreasePending(objects)
+                    emitRenewalPreferenceChanges(objects)
+                    emitBillingIssues(objects)
+                }
+            }
+
+            // Left open by default so the app can validate the JWS first and
+            // call finishTransaction; StoreKit redelivers it until then
+            if autoFinishTransactionUpdates {
+                await transaction.finish()
+            }
+
+        case .none:
+            break
+        }
+    }
+
+    /// Keep in sync with TransactionEvent in src/models.rs
+    private func emitTransactionEvent(
+        productId: String, state: JsonObject, verification: VerificationResult<Transaction>? = nil
+    ) {
+        var event: JsonObject = [
+            "productId": productId,
+            "state": state,
+            "timestamp": Int(Date().timeIntervalSince1970 * 1000),
+        ]
+        if let verification {
+            event["verification"] = verificationName(verification)
+        }
+        if let jsonString = try? serializeToJSON(event) {
+            try? trigger("transactionUpdated", jsonString)
+        }
+    }
+
+    /// Keep in sync with PriceIncreasePendingEvent in src/models.rs
+    private func emitPriceIncreasePending(_ statuses: [JsonObject]) {
+        for status in statuses where status["priceIncreaseStatus"] as? String == "pending" {
+            let event: JsonObject = [
+                "productId": status["productId"] ?? "",
+                "originalTransactionId": status["originalTransactionId"] ?? "",
+            ]
+            if let jsonString = try? serializeToJSON(event) {
+                try? trigger("priceIncreasePending", jsonString)
+            }
+        }
+    }
+
+    /// Keep in sync with RenewalPreferenceChangedEvent in src/models.rs
+    ///
+    /// The first status seen for a subscription only records its preference,
+    /// so launching the app doesn't report every subscription as changed.
+    private func emitRenewalPreferenceChanges(_ statuses: [JsonObject]) {
+        for status in statuses {
+            guard let originalTransactionId = status["originalTransactionId"] as? String else {
+                continue
+            }
+            var preference: JsonObject = ["willAutoRenew": status["willAutoRenew"] as? Bool ?? false]
+            if let autoRenewProductId = status["autoRenewProductId"] as? String {
+                preference["autoRenewProductId"] = autoRenewProductId
+            }
+
+            renewalPreferencesLock.lock()
+            let previous = renewalPreferences.updateValue(preference, forKey: originalTransactionId)
+            renewalPreferencesLock.unlock()
+
+            guard let previous, !NSDictionary(dictionary: previous).isEqual(to: preference) else {
+                continue
+            }
+            var event = preference
+            event["productId"] = status["productId"] ?? ""
+            event["originalTransactionId"] = originalTransactionId
+            if let jsonString = try? serializeToJSON(event) {
+                try? trigger("renewalPreferenceChanged", jsonString)
+            }
+        }
+    }
+
+    /// Keep in sync with BillingIssueDetectedEvent in src/models.rs
+    private func emitBillingIssues(_ statuses: [JsonObject]) {
+        for status in statuses {
+            guard let originalTransactionId = status["originalTransactionId"] as? String,
+                let state = status["state"] as? String
+            else {
+                continue
+            }
+            let hasIssue = state == "inGracePeriod" || state == "inBillingRetry"
+
+            // Grace period turning into billing retry is the same issue
+            billingIssuesLock.lock()
+            let isNew: Bool
+            if hasIssue {
+                isNew = billingIssues.insert(originalTransactionId).inserted
+            } else {
+                billingIssues.remove(originalTransactionId)
+                isNew = false
+            }
+            billingIssuesLock.unlock()
+
+            guard isNew else { continue }
+            var event: JsonObject = [
+                "productId": status["productId"] ?? "",
+                "originalTransactionId": originalTransactionId,
+                "state": state,
+            ]
+            if let gracePeriodExpirationDate = status["gracePeriodExpirationDate"] {
+                event["gracePeriodExpirationDate"] = gracePeriodExpirationDate
+            }
+            if let jsonString = try? serializeToJSON(event) {
+                try? trigger("billingIssueDetected", jsonString)
+            }
+        }
+    }
+
+    /// Statuses of every subscription group the user is entitled to.
+    private func subscriptionStatusSnapshot() async -> [JsonObject] {
+        var seenGroups = Set<String>()
+        var statuses: [JsonObject] = []
+        for await result in Transaction.currentEntitlements {
+            guard case .verified(let transaction) = result,
+                transaction.productType == .autoRenewable,
+                let product = try? await Product.products(for: [transaction.productID]).first,
+                let subscription = product.subscription,
+                seenGroups.insert(subscription.subscriptionGroupID).inserted,
+                let groupStatuses = try? await subscription.status
+            else {
+                continue
+            }
+            statuses.append(contentsOf: groupStatuses.compactMap(subscriptionStatusObject))
+        }
+        return statuses
+    }
+
+    /// Builds `.promotionalOffer` from the JSON-encoded PromotionalOffer in src/models.rs.
+    private func promotionalOfferOption(_ json: String) throws(FFIResult) -> Product.PurchaseOption {
+        guard let data = json.data(using: .utf8),
+            let offer = try? JSONDecoder().decode(PromotionalOfferArgs.self, from: data),
+            let nonce = UUID(uuidString: offer.nonce),
+            let signature = Data(base64Encoded: offer.signature)
+        else {
+            throw FFIResult.coded(
+                "invalidOffer", "Invalid promotional offer: nonce must be a UUID and signature base64")
+        }
+        return .promotionalOffer(
+            offerID: offer.offerId, keyID: offer.keyId, nonce: nonce, signature: signature,
+            timestamp: offer.timestamp)
+    }
+
+    private func winBackOfferOption(_ offerId: String, for product: Product) throws(FFIResult)
+        -> Product.PurchaseOption
+    {
+        guard #available(macOS 15.0, *) else {
+            throw FFIResult.coded("notSupported", "Win-back offers require macOS 15.0 or later")
+        }
+        guard let offer = product.subscription?.winBackOffers.first(where: { $0.id == offerId })
+        else {
+            throw FFIResult.coded("invalidOffer", "Unknown win-back offer \(offerId)")
+        }
+        return .winBackOffer(offer)
+    }
+
+    /// Keep in sync with IapErrorCode in src/error.rs
+    private func offerErrorCode(_ error: Product.PurchaseError) -> String {
+        switch error {
+        case .invalidOfferSignature:
+            return "invalidOfferSignature"
+        case .invalidOfferIdentifier, .invalidOfferPrice, .missingOfferParameters,
+            .ineligibleForOffer:
+            return "invalidOffer"
+        case .purchaseNotAllowed:
+            return "paymentNotAllowed"
+        case .productUnavailable:
+            return "itemUnavailable"
+        default:
+            return "unknown"
+        }
+    }
+
+    /// View controller of the key (or main) window, for presenting StoreKit sheets.
+    private func presentingViewController() async -> NSViewController? {
+        return await MainActor.run {
+            (NSApp.keyWindow ?? NSApp.mainWindow)?.contentViewController
+        }
+    }
+
+    /// Keep in sync with StorefrontInfo in src/models.rs
+    private func storefrontObject(_ storefront: Storefront) -> JsonObject {
+        return ["id": storefront.id, "countryCode": storefront.countryCode]
+    }
+
+    private func emitRestored(_ transaction: Transaction) {
+        emitTransactionEvent(
+            productId: transaction.productID,
+            state: ["type": "restored", "originalTransactionId": String(transaction.originalID)])
+    }
+
+    private func createHistoryRecord(
+        from transaction: Transaction, verification: VerificationResult<Transaction>
+    ) -> JsonObject {
+        var record: JsonObject = [
+            "productId": transaction.productID,
+            "purchaseTime": rfc3339(transaction.purchaseDate),
+            "purchaseToken": String(transaction.id),
+            "transactionId": String(transaction.id),
+            "originalTransactionId": String(transaction.originalID),
+            "productType": productTypeName(transaction.productType),
+            "quantity": transaction.purchasedQuantity,
+            "originalJson": "",  // Not available in StoreKit 2
+            "signature": "",  // Not available in StoreKit 2
+            "verification": verificationName(verification),
+        ]
+        if let webOrderLineItemID = transaction.webOrderLineItemID {
+            record["webOrderLineItemId"] = webOrderLineItemID
+        }
+        if let transactionReason = transactionReasonName(transaction) {
+            record["transactionReason"] = transactionReason
+        }
+        record.merge(revocationFields(transaction)) { $1 }
+        return record
+    }
+
+    /// Keep in sync with Purchase in src/models.rs. Stands in for a purchase
+    /// StoreKit reported as pending: there is no transaction yet, so only the
+    /// product id and state are meaningful.
+    private func pendingPurchaseObject(productId: String) -> JsonObject {
+        return [
             "packageName": Bundle.main.bundleIdentifier ?? "",
+            "productId": productId,
+            "purchaseTime": rfc3339(Date()),
+            "purchaseToken": "",
+            "purchaseState": PurchaseStateValue.deferred.rawValue,
+            "isAutoRenewing": false,
+            "isAcknowledged": false,
+            "originalJson": "",
+            "signature": "",
+        ]
+    }
+
+    /// Whether `transaction` completes a purchase `purchase()` reported as
+    /// pending. Clears the pending entry so it completes only once.
+    private func takePendingApproval(_ transaction: Transaction) -> Bool {
+        guard transaction.revocationDate == nil else {
+            return false
+        }
+        pendingApprovalsLock.lock()
+        defer { pendingApprovalsLock.unlock() }
+        return pendingApprovals.remove(transaction.productID) != nil
+    }
+
+    /// Keep in sync with DistributionType in src/models.rs
+    private func distributionType(subjectCn: String?) -> String {
+        guard let subjectCn else {
+            return "unknown"
+        }
+        if subjectCn.hasPrefix("Apple Development") || subjectCn.hasPrefix("Mac Developer") {
+            return "development"
+        }
+        // Mac App Store downloads are re-signed by Apple; uploads carry the
+        // team's distribution certificate
+        let appStoreSigners = [
+            "Apple Mac OS Application Signing", "Apple Distribution",
+            "3rd Party Mac Developer Application",
+        ]
+        guard appStoreSigners.contains(where: subjectCn.hasPrefix) else {
+            return "unknown"
+        }
+        // TestFlight builds are App Store signed but get a sandbox receipt
+        let receipt = Bundle.main.appStoreReceiptURL?.lastPathComponent
+        return receipt == "sandboxReceipt" ? "testFlight" : "appStore"
+    }
+
+    /// The transaction to deliver: verified ones always, unverified ones only
+    /// when `allowUnverifiedTransactions` is set.
+    private func acceptedTransaction(_ result: VerificationResult<Transaction>) -> Transaction? {
+        switch result {
+        case .verified(let transaction):
+            return transaction
+        case .unverified(let transaction, _):
+            return allowUnverifiedTransactions ? transaction : nil
+        }
+    }
+
+    /// Keep in sync with TransactionVerification in src/models.rs
+    private func verificationName(_ result: VerificationResult<Transaction>) -> String {
+        if case .verified = result {
+            return "verified"
+        }
+        return "unverified"
+    }
+
+    /// `revocationTime`/`revocationReason` shared by Purchase, PurchaseHistoryRecord
+    /// and PurchaseRevokedEvent in src/models.rs; empty unless revoked.
+    private func revocationFields(_ transaction: Transaction) -> JsonObject {
+        var fields: JsonObject = [:]
+        if let revocationDate = transaction.revocationDate {
+            fields["revocationTime"] = rfc3339(revocationDate)
+        }
+        if let revocationReason = transaction.revocationReason {
+            fields["revocationReason"] =
+                revocationReason == .developerIssue ? "developerIssue" : "other"
+        }
+        return fields
+    }
+
+    /// Keep in sync with SubscriptionRenewalInfo in src/models.rs
+    private func renewalInfoObject(
+        _ status: Product.SubscriptionInfo.Status, expirationDate: Date?
+    ) -> JsonObject? {
+        guard case .verified(let renewalInfo) = status.renewalInfo else {
+            return nil
+        }
+
+        var object: JsonObject = [
+            "autoRenewEnabled": renewalInfo.willAutoRenew,
+            "billingRetryPeriod": renewalInfo.isInBillingRetry,
+        ]
+        if let expirationDate = expirationDate {
+            object["expirationDate"] = rfc3339(expirationDate)
+        }
+        if let gracePeriodExpirationDate = renewalInfo.gracePeriodExpirationDate {
+            object["gracePeriodExpiresDate"] = rfc3339(gracePeriodExpirationDate)
+        }
+        if let autoRenewPreference = renewalInfo.autoRenewPreference {
+            object["renewalProductId"] = autoRenewPreference
+        }
+        object["priceIncreaseStatus"] = priceIncreaseStatusName(renewalInfo.priceIncreaseStatus)
+        return object
+    }
+
+    /// Keep in sync with PriceIncreaseStatus in src/models.rs
+    private func priceIncreaseStatusName(
+        _ status: Product.SubscriptionInfo.RenewalInfo.PriceIncreaseStatus
+    ) -> String? {
+        switch status {
+        case .noIncreasePending:
+            return "noIncreasePending"
+        case .pending:
+            return "pending"
+        case .agreed:
+            return "agreed"
+        @unknown default:
+            return nil
+        }
+    }
+
+    /// Keep in sync with SubscriptionStatus in src/models.rs
+    private func subscriptionStatusObject(_ status: Product.SubscriptionInfo.Status) -> JsonObject? {
+        guard case .verified(let transaction) = status.transaction else {
+            return nil
+        }
+
+        let state: String
+        switch status.state {
+        case .subscribed:
+            state = "subscribed"
+        case .expired:
+            state = "expired"
+        case .inBillingRetryPeriod:
+            state = "inBillingRetry"
+        case .inGracePeriod:
+            state = "inGracePeriod"
+        case .revoked:
+            state = "revoked"
+        default:
+            state = "unknown"
+        }
+
+        var object: JsonObject = [
             "productId": transaction.productID,
-            "purchaseTime": Int(transaction.purchaseDate.timeIntervalSince1970 * 1000),
+            "state": state,
+            "transactionId": String(transaction.id),
+            "originalTransactionId": String(transaction.originalID),
+            "isFamilyShared": transaction.ownershipType == .familyShared,
+            "willAutoRenew": false,
+        ]
+        if let subscriptionGroupID = transaction.subscriptionGroupID {
+            object["subscriptionGroupId"] = subscriptionGroupID
+        }
+        if let expirationDate = transaction.expirationDate {
+            object["expirationDate"] = rfc3339(expirationDate)
+        }
+
+        if case .verified(let renewalInfo) = status.renewalInfo {
+            object["willAutoRenew"] = renewalInfo.willAutoRenew
+            object["billingRetryPeriod"] = renewalInfo.isInBillingRetry
+            if let autoRenewPreference = renewalInfo.autoRenewPreference {
+                object["autoRenewProductId"] = autoRenewPreference
+            }
+            if let gracePeriodExpirationDate = renewalInfo.gracePeriodExpirationDate {
+                object["gracePeriodExpirationDate"] = rfc3339(gracePeriodExpirationDate)
+            }
+            if let expirationReason = renewalInfo.expirationReason {
+                object["expirationReason"] = expirationReasonName(expirationReason)
+            }
+            object["priceIncreaseStatus"] = priceIncreaseStatusName(renewalInfo.priceIncreaseStatus)
+            if #available(macOS 14.0, *),
+               let renewalPrice = renewalInfo.renewalPrice,
+               let currency = renewalInfo.currency {
+                object["renewalPriceAmountMicros"] = priceAmountMicros(renewalPrice)
+                object["renewalCurrency"] = currency.identifier
+            }
+        }
+        return object
+    }
+
+    private func expirationReasonName(
+        _ reason: Product.SubscriptionInfo.RenewalInfo.ExpirationReason
+    ) -> String {
+        switch reason {
+        case .autoRenewDisabled:
+            return "autoRenewDisabled"
+        case .billingError:
+            return "billingError"
+        case .didNotConsentToPriceIncrease:
+            return "didNotConsentToPriceIncrease"
+        case .productUnavailable:
+            return "productUnavailable"
+        default:
+            return "unknown"
+        }
+    }
+
+    /// Keep in sync with ProductType in src/models.rs
+    /// Matches the `"subs"`/`"inapp"` wire value of ProductType in src/models.rs.
+    private func productTypeMatches(_ type: Product.ProductType, requested: String) -> Bool {
+        switch requested {
+        case "subs":
+            return type == .autoRenewable || type == .nonRenewable
+        case "inapp":
+            return type == .consumable || type == .nonConsumable
+        default:
+            return true
+        }
+    }
+
+    private func productTypeName(_ type: Product.ProductType) -> String {
+        switch type {
+        case .consumable:
+            return "consumable"
+        case .autoRenewable:
+            return "autoRenewableSubscription"
+        case .nonRenewable:
+            return "nonRenewingSubscription"
+        default:
+            return "nonConsumable"
+        }
+    }
+
+    private func serializeToJSON(_ object: JsonObject) throws(FFIResult) -> String {
+        guard let data = try? JSONSerialization.data(withJSONObject: object),
+            let jsonString = String(data: data, encoding: .utf8)
+        else {
+            throw FFIResult.Err(RustString("Failed to serialize JSON"))
+        }
+        return jsonString
+    }
+
+    private func formatSubscriptionPeriod(_ period: Product.SubscriptionPeriod) -> String {
+        switch period.unit {
+        case .day:
+            return "P\(period.value)D"
+        case .week:
+            return "P\(period.value)W"
+        case .month:
+            return "P\(period.value)M"
+        case .year:
+            return "P\(period.value)Y"
+        @unknown default:
+            return "P1M"
+        }
+    }
+
+    /// Keep in sync with SubscriptionPeriod in src/models.rs
+    private func subscriptionPeriodObject(_ period: Product.SubscriptionPeriod) -> JsonObject {
+        let unit: String
+        switch period.unit {
+        case .day:
+            unit = "day"
+        case .week:
+            unit = "week"
+        case .year:
+            unit = "year"
+        default:
+            unit = "month"
+        }
+        return ["unit": unit, "value": period.value]
+    }
+
+    /// Keep in sync with SubscriptionOfferType in src/models.rs
+    private func offerTypeName(_ type: Product.SubscriptionOffer.OfferType) -> String {
+        if #available(macOS 15.0, *), type == .winBack {
+            return "winBack"
+        }
+        return type == .introductory ? "introductory" : "promotional"
+    }
+
+    /// Keep in sync with SubscriptionOfferInfo in src/models.rs
+    private func subscriptionOfferObject(_ offer: Product.SubscriptionOffer, for product: Product)
+        -> JsonObject
+    {
+        let paymentMode: String
+        switch offer.paymentMode {
+        case .freeTrial:
+            paymentMode = "freeTrial"
+        case .payUpFront:
+            paymentMode = "payUpFront"
+        default:
+            paymentMode = "payAsYouGo"
+        }
+
+        var offerDict: JsonObject = [
+            "offerType": offerTypeName(offer.type),
+            "price": offer.displayPrice,
+            "priceLocale": getPriceLocale(for: product),
+            "period": subscriptionPeriodObject(offer.period),
+            "periodCount": offer.periodCount,
+            "paymentMode": paymentMode,
+        ]
+        if let id = offer.id {
+            offerDict["id"] = id
+        }
+        return offerDict
+    }
+
+    private func getPriceLocale(for product: Product) -> String {
+        if #available(macOS 13.0, *) {
+            return product.priceFormatStyle.locale.identifier
+        } else {
+            // Fallback for macOS 12: price locale not directly available
+            return ""
+        }
+    }
+
+    private func getCurrencyCode(for product: Product) -> String {
+        // Unlike `locale.currency`, the format style's ISO 4217 code needs no newer OS
+        return product.priceFormatStyle.currencyCode
+    }
+
+    private func priceAmountMicros(_ decimal: Decimal) -> Int64 {
+        // Round before converting: `int64Value` misreads fractional decimals
+        var micros = decimal * 1_000_000
+        var rounded = Decimal()
+        NSDecimalRound(&rounded, &micros, 0, .plain)
+        return NSDecimalNumber(decimal: rounded).int64Value
+    }
+
+    private func createPurchaseObject(from verificationResult: VerificationResult<Transaction>, product: Product?) async throws(FFIResult)
+        -> JsonObject
+    {
+        guard let transaction = acceptedTransaction(verificationResult) else {
+            throw FFIResult.coded("verificationFailed", "Transaction verification failed")
+        }
+
+        // Without product metadata (e.g. offline) assume an auto-renewable
+        // entitlement is still renewing.
+        var isAutoRenewing = product == nil && transaction.productType == .autoRenewable
+        var nextRenewalDate: Date?
+
+        // Check if it's an auto-renewable subscription
+        if let product, product.type == .autoRenewable {
+            // Check subscription status
+            if let statuses = try? await product.subscription?.status {
+                for status in statuses {
+                    if status.state == .subscribed {
+                        // `.subscribed` means the subscription is currently active, but a cancelled
+                        // (yet unexpired) subscription also has this state. Use willAutoRenew.
+                        if case .verified(let renewalInfo) = status.renewalInfo {
+                            isAutoRenewing = renewalInfo.willAutoRenew
+                            if renewalInfo.willAutoRenew {
+                                nextRenewalDate = renewalDate(renewalInfo, expirationDate: transaction.expirationDate)
+                            }
+                        } else {
+                            isAutoRenewing = true
+                        }
+                        break
+                    }
+                }
+            }
+        }
+
+        var purchase: JsonObject = [
+            "orderId": String(transaction.id),
+            "originalId": String(transaction.originalID),
+            "originalTransactionId": String(transaction.originalID),
+            "jwsRepresentation": verificationResult.jwsRepresentation,
+            "packageName": Bundle.main.bundleIdentifier ?? "",
+            "productId": transaction.productID,
+            "purchaseTime": rfc3339(transaction.purchaseDate),
             "purchaseToken": String(transaction.id),
             "purchaseState": transaction.revocationDate == nil
                 ? PurchaseStateValue.purchased.rawValue : PurchaseStateValue.canceled.rawValue,
@@ -387,11 +1749,116 @@ class IapPlugin {
             "isAcknowledged": true,  // Always true on macOS
             "originalJson": "",  // Not available in StoreKit 2
             "signature": "",  // Not available in StoreKit 2
+            "ownershipType": ownershipTypeName(transaction.ownershipType),
+            "environment": transaction.environment.rawValue.lowercased(),
+            "verification": verificationName(verificationResult),
         ]
+        if let webOrderLineItemID = transaction.webOrderLineItemID {
+            purchase["webOrderLineItemId"] = webOrderLineItemID
+        }
+        if let subscriptionGroupID = transaction.subscriptionGroupID {
+            purchase["subscriptionGroupId"] = subscriptionGroupID
+        }
+        purchase.merge(revocationFields(transaction)) { $1 }
+        purchase.merge(offerFields(transaction)) { $1 }
+        if let transactionReason = transactionReasonName(transaction) {
+            purchase["transactionReason"] = transactionReason
+        }
+        if let appAccountToken = transaction.appAccountToken {
+            purchase["appAccountToken"] = appAccountToken.uuidString.lowercased()
+        }
+        purchase["quantity"] = transaction.purchasedQuantity
+        if let expirationDate = transaction.expirationDate {
+            purchase["expirationDate"] = rfc3339(expirationDate)
+        }
+        if let nextRenewalDate {
+            purchase["nextRenewalDate"] = rfc3339(nextRenewalDate)
+        }
+        return purchase
+    }
+
+    /// `RenewalInfo.renewalDate` where available; older systems renew when the
+    /// current period expires.
+    private func renewalDate(_ renewalInfo: Product.SubscriptionInfo.RenewalInfo, expirationDate: Date?) -> Date? {
+        if #available(macOS 14.0, *) {
+            return renewalInfo.renewalDate ?? expirationDate
+        }
+        return expirationDate
+    }
+
+    /// Keep in sync with Timestamp in src/timestamp.rs
+    /// Dates in payloads are RFC 3339 strings with milliseconds, e.g.
+    /// "2024-01-31T12:00:00.000Z".
+    private func rfc3339(_ date: Date) -> String {
+        let formatter = ISO8601DateFormatter()
+        formatter.formatOptions = [.withInternetDateTime, .withFractionalSeconds]
+        return formatter.string(from: date)
+    }
+
+    /// `offerId`/`offerType` on Purchase in src/models.rs; empty unless the
+    /// transaction redeemed an offer.
+    private func offerFields(_ transaction: Transaction) -> JsonObject {
+        var fields: JsonObject = [:]
+        let offerType: Transaction.OfferType?
+        let offerId: String?
+        if #available(macOS 14.2, *) {
+            offerType = transaction.offer?.type
+            offerId = transaction.offer?.id
+        } else {
+            offerType = transaction.offerType
+            offerId = transaction.offerID
+        }
+        if let offerType {
+            fields["offerType"] = transactionOfferTypeName(offerType)
+        }
+        if let offerId {
+            fields["offerId"] = offerId
+        }
+        return fields
+    }
+
+    /// "introductory", "promotional", "code" or "winBack"
+    private func transactionOfferTypeName(_ type: Transaction.OfferType) -> String? {
+        if #available(macOS 15.0, *), type == .winBack {
+            return "winBack"
+        }
+        switch type {
+        case .introductory:
+            return "introductory"
+        case .promotional:
+            return "promotional"
+        case .code:
+            return "code"
+        default:
+            return nil
+        }
+    }
+
+    /// Keep in sync with OwnershipType in src/models.rs
+    private func ownershipTypeName(_ type: Transaction.OwnershipType) -> String {
+        return type == .familyShared ? "familyShared" : "purchased"
+    }
+
+    /// Keep in sync with TransactionReason in src/models.rs. `Transaction.reason`
+    /// needs macOS 14.0; older systems send no reason.
+    private func transactionReasonName(_ transaction: Transaction) -> String? {
+        guard #available(macOS 14.0, *) else {
+            return nil
+        }
+        switch transaction.reason {
+        case .purchase:
+            return "purchase"
+        case .renewal:
+            return "renewal"
+        default:
+            return "unknown"
+        }
     }
 }
 
 // Initialize the plugin
-func initPlugin() -> IapPlugin {
-    return IapPlugin()
+func initPlugin(allowUnverifiedTransactions: Bool, autoFinishTransactionUpdates: Bool) -> IapPlugin {
+    return IapPlugin(
+        allowUnverifiedTransactions: allowUnverifiedTransactions,
+        autoFinishTransactionUpdates: autoFinishTransactionUpdates)
 }
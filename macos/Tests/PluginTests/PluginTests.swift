@@ -39,6 +39,9 @@ final class PluginTests: XCTestCase {
         XCTAssertEqual(PurchaseStateValue(rawValue: 0), .purchased)
         XCTAssertEqual(PurchaseStateValue(rawValue: 1), .canceled)
         XCTAssertEqual(PurchaseStateValue(rawValue: 2), .pending)
+        XCTAssertEqual(PurchaseStateValue(rawValue: 3), .failed)
+        XCTAssertEqual(PurchaseStateValue(rawValue: 4), .refunded)
+        XCTAssertEqual(PurchaseStateValue(rawValue: 5), .deferred)
         XCTAssertNil(PurchaseStateValue(rawValue: 99))
     }
 
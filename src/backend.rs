@@ -0,0 +1,174 @@
+use std::future::Future;
+
+use tauri::Runtime;
+
+use crate::models::{
+    GetProductsResponse, GetPurchaseHistoryResponse, Product, ProductStatus, ProductType, Purchase,
+    PurchaseRequest, RestorePurchasesRequest, RestorePurchasesResponse,
+};
+
+/// Outcome of [`IapBackend::get_products_batch`]: the products every
+/// successful chunk returned, plus each failed chunk's ids with its error.
+#[derive(Debug, Default)]
+pub struct PartialSuccess {
+    pub products: Vec<Product>,
+    pub errors: Vec<(Vec<String>, crate::Error)>,
+}
+
+impl PartialSuccess {
+    /// Whether every chunk succeeded.
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// The merged products, or the first chunk's error if any chunk failed.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first failed chunk.
+    pub fn into_result(self) -> crate::Result<GetProductsResponse> {
+        match self.errors.into_iter().next() {
+            Some((_, err)) => Err(err),
+            None => Ok(GetProductsResponse {
+                products: self.products,
+            }),
+        }
+    }
+}
+
+/// The store operations behind a purchase flow, implemented by the platform
+/// [`Iap`](crate::IapExt::iap) handle and by [`MockIap`](crate::testing::MockIap).
+///
+/// Write application code against `impl IapBackend` to unit test it without a
+/// running Tauri app or a store SDK. The returned futures are not `Send`: the
+/// `StoreKit` bridge on macOS hands out futures that can't cross threads.
+pub trait IapBackend {
+    fn get_products(
+        &self,
+        product_ids: Vec<String>,
+        product_type: ProductType,
+    ) -> impl Future<Output = crate::Result<GetProductsResponse>>;
+
+    fn purchase(&self, payload: PurchaseRequest) -> impl Future<Output = crate::Result<Purchase>>;
+
+    fn restore_purchases(
+        &self,
+        request: RestorePurchasesRequest,
+    ) -> impl Future<Output = crate::Result<RestorePurchasesResponse>>;
+
+    fn get_purchase_history(
+        &self,
+    ) -> impl Future<Output = crate::Result<GetPurchaseHistoryResponse>>;
+
+    fn acknowledge_purchase(
+        &self,
+        purchase_token: String,
+    ) -> impl Future<Output = crate::Result<()>>;
+
+    fn consume_purchase(&self, purchase_token: String) -> impl Future<Output = crate::Result<()>>;
+
+    fn get_product_status(
+        &self,
+        product_id: String,
+        product_type: ProductType,
+    ) -> impl Future<Output = crate::Result<ProductStatus>>;
+
+    fn finish_transaction(&self, transaction_id: String)
+    -> impl Future<Output = crate::Result<()>>;
+
+    /// [`Self::get_products`] for long id lists, split into requests of at
+    /// most `chunk_size` ids that run concurrently. A failed chunk doesn't
+    /// discard the others; its ids and error are kept in
+    /// [`PartialSuccess::errors`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error::InvalidArgument`] when `chunk_size` is 0.
+    fn get_products_batch(
+        &self,
+        product_ids: Vec<String>,
+        product_type: ProductType,
+        chunk_size: usize,
+    ) -> impl Future<Output = crate::Result<PartialSuccess>> {
+        async move {
+            if chunk_size == 0 {
+                return Err(crate::Error::InvalidArgument(
+                    "chunk_size must be at least 1".to_string(),
+                ));
+            }
+            let chunks: Vec<Vec<String>> = product_ids
+                .chunks(chunk_size)
+                .map(<[String]>::to_vec)
+                .collect();
+            let responses = futures_util::future::join_all(
+                chunks
+                    .iter()
+                    .map(|chunk| self.get_products(chunk.clone(), product_type)),
+            )
+            .await;
+
+            let mut batch = PartialSuccess::default();
+            for (chunk, response) in chunks.into_iter().zip(responses) {
+                match response {
+                    Ok(response) => batch.products.extend(response.products),
+                    Err(err) => batch.errors.push((chunk, err)),
+                }
+            }
+            Ok(batch)
+        }
+    }
+}
+
+impl<R: Runtime> IapBackend for crate::Iap<R> {
+    fn get_products(
+        &self,
+        product_ids: Vec<String>,
+        product_type: ProductType,
+    ) -> impl Future<Output = crate::Result<GetProductsResponse>> {
+        Self::get_products(self, product_ids, product_type)
+    }
+
+    fn purchase(&self, payload: PurchaseRequest) -> impl Future<Output = crate::Result<Purchase>> {
+        Self::purchase(self, payload)
+    }
+
+    fn restore_purchases(
+        &self,
+        request: RestorePurchasesRequest,
+    ) -> impl Future<Output = crate::Result<RestorePurchasesResponse>> {
+        Self::restore_purchases(self, request)
+    }
+
+    fn get_purchase_history(
+        &self,
+    ) -> impl Future<Output = crate::Result<GetPurchaseHistoryResponse>> {
+        Self::get_purchase_history(self)
+    }
+
+    fn acknowledge_purchase(
+        &self,
+        purchase_token: String,
+    ) -> impl Future<Output = crate::Result<()>> {
+        Self::acknowledge_purchase(self, purchase_token)
+    }
+
+    fn consume_purchase(&self, purchase_token: String) -> impl Future<Output = crate::Result<()>> {
+        Self::consume_purchase(self, purchase_token)
+    }
+
+    fn get_product_status(
+        &self,
+        product_id: String,
+        product_type: ProductType,
+    ) -> impl Future<Output = crate::Result<ProductStatus>> {
+        Self::get_product_status(self, product_id, product_type)
+    }
+
+    fn finish_transaction(
+        &self,
+        transaction_id: String,
+    ) -> impl Future<Output = crate::Result<()>> {
+        Self::finish_transaction(self, transaction_id)
+    }
+}
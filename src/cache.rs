@@ -0,0 +1,284 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{PoisonError, RwLock, RwLockWriteGuard};
+use std::time::{Duration, Instant};
+
+use crate::{GetProductsResponse, ProductStatus, ProductType};
+
+type Entries = HashMap<(String, ProductType), (ProductStatus, Instant)>;
+type ProductsEntries = BTreeMap<(Vec<String>, ProductType), (GetProductsResponse, Instant)>;
+
+/// `get_product_status` results kept for
+/// [`IapConfig::product_status_cache_ttl_secs`](crate::IapConfig::product_status_cache_ttl_secs),
+/// so repeated checks skip the round trip to the store. A TTL of 0 caches
+/// nothing.
+#[derive(Debug)]
+pub struct ProductStatusCache {
+    ttl: Duration,
+    entries: RwLock<Entries>,
+}
+
+impl ProductStatusCache {
+    pub fn new(ttl_secs: u64) -> Self {
+        Self {
+            ttl: Duration::from_secs(ttl_secs),
+            entries: RwLock::default(),
+        }
+    }
+
+    /// The cached status, unless it's missing or older than the TTL.
+    pub fn get(&self, product_id: &str, product_type: ProductType) -> Option<ProductStatus> {
+        let entries = self.entries.read().unwrap_or_else(PoisonError::into_inner);
+        entries
+            .get(&(product_id.to_string(), product_type))
+            .filter(|(_, cached_at)| cached_at.elapsed() < self.ttl)
+            .map(|(status, _)| status.clone())
+    }
+
+    pub fn insert(&self, product_id: String, product_type: ProductType, status: ProductStatus) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        self.write()
+            .insert((product_id, product_type), (status, Instant::now()));
+    }
+
+    /// Drops every entry for `product_id`, whatever its product type.
+    pub fn invalidate(&self, product_id: &str) {
+        self.write().retain(|(id, _), _| id != product_id);
+    }
+
+    pub fn clear(&self) {
+        self.write().clear();
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Entries> {
+        self.entries.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// `get_products` responses kept for
+/// [`IapConfig::products_cache_ttl`](crate::IapConfig::products_cache_ttl).
+/// Entries are keyed by the sorted, deduplicated ids, so the same products
+/// requested in another order hit the same entry.
+#[derive(Debug)]
+pub struct ProductsCache {
+    ttl: Duration,
+    entries: RwLock<ProductsEntries>,
+}
+
+impl ProductsCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::default(),
+        }
+    }
+
+    /// The cached response, unless it's missing or older than the TTL.
+    pub fn get(
+        &self,
+        product_ids: &[String],
+        product_type: ProductType,
+    ) -> Option<GetProductsResponse> {
+        let entries = self.entries.read().unwrap_or_else(PoisonError::into_inner);
+        entries
+            .get(&(key(product_ids), product_type))
+            .filter(|(_, cached_at)| cached_at.elapsed() < self.ttl)
+            .map(|(response, _)| response.clone())
+    }
+
+    pub fn insert(
+        &self,
+        product_ids: &[String],
+        product_type: ProductType,
+        response: GetProductsResponse,
+    ) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        self.entries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert((key(product_ids), product_type), (response, Instant::now()));
+    }
+
+    pub fn clear(&self) {
+        self.entries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+fn key(product_ids: &[String]) -> Vec<String> {
+    let mut ids = product_ids.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(product_id: &str) -> ProductStatus {
+        serde_json::from_value(serde_json::json!({
+            "productId": product_id,
+            "isOwned": true,
+        }))
+        .expect("Failed to deserialize ProductStatus")
+    }
+
+    #[test]
+    fn test_product_status_cache_hits_until_invalidated() {
+        let cache = ProductStatusCache::new(60);
+        cache.insert(
+            "premium".to_string(),
+            ProductType::NonConsumable,
+            status("premium"),
+        );
+        cache.insert(
+            "monthly".to_string(),
+            ProductType::AutoRenewableSubscription,
+            status("monthly"),
+        );
+
+        let hit = cache
+            .get("premium", ProductType::NonConsumable)
+            .expect("Expected a cached status");
+        assert_eq!(hit.product_id, "premium");
+        assert!(
+            cache
+                .get("premium", ProductType::AutoRenewableSubscription)
+                .is_none()
+        );
+
+        cache.invalidate("premium");
+        assert!(cache.get("premium", ProductType::NonConsumable).is_none());
+        assert!(
+            cache
+                .get("monthly", ProductType::AutoRenewableSubscription)
+                .is_some()
+        );
+
+        cache.clear();
+        assert!(
+            cache
+                .get("monthly", ProductType::AutoRenewableSubscription)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_product_status_cache_disabled_with_zero_ttl() {
+        let cache = ProductStatusCache::new(0);
+        cache.insert(
+            "premium".to_string(),
+            ProductType::NonConsumable,
+            status("premium"),
+        );
+        assert!(cache.get("premium", ProductType::NonConsumable).is_none());
+    }
+
+    fn products(ids: &[&str]) -> GetProductsResponse {
+        GetProductsResponse {
+            products: ids
+                .iter()
+                .map(|id| {
+                    serde_json::from_value(serde_json::json!({
+                        "productId": id,
+                        "title": id,
+                        "description": "",
+                        "productType": "inapp",
+                    }))
+                    .expect("Failed to deserialize Product")
+                })
+                .collect(),
+        }
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_products_cache_ignores_id_order() {
+        let cache = ProductsCache::new(Duration::from_secs(300));
+        cache.insert(
+            &ids(&["coins_100", "premium"]),
+            ProductType::NonConsumable,
+            products(&["coins_100", "premium"]),
+        );
+
+        let hit = cache
+            .get(
+                &ids(&["premium", "coins_100", "premium"]),
+                ProductType::NonConsumable,
+            )
+            .expect("Expected a cached response");
+        assert_eq!(hit.products.len(), 2);
+        assert!(
+            cache
+                .get(&ids(&["premium"]), ProductType::NonConsumable)
+                .is_none()
+        );
+        assert!(
+            cache
+                .get(
+                    &ids(&["coins_100", "premium"]),
+                    ProductType::AutoRenewableSubscription
+                )
+                .is_none()
+        );
+
+        cache.clear();
+        assert!(
+            cache
+                .get(&ids(&["coins_100", "premium"]), ProductType::NonConsumable)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_products_cache_expiry_and_zero_ttl() {
+        let disabled = ProductsCache::new(Duration::ZERO);
+        disabled.insert(
+            &ids(&["premium"]),
+            ProductType::NonConsumable,
+            products(&["premium"]),
+        );
+        assert!(
+            disabled
+                .get(&ids(&["premium"]), ProductType::NonConsumable)
+                .is_none()
+        );
+
+        let cache = ProductsCache::new(Duration::from_millis(1));
+        cache.insert(
+            &ids(&["premium"]),
+            ProductType::NonConsumable,
+            products(&["premium"]),
+        );
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(
+            cache
+                .get(&ids(&["premium"]), ProductType::NonConsumable)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_product_status_cache_expires_entries() {
+        let cache = ProductStatusCache {
+            ttl: Duration::from_millis(1),
+            entries: RwLock::default(),
+        };
+        cache.insert(
+            "premium".to_string(),
+            ProductType::NonConsumable,
+            status("premium"),
+        );
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(cache.get("premium", ProductType::NonConsumable).is_none());
+    }
+}
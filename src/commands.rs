@@ -1,15 +1,34 @@
+use std::collections::HashMap;
+
 use tauri::{AppHandle, Runtime, command};
 
 use crate::models::{
-    AcknowledgePurchaseRequest, ConsumePurchaseRequest, GetProductStatusRequest,
-    GetProductsRequest, GetProductsResponse, InitializeResponse, ProductStatus, Purchase,
-    PurchaseRequest, RestorePurchasesRequest, RestorePurchasesResponse,
+    AcknowledgePurchaseRequest, AppTransactionInfo, CanMakePaymentsResponse,
+    CheckIntroductoryOfferEligibilityRequest, ConsumePurchaseRequest, CurrentEntitlementRequest,
+    ExternalPurchaseLinkAvailability, FinishTransactionRequest, GetAllProductStatusesRequest,
+    GetAppReceiptRequest, GetAppTransactionRequest, GetCurrentEntitlementsResponse,
+    GetProductStatusRequest, GetProductsRequest, GetProductsResponse, GetPurchaseHistoryResponse,
+    GetReceiptRequest, GetRevokedPurchasesRequest, GetSubscriptionStatusRequest,
+    GetSubscriptionStatusResponse, GetWinBackOffersRequest, GetWinBackOffersResponse,
+    IapEnvironment, InitializeResponse, IsEligibleForIntroOfferRequest,
+    IsEligibleForIntroOfferResponse, IsSupportedResponse, ProductStatus, Purchase,
+    PurchaseHistoryRequest, PurchaseReceipt, PurchaseRequest, RefundRequest, RefundRequestResult,
+    RefundRequestStatus, RestorePurchasesRequest, RestorePurchasesResponse, StorefrontInfo,
 };
 use crate::{IapExt, Result};
 
 #[command]
-pub async fn initialize<R: Runtime>(_app: AppHandle<R>) -> Result<InitializeResponse> {
-    Err(std::io::Error::other("initialize() is deprecated and no longer needed. The billing client initializes automatically.").into())
+pub async fn initialize<R: Runtime>(app: AppHandle<R>) -> Result<InitializeResponse> {
+    if !app.iap().config().auto_finish_unfinished {
+        return Err(std::io::Error::other("initialize() is deprecated and no longer needed. The billing client initializes automatically.").into());
+    }
+    // Called from JS once its listeners are registered, so none of the
+    // `purchaseCompleted` events is lost.
+    let finished = app.iap().finish_unfinished_transactions().await?;
+    Ok(InitializeResponse {
+        success: true,
+        finished_transactions: Some(finished),
+    })
 }
 
 #[command]
@@ -17,6 +36,12 @@ pub async fn get_products<R: Runtime>(
     app: AppHandle<R>,
     payload: GetProductsRequest,
 ) -> Result<GetProductsResponse> {
+    if payload.force_refresh {
+        return app
+            .iap()
+            .refresh_products(payload.product_ids, payload.product_type)
+            .await;
+    }
     app.iap()
         .get_products(payload.product_ids, payload.product_type)
         .await
@@ -35,6 +60,21 @@ pub async fn restore_purchases<R: Runtime>(
     app.iap().restore_purchases(payload).await
 }
 
+#[command]
+pub async fn get_purchase_history<R: Runtime>(
+    app: AppHandle<R>,
+) -> Result<GetPurchaseHistoryResponse> {
+    app.iap().get_purchase_history().await
+}
+
+#[command]
+pub async fn get_purchase_history_filtered<R: Runtime>(
+    app: AppHandle<R>,
+    payload: PurchaseHistoryRequest,
+) -> Result<GetPurchaseHistoryResponse> {
+    app.iap().get_purchase_history_filtered(payload).await
+}
+
 #[command]
 pub async fn acknowledge_purchase<R: Runtime>(
     app: AppHandle<R>,
@@ -60,3 +100,184 @@ pub async fn get_product_status<R: Runtime>(
         .get_product_status(payload.product_id, payload.product_type)
         .await
 }
+
+#[command]
+pub async fn finish_transaction<R: Runtime>(
+    app: AppHandle<R>,
+    payload: FinishTransactionRequest,
+) -> Result<()> {
+    app.iap().finish_transaction(payload.transaction_id).await
+}
+
+#[command]
+pub async fn get_app_transaction<R: Runtime>(
+    app: AppHandle<R>,
+    payload: GetAppTransactionRequest,
+) -> Result<AppTransactionInfo> {
+    app.iap().get_app_transaction(payload.force_refresh).await
+}
+
+#[command]
+pub async fn get_current_entitlements<R: Runtime>(
+    app: AppHandle<R>,
+) -> Result<GetCurrentEntitlementsResponse> {
+    app.iap().get_current_entitlements().await
+}
+
+#[command]
+pub async fn get_subscription_status<R: Runtime>(
+    app: AppHandle<R>,
+    payload: GetSubscriptionStatusRequest,
+) -> Result<GetSubscriptionStatusResponse> {
+    app.iap().get_subscription_status(payload.product_id).await
+}
+
+#[command]
+pub async fn show_manage_subscriptions<R: Runtime>(app: AppHandle<R>) -> Result<()> {
+    app.iap().show_manage_subscriptions().await
+}
+
+#[command]
+pub async fn present_offer_code_redemption_sheet<R: Runtime>(app: AppHandle<R>) -> Result<()> {
+    app.iap().present_offer_code_redemption_sheet().await
+}
+
+#[command]
+pub async fn get_storefront<R: Runtime>(app: AppHandle<R>) -> Result<StorefrontInfo> {
+    app.iap().get_storefront().await
+}
+
+#[command]
+pub async fn get_unfinished_transactions<R: Runtime>(app: AppHandle<R>) -> Result<Vec<Purchase>> {
+    app.iap().get_unfinished_transactions().await
+}
+
+#[command]
+pub async fn begin_refund_request<R: Runtime>(
+    app: AppHandle<R>,
+    payload: RefundRequest,
+) -> Result<RefundRequestResult> {
+    app.iap().begin_refund_request(payload.transaction_id).await
+}
+
+#[command]
+pub async fn request_refund<R: Runtime>(
+    app: AppHandle<R>,
+    payload: RefundRequest,
+) -> Result<RefundRequestStatus> {
+    app.iap().request_refund(payload.transaction_id).await
+}
+
+#[command]
+pub async fn get_win_back_offers<R: Runtime>(
+    app: AppHandle<R>,
+    payload: GetWinBackOffersRequest,
+) -> Result<GetWinBackOffersResponse> {
+    app.iap().get_win_back_offers(payload.product_id).await
+}
+
+/// Kept for guest-js callers of `presentCodeRedemptionSheet`; same as
+/// [`present_offer_code_redemption_sheet`].
+#[command]
+pub async fn present_code_redemption_sheet<R: Runtime>(app: AppHandle<R>) -> Result<()> {
+    app.iap().present_offer_code_redemption_sheet().await
+}
+
+#[command]
+pub async fn is_eligible_for_intro_offer<R: Runtime>(
+    app: AppHandle<R>,
+    payload: IsEligibleForIntroOfferRequest,
+) -> Result<IsEligibleForIntroOfferResponse> {
+    app.iap()
+        .is_eligible_for_intro_offer(payload.product_id)
+        .await
+}
+
+#[command]
+pub async fn get_environment<R: Runtime>(app: AppHandle<R>) -> Result<IapEnvironment> {
+    app.iap().get_environment().await
+}
+
+#[command]
+pub async fn get_receipt<R: Runtime>(
+    app: AppHandle<R>,
+    payload: GetReceiptRequest,
+) -> Result<PurchaseReceipt> {
+    app.iap().get_receipt(payload.transaction_id).await
+}
+
+#[command]
+pub async fn refresh_receipt<R: Runtime>(app: AppHandle<R>) -> Result<()> {
+    app.iap().refresh_receipt().await
+}
+
+#[command]
+pub async fn can_make_payments<R: Runtime>(app: AppHandle<R>) -> Result<CanMakePaymentsResponse> {
+    app.iap().can_make_payments().await
+}
+
+#[command]
+pub async fn check_introductory_offer_eligibility<R: Runtime>(
+    app: AppHandle<R>,
+    payload: CheckIntroductoryOfferEligibilityRequest,
+) -> Result<HashMap<String, bool>> {
+    app.iap()
+        .check_introductory_offer_eligibility(payload.product_ids)
+        .await
+}
+
+#[command]
+pub async fn is_supported<R: Runtime>(app: AppHandle<R>) -> Result<IsSupportedResponse> {
+    app.iap().is_supported().await
+}
+
+#[command]
+pub async fn get_all_product_statuses<R: Runtime>(
+    app: AppHandle<R>,
+    payload: GetAllProductStatusesRequest,
+) -> Result<Vec<ProductStatus>> {
+    app.iap()
+        .get_all_product_statuses(payload.product_type)
+        .await
+}
+
+#[command]
+pub async fn can_open_external_purchase_link<R: Runtime>(
+    app: AppHandle<R>,
+) -> Result<ExternalPurchaseLinkAvailability> {
+    app.iap().can_open_external_purchase_link().await
+}
+
+#[command]
+pub async fn open_external_purchase_link<R: Runtime>(app: AppHandle<R>) -> Result<()> {
+    app.iap().open_external_purchase_link().await
+}
+
+#[command]
+pub async fn display_pending_store_messages<R: Runtime>(app: AppHandle<R>) -> Result<u32> {
+    app.iap().display_pending_store_messages().await
+}
+
+#[command]
+pub async fn current_entitlement<R: Runtime>(
+    app: AppHandle<R>,
+    payload: CurrentEntitlementRequest,
+) -> Result<Option<Purchase>> {
+    app.iap().current_entitlement(payload.product_id).await
+}
+
+#[command]
+pub async fn get_app_receipt<R: Runtime>(
+    app: AppHandle<R>,
+    payload: GetAppReceiptRequest,
+) -> Result<String> {
+    app.iap().get_app_receipt(payload.refresh_if_missing).await
+}
+
+#[command]
+pub async fn get_revoked_purchases<R: Runtime>(
+    app: AppHandle<R>,
+    payload: GetRevokedPurchasesRequest,
+) -> Result<Vec<Purchase>> {
+    app.iap().get_revoked_purchases(payload.since).await
+}
@@ -0,0 +1,285 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::RetryPolicy;
+
+/// Plugin configuration, read from `plugins.iap` in `tauri.conf.json`:
+///
+/// ```json
+/// { "plugins": { "iap": { "allowUnverifiedTransactions": true } } }
+/// ```
+///
+/// Every field is optional; leaving the section out keeps the defaults.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IapConfig {
+    /// Deliver `StoreKit` transactions whose signature didn't verify, marked
+    /// with [`TransactionVerification::Unverified`](crate::TransactionVerification),
+    /// instead of dropping them. Purchases reject with
+    /// [`IapErrorCode::VerificationFailed`](crate::IapErrorCode) while this is
+    /// off, which is the default. Ignored on Android and Windows.
+    pub allow_unverified_transactions: bool,
+    /// Reject every call on macOS unless the app runs from a `.app` bundle,
+    /// which `StoreKit` needs to reach the App Store. Unset, this is on in
+    /// release builds and off in debug builds, so unbundled `cargo run` and
+    /// `tauri dev` binaries can still call the plugin. Ignored elsewhere.
+    pub require_app_bundle: Option<bool>,
+    /// Fail plugin setup on macOS when the app isn't running from a `.app`
+    /// bundle, instead of waiting for the first call. Off by default.
+    pub require_app_bundle_at_startup: bool,
+    /// Acknowledge Android purchases as soon as `purchaseUpdated` has been
+    /// delivered, so none is refunded for missing the 3-day deadline. Each
+    /// one is reported with [`TransactionState::AutoAcknowledged`](crate::TransactionState::AutoAcknowledged).
+    /// Off by default; ignored elsewhere, where no acknowledgement is needed.
+    pub auto_acknowledge: bool,
+    /// Wait this long after delivering the purchase before acknowledging it.
+    /// Capped at [`MAX_AUTO_ACKNOWLEDGE_DELAY_MS`]; 0 by default.
+    pub auto_acknowledge_delay_ms: u64,
+    /// Payloads kept per event on desktop until its first listener registers,
+    /// e.g. renewals delivered before the webview subscribes. The oldest is
+    /// dropped once full; the first listener to register receives the rest.
+    /// Nothing is kept once a listener has registered, even after it is
+    /// removed. Defaults to [`DEFAULT_LISTENER_BUFFER_SIZE`]; 0 disables
+    /// buffering.
+    pub listener_buffer_size: Option<usize>,
+    /// Hold `StoreKit` App Store messages (price increase consent, billing
+    /// issues, ...) until `display_pending_store_messages` is called, instead
+    /// of showing them over whatever screen is open. Each message still fires
+    /// `storeMessageReceived` when it arrives. Off by default; iOS 16+ only.
+    pub defer_store_messages: bool,
+    /// Give up waiting for a macOS product query (`get_products`,
+    /// `get_product_status`, `get_subscription_status`, intro offer
+    /// eligibility, ...) after this many milliseconds and fail with
+    /// [`Error::Timeout`](crate::Error::Timeout), e.g. when the App Store
+    /// daemon is wedged. Defaults to [`DEFAULT_PRODUCT_QUERY_TIMEOUT_MS`]; 0
+    /// waits forever. Ignored elsewhere. `purchase` is never timed out: the
+    /// sheet can legitimately stay open for minutes, and giving up on the
+    /// wait wouldn't stop the purchase from going through.
+    pub product_query_timeout_ms: Option<u64>,
+    /// Retry macOS `get_products`, `get_product_status` and
+    /// `restore_purchases` after transient errors such as a dropped
+    /// connection. Unset, nothing is retried; `{}` retries with
+    /// [`RetryPolicy::default`]. `purchase` is never retried, since the user
+    /// may already have confirmed the payment when the error arrives, and
+    /// neither is a `restore_purchases` with `force_sync`.
+    pub retry_policy: Option<RetryPolicy>,
+    /// Serve repeated macOS `get_product_status` calls for the same product
+    /// from memory for this many seconds. A `transactionUpdated` event for
+    /// the product drops its entry early, and `clear_product_status_cache`
+    /// drops them all. 0, the default, always asks `StoreKit`. Ignored
+    /// elsewhere.
+    pub product_status_cache_ttl_secs: u64,
+    /// Serve repeated macOS `get_products` calls for the same ids and type
+    /// from memory for this many seconds. Any app window gaining focus
+    /// empties the cache, so prices changed while the app was in the
+    /// background are picked up. Defaults to
+    /// [`DEFAULT_PRODUCTS_CACHE_TTL_SECS`]; 0 always asks `StoreKit`.
+    /// Ignored elsewhere.
+    pub products_cache_ttl_secs: Option<u64>,
+    /// Persist what the user owns to the app data dir after each successful
+    /// macOS entitlement query, and let `get_product_status` answer from it,
+    /// marked [`ProductStatus::from_cache`](crate::ProductStatus::from_cache),
+    /// when the store can't be reached and the entry was verified at most
+    /// this many seconds ago. Unset, nothing is written. Ignored elsewhere.
+    pub offline_entitlement_max_age_secs: Option<u64>,
+    /// Make `initialize` deliver every transaction in `Transaction.unfinished`
+    /// once as `purchaseCompleted` and then finish it, to flush ones an
+    /// earlier app version left behind. Off by default, since apps that
+    /// validate on a server must finish transactions themselves. iOS and
+    /// macOS only; elsewhere `initialize` rejects with `notSupported`.
+    pub auto_finish_unfinished: bool,
+    /// Finish every transaction `Transaction.updates` delivers (renewals,
+    /// Ask to Buy approvals, redeemed codes, ones left unfinished at the last
+    /// launch) right after its `purchaseUpdated` event, as earlier versions
+    /// always did. Off by default: the app finishes them with
+    /// `finish_transaction` once its server has validated the JWS. iOS and
+    /// macOS only.
+    pub auto_finish_transaction_updates: bool,
+}
+
+/// Longest [`IapConfig::auto_acknowledge_delay_ms`] honored.
+pub const MAX_AUTO_ACKNOWLEDGE_DELAY_MS: u64 = 2000;
+
+/// [`IapConfig::listener_buffer_size`] when unset.
+pub const DEFAULT_LISTENER_BUFFER_SIZE: usize = 64;
+
+/// [`IapConfig::product_query_timeout_ms`] when unset.
+pub const DEFAULT_PRODUCT_QUERY_TIMEOUT_MS: u64 = 30_000;
+
+/// [`IapConfig::products_cache_ttl_secs`] when unset.
+pub const DEFAULT_PRODUCTS_CACHE_TTL_SECS: u64 = 300;
+
+impl IapConfig {
+    /// [`Self::require_app_bundle`] with its build-dependent default applied.
+    #[must_use]
+    pub fn requires_app_bundle(&self) -> bool {
+        self.require_app_bundle.unwrap_or(!cfg!(debug_assertions))
+    }
+
+    /// [`Self::listener_buffer_size`] with its default applied.
+    #[must_use]
+    pub fn listener_buffer_size(&self) -> usize {
+        self.listener_buffer_size
+            .unwrap_or(DEFAULT_LISTENER_BUFFER_SIZE)
+    }
+
+    /// [`Self::product_query_timeout_ms`] with its default applied, or `None`
+    /// to wait forever.
+    #[must_use]
+    pub fn product_query_timeout(&self) -> Option<Duration> {
+        timeout(
+            self.product_query_timeout_ms
+                .unwrap_or(DEFAULT_PRODUCT_QUERY_TIMEOUT_MS),
+        )
+    }
+
+    /// [`Self::products_cache_ttl_secs`] with its default applied.
+    #[must_use]
+    pub fn products_cache_ttl(&self) -> Duration {
+        Duration::from_secs(
+            self.products_cache_ttl_secs
+                .unwrap_or(DEFAULT_PRODUCTS_CACHE_TTL_SECS),
+        )
+    }
+}
+
+fn timeout(millis: u64) -> Option<Duration> {
+    (millis > 0).then(|| Duration::from_millis(millis))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_defaults_to_rejecting_unverified_transactions() {
+        let config: IapConfig =
+            serde_json::from_str("{}").expect("Failed to deserialize IapConfig");
+        assert_eq!(config, IapConfig::default());
+        assert!(!config.allow_unverified_transactions);
+
+        let config: IapConfig = serde_json::from_str(r#"{"allowUnverifiedTransactions":true}"#)
+            .expect("Failed to deserialize IapConfig");
+        assert!(config.allow_unverified_transactions);
+    }
+
+    #[test]
+    fn test_config_require_app_bundle_follows_build_profile_unless_set() {
+        let config = IapConfig::default();
+        assert_eq!(config.requires_app_bundle(), !cfg!(debug_assertions));
+        assert!(!config.require_app_bundle_at_startup);
+
+        for required in [true, false] {
+            let config: IapConfig =
+                serde_json::from_value(serde_json::json!({ "requireAppBundle": required }))
+                    .expect("Failed to deserialize IapConfig");
+            assert_eq!(config.require_app_bundle, Some(required));
+            assert_eq!(config.requires_app_bundle(), required);
+        }
+    }
+
+    #[test]
+    fn test_config_auto_acknowledge() {
+        let config = IapConfig::default();
+        assert!(!config.auto_acknowledge);
+        assert_eq!(config.auto_acknowledge_delay_ms, 0);
+
+        let config: IapConfig =
+            serde_json::from_str(r#"{"autoAcknowledge":true,"autoAcknowledgeDelayMs":500}"#)
+                .expect("Failed to deserialize IapConfig");
+        assert!(config.auto_acknowledge);
+        assert_eq!(config.auto_acknowledge_delay_ms, 500);
+    }
+
+    #[test]
+    fn test_config_listener_buffer_size() {
+        assert_eq!(
+            IapConfig::default().listener_buffer_size(),
+            DEFAULT_LISTENER_BUFFER_SIZE
+        );
+
+        let config: IapConfig = serde_json::from_str(r#"{"listenerBufferSize":0}"#)
+            .expect("Failed to deserialize IapConfig");
+        assert_eq!(config.listener_buffer_size(), 0);
+    }
+
+    #[test]
+    fn test_config_defer_store_messages() {
+        assert!(!IapConfig::default().defer_store_messages);
+
+        let config: IapConfig = serde_json::from_str(r#"{"deferStoreMessages":true}"#)
+            .expect("Failed to deserialize IapConfig");
+        assert!(config.defer_store_messages);
+    }
+
+    #[test]
+    fn test_config_retry_policy() {
+        assert_eq!(IapConfig::default().retry_policy, None);
+
+        let config: IapConfig =
+            serde_json::from_str(r#"{"retryPolicy":{}}"#).expect("Failed to deserialize IapConfig");
+        assert_eq!(config.retry_policy, Some(RetryPolicy::default()));
+    }
+
+    #[test]
+    fn test_config_product_status_cache_ttl() {
+        assert_eq!(IapConfig::default().product_status_cache_ttl_secs, 0);
+
+        let config: IapConfig = serde_json::from_str(r#"{"productStatusCacheTtlSecs":300}"#)
+            .expect("Failed to deserialize IapConfig");
+        assert_eq!(config.product_status_cache_ttl_secs, 300);
+    }
+
+    #[test]
+    fn test_config_products_cache_ttl() {
+        assert_eq!(
+            IapConfig::default().products_cache_ttl(),
+            Duration::from_secs(DEFAULT_PRODUCTS_CACHE_TTL_SECS)
+        );
+
+        let config: IapConfig = serde_json::from_str(r#"{"productsCacheTtlSecs":0}"#)
+            .expect("Failed to deserialize IapConfig");
+        assert_eq!(config.products_cache_ttl(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_config_offline_entitlement_max_age() {
+        assert_eq!(IapConfig::default().offline_entitlement_max_age_secs, None);
+
+        let config: IapConfig = serde_json::from_str(r#"{"offlineEntitlementMaxAgeSecs":604800}"#)
+            .expect("Failed to deserialize IapConfig");
+        assert_eq!(config.offline_entitlement_max_age_secs, Some(604_800));
+    }
+
+    #[test]
+    fn test_config_auto_finish_unfinished() {
+        assert!(!IapConfig::default().auto_finish_unfinished);
+
+        let config: IapConfig = serde_json::from_str(r#"{"autoFinishUnfinished":true}"#)
+            .expect("Failed to deserialize IapConfig");
+        assert!(config.auto_finish_unfinished);
+    }
+
+    #[test]
+    fn test_config_auto_finish_transaction_updates() {
+        assert!(!IapConfig::default().auto_finish_transaction_updates);
+
+        let config: IapConfig = serde_json::from_str(r#"{"autoFinishTransactionUpdates":true}"#)
+            .expect("Failed to deserialize IapConfig");
+        assert!(config.auto_finish_transaction_updates);
+    }
+
+    #[test]
+    fn test_config_timeouts() {
+        let config = IapConfig::default();
+        assert_eq!(
+            config.product_query_timeout(),
+            Some(Duration::from_millis(DEFAULT_PRODUCT_QUERY_TIMEOUT_MS))
+        );
+
+        let config: IapConfig = serde_json::from_str(r#"{"productQueryTimeoutMs":0}"#)
+            .expect("Failed to deserialize IapConfig");
+        assert_eq!(config.product_query_timeout(), None);
+    }
+}
@@ -1,76 +1,511 @@
-// Linux is unsupported — every method is a stub that returns `Err`.
+// Linux has no platform store, so every method is a stub that returns
+// `Error::NotSupported`; `is_supported` and `can_make_payments` answer "no"
+// instead. Only built for Linux: macOS and Windows have their own modules, so
+// nothing here touches `StoreKit`, `codesign` or the Windows Store APIs.
+
+use std::collections::HashMap;
 
-use serde::de::DeserializeOwned;
 use tauri::{AppHandle, Runtime, plugin::PluginApi};
 
+use crate::{IapConfig, Timestamp};
+
 use crate::models::{
-    GetProductsResponse, GetPurchaseHistoryResponse, ProductStatus, Purchase, PurchaseRequest,
-    RestorePurchasesRequest, RestorePurchasesResponse,
+    AppTransactionInfo, CanMakePaymentsResponse, ExternalPurchaseLinkAvailability,
+    GetCurrentEntitlementsResponse, GetProductsResponse, GetPurchaseHistoryResponse,
+    GetSubscriptionStatusResponse, GetWinBackOffersResponse, IapEnvironment,
+    IsEligibleForIntroOfferResponse, IsSupportedResponse, ProductStatus, ProductType, Purchase,
+    PurchaseHistoryRequest, PurchaseReceipt, PurchaseRequest, RefundRequestResult,
+    RefundRequestStatus, RestorePurchasesRequest, RestorePurchasesResponse, StorefrontInfo,
 };
 
 #[allow(clippy::unnecessary_wraps)]
-pub fn init<R: Runtime, C: DeserializeOwned>(
+pub fn init<R: Runtime>(
     app: &AppHandle<R>,
-    _api: &PluginApi<R, C>,
+    api: &PluginApi<R, Option<IapConfig>>,
 ) -> crate::Result<Iap<R>> {
-    Ok(Iap(app.clone()))
+    Ok(Iap {
+        _app: app.clone(),
+        config: api.config().clone().unwrap_or_default(),
+    })
+}
+
+fn not_supported() -> crate::Error {
+    crate::Error::NotSupported {
+        platform: std::env::consts::OS.to_string(),
+    }
 }
 
 /// Access to the iap APIs.
-pub struct Iap<R: Runtime>(AppHandle<R>);
+pub struct Iap<R: Runtime> {
+    _app: AppHandle<R>,
+    config: IapConfig,
+}
 
 #[allow(clippy::unused_async, clippy::unused_self)]
 impl<R: Runtime> Iap<R> {
+    /// The `plugins.iap` options from `tauri.conf.json`, with defaults for
+    /// anything left out.
+    #[must_use]
+    pub const fn config(&self) -> &IapConfig {
+        &self.config
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_products",
+            skip(self, _product_ids, _product_type),
+            level = "debug",
+            err
+        )
+    )]
     pub async fn get_products(
         &self,
         _product_ids: Vec<String>,
-        _product_type: String,
+        _product_type: ProductType,
     ) -> crate::Result<GetProductsResponse> {
-        Err(crate::Error::from(std::io::Error::other(
-            "IAP is not supported on this platform",
-        )))
+        Err(not_supported())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::refresh_products",
+            skip(self, _product_ids, _product_type),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn refresh_products(
+        &self,
+        _product_ids: Vec<String>,
+        _product_type: ProductType,
+    ) -> crate::Result<GetProductsResponse> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::purchase", skip(self, _payload), level = "debug", err)
+    )]
     pub async fn purchase(&self, _payload: PurchaseRequest) -> crate::Result<Purchase> {
-        Err(crate::Error::from(std::io::Error::other(
-            "IAP is not supported on this platform",
-        )))
+        Err(not_supported())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::restore_purchases",
+            skip(self, _request),
+            level = "debug",
+            err
+        )
+    )]
     pub async fn restore_purchases(
         &self,
         _request: RestorePurchasesRequest,
     ) -> crate::Result<RestorePurchasesResponse> {
-        Err(crate::Error::from(std::io::Error::other(
-            "IAP is not supported on this platform",
-        )))
+        Err(not_supported())
     }
 
-    pub fn get_purchase_history(&self) -> crate::Result<GetPurchaseHistoryResponse> {
-        Err(crate::Error::from(std::io::Error::other(
-            "IAP is not supported on this platform",
-        )))
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_purchase_history", skip(self), level = "debug", err)
+    )]
+    pub async fn get_purchase_history(&self) -> crate::Result<GetPurchaseHistoryResponse> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_purchase_history_filtered",
+            skip(self, _request),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_purchase_history_filtered(
+        &self,
+        _request: PurchaseHistoryRequest,
+    ) -> crate::Result<GetPurchaseHistoryResponse> {
+        Err(not_supported())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::acknowledge_purchase",
+            skip(self, _purchase_token),
+            level = "debug",
+            err
+        )
+    )]
     pub async fn acknowledge_purchase(&self, _purchase_token: String) -> crate::Result<()> {
-        Err(crate::Error::from(std::io::Error::other(
-            "IAP is not supported on this platform",
-        )))
+        Err(not_supported())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::consume_purchase",
+            skip(self, _purchase_token),
+            level = "debug",
+            err
+        )
+    )]
     pub async fn consume_purchase(&self, _purchase_token: String) -> crate::Result<()> {
-        Err(crate::Error::from(std::io::Error::other(
-            "IAP is not supported on this platform",
-        )))
+        Err(not_supported())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_product_status",
+            skip(self, _product_id, _product_type),
+            level = "debug",
+            err
+        )
+    )]
     pub async fn get_product_status(
         &self,
         _product_id: String,
-        _product_type: String,
+        _product_type: ProductType,
     ) -> crate::Result<ProductStatus> {
-        Err(crate::Error::from(std::io::Error::other(
-            "IAP is not supported on this platform",
-        )))
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::finish_transaction",
+            skip(self, _transaction_id),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn finish_transaction(&self, _transaction_id: String) -> crate::Result<()> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_app_transaction",
+            skip(self, _force_refresh),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_app_transaction(
+        &self,
+        _force_refresh: bool,
+    ) -> crate::Result<AppTransactionInfo> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_current_entitlements",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_current_entitlements(&self) -> crate::Result<GetCurrentEntitlementsResponse> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_subscription_status",
+            skip(self, _product_id),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_subscription_status(
+        &self,
+        _product_id: String,
+    ) -> crate::Result<GetSubscriptionStatusResponse> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::show_manage_subscriptions",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn show_manage_subscriptions(&self) -> crate::Result<()> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::present_offer_code_redemption_sheet",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn present_offer_code_redemption_sheet(&self) -> crate::Result<()> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_storefront", skip(self), level = "debug", err)
+    )]
+    pub async fn get_storefront(&self) -> crate::Result<StorefrontInfo> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_unfinished_transactions",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_unfinished_transactions(&self) -> crate::Result<Vec<Purchase>> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::begin_refund_request",
+            skip(self, _transaction_id),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn begin_refund_request(
+        &self,
+        _transaction_id: String,
+    ) -> crate::Result<RefundRequestResult> {
+        Err(not_supported())
+    }
+
+    /// [`Self::begin_refund_request`], reporting a failed submission as
+    /// [`RefundRequestStatus::Error`] instead of rejecting.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::request_refund", skip(self), level = "debug", err)
+    )]
+    pub async fn request_refund(
+        &self,
+        transaction_id: String,
+    ) -> crate::Result<RefundRequestStatus> {
+        RefundRequestStatus::from_outcome(self.begin_refund_request(transaction_id).await)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_win_back_offers",
+            skip(self, _product_id),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_win_back_offers(
+        &self,
+        _product_id: String,
+    ) -> crate::Result<GetWinBackOffersResponse> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::is_eligible_for_intro_offer",
+            skip(self, _product_id),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn is_eligible_for_intro_offer(
+        &self,
+        _product_id: String,
+    ) -> crate::Result<IsEligibleForIntroOfferResponse> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_environment", skip(self), level = "debug", err)
+    )]
+    pub async fn get_environment(&self) -> crate::Result<IapEnvironment> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_receipt",
+            skip(self, _transaction_id),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_receipt(&self, _transaction_id: String) -> crate::Result<PurchaseReceipt> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::refresh_receipt", skip(self), level = "debug", err)
+    )]
+    pub async fn refresh_receipt(&self) -> crate::Result<()> {
+        Err(not_supported())
+    }
+
+    /// Never an error, so a paywall can call it unconditionally.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::can_make_payments", skip(self), level = "debug", err)
+    )]
+    pub async fn can_make_payments(&self) -> crate::Result<CanMakePaymentsResponse> {
+        Ok(CanMakePaymentsResponse {
+            can_make_payments: false,
+            reason: Some("notSupported".to_string()),
+        })
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::check_introductory_offer_eligibility",
+            skip(self, _product_ids),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn check_introductory_offer_eligibility(
+        &self,
+        _product_ids: Vec<String>,
+    ) -> crate::Result<HashMap<String, bool>> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::is_supported", skip(self), level = "debug", err)
+    )]
+    pub async fn is_supported(&self) -> crate::Result<IsSupportedResponse> {
+        Ok(IsSupportedResponse {
+            is_supported: false,
+            reason: Some("notSupported".to_string()),
+        })
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_all_product_statuses",
+            skip(self, _product_type),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_all_product_statuses(
+        &self,
+        _product_type: ProductType,
+    ) -> crate::Result<Vec<ProductStatus>> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::can_open_external_purchase_link",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn can_open_external_purchase_link(
+        &self,
+    ) -> crate::Result<ExternalPurchaseLinkAvailability> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::open_external_purchase_link",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn open_external_purchase_link(&self) -> crate::Result<()> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::display_pending_store_messages",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn display_pending_store_messages(&self) -> crate::Result<u32> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::current_entitlement", skip(self), level = "debug", err)
+    )]
+    pub async fn current_entitlement(
+        &self,
+        _product_id: String,
+    ) -> crate::Result<Option<Purchase>> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_app_receipt", skip(self), level = "debug", err)
+    )]
+    pub async fn get_app_receipt(&self, _refresh_if_missing: bool) -> crate::Result<String> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_revoked_purchases", skip(self), level = "debug", err)
+    )]
+    pub async fn get_revoked_purchases(
+        &self,
+        _since: Option<Timestamp>,
+    ) -> crate::Result<Vec<Purchase>> {
+        Err(not_supported())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::finish_unfinished_transactions",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn finish_unfinished_transactions(&self) -> crate::Result<u32> {
+        Err(not_supported())
     }
 }
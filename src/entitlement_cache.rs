@@ -0,0 +1,265 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{IapErrorCode, ProductStatus, Timestamp};
+
+/// File under the app data dir holding the cached entitlements.
+pub const FILE_NAME: &str = "iap-entitlements.json";
+
+/// An owned product as the store last confirmed it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedEntitlement {
+    pub product_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration_time: Option<Timestamp>,
+    pub last_verified: Timestamp,
+}
+
+/// Entitlements persisted after each successful store query, so a paying
+/// user isn't locked out when `StoreKit` can't reach the App Store. The file
+/// isn't signed: treat a hit as "probably still owned", not as proof.
+#[derive(Debug)]
+pub struct EntitlementCache {
+    path: PathBuf,
+    max_age: Duration,
+    entries: Mutex<BTreeMap<String, CachedEntitlement>>,
+}
+
+impl EntitlementCache {
+    /// Reads `path` if it exists. A missing or unreadable file starts empty.
+    pub fn load(path: PathBuf, max_age: Duration) -> Self {
+        let entries = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Vec<CachedEntitlement>>(&bytes).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|entry| (entry.product_id.clone(), entry))
+            .collect();
+        Self {
+            path,
+            max_age,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    /// Marks `product_id` as owned until `expiration_time`, verified `now`.
+    pub fn record(&self, product_id: &str, expiration_time: Option<Timestamp>, now: Timestamp) {
+        let mut entries = self.lock();
+        entries.insert(
+            product_id.to_string(),
+            CachedEntitlement {
+                product_id: product_id.to_string(),
+                expiration_time,
+                last_verified: now,
+            },
+        );
+        self.save(&entries);
+    }
+
+    /// Forgets `product_id`, e.g. once the store reports it no longer owned.
+    pub fn remove(&self, product_id: &str) {
+        let mut entries = self.lock();
+        if entries.remove(product_id).is_some() {
+            self.save(&entries);
+        }
+    }
+
+    /// Replaces every entry with `owned`, the complete set of current
+    /// entitlements as `(product id, expiration)` pairs.
+    pub fn replace_all(
+        &self,
+        owned: impl IntoIterator<Item = (String, Option<Timestamp>)>,
+        now: Timestamp,
+    ) {
+        let mut entries = self.lock();
+        *entries = owned
+            .into_iter()
+            .map(|(product_id, expiration_time)| {
+                let entry = CachedEntitlement {
+                    product_id: product_id.clone(),
+                    expiration_time,
+                    last_verified: now,
+                };
+                (product_id, entry)
+            })
+            .collect();
+        self.save(&entries);
+    }
+
+    /// The entry for `product_id`, unless it was verified more than the max
+    /// age before `now` or its subscription has expired since.
+    pub fn lookup(&self, product_id: &str, now: Timestamp) -> Option<CachedEntitlement> {
+        let max_age = i64::try_from(self.max_age.as_millis()).unwrap_or(i64::MAX);
+        self.lock()
+            .get(product_id)
+            .filter(|entry| {
+                now.unix_millis()
+                    .saturating_sub(entry.last_verified.unix_millis())
+                    <= max_age
+            })
+            .filter(|entry| entry.expiration_time.is_none_or(|expires| expires > now))
+            .cloned()
+    }
+
+    /// [`Self::lookup`] as the [`ProductStatus`] to answer with.
+    pub fn product_status(&self, product_id: &str, now: Timestamp) -> Option<ProductStatus> {
+        let entry = self.lookup(product_id, now)?;
+        Some(ProductStatus {
+            product_id: entry.product_id,
+            is_owned: true,
+            purchase_state: None,
+            purchase_time: None,
+            expiration_time: entry.expiration_time,
+            is_auto_renewing: None,
+            is_acknowledged: None,
+            purchase_token: None,
+            renewal_info: None,
+            latest_transaction: None,
+            from_cache: true,
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<String, CachedEntitlement>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Best effort: a failed write only costs the offline fallback.
+    fn save(&self, entries: &BTreeMap<String, CachedEntitlement>) {
+        let result = serde_json::to_vec(&entries.values().collect::<Vec<_>>())
+            .map_err(std::io::Error::other)
+            .and_then(|json| {
+                if let Some(dir) = self.path.parent() {
+                    std::fs::create_dir_all(dir)?;
+                }
+                std::fs::write(&self.path, json)
+            });
+        if let Err(e) = result {
+            log::warn!(
+                "Failed to write entitlement cache {}: {e}",
+                self.path.display()
+            );
+        }
+    }
+}
+
+/// Whether `err` means the store couldn't be reached, as opposed to an
+/// answer the cache mustn't override.
+pub fn is_network_error(err: &crate::Error) -> bool {
+    matches!(
+        err.error_code(),
+        Some(
+            IapErrorCode::NetworkError
+                | IapErrorCode::ServiceUnavailable
+                | IapErrorCode::ServiceDisconnected
+                | IapErrorCode::ServiceTimeout
+        )
+    )
+}
+
+/// The current time as a [`Timestamp`].
+pub fn now() -> Timestamp {
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| {
+            i64::try_from(elapsed.as_millis()).unwrap_or(i64::MAX)
+        });
+    Timestamp::from_unix_millis(millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY_MS: i64 = 86_400_000;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("tauri-plugin-iap-{name}-{}", std::process::id()))
+            .join(FILE_NAME)
+    }
+
+    fn at(millis: i64) -> Timestamp {
+        Timestamp::from_unix_millis(millis)
+    }
+
+    #[test]
+    fn test_entitlement_cache_persists_across_loads() {
+        let path = temp_path("persist");
+        let max_age = Duration::from_secs(7 * 86_400);
+
+        let cache = EntitlementCache::load(path.clone(), max_age);
+        cache.record("premium", None, at(1_000));
+        cache.record("monthly", Some(at(30 * DAY_MS)), at(1_000));
+
+        let reloaded = EntitlementCache::load(path.clone(), max_age);
+        assert_eq!(
+            reloaded.lookup("monthly", at(2_000)),
+            Some(CachedEntitlement {
+                product_id: "monthly".to_string(),
+                expiration_time: Some(at(30 * DAY_MS)),
+                last_verified: at(1_000),
+            })
+        );
+
+        reloaded.replace_all([("monthly".to_string(), None)], at(3_000));
+        let reloaded = EntitlementCache::load(path.clone(), max_age);
+        assert!(reloaded.lookup("premium", at(4_000)).is_none());
+        reloaded.remove("monthly");
+        assert!(
+            EntitlementCache::load(path.clone(), max_age)
+                .lookup("monthly", at(4_000))
+                .is_none()
+        );
+
+        let _ = std::fs::remove_dir_all(path.parent().expect("path has a parent"));
+    }
+
+    #[test]
+    fn test_entitlement_cache_expiry() {
+        let path = temp_path("expiry");
+        let cache = EntitlementCache::load(path.clone(), Duration::from_secs(86_400));
+        cache.record("premium", None, at(0));
+        cache.record("monthly", Some(at(DAY_MS / 2)), at(0));
+
+        let status = cache
+            .product_status("premium", at(DAY_MS))
+            .expect("Expected a cached status within the max age");
+        assert!(status.is_owned);
+        assert!(status.from_cache);
+        assert!(cache.lookup("premium", at(DAY_MS + 1)).is_none());
+
+        // The subscription lapsed before the max age ran out.
+        assert!(cache.lookup("monthly", at(DAY_MS / 4)).is_some());
+        assert!(cache.lookup("monthly", at(DAY_MS / 2)).is_none());
+
+        let _ = std::fs::remove_dir_all(path.parent().expect("path has a parent"));
+    }
+
+    #[test]
+    fn test_entitlement_cache_ignores_corrupt_file() {
+        let path = temp_path("corrupt");
+        std::fs::create_dir_all(path.parent().expect("path has a parent"))
+            .expect("Failed to create temp dir");
+        std::fs::write(&path, "not json").expect("Failed to write temp file");
+
+        let cache = EntitlementCache::load(path.clone(), Duration::from_secs(60));
+        assert!(cache.lookup("premium", at(0)).is_none());
+
+        let _ = std::fs::remove_dir_all(path.parent().expect("path has a parent"));
+    }
+
+    #[test]
+    fn test_is_network_error() {
+        assert!(is_network_error(&crate::Error::Timeout(
+            Duration::from_secs(30)
+        )));
+        assert!(!is_network_error(&crate::Error::InvalidArgument(
+            "bad".to_string()
+        )));
+    }
+}
@@ -1,7 +1,222 @@
-use serde::{Serialize, ser::Serializer};
+use serde::{Deserialize, Deserializer, Serialize, ser::Serializer};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Store error, normalized across platforms so callers can `match` instead of
+/// parsing the raw `ErrorResponse::code` string.
+///
+/// Build one from a raw code with [`IapErrorCode::from`]. Parsing is
+/// best-effort: the plugin's own camelCase codes (`"notSupported"`,
+/// `"transactionNotFound"`, ...), Google Play `BillingResponseCode` names and
+/// integers, `StoreKit` `SKError.Code` integers and `NSURLError` network
+/// statuses are recognized; anything else is kept as
+/// [`IapErrorCode::PlatformSpecific`]. Small integers are ambiguous between
+/// Play Billing and `StoreKit`, so they are read as `SKError.Code` on
+/// iOS/macOS and as `BillingResponseCode` everywhere else.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum IapErrorCode {
+    /// The user dismissed the purchase sheet.
+    UserCancelled,
+    /// Billing is unavailable for this account or device.
+    BillingUnavailable,
+    /// The store service is temporarily unreachable or returned a server error.
+    ServiceUnavailable,
+    /// The connection to the store service was lost (Android).
+    ServiceDisconnected,
+    /// The store service did not respond in time (Android), or a call
+    /// outlived its [`crate::Error::Timeout`] limit (macOS).
+    ServiceTimeout,
+    /// The requested store feature is not supported on this device (Android).
+    FeatureNotSupported,
+    /// The product does not exist or is not available for purchase.
+    ItemUnavailable,
+    /// The user already owns this non-consumable or subscription.
+    ItemAlreadyOwned,
+    /// The user does not own the item being acknowledged or consumed.
+    ItemNotOwned,
+    /// The request was malformed (invalid arguments, misconfigured product).
+    DeveloperError,
+    /// A network error prevented the request from completing.
+    NetworkError,
+    /// The payment parameters were invalid (`StoreKit`).
+    PaymentInvalid,
+    /// The user is not allowed to make payments, e.g. parental controls (`StoreKit`).
+    PaymentNotAllowed,
+    /// The command is not available on the current platform.
+    NotSupported,
+    /// The OS is older than `StoreKit` 2 requires (macOS).
+    StoreKitUnavailable,
+    /// No unfinished transaction matches the given identifier (`StoreKit`).
+    TransactionNotFound,
+    /// The store's signature on a transaction could not be verified.
+    VerificationFailed,
+    /// The promotional offer's signature was rejected (`StoreKit`).
+    InvalidOfferSignature,
+    /// The promotional offer is unknown, misconfigured or the user isn't
+    /// eligible for it (`StoreKit`).
+    InvalidOffer,
+    /// A product id was empty; see [`crate::validate_product_id`].
+    EmptyProductId,
+    /// A product id was longer than [`crate::MAX_PRODUCT_ID_LEN`].
+    ProductIdTooLong,
+    /// A product id contained whitespace, control or non-ASCII characters.
+    InvalidProductId,
+    /// The app has no App Store receipt and none was fetched (`StoreKit`).
+    ReceiptUnavailable,
+    /// The store reported a generic, unspecified failure.
+    Unknown,
+    /// Any code without a dedicated variant, kept verbatim.
+    PlatformSpecific(String),
+}
+
+impl IapErrorCode {
+    /// The plugin's camelCase name for this code, or the raw code for
+    /// [`IapErrorCode::PlatformSpecific`].
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::UserCancelled => "userCancelled",
+            Self::BillingUnavailable => "billingUnavailable",
+            Self::ServiceUnavailable => "serviceUnavailable",
+            Self::ServiceDisconnected => "serviceDisconnected",
+            Self::ServiceTimeout => "serviceTimeout",
+            Self::FeatureNotSupported => "featureNotSupported",
+            Self::ItemUnavailable => "itemUnavailable",
+            Self::ItemAlreadyOwned => "itemAlreadyOwned",
+            Self::ItemNotOwned => "itemNotOwned",
+            Self::DeveloperError => "developerError",
+            Self::NetworkError => "networkError",
+            Self::PaymentInvalid => "paymentInvalid",
+            Self::PaymentNotAllowed => "paymentNotAllowed",
+            Self::NotSupported => "notSupported",
+            Self::StoreKitUnavailable => "storeKitUnavailable",
+            Self::TransactionNotFound => "transactionNotFound",
+            Self::VerificationFailed => "verificationFailed",
+            Self::InvalidOfferSignature => "invalidOfferSignature",
+            Self::InvalidOffer => "invalidOffer",
+            Self::EmptyProductId => "emptyProductId",
+            Self::ProductIdTooLong => "productIdTooLong",
+            Self::InvalidProductId => "invalidProductId",
+            Self::ReceiptUnavailable => "receiptUnavailable",
+            Self::Unknown => "unknown",
+            Self::PlatformSpecific(code) => code,
+        }
+    }
+
+    /// `StoreKit` `SKError.Code` values.
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    const fn from_numeric(code: i64) -> Option<Self> {
+        match code {
+            0 => Some(Self::Unknown),
+            2 => Some(Self::UserCancelled),
+            3 => Some(Self::PaymentInvalid),
+            4 => Some(Self::PaymentNotAllowed),
+            5 => Some(Self::ItemUnavailable),
+            7 => Some(Self::NetworkError),
+            12 => Some(Self::InvalidOfferSignature),
+            11 | 13 | 14 => Some(Self::InvalidOffer),
+            _ => Self::from_url_error(code),
+        }
+    }
+
+    /// Google Play `BillingClient.BillingResponseCode` values.
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    const fn from_numeric(code: i64) -> Option<Self> {
+        match code {
+            -3 => Some(Self::ServiceTimeout),
+            -2 => Some(Self::FeatureNotSupported),
+            -1 => Some(Self::ServiceDisconnected),
+            1 => Some(Self::UserCancelled),
+            2 => Some(Self::ServiceUnavailable),
+            3 => Some(Self::BillingUnavailable),
+            4 => Some(Self::ItemUnavailable),
+            5 => Some(Self::DeveloperError),
+            6 => Some(Self::Unknown),
+            7 => Some(Self::ItemAlreadyOwned),
+            8 => Some(Self::ItemNotOwned),
+            12 => Some(Self::NetworkError),
+            _ => Self::from_url_error(code),
+        }
+    }
+
+    /// `NSURLError` connectivity statuses surfaced through `StoreKit` and
+    /// `WebView` networking.
+    const fn from_url_error(code: i64) -> Option<Self> {
+        match code {
+            -1001 | -1003 | -1004 | -1005 | -1009 | -1020 => Some(Self::NetworkError),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for IapErrorCode {
+    fn from(code: &str) -> Self {
+        let trimmed = code.trim();
+        if let Ok(numeric) = trimmed.parse::<i64>() {
+            return Self::from_numeric(numeric)
+                .unwrap_or_else(|| Self::PlatformSpecific(trimmed.to_string()));
+        }
+        match trimmed {
+            "userCancelled"
+            | "userCanceled"
+            | "USER_CANCELED"
+            | "paymentCancelled"
+            | "purchaseNotCompleted" => Self::UserCancelled,
+            "billingUnavailable" | "BILLING_UNAVAILABLE" => Self::BillingUnavailable,
+            "serviceUnavailable" | "SERVICE_UNAVAILABLE" | "serverError" => {
+                Self::ServiceUnavailable
+            }
+            "serviceDisconnected" | "SERVICE_DISCONNECTED" => Self::ServiceDisconnected,
+            "serviceTimeout" | "SERVICE_TIMEOUT" => Self::ServiceTimeout,
+            "featureNotSupported" | "FEATURE_NOT_SUPPORTED" => Self::FeatureNotSupported,
+            "itemUnavailable" | "ITEM_UNAVAILABLE" | "productNotFound" => Self::ItemUnavailable,
+            "itemAlreadyOwned" | "ITEM_ALREADY_OWNED" => Self::ItemAlreadyOwned,
+            "itemNotOwned" | "ITEM_NOT_OWNED" => Self::ItemNotOwned,
+            "developerError" | "DEVELOPER_ERROR" => Self::DeveloperError,
+            "networkError" | "NETWORK_ERROR" => Self::NetworkError,
+            "paymentInvalid" => Self::PaymentInvalid,
+            "paymentNotAllowed" => Self::PaymentNotAllowed,
+            "notSupported" => Self::NotSupported,
+            "storeKitUnavailable" => Self::StoreKitUnavailable,
+            "transactionNotFound" => Self::TransactionNotFound,
+            "verificationFailed" => Self::VerificationFailed,
+            "invalidOfferSignature" => Self::InvalidOfferSignature,
+            "invalidOffer" => Self::InvalidOffer,
+            "emptyProductId" => Self::EmptyProductId,
+            "productIdTooLong" => Self::ProductIdTooLong,
+            "invalidProductId" => Self::InvalidProductId,
+            "receiptUnavailable" => Self::ReceiptUnavailable,
+            "unknown" | "ERROR" => Self::Unknown,
+            other => Self::PlatformSpecific(other.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for IapErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for IapErrorCode {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for IapErrorCode {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let code = String::deserialize(deserializer)?;
+        Ok(Self::from(code.as_str()))
+    }
+}
+
 /// Replica of the [`tauri::plugin::mobile::ErrorResponse`] for desktop platforms.
 #[cfg(desktop)]
 #[derive(Debug, thiserror::Error, Clone, serde::Deserialize)]
@@ -10,11 +225,40 @@ pub struct ErrorResponse<T = ()> {
     pub code: Option<String>,
     /// Error message.
     pub message: Option<String>,
+    /// `code` parsed into an [`IapErrorCode`]. Not part of the wire format;
+    /// filled in by [`ErrorResponse::new`] and [`ErrorResponse::with_error_code`].
+    #[serde(skip)]
+    pub error_code: Option<IapErrorCode>,
     /// Optional error data.
     #[serde(flatten)]
     pub data: T,
 }
 
+#[cfg(desktop)]
+impl ErrorResponse {
+    /// Builds a response with `error_code` derived from `code`.
+    pub fn new(code: Option<&str>, message: impl Into<String>) -> Self {
+        Self {
+            code: code.map(str::to_string),
+            message: Some(message.into()),
+            error_code: code.map(IapErrorCode::from),
+            data: (),
+        }
+    }
+}
+
+#[cfg(desktop)]
+impl<T> ErrorResponse<T> {
+    /// Fills `error_code` from `code` on a deserialized response.
+    #[must_use]
+    pub fn with_error_code(mut self) -> Self {
+        if self.error_code.is_none() {
+            self.error_code = self.code.as_deref().map(IapErrorCode::from);
+        }
+        self
+    }
+}
+
 #[cfg(desktop)]
 impl<T> std::fmt::Display for ErrorResponse<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -50,6 +294,25 @@ pub enum PluginInvokeError {
 pub enum Error {
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+    /// Rejected by [`crate::validate_product_id`] before reaching the store;
+    /// `code` names the rule the id broke.
+    #[error("invalid product id: {message}")]
+    InvalidProductId { code: IapErrorCode, message: String },
+    /// The store didn't answer within the limit set by
+    /// [`IapConfig::product_query_timeout_ms`](crate::IapConfig::product_query_timeout_ms).
+    /// Carries the time waited; retrying is safe.
+    #[error("the store did not respond within {0:?}")]
+    Timeout(std::time::Duration),
+    /// The platform has no store integration, e.g. Linux; `platform` is
+    /// [`std::env::consts::OS`]. Match on it to hide purchase UI instead of
+    /// reporting a failure.
+    #[error("in-app purchases are not supported on {platform}")]
+    NotSupported { platform: String },
+    #[cfg(feature = "jws")]
+    #[error(transparent)]
+    Jws(#[from] jsonwebtoken::errors::Error),
     #[cfg(mobile)]
     #[error(transparent)]
     PluginInvoke(#[from] tauri::plugin::mobile::PluginInvokeError),
@@ -61,6 +324,30 @@ pub enum Error {
     WindowsApi(#[from] windows::core::Error),
 }
 
+impl Error {
+    /// Structured store error code, when the error was a store rejection that
+    /// carried one.
+    #[must_use]
+    pub fn error_code(&self) -> Option<IapErrorCode> {
+        match self {
+            #[cfg(desktop)]
+            Self::PluginInvoke(PluginInvokeError::InvokeRejected(response)) => response
+                .error_code
+                .clone()
+                .or_else(|| response.code.as_deref().map(IapErrorCode::from)),
+            #[cfg(mobile)]
+            Self::PluginInvoke(tauri::plugin::mobile::PluginInvokeError::InvokeRejected(
+                response,
+            )) => response.code.as_deref().map(IapErrorCode::from),
+            Self::InvalidArgument(_) => Some(IapErrorCode::DeveloperError),
+            Self::InvalidProductId { code, .. } => Some(code.clone()),
+            Self::Timeout(_) => Some(IapErrorCode::ServiceTimeout),
+            Self::NotSupported { .. } => Some(IapErrorCode::NotSupported),
+            _ => None,
+        }
+    }
+}
+
 impl Serialize for Error {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
@@ -97,6 +384,113 @@ mod tests {
         assert!(error.to_string().contains("access denied"));
     }
 
+    #[test]
+    fn test_error_invalid_argument_display() {
+        let error = Error::InvalidArgument("product_id is required".to_string());
+        assert_eq!(
+            error.to_string(),
+            "invalid argument: product_id is required"
+        );
+    }
+
+    #[test]
+    fn test_error_timeout_display_and_code() {
+        let error = Error::Timeout(std::time::Duration::from_secs(30));
+        assert_eq!(error.to_string(), "the store did not respond within 30s");
+        assert_eq!(error.error_code(), Some(IapErrorCode::ServiceTimeout));
+    }
+
+    #[test]
+    fn test_error_not_supported_display_and_code() {
+        let error = Error::NotSupported {
+            platform: "linux".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "in-app purchases are not supported on linux"
+        );
+        assert_eq!(error.error_code(), Some(IapErrorCode::NotSupported));
+    }
+
+    #[test]
+    fn test_iap_error_code_from_named_codes() {
+        assert_eq!(
+            IapErrorCode::from("transactionNotFound"),
+            IapErrorCode::TransactionNotFound
+        );
+        assert_eq!(
+            IapErrorCode::from("notSupported"),
+            IapErrorCode::NotSupported
+        );
+        assert_eq!(
+            IapErrorCode::from("storeKitUnavailable"),
+            IapErrorCode::StoreKitUnavailable
+        );
+        assert_eq!(
+            IapErrorCode::from("ITEM_ALREADY_OWNED"),
+            IapErrorCode::ItemAlreadyOwned
+        );
+        assert_eq!(
+            IapErrorCode::from("networkError"),
+            IapErrorCode::NetworkError
+        );
+        assert_eq!(
+            IapErrorCode::from("invalidOfferSignature"),
+            IapErrorCode::InvalidOfferSignature
+        );
+        assert_eq!(
+            IapErrorCode::from("productIdTooLong"),
+            IapErrorCode::ProductIdTooLong
+        );
+        assert_eq!(
+            IapErrorCode::from("storeNotInitialized"),
+            IapErrorCode::PlatformSpecific("storeNotInitialized".to_string())
+        );
+    }
+
+    #[test]
+    fn test_iap_error_code_from_network_status() {
+        assert_eq!(IapErrorCode::from("-1009"), IapErrorCode::NetworkError);
+        assert_eq!(
+            IapErrorCode::from("-25300"),
+            IapErrorCode::PlatformSpecific("-25300".to_string())
+        );
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    #[test]
+    fn test_iap_error_code_from_billing_response_code() {
+        assert_eq!(IapErrorCode::from("1"), IapErrorCode::UserCancelled);
+        assert_eq!(IapErrorCode::from("3"), IapErrorCode::BillingUnavailable);
+        assert_eq!(IapErrorCode::from("7"), IapErrorCode::ItemAlreadyOwned);
+        assert_eq!(IapErrorCode::from("8"), IapErrorCode::ItemNotOwned);
+        assert_eq!(IapErrorCode::from("-1"), IapErrorCode::ServiceDisconnected);
+        assert_eq!(IapErrorCode::from("12"), IapErrorCode::NetworkError);
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    #[test]
+    fn test_iap_error_code_from_sk_error_code() {
+        assert_eq!(IapErrorCode::from("2"), IapErrorCode::UserCancelled);
+        assert_eq!(IapErrorCode::from("4"), IapErrorCode::PaymentNotAllowed);
+        assert_eq!(IapErrorCode::from("5"), IapErrorCode::ItemUnavailable);
+        assert_eq!(
+            IapErrorCode::from("12"),
+            IapErrorCode::InvalidOfferSignature
+        );
+        assert_eq!(IapErrorCode::from("14"), IapErrorCode::InvalidOffer);
+    }
+
+    #[test]
+    fn test_iap_error_code_serialize() {
+        let json = serde_json::to_string(&IapErrorCode::UserCancelled)
+            .expect("Failed to serialize IapErrorCode");
+        assert_eq!(json, r#""userCancelled""#);
+        let json = serde_json::to_string(&IapErrorCode::PlatformSpecific("custom".to_string()))
+            .expect("Failed to serialize IapErrorCode");
+        assert_eq!(json, r#""custom""#);
+    }
+
     #[cfg(desktop)]
     mod desktop_tests {
         use super::*;
@@ -106,6 +500,7 @@ mod tests {
             let response = ErrorResponse {
                 code: Some("ERR001".to_string()),
                 message: None,
+                error_code: None,
                 data: (),
             };
             assert_eq!(response.to_string(), "[ERR001]");
@@ -116,6 +511,7 @@ mod tests {
             let response = ErrorResponse {
                 code: None,
                 message: Some("Something went wrong".to_string()),
+                error_code: None,
                 data: (),
             };
             assert_eq!(response.to_string(), "Something went wrong");
@@ -126,6 +522,7 @@ mod tests {
             let response = ErrorResponse {
                 code: Some("ERR001".to_string()),
                 message: Some("Something went wrong".to_string()),
+                error_code: None,
                 data: (),
             };
             assert_eq!(response.to_string(), "[ERR001] - Something went wrong");
@@ -136,6 +533,7 @@ mod tests {
             let response: ErrorResponse = ErrorResponse {
                 code: None,
                 message: None,
+                error_code: None,
                 data: (),
             };
             assert_eq!(response.to_string(), "");
@@ -159,11 +557,56 @@ mod tests {
             assert_eq!(response.message, None);
         }
 
+        #[test]
+        fn test_error_response_new_sets_error_code() {
+            let response = ErrorResponse::new(Some("notSupported"), "not here");
+            assert_eq!(response.code, Some("notSupported".to_string()));
+            assert_eq!(response.error_code, Some(IapErrorCode::NotSupported));
+
+            let response = ErrorResponse::new(None, "bare message");
+            assert_eq!(response.error_code, None);
+        }
+
+        #[test]
+        fn test_error_response_with_error_code() {
+            let json = r#"{"code":"verificationFailed","message":"bad signature"}"#;
+            let response: ErrorResponse = serde_json::from_str::<ErrorResponse>(json)
+                .expect("Failed to deserialize ErrorResponse")
+                .with_error_code();
+            assert_eq!(response.error_code, Some(IapErrorCode::VerificationFailed));
+        }
+
+        #[test]
+        fn test_receipt_unavailable_error_code() {
+            let json =
+                r#"{"code":"receiptUnavailable","message":"The app has no App Store receipt"}"#;
+            let response = serde_json::from_str::<ErrorResponse>(json)
+                .expect("Failed to deserialize ErrorResponse")
+                .with_error_code();
+            let error: Error = PluginInvokeError::InvokeRejected(response).into();
+            assert_eq!(error.error_code(), Some(IapErrorCode::ReceiptUnavailable));
+            assert_eq!(
+                IapErrorCode::ReceiptUnavailable.as_str(),
+                "receiptUnavailable"
+            );
+        }
+
+        #[test]
+        fn test_error_error_code() {
+            let error: Error =
+                PluginInvokeError::InvokeRejected(ErrorResponse::new(Some("7"), "owned")).into();
+            assert_eq!(error.error_code(), Some(IapErrorCode::ItemAlreadyOwned));
+
+            let error = Error::Io(std::io::Error::other("io"));
+            assert_eq!(error.error_code(), None);
+        }
+
         #[test]
         fn test_plugin_invoke_error_invoke_rejected() {
             let response = ErrorResponse {
                 code: Some("rejected".to_string()),
                 message: Some("Request rejected".to_string()),
+                error_code: None,
                 data: (),
             };
             let error = PluginInvokeError::InvokeRejected(response);
@@ -197,6 +640,7 @@ mod tests {
             let response = ErrorResponse {
                 code: Some("test".to_string()),
                 message: Some("test".to_string()),
+                error_code: None,
                 data: (),
             };
             let plugin_error = PluginInvokeError::InvokeRejected(response);
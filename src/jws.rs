@@ -0,0 +1,171 @@
+//! Decoding of `StoreKit` 2 JWS transactions, such as
+//! [`Purchase::jws_representation`](crate::Purchase::jws_representation) or
+//! the `signedTransactionInfo` of an App Store Server Notification. Requires
+//! the `jws` feature.
+//!
+//! Apple signs these tokens with ES256 and puts the signing certificate chain
+//! in the `x5c` header. [`decode_jws_transaction`] reads the payload without
+//! checking the signature, which is fine for display or logging but not for
+//! granting entitlements. To trust a transaction, validate the `x5c` chain
+//! (read it with [`jsonwebtoken::decode_header`]) up to Apple Root CA - G3,
+//! then pass the leaf certificate's public key to [`verify_jws_transaction`].
+//! This crate doesn't bundle Apple's root certificate or an X.509 validator;
+//! Apple's App Store Server Library does both on a backend.
+
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::Timestamp;
+
+/// Payload of a signed `StoreKit` transaction, after Apple's
+/// `JWSTransactionDecodedPayload`. Only the fields shared by every product
+/// type are kept.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JwsTransaction {
+    pub transaction_id: String,
+    pub original_transaction_id: String,
+    pub bundle_id: String,
+    pub product_id: String,
+    pub purchase_date: Timestamp,
+    pub quantity: u32,
+    /// `"Auto-Renewable Subscription"`, `"Non-Consumable"`, `"Consumable"` or
+    /// `"Non-Renewing Subscription"`.
+    #[serde(rename = "type")]
+    pub type_field: String,
+    /// UUID the app passed as
+    /// [`PurchaseOptions::app_account_token`](crate::PurchaseOptions::app_account_token).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_account_token: Option<String>,
+    /// When the App Store signed the token.
+    pub signed_date: Timestamp,
+    /// `"Production"`, `"Sandbox"` or `"Xcode"`.
+    pub environment: String,
+}
+
+/// Decodes the payload of a `StoreKit` JWS transaction without verifying its
+/// signature. See the [module docs](crate::jws) before trusting the result.
+///
+/// # Errors
+///
+/// Returns [`crate::Error::Jws`] when `jws` isn't a well-formed JWS or its
+/// payload isn't a transaction.
+pub fn decode_jws_transaction(jws: &str) -> crate::Result<JwsTransaction> {
+    let mut validation = validation();
+    validation.insecure_disable_signature_validation();
+    Ok(jsonwebtoken::decode(jws, &DecodingKey::from_secret(&[]), &validation)?.claims)
+}
+
+/// Decodes a `StoreKit` JWS transaction after checking its ES256 signature
+/// against `key`, the public key of the leaf certificate in the token's
+/// `x5c` chain. Validating that chain is up to the caller.
+///
+/// # Errors
+///
+/// Returns [`crate::Error::Jws`] when the signature doesn't match `key`, the
+/// token isn't signed with ES256, or the payload isn't a transaction.
+pub fn verify_jws_transaction(jws: &str, key: &DecodingKey) -> crate::Result<JwsTransaction> {
+    Ok(jsonwebtoken::decode(jws, key, &validation())?.claims)
+}
+
+/// ES256 with the registered-claim checks turned off: transactions carry no
+/// `exp` or `aud`.
+fn validation() -> Validation {
+    let mut validation = Validation::new(Algorithm::ES256);
+    validation.required_spec_claims.clear();
+    validation.validate_exp = false;
+    validation.validate_aud = false;
+    validation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{EncodingKey, Header};
+
+    fn sign(claims: &serde_json::Value) -> String {
+        jsonwebtoken::encode(
+            &Header::new(Algorithm::HS256),
+            claims,
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .expect("Failed to encode JWS")
+    }
+
+    fn payload() -> serde_json::Value {
+        serde_json::json!({
+            "transactionId": "2000000123456789",
+            "originalTransactionId": "2000000100000000",
+            "bundleId": "com.example.app",
+            "productId": "premium_monthly",
+            "purchaseDate": 1_700_000_000_000_i64,
+            "originalPurchaseDate": 1_690_000_000_000_i64,
+            "expiresDate": 1_702_592_000_000_i64,
+            "quantity": 1,
+            "type": "Auto-Renewable Subscription",
+            "appAccountToken": "6f9619ff-8b86-d011-b42d-00c04fc964ff",
+            "inAppOwnershipType": "PURCHASED",
+            "signedDate": 1_700_000_001_000_i64,
+            "environment": "Sandbox",
+        })
+    }
+
+    #[test]
+    fn test_decode_jws_transaction_reads_the_payload() {
+        let transaction =
+            decode_jws_transaction(&sign(&payload())).expect("Failed to decode JWS transaction");
+
+        assert_eq!(transaction.transaction_id, "2000000123456789");
+        assert_eq!(transaction.original_transaction_id, "2000000100000000");
+        assert_eq!(transaction.bundle_id, "com.example.app");
+        assert_eq!(transaction.product_id, "premium_monthly");
+        assert_eq!(
+            transaction.purchase_date,
+            Timestamp::from_unix_millis(1_700_000_000_000)
+        );
+        assert_eq!(transaction.quantity, 1);
+        assert_eq!(transaction.type_field, "Auto-Renewable Subscription");
+        assert_eq!(
+            transaction.app_account_token.as_deref(),
+            Some("6f9619ff-8b86-d011-b42d-00c04fc964ff")
+        );
+        assert_eq!(
+            transaction.signed_date,
+            Timestamp::from_unix_millis(1_700_000_001_000)
+        );
+        assert_eq!(transaction.environment, "Sandbox");
+    }
+
+    #[test]
+    fn test_decode_jws_transaction_without_app_account_token() {
+        let mut claims = payload();
+        claims
+            .as_object_mut()
+            .expect("payload is an object")
+            .remove("appAccountToken");
+
+        let transaction =
+            decode_jws_transaction(&sign(&claims)).expect("Failed to decode JWS transaction");
+        assert_eq!(transaction.app_account_token, None);
+    }
+
+    #[test]
+    fn test_decode_jws_transaction_rejects_malformed_tokens() {
+        for jws in ["", "not-a-jws", "a.b.c"] {
+            assert!(
+                matches!(decode_jws_transaction(jws), Err(crate::Error::Jws(_))),
+                "{jws:?} should not decode"
+            );
+        }
+
+        let missing_product = serde_json::json!({ "transactionId": "2000000123456789" });
+        assert!(decode_jws_transaction(&sign(&missing_product)).is_err());
+    }
+
+    #[test]
+    fn test_verify_jws_transaction_requires_es256() {
+        let err = verify_jws_transaction(&sign(&payload()), &DecodingKey::from_secret(b"secret"))
+            .expect_err("An HS256 token should not verify");
+        assert!(matches!(err, crate::Error::Jws(_)));
+    }
+}
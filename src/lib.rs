@@ -14,24 +14,64 @@ mod mobile;
 #[cfg(target_os = "windows")]
 mod windows;
 
+mod backend;
+#[cfg(target_os = "macos")]
+mod cache;
 pub(crate) mod commands;
+mod config;
+#[cfg(target_os = "macos")]
+mod entitlement_cache;
 mod error;
+#[cfg(feature = "jws")]
+pub mod jws;
 #[cfg(desktop)]
 pub(crate) mod listeners;
 mod models;
+#[cfg(target_os = "macos")]
+mod retry;
+pub mod server_notifications;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod timestamp;
+#[cfg(not(target_os = "linux"))]
+mod trace;
+mod validation;
 
-pub use error::{Error, Result};
+pub use backend::{IapBackend, PartialSuccess};
+pub use config::{
+    DEFAULT_LISTENER_BUFFER_SIZE, DEFAULT_PRODUCT_QUERY_TIMEOUT_MS,
+    DEFAULT_PRODUCTS_CACHE_TTL_SECS, IapConfig, MAX_AUTO_ACKNOWLEDGE_DELAY_MS,
+};
+pub use error::{Error, IapErrorCode, Result};
+#[cfg(desktop)]
+pub use listeners::{EventStream, ListenerHandle};
+pub use timestamp::Timestamp;
+pub use validation::{MAX_PRODUCT_ID_LEN, validate_product_id};
 
 #[cfg(target_os = "linux")]
-use desktop::Iap;
+pub use desktop::Iap;
 #[cfg(target_os = "macos")]
-use macos::Iap;
+pub use macos::Iap;
 #[cfg(mobile)]
-use mobile::Iap;
+pub use mobile::Iap;
 #[cfg(target_os = "windows")]
-use windows::Iap;
+pub use windows::Iap;
 
 /// Extensions to [`tauri::App`], [`tauri::AppHandle`] and [`tauri::Window`] to access the iap APIs.
+///
+/// ```no_run
+/// use tauri_plugin_iap::{IapExt, ProductType};
+///
+/// async fn coin_packs<R: tauri::Runtime>(
+///     app: &tauri::AppHandle<R>,
+/// ) -> tauri_plugin_iap::Result<usize> {
+///     let response = app
+///         .iap()
+///         .get_products(vec!["coins_100".into()], ProductType::Consumable)
+///         .await?;
+///     Ok(response.products.len())
+/// }
+/// ```
 pub trait IapExt<R: Runtime> {
     fn iap(&self) -> &Iap<R>;
 }
@@ -42,18 +82,57 @@ impl<R: Runtime, T: Manager<R>> crate::IapExt<R> for T {
     }
 }
 
-/// Initializes the plugin.
+/// Initializes the plugin. See [`IapConfig`] for the `tauri.conf.json` options.
 #[must_use]
-pub fn init<R: Runtime>() -> TauriPlugin<R> {
-    Builder::new("iap")
+pub fn init<R: Runtime>() -> TauriPlugin<R, Option<IapConfig>> {
+    // Say so once at startup rather than only through rejected commands.
+    #[cfg(all(target_os = "linux", feature = "tracing"))]
+    tracing::warn!(
+        platform = std::env::consts::OS,
+        "In-app purchases are not supported; IAP commands will fail with Error::NotSupported"
+    );
+    #[cfg(all(target_os = "linux", not(feature = "tracing")))]
+    log::warn!(
+        "In-app purchases are not supported on {}; IAP commands will fail with Error::NotSupported.",
+        std::env::consts::OS
+    );
+    let builder = Builder::<R, Option<IapConfig>>::new("iap")
         .invoke_handler(tauri::generate_handler![
             commands::initialize,
             commands::get_products,
             commands::purchase,
             commands::restore_purchases,
+            commands::get_purchase_history,
+            commands::get_purchase_history_filtered,
             commands::acknowledge_purchase,
             commands::consume_purchase,
             commands::get_product_status,
+            commands::finish_transaction,
+            commands::get_app_transaction,
+            commands::get_current_entitlements,
+            commands::get_subscription_status,
+            commands::show_manage_subscriptions,
+            commands::present_offer_code_redemption_sheet,
+            commands::get_storefront,
+            commands::get_unfinished_transactions,
+            commands::begin_refund_request,
+            commands::request_refund,
+            commands::get_win_back_offers,
+            commands::present_code_redemption_sheet,
+            commands::is_eligible_for_intro_offer,
+            commands::get_environment,
+            commands::get_receipt,
+            commands::refresh_receipt,
+            commands::can_make_payments,
+            commands::check_introductory_offer_eligibility,
+            commands::is_supported,
+            commands::get_all_product_statuses,
+            commands::can_open_external_purchase_link,
+            commands::open_external_purchase_link,
+            commands::display_pending_store_messages,
+            commands::current_entitlement,
+            commands::get_app_receipt,
+            commands::get_revoked_purchases,
             #[cfg(desktop)]
             listeners::register_listener,
             #[cfg(desktop)]
@@ -61,7 +140,10 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
         ])
         .setup(|app, api| {
             #[cfg(desktop)]
-            listeners::init();
+            listeners::init(api.config().as_ref().map_or(
+                DEFAULT_LISTENER_BUFFER_SIZE,
+                IapConfig::listener_buffer_size,
+            ));
             #[cfg(target_os = "macos")]
             let iap = macos::init(app, &api)?;
             #[cfg(mobile)]
@@ -72,6 +154,13 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
             let iap = desktop::init(app, &api)?;
             app.manage(iap);
             Ok(())
-        })
-        .build()
+        });
+    #[cfg(target_os = "macos")]
+    let builder = builder.on_window_event(|window, event| {
+        // Coming back to the foreground: prices may have changed meanwhile.
+        if let tauri::WindowEvent::Focused(true) = event {
+            window.iap().clear_products_cache();
+        }
+    });
+    builder.build()
 }
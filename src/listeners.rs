@@ -6,85 +6,339 @@
 //!
 //! Provides channel-based event delivery for transaction updates and other IAP events.
 
-use std::collections::HashMap;
-use std::sync::{OnceLock, RwLock};
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::task::{Context, Poll};
+
+use dashmap::DashMap;
+use dashmap::mapref::one::RefMut;
+use futures_util::Stream;
+use serde::de::DeserializeOwned;
+use tauri::ipc::{Channel, InvokeResponseBody, IpcResponse};
+use tokio::sync::mpsc;
 
 use crate::error::{ErrorResponse, PluginInvokeError};
+use crate::models::{
+    BILLING_ISSUE_DETECTED_EVENT, BillingIssueDetectedEvent, PRICE_INCREASE_PENDING_EVENT,
+    PURCHASE_COMPLETED_EVENT, PURCHASE_REVOKED_EVENT, PriceIncreasePendingEvent, Purchase,
+    PurchaseRevokedEvent, RENEWAL_PREFERENCE_CHANGED_EVENT, RenewalPreferenceChangedEvent,
+    STOREFRONT_CHANGED_EVENT, StorefrontInfo, TRANSACTION_UPDATED_EVENT, TransactionEvent,
+};
+
+type ChannelMap = DashMap<u32, tauri::ipc::Channel<serde_json::Value>>;
+// Sharded maps instead of one `RwLock`, so a burst of transaction events
+// doesn't contend with listeners being added or removed. Shard locks don't
+// poison.
+type ListenerMap = DashMap<String, ChannelMap>;
 
-type ChannelMap = HashMap<u32, tauri::ipc::Channel<serde_json::Value>>;
-type ListenerMap = HashMap<String, ChannelMap>;
+static LISTENERS: OnceLock<ListenerMap> = OnceLock::new();
 
-static LISTENERS: OnceLock<RwLock<ListenerMap>> = OnceLock::new();
+// Payloads sent before an event's first listener registered, e.g.
+// `Transaction.updates` firing before the webview subscribes. Flushed by
+// `register_listener` and `register_one_shot_listener`.
+static PENDING: OnceLock<DashMap<String, Pending>> = OnceLock::new();
+static BUFFER_SIZE: AtomicUsize = AtomicUsize::new(crate::DEFAULT_LISTENER_BUFFER_SIZE);
+
+#[derive(Default)]
+struct Pending {
+    queue: VecDeque<serde_json::Value>,
+    // Set once a listener has registered for the event. From then on nothing
+    // is buffered, so payloads sent after every listener is gone (a one-shot
+    // fired, a page reload) aren't replayed to the next one.
+    listened: bool,
+}
+
+fn pending(event: &str) -> RefMut<'static, String, Pending> {
+    PENDING
+        .get_or_init(DashMap::new)
+        .entry(event.to_string())
+        .or_default()
+}
 
 /// Initialize the listeners registry. Call this during plugin init.
-pub fn init() {
-    let _ = LISTENERS.get_or_init(|| RwLock::new(HashMap::new()));
+///
+/// Up to `buffer_size` payloads per event are kept while nobody listens for
+/// it; 0 drops them instead.
+pub fn init(buffer_size: usize) {
+    let _ = LISTENERS.get_or_init(DashMap::new);
+    BUFFER_SIZE.store(buffer_size, Ordering::Relaxed);
 }
 
 /// Trigger an event to all registered listeners for the given event name.
 ///
 /// Called by platform-specific code when transaction updates occur.
+/// `transactionUpdated`, `storefrontChanged`, `purchaseCompleted`,
+/// `purchaseRevoked`, `priceIncreasePending`, `renewalPreferenceChanged` and
+/// `billingIssueDetected` payloads are validated against
+/// [`TransactionEvent`], [`StorefrontInfo`], [`Purchase`],
+/// [`PurchaseRevokedEvent`], [`PriceIncreasePendingEvent`],
+/// [`RenewalPreferenceChangedEvent`] and [`BillingIssueDetectedEvent`] so
+/// listeners get the same shape on every platform.
 #[allow(dead_code)]
 pub fn trigger(event: &str, payload: &str) -> crate::Result<()> {
+    let parse_error = |e: serde_json::Error| {
+        crate::Error::from(PluginInvokeError::InvokeRejected(ErrorResponse::new(
+            None,
+            format!("Failed to parse payload JSON: {e}"),
+        )))
+    };
+
+    match event {
+        TRANSACTION_UPDATED_EVENT => {
+            let transaction_event: TransactionEvent =
+                serde_json::from_str(payload).map_err(parse_error)?;
+            emit(event, &transaction_event)
+        }
+        STOREFRONT_CHANGED_EVENT => {
+            let storefront: StorefrontInfo = serde_json::from_str(payload).map_err(parse_error)?;
+            emit(event, &storefront)
+        }
+        PURCHASE_COMPLETED_EVENT => {
+            let purchase: Purchase = serde_json::from_str(payload).map_err(parse_error)?;
+            emit(event, &purchase)
+        }
+        PURCHASE_REVOKED_EVENT => {
+            let revoked: PurchaseRevokedEvent =
+                serde_json::from_str(payload).map_err(parse_error)?;
+            emit(event, &revoked)
+        }
+        PRICE_INCREASE_PENDING_EVENT => {
+            let pending: PriceIncreasePendingEvent =
+                serde_json::from_str(payload).map_err(parse_error)?;
+            emit(event, &pending)
+        }
+        RENEWAL_PREFERENCE_CHANGED_EVENT => {
+            let changed: RenewalPreferenceChangedEvent =
+                serde_json::from_str(payload).map_err(parse_error)?;
+            emit(event, &changed)
+        }
+        BILLING_ISSUE_DETECTED_EVENT => {
+            let issue: BillingIssueDetectedEvent =
+                serde_json::from_str(payload).map_err(parse_error)?;
+            emit(event, &issue)
+        }
+        _ => {
+            let value: serde_json::Value = serde_json::from_str(payload).map_err(parse_error)?;
+            send(event, &value)
+        }
+    }
+}
+
+/// Serialize `payload` and send it to all registered listeners for `event`.
+#[allow(dead_code)]
+pub fn emit<T: serde::Serialize>(event: &str, payload: &T) -> crate::Result<()> {
+    let value = serde_json::to_value(payload).map_err(PluginInvokeError::CannotSerializePayload)?;
+    send(event, &value)
+}
+
+fn send(event: &str, value: &serde_json::Value) -> crate::Result<()> {
     let listeners = LISTENERS.get().ok_or_else(|| {
-        crate::Error::from(PluginInvokeError::InvokeRejected(ErrorResponse {
-            code: None,
-            message: Some("Listeners not initialized".to_string()),
-            data: (),
-        }))
+        crate::Error::from(PluginInvokeError::InvokeRejected(ErrorResponse::new(
+            None,
+            "Listeners not initialized",
+        )))
     })?;
 
-    // Clone the channel set out of the guard, then drop the lock before
-    // parsing/sending to avoid holding a read lock across slow operations.
-    let channels = {
-        let guard = listeners.read().map_err(|e| {
-            crate::Error::from(PluginInvokeError::InvokeRejected(ErrorResponse {
-                code: None,
-                message: Some(format!("Failed to acquire read lock: {e}")),
-                data: (),
-            }))
-        })?;
-        guard.get(event).cloned()
-    };
+    // Checked under the event's buffer entry, so a listener registering
+    // concurrently either finds this payload buffered or is sent it.
+    let mut buffer = pending(event);
+    // Clone the channels out and drop the shard guards before sending: a
+    // handler that removes its own listener would otherwise deadlock.
+    let channels: Vec<_> = listeners
+        .get(event)
+        .map(|channels| channels.iter().map(|entry| entry.value().clone()).collect())
+        .unwrap_or_default();
 
-    if let Some(channels) = channels {
-        let value: serde_json::Value = serde_json::from_str(payload).map_err(|e| {
-            crate::Error::from(PluginInvokeError::InvokeRejected(ErrorResponse {
-                code: None,
-                message: Some(format!("Failed to parse payload JSON: {e}")),
-                data: (),
-            }))
-        })?;
-        for channel in channels.values() {
-            let _ = channel.send(value.clone());
+    if channels.is_empty() {
+        if !buffer.listened {
+            push_bounded(
+                &mut buffer.queue,
+                value.clone(),
+                BUFFER_SIZE.load(Ordering::Relaxed),
+            );
         }
+        return Ok(());
+    }
+    drop(buffer);
+
+    for channel in channels {
+        let _ = channel.send(value.clone());
     }
     Ok(())
 }
 
-/// Register a channel to receive events for the given event name.
+/// Append `value`, dropping the oldest payloads beyond `capacity`.
+fn push_bounded(
+    buffer: &mut VecDeque<serde_json::Value>,
+    value: serde_json::Value,
+    capacity: usize,
+) {
+    if capacity == 0 {
+        return;
+    }
+    while buffer.len() >= capacity {
+        buffer.pop_front();
+    }
+    buffer.push_back(value);
+}
+
+/// Register a channel to receive events for the given event name. Payloads
+/// buffered before the event's first listener registered are sent to it
+/// first.
+// Always succeeds, but stays fallible so the command signature matches
+// `remove_listener`.
+#[allow(clippy::unnecessary_wraps)]
 #[tauri::command]
 pub fn register_listener(
     event: String,
     handler: tauri::ipc::Channel<serde_json::Value>,
 ) -> crate::Result<()> {
-    let listeners = LISTENERS.get_or_init(|| RwLock::new(HashMap::new()));
-    {
-        let mut guard = listeners.write().map_err(|e| {
-            crate::Error::from(PluginInvokeError::InvokeRejected(ErrorResponse {
-                code: None,
-                message: Some(format!("Failed to acquire write lock: {e}")),
-                data: (),
-            }))
-        })?;
-        guard
-            .entry(event)
-            .or_default()
-            .insert(handler.id(), handler);
+    let buffered = {
+        let mut buffer = pending(&event);
+        buffer.listened = true;
+        insert(event, handler.id(), handler.clone());
+        std::mem::take(&mut buffer.queue)
+    };
+    for value in buffered {
+        let _ = handler.send(value);
     }
     Ok(())
 }
 
+fn insert(event: String, channel_id: u32, channel: Channel<serde_json::Value>) {
+    LISTENERS
+        .get_or_init(DashMap::new)
+        .entry(event)
+        .or_default()
+        .insert(channel_id, channel);
+}
+
+/// Register `handler` for the next `event` only: the listener removes itself
+/// on first delivery, and a concurrent second delivery is dropped. If
+/// payloads were buffered before the event's first listener, the oldest is
+/// delivered straight away and the rest stay for the next listener. Returns
+/// the channel id, which can be passed to [`remove_listener`] to cancel before
+/// the event arrives.
+// Fallible like `register_listener`, so callers handle both the same way.
+#[allow(clippy::unnecessary_wraps)]
+pub fn register_one_shot_listener(
+    event: String,
+    handler: Channel<serde_json::Value>,
+) -> crate::Result<u32> {
+    let channel_id = handler.id();
+    let mut buffer = pending(&event);
+    buffer.listened = true;
+    if let Some(value) = buffer.queue.pop_front() {
+        drop(buffer);
+        let _ = handler.send(value);
+        return Ok(channel_id);
+    }
+
+    let consumed = AtomicBool::new(false);
+    let owner = event.clone();
+    let once = Channel::<serde_json::Value>::new(move |body| {
+        if consumed.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
+        let _ = remove_listener(owner.clone(), channel_id);
+        if let InvokeResponseBody::Json(json) = body
+            && let Ok(payload) = serde_json::from_str(&json)
+        {
+            handler.send(payload)?;
+        }
+        Ok(())
+    });
+    // Keyed by the caller's channel id rather than the wrapper's, so the id
+    // handed back is the one the caller already knows. Inserted under the
+    // buffer entry, like `register_listener`.
+    insert(event, channel_id, once);
+    drop(buffer);
+    Ok(channel_id)
+}
+
+/// Removes its listener when dropped, so a Rust-side observer can't outlive
+/// its owner.
+#[must_use = "the listener is removed as soon as the handle is dropped"]
+#[derive(Debug)]
+pub struct ListenerHandle {
+    event: String,
+    channel_id: u32,
+}
+
+impl Drop for ListenerHandle {
+    fn drop(&mut self) {
+        let _ = remove_listener(std::mem::take(&mut self.event), self.channel_id);
+    }
+}
+
+/// Register a typed channel for `event`. Payloads that don't deserialize into
+/// `T` are dropped, which can't happen for the events [`trigger`] validates.
+pub fn observe<T>(event: &str, handler: Channel<T>) -> crate::Result<ListenerHandle>
+where
+    T: DeserializeOwned + IpcResponse + Send + Sync + 'static,
+{
+    let forwarder = Channel::<serde_json::Value>::new(move |body| {
+        if let InvokeResponseBody::Json(json) = body
+            && let Ok(payload) = serde_json::from_str::<T>(&json)
+        {
+            handler.send(payload)?;
+        }
+        Ok(())
+    });
+    listen(event, forwarder)
+}
+
+/// [`Stream`] of one event's payloads, from [`subscribe`]. The listener is
+/// removed when the stream is dropped.
+#[must_use = "streams do nothing unless polled, and the listener is removed when dropped"]
+#[derive(Debug)]
+pub struct EventStream<T> {
+    receiver: mpsc::UnboundedReceiver<T>,
+    _handle: ListenerHandle,
+}
+
+impl<T> Stream for EventStream<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.get_mut().receiver.poll_recv(cx)
+    }
+}
+
+/// Like [`observe`], but yields the payloads as a [`Stream`]. The queue is
+/// unbounded so a slow consumer never loses a transaction; payloads that
+/// don't deserialize into `T` are dropped.
+pub fn subscribe<T>(event: &str) -> crate::Result<EventStream<T>>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let forwarder = Channel::<serde_json::Value>::new(move |body| {
+        if let InvokeResponseBody::Json(json) = body
+            && let Ok(payload) = serde_json::from_str::<T>(&json)
+        {
+            // The stream was dropped; its handle removes this listener.
+            let _ = sender.send(payload);
+        }
+        Ok(())
+    });
+    Ok(EventStream {
+        receiver,
+        _handle: listen(event, forwarder)?,
+    })
+}
+
+fn listen(event: &str, forwarder: Channel<serde_json::Value>) -> crate::Result<ListenerHandle> {
+    let channel_id = forwarder.id();
+    register_listener(event.to_string(), forwarder)?;
+    Ok(ListenerHandle {
+        event: event.to_string(),
+        channel_id,
+    })
+}
+
 /// Remove a previously registered listener by event name and channel ID.
 // Tauri commands require owned/deserializable types for args, so `event` must be
 // `String` even though the body only borrows it.
@@ -92,23 +346,415 @@ pub fn register_listener(
 #[tauri::command]
 pub fn remove_listener(event: String, channel_id: u32) -> crate::Result<()> {
     let listeners = LISTENERS.get().ok_or_else(|| {
-        crate::Error::from(PluginInvokeError::InvokeRejected(ErrorResponse {
-            code: None,
-            message: Some("Listeners not initialized".to_string()),
-            data: (),
-        }))
+        crate::Error::from(PluginInvokeError::InvokeRejected(ErrorResponse::new(
+            None,
+            "Listeners not initialized",
+        )))
     })?;
-    {
-        let mut guard = listeners.write().map_err(|e| {
-            crate::Error::from(PluginInvokeError::InvokeRejected(ErrorResponse {
-                code: None,
-                message: Some(format!("Failed to acquire write lock: {e}")),
-                data: (),
-            }))
-        })?;
-        if let Some(channels) = guard.get_mut(&event) {
-            channels.remove(&channel_id);
-        }
+    if let Some(channels) = listeners.get(&event) {
+        channels.remove(&channel_id);
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    type Received = Arc<Mutex<Vec<serde_json::Value>>>;
+
+    fn recording_channel() -> (Channel<serde_json::Value>, Received) {
+        let received = Received::default();
+        let sink = Arc::clone(&received);
+        let channel = Channel::new(move |body| {
+            if let InvokeResponseBody::Json(json) = body {
+                let value = serde_json::from_str(&json).expect("Channel body must be JSON");
+                sink.lock().expect("Lock poisoned").push(value);
+            }
+            Ok(())
+        });
+        (channel, received)
+    }
+
+    #[test]
+    fn test_trigger_dispatches_storefront_changed() {
+        init(crate::DEFAULT_LISTENER_BUFFER_SIZE);
+        let (channel, received) = recording_channel();
+        let channel_id = channel.id();
+        register_listener(STOREFRONT_CHANGED_EVENT.to_string(), channel)
+            .expect("Failed to register listener");
+        let (other, other_received) = recording_channel();
+        let other_id = other.id();
+        register_listener(TRANSACTION_UPDATED_EVENT.to_string(), other)
+            .expect("Failed to register listener");
+
+        trigger(
+            STOREFRONT_CHANGED_EVENT,
+            r#"{"id":"143441","countryCode":"USA"}"#,
+        )
+        .expect("Failed to trigger storefrontChanged");
+
+        assert_eq!(
+            *received.lock().expect("Lock poisoned"),
+            vec![serde_json::json!({"id": "143441", "countryCode": "USA"})]
+        );
+        assert!(other_received.lock().expect("Lock poisoned").is_empty());
+
+        remove_listener(STOREFRONT_CHANGED_EVENT.to_string(), channel_id)
+            .expect("Failed to remove listener");
+        remove_listener(TRANSACTION_UPDATED_EVENT.to_string(), other_id)
+            .expect("Failed to remove listener");
+    }
+
+    #[test]
+    fn test_trigger_dispatches_purchase_revoked() {
+        init(crate::DEFAULT_LISTENER_BUFFER_SIZE);
+        let (channel, received) = recording_channel();
+        let channel_id = channel.id();
+        register_listener(PURCHASE_REVOKED_EVENT.to_string(), channel)
+            .expect("Failed to register listener");
+
+        trigger(
+            PURCHASE_REVOKED_EVENT,
+            r#"{"productId":"premium","transactionId":"2000000123","revocationTime":1700000500000,"revocationReason":"other"}"#,
+        )
+        .expect("Failed to trigger purchaseRevoked");
+
+        assert_eq!(
+            *received.lock().expect("Lock poisoned"),
+            vec![serde_json::json!({
+                "productId": "premium",
+                "transactionId": "2000000123",
+                "revocationTime": "2023-11-14T22:21:40.000Z",
+                "revocationReason": "other",
+            })]
+        );
+        assert!(trigger(PURCHASE_REVOKED_EVENT, r#"{"productId":"premium"}"#).is_err());
+
+        remove_listener(PURCHASE_REVOKED_EVENT.to_string(), channel_id)
+            .expect("Failed to remove listener");
+    }
+
+    #[test]
+    fn test_trigger_dispatches_price_increase_pending() {
+        init(crate::DEFAULT_LISTENER_BUFFER_SIZE);
+        let (channel, received) = recording_channel();
+        let channel_id = channel.id();
+        register_listener(PRICE_INCREASE_PENDING_EVENT.to_string(), channel)
+            .expect("Failed to register listener");
+
+        trigger(
+            PRICE_INCREASE_PENDING_EVENT,
+            r#"{"productId":"premium","originalTransactionId":"1000000001"}"#,
+        )
+        .expect("Failed to trigger priceIncreasePending");
+
+        assert_eq!(
+            *received.lock().expect("Lock poisoned"),
+            vec![serde_json::json!({
+                "productId": "premium",
+                "originalTransactionId": "1000000001",
+            })]
+        );
+        assert!(trigger(PRICE_INCREASE_PENDING_EVENT, r#"{"productId":"premium"}"#).is_err());
+
+        remove_listener(PRICE_INCREASE_PENDING_EVENT.to_string(), channel_id)
+            .expect("Failed to remove listener");
+    }
+
+    #[test]
+    fn test_trigger_dispatches_renewal_preference_changed() {
+        init(crate::DEFAULT_LISTENER_BUFFER_SIZE);
+        let (channel, received) = recording_channel();
+        let channel_id = channel.id();
+        register_listener(RENEWAL_PREFERENCE_CHANGED_EVENT.to_string(), channel)
+            .expect("Failed to register listener");
+
+        trigger(
+            RENEWAL_PREFERENCE_CHANGED_EVENT,
+            r#"{"productId":"premium","originalTransactionId":"1000000001","autoRenewProductId":"basic","willAutoRenew":true}"#,
+        )
+        .expect("Failed to trigger renewalPreferenceChanged");
+
+        assert_eq!(
+            *received.lock().expect("Lock poisoned"),
+            vec![serde_json::json!({
+                "productId": "premium",
+                "originalTransactionId": "1000000001",
+                "autoRenewProductId": "basic",
+                "willAutoRenew": true,
+            })]
+        );
+        assert!(
+            trigger(
+                RENEWAL_PREFERENCE_CHANGED_EVENT,
+                r#"{"productId":"premium","originalTransactionId":"1000000001"}"#
+            )
+            .is_err()
+        );
+
+        remove_listener(RENEWAL_PREFERENCE_CHANGED_EVENT.to_string(), channel_id)
+            .expect("Failed to remove listener");
+    }
+
+    #[test]
+    fn test_trigger_dispatches_billing_issue_detected() {
+        init(crate::DEFAULT_LISTENER_BUFFER_SIZE);
+        let (channel, received) = recording_channel();
+        let channel_id = channel.id();
+        register_listener(BILLING_ISSUE_DETECTED_EVENT.to_string(), channel)
+            .expect("Failed to register listener");
+
+        trigger(
+            BILLING_ISSUE_DETECTED_EVENT,
+            r#"{"productId":"premium","originalTransactionId":"1000000001","state":"inBillingRetry"}"#,
+        )
+        .expect("Failed to trigger billingIssueDetected");
+
+        assert_eq!(
+            *received.lock().expect("Lock poisoned"),
+            vec![serde_json::json!({
+                "productId": "premium",
+                "originalTransactionId": "1000000001",
+                "state": "inBillingRetry",
+            })]
+        );
+        assert!(trigger(BILLING_ISSUE_DETECTED_EVENT, r#"{"productId":"premium"}"#).is_err());
+
+        remove_listener(BILLING_ISSUE_DETECTED_EVENT.to_string(), channel_id)
+            .expect("Failed to remove listener");
+    }
+
+    #[test]
+    fn test_trigger_dispatches_purchase_completed() {
+        init(crate::DEFAULT_LISTENER_BUFFER_SIZE);
+        let (channel, received) = recording_channel();
+        let channel_id = channel.id();
+        register_listener(PURCHASE_COMPLETED_EVENT.to_string(), channel)
+            .expect("Failed to register listener");
+
+        trigger(
+            PURCHASE_COMPLETED_EVENT,
+            r#"{"orderId":"2000000123","packageName":"com.example.app","productId":"premium","purchaseTime":1700000000000,"purchaseToken":"2000000123","purchaseState":0,"isAutoRenewing":false,"isAcknowledged":true,"originalJson":"","signature":"","originalId":"2000000123"}"#,
+        )
+        .expect("Failed to trigger purchaseCompleted");
+
+        let received = received.lock().expect("Lock poisoned");
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0]["productId"], "premium");
+        assert_eq!(received[0]["purchaseState"], 0);
+        drop(received);
+        assert!(trigger(PURCHASE_COMPLETED_EVENT, r#"{"productId":"premium"}"#).is_err());
+
+        remove_listener(PURCHASE_COMPLETED_EVENT.to_string(), channel_id)
+            .expect("Failed to remove listener");
+    }
+
+    #[test]
+    fn test_observe_delivers_typed_events_until_dropped() {
+        init(crate::DEFAULT_LISTENER_BUFFER_SIZE);
+        let received = Arc::new(Mutex::new(Vec::<TransactionEvent>::new()));
+        let sink = Arc::clone(&received);
+        let handler = Channel::<TransactionEvent>::new(move |body| {
+            if let InvokeResponseBody::Json(json) = body {
+                let event = serde_json::from_str(&json).expect("Channel body must be JSON");
+                sink.lock().expect("Lock poisoned").push(event);
+            }
+            Ok(())
+        });
+        let payload =
+            r#"{"productId":"premium","state":{"type":"purchasing"},"timestamp":1700000000000}"#;
+        // A private event name keeps the other tests' transactionUpdated
+        // listeners out of the way.
+        let event = "observeTest";
+
+        let handle = observe(event, handler).expect("Failed to register observer");
+        trigger(event, payload).expect("Failed to trigger event");
+        drop(handle);
+        trigger(event, payload).expect("Failed to trigger event");
+
+        let received = received.lock().expect("Lock poisoned");
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].product_id, "premium");
+    }
+
+    #[test]
+    fn test_subscribe_streams_typed_events_until_dropped() {
+        use futures_util::StreamExt;
+
+        init(crate::DEFAULT_LISTENER_BUFFER_SIZE);
+        let event = "subscribeTest";
+        let mut stream =
+            subscribe::<TransactionEvent>(event).expect("Failed to subscribe to event");
+
+        trigger(
+            event,
+            r#"{"productId":"premium","state":{"type":"purchasing"},"timestamp":1700000000000}"#,
+        )
+        .expect("Failed to trigger event");
+        trigger(event, r#"{"unrelated":true}"#).expect("Failed to trigger event");
+        trigger(
+            event,
+            r#"{"productId":"coins","state":{"type":"purchasing"},"timestamp":1700000000001}"#,
+        )
+        .expect("Failed to trigger event");
+
+        let first = tauri::async_runtime::block_on(stream.next()).expect("Stream ended");
+        let second = tauri::async_runtime::block_on(stream.next()).expect("Stream ended");
+        assert_eq!(first.product_id, "premium");
+        assert_eq!(second.product_id, "coins");
+
+        drop(stream);
+        let registered = LISTENERS
+            .get()
+            .expect("Listeners not initialized")
+            .get(event)
+            .is_some_and(|channels| !channels.is_empty());
+        assert!(!registered);
+    }
+
+    #[test]
+    fn test_handler_can_remove_its_own_listener() {
+        init(crate::DEFAULT_LISTENER_BUFFER_SIZE);
+        let event = "reentrantTest";
+        let channel_id = Arc::new(OnceLock::new());
+        let deliveries = Arc::new(Mutex::new(0));
+        let (own_id, count) = (Arc::clone(&channel_id), Arc::clone(&deliveries));
+        let channel = Channel::<serde_json::Value>::new(move |_| {
+            *count.lock().expect("Lock poisoned") += 1;
+            if let Some(id) = own_id.get() {
+                remove_listener(event.to_string(), *id).expect("Failed to remove listener");
+            }
+            Ok(())
+        });
+        channel_id
+            .set(channel.id())
+            .expect("Channel id already set");
+        register_listener(event.to_string(), channel).expect("Failed to register listener");
+
+        trigger(event, "{}").expect("Failed to trigger event");
+        trigger(event, "{}").expect("Failed to trigger event");
+
+        assert_eq!(*deliveries.lock().expect("Lock poisoned"), 1);
+    }
+
+    #[test]
+    fn test_one_shot_listener_removes_itself_after_first_event() {
+        init(crate::DEFAULT_LISTENER_BUFFER_SIZE);
+        let event = "oneShotTest";
+        let (channel, received) = recording_channel();
+
+        let channel_id = register_one_shot_listener(event.to_string(), channel)
+            .expect("Failed to register one-shot listener");
+        trigger(event, r#"{"n":1}"#).expect("Failed to trigger event");
+
+        let registered = LISTENERS
+            .get()
+            .expect("Listeners not initialized")
+            .get(event)
+            .is_some_and(|channels| channels.contains_key(&channel_id));
+        assert!(!registered);
+
+        trigger(event, r#"{"n":2}"#).expect("Failed to trigger event");
+        assert_eq!(
+            *received.lock().expect("Lock poisoned"),
+            vec![serde_json::json!({"n": 1})]
+        );
+    }
+
+    #[test]
+    fn test_trigger_before_registration_is_flushed_to_first_listener() {
+        init(crate::DEFAULT_LISTENER_BUFFER_SIZE);
+        let event = "bufferedTest";
+
+        trigger(event, r#"{"n":1}"#).expect("Failed to trigger event");
+        trigger(event, r#"{"n":2}"#).expect("Failed to trigger event");
+
+        let (first, first_received) = recording_channel();
+        register_listener(event.to_string(), first).expect("Failed to register listener");
+        assert_eq!(
+            *first_received.lock().expect("Lock poisoned"),
+            vec![serde_json::json!({"n": 1}), serde_json::json!({"n": 2})]
+        );
+
+        // The buffer was handed over; a later listener only gets new events.
+        let (second, second_received) = recording_channel();
+        register_listener(event.to_string(), second).expect("Failed to register listener");
+        assert!(second_received.lock().expect("Lock poisoned").is_empty());
+        trigger(event, r#"{"n":3}"#).expect("Failed to trigger event");
+        assert_eq!(
+            *second_received.lock().expect("Lock poisoned"),
+            vec![serde_json::json!({"n": 3})]
+        );
+    }
+
+    #[test]
+    fn test_trigger_after_last_listener_left_is_not_buffered() {
+        init(crate::DEFAULT_LISTENER_BUFFER_SIZE);
+        let event = "unbufferedAfterListenTest";
+
+        let (first, _) = recording_channel();
+        let first_id = first.id();
+        register_listener(event.to_string(), first).expect("Failed to register listener");
+        remove_listener(event.to_string(), first_id).expect("Failed to remove listener");
+
+        // Nobody listens any more, but a listener already has, so this is
+        // dropped rather than replayed to the next one.
+        trigger(event, r#"{"n":1}"#).expect("Failed to trigger event");
+        let (second, second_received) = recording_channel();
+        register_listener(event.to_string(), second).expect("Failed to register listener");
+        assert!(second_received.lock().expect("Lock poisoned").is_empty());
+    }
+
+    #[test]
+    fn test_one_shot_listener_takes_the_oldest_buffered_payload() {
+        init(crate::DEFAULT_LISTENER_BUFFER_SIZE);
+        let event = "bufferedOneShotTest";
+
+        trigger(event, r#"{"n":1}"#).expect("Failed to trigger event");
+        trigger(event, r#"{"n":2}"#).expect("Failed to trigger event");
+
+        let (once, once_received) = recording_channel();
+        let channel_id = register_one_shot_listener(event.to_string(), once)
+            .expect("Failed to register one-shot listener");
+        assert_eq!(
+            *once_received.lock().expect("Lock poisoned"),
+            vec![serde_json::json!({"n": 1})]
+        );
+        let registered = LISTENERS
+            .get()
+            .expect("Listeners not initialized")
+            .get(event)
+            .is_some_and(|channels| channels.contains_key(&channel_id));
+        assert!(!registered);
+
+        let (rest, rest_received) = recording_channel();
+        register_listener(event.to_string(), rest).expect("Failed to register listener");
+        assert_eq!(
+            *rest_received.lock().expect("Lock poisoned"),
+            vec![serde_json::json!({"n": 2})]
+        );
+    }
+
+    #[test]
+    fn test_push_bounded_keeps_the_newest_payloads() {
+        let mut buffer = VecDeque::new();
+        for n in 0..3 {
+            push_bounded(&mut buffer, serde_json::json!({ "n": n }), 2);
+        }
+        assert_eq!(
+            buffer,
+            [serde_json::json!({"n": 1}), serde_json::json!({"n": 2})]
+        );
+
+        push_bounded(&mut buffer, serde_json::json!({"n": 3}), 0);
+        assert_eq!(buffer.len(), 2);
+    }
+
+    #[test]
+    fn test_trigger_rejects_malformed_storefront_payload() {
+        init(crate::DEFAULT_LISTENER_BUFFER_SIZE);
+        assert!(trigger(STOREFRONT_CHANGED_EVENT, r#"{"countryCode":"USA"}"#).is_err());
+    }
+}
@@ -1,10 +1,29 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+
 use serde::de::DeserializeOwned;
-use tauri::{AppHandle, Runtime, plugin::PluginApi};
+use tauri::{AppHandle, Manager, Runtime, ipc::Channel, plugin::PluginApi};
 
+use crate::cache::{ProductStatusCache, ProductsCache};
+use crate::entitlement_cache::{self, EntitlementCache};
+use crate::listeners::{EventStream, ListenerHandle};
 use crate::models::{
-    GetProductsResponse, ProductStatus, Purchase, PurchaseRequest, RestorePurchasesRequest,
-    RestorePurchasesResponse,
+    AppTransactionInfo, BeginRefundRequestResponse, CanMakePaymentsResponse,
+    CheckIntroductoryOfferEligibilityResponse, CurrentEntitlementResponse,
+    ExternalPurchaseLinkAvailability, FinishUnfinishedTransactionsResponse,
+    GetAllProductStatusesResponse, GetAppReceiptResponse, GetCurrentEntitlementsResponse,
+    GetEnvironmentResponse, GetProductsResponse, GetPurchaseHistoryResponse,
+    GetRevokedPurchasesResponse, GetSubscriptionStatusResponse, GetUnfinishedTransactionsResponse,
+    GetWinBackOffersResponse, IapDiagnostics, IapEnvironment, IsEligibleForIntroOfferResponse,
+    IsSupportedResponse, ProductStatus, ProductType, Purchase, PurchaseHistoryRequest,
+    PurchaseReceipt, PurchaseRequest, RefundRequestResult, RefundRequestStatus,
+    RestorePurchasesRequest, RestorePurchasesResponse, SigningInfo, StorefrontInfo,
+    TRANSACTION_UPDATED_EVENT, TransactionEvent,
 };
+use crate::retry::retry_with_backoff;
+use crate::trace::LogSuccess;
+use crate::{IapConfig, Timestamp};
 
 /// Validation checks for macOS IAP functionality.
 ///
@@ -12,27 +31,161 @@ use crate::models::{
 /// with the App Store. During development with `tauri dev`, the binary runs
 /// directly without a bundle, causing `StoreKit` calls to fail silently or crash.
 mod validation {
-    /// Ensures the app is running from a .app bundle.
+    use std::path::Path;
+    use std::sync::{Once, OnceLock};
+
+    /// Runs [`require_bundle`] when `required`, as resolved by
+    /// [`IapConfig::requires_app_bundle`](crate::IapConfig::requires_app_bundle).
+    /// Skipping it logs a warning the first time.
+    pub fn check_bundle(required: bool) -> crate::Result<()> {
+        static WARNED: Once = Once::new();
+
+        if required {
+            return require_bundle();
+        }
+        WARNED.call_once(|| {
+            log::warn!(
+                "IAP is not checking for a .app bundle; StoreKit calls may fail outside one. \
+                 Set plugins.iap.requireAppBundle to enforce the check."
+            );
+        });
+        Ok(())
+    }
+
+    /// Ensures the app is running from a .app bundle. The executable can't
+    /// move mid-process, so the outcome is computed on the first call and
+    /// reused; a cached failure keeps its original message.
     pub fn require_bundle() -> crate::Result<()> {
-        std::env::current_exe()
-            .ok()
-            .and_then(|exe| {
-                let macos = exe.parent()?;
-                let contents = macos.parent()?;
-                let bundle = contents.parent()?;
-                (macos.ends_with("MacOS")
-                    && contents.ends_with("Contents")
-                    && bundle.to_string_lossy().ends_with(".app"))
-                .then_some(())
-            })
-            .ok_or_else(|| {
-                crate::error::PluginInvokeError::InvokeRejected(crate::error::ErrorResponse {
-                    code: None,
-                    message: Some("IAP requires the app to run from a .app bundle.".to_string()),
-                    data: (),
-                })
-                .into()
-            })
+        static BUNDLE_ERROR: OnceLock<Option<String>> = OnceLock::new();
+
+        match BUNDLE_ERROR.get_or_init(|| bundle_error(std::env::current_exe().ok().as_deref())) {
+            None => Ok(()),
+            Some(message) => Err(crate::error::PluginInvokeError::InvokeRejected(
+                crate::error::ErrorResponse::new(None, message.clone()),
+            )
+            .into()),
+        }
+    }
+
+    /// Uncached check behind [`require_bundle`]: `None` when `exe` is
+    /// bundled, otherwise the message to reject with.
+    pub fn bundle_error(exe: Option<&Path>) -> Option<String> {
+        if exe.is_some_and(is_bundled_executable) {
+            None
+        } else {
+            Some(bundle_error_message(exe))
+        }
+    }
+
+    /// Whether `exe` sits at `<name>.app/Contents/MacOS/<binary>`.
+    pub fn is_bundled_executable(exe: &Path) -> bool {
+        let Some(macos) = exe.parent() else {
+            return false;
+        };
+        let Some(contents) = macos.parent() else {
+            return false;
+        };
+        let Some(bundle) = contents.parent() else {
+            return false;
+        };
+        macos.ends_with("MacOS")
+            && contents.ends_with("Contents")
+            && bundle.extension().is_some_and(|ext| ext == "app")
+    }
+
+    /// Says where the app is running from and how to fix it, so bug reports
+    /// from unbundled builds are self-explanatory.
+    pub fn bundle_error_message(exe: Option<&Path>) -> String {
+        let location = exe.map_or_else(
+            || "an unknown location".to_string(),
+            |exe| format!("`{}`", exe.display()),
+        );
+        format!(
+            "IAP requires the app to run from a .app bundle, but it is running from {location}. \
+             Run the bundle produced by `tauri build`, or set plugins.iap.requireAppBundle to \
+             false during development."
+        )
+    }
+
+    /// Oldest macOS the Swift package targets. `StoreKit` 2 itself ships with
+    /// macOS 12, but `AppTransaction` and `Transaction.environment` need 13.
+    pub const MIN_MACOS_VERSION: (u32, u32) = (13, 0);
+
+    /// Rejects with `storeKitUnavailable` when the running macOS is older than
+    /// [`MIN_MACOS_VERSION`]. The OS can't change mid-process, so the
+    /// outcome is computed once.
+    pub fn check_storekit() -> crate::Result<()> {
+        match storekit_error() {
+            None => Ok(()),
+            Some(message) => Err(crate::error::PluginInvokeError::InvokeRejected(
+                crate::error::ErrorResponse::new(Some("storeKitUnavailable"), message.clone()),
+            )
+            .into()),
+        }
+    }
+
+    /// Cached [`storekit_error_message`] for the running OS.
+    pub fn storekit_error() -> Option<&'static String> {
+        static STOREKIT_ERROR: OnceLock<Option<String>> = OnceLock::new();
+
+        STOREKIT_ERROR
+            .get_or_init(|| storekit_error_message(os_product_version().as_deref()))
+            .as_ref()
+    }
+
+    /// `None` when `version` meets [`MIN_MACOS_VERSION`]. An unreadable
+    /// version is let through rather than blocking every store call.
+    pub fn storekit_error_message(version: Option<&str>) -> Option<String> {
+        let version = version?;
+        if parse_os_version(version)? >= MIN_MACOS_VERSION {
+            return None;
+        }
+        let (major, minor) = MIN_MACOS_VERSION;
+        Some(format!(
+            "StoreKit 2 requires macOS {major}.{minor} or later, but this Mac runs macOS {version}."
+        ))
+    }
+
+    /// `"12.7.4"` -> `(12, 7)`. A missing minor version reads as 0.
+    pub fn parse_os_version(version: &str) -> Option<(u32, u32)> {
+        let mut parts = version.trim().split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next().map_or(Some(0), |minor| minor.parse().ok())?;
+        Some((major, minor))
+    }
+
+    /// `sysctl kern.osproductversion`, e.g. `"14.4.1"`.
+    fn os_product_version() -> Option<String> {
+        use std::ffi::{c_char, c_int, c_void};
+
+        unsafe extern "C" {
+            fn sysctlbyname(
+                name: *const c_char,
+                oldp: *mut c_void,
+                oldlenp: *mut usize,
+                newp: *mut c_void,
+                newlen: usize,
+            ) -> c_int;
+        }
+
+        let mut buf = [0u8; 32];
+        let mut len = buf.len();
+        // SAFETY: the name is NUL-terminated and `len` is the buffer's size,
+        // which the kernel never writes past.
+        let status = unsafe {
+            sysctlbyname(
+                c"kern.osproductversion".as_ptr(),
+                buf.as_mut_ptr().cast(),
+                &raw mut len,
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        if status != 0 {
+            return None;
+        }
+        let version = buf.get(..len)?.split(|&b| b == 0).next()?;
+        String::from_utf8(version.to_vec()).ok()
     }
 }
 
@@ -50,7 +203,10 @@ mod ffi {
         #[swift_bridge(Sendable)]
         type IapPlugin;
         #[swift_bridge(init, swift_name = "initPlugin")]
-        fn init_plugin() -> IapPlugin;
+        fn init_plugin(
+            allowUnverifiedTransactions: bool,
+            autoFinishTransactionUpdates: bool,
+        ) -> IapPlugin;
 
         async fn getProducts(
             &self,
@@ -62,13 +218,56 @@ mod ffi {
             productId: String,
             productType: String,
             offerToken: Option<String>,
+            quantity: u32,
+            autoFinish: bool,
+            promotionalOffer: Option<String>,
+            winBackOfferId: Option<String>,
+            appAccountToken: Option<String>,
+        ) -> Result<String, FFIResult>;
+        async fn restorePurchases(
+            &self,
+            productType: String,
+            forceSync: bool,
+        ) -> Result<String, FFIResult>;
+        async fn getPurchaseHistory(&self) -> Result<String, FFIResult>;
+        async fn getPurchaseHistoryFiltered(
+            &self,
+            productType: Option<String>,
+            sinceTimestamp: Option<u64>,
+            limit: Option<u32>,
         ) -> Result<String, FFIResult>;
-        async fn restorePurchases(&self, productType: String) -> Result<String, FFIResult>;
         async fn getProductStatus(
             &self,
             productId: String,
             productType: String,
         ) -> Result<String, FFIResult>;
+        async fn getAllProductStatuses(&self, productType: String) -> Result<String, FFIResult>;
+        async fn canOpenExternalPurchaseLink(&self) -> Result<String, FFIResult>;
+        async fn openExternalPurchaseLink(&self) -> Result<String, FFIResult>;
+        async fn currentEntitlement(&self, productId: String) -> Result<String, FFIResult>;
+        async fn getAppReceipt(&self, refreshIfMissing: bool) -> Result<String, FFIResult>;
+        async fn getRevokedPurchases(&self, sinceMillis: Option<i64>) -> Result<String, FFIResult>;
+        async fn finishUnfinishedTransactions(&self) -> Result<String, FFIResult>;
+        async fn finishTransaction(&self, transactionId: String) -> Result<String, FFIResult>;
+        async fn getAppTransaction(&self, forceRefresh: bool) -> Result<String, FFIResult>;
+        async fn getCurrentEntitlements(&self) -> Result<String, FFIResult>;
+        async fn getSubscriptionStatus(&self, productId: String) -> Result<String, FFIResult>;
+        async fn showManageSubscriptions(&self) -> Result<String, FFIResult>;
+        async fn presentOfferCodeRedemptionSheet(&self) -> Result<String, FFIResult>;
+        async fn getStorefront(&self) -> Result<String, FFIResult>;
+        async fn getUnfinishedTransactions(&self) -> Result<String, FFIResult>;
+        async fn beginRefundRequest(&self, transactionId: String) -> Result<String, FFIResult>;
+        async fn getWinBackOffers(&self, productId: String) -> Result<String, FFIResult>;
+        async fn isEligibleForIntroOffer(&self, productId: String) -> Result<String, FFIResult>;
+        async fn getEnvironment(&self) -> Result<String, FFIResult>;
+        async fn getReceipt(&self, transactionId: String) -> Result<String, FFIResult>;
+        async fn refreshReceipt(&self) -> Result<String, FFIResult>;
+        async fn canMakePayments(&self) -> Result<String, FFIResult>;
+        async fn checkIntroductoryOfferEligibility(
+            &self,
+            productIds: Vec<String>,
+        ) -> Result<String, FFIResult>;
+        async fn getSigningInfo(&self) -> Result<String, FFIResult>;
     }
 }
 
@@ -84,37 +283,93 @@ impl ParseFfiResponse for Result<String, ffi::FFIResult> {
         match self {
             Ok(json) => serde_json::from_str(&json)
                 .map_err(|e| crate::error::PluginInvokeError::CannotDeserializeResponse(e).into()),
+            // Swift sends errors that carry a stable code as a JSON-encoded
+            // `ErrorResponse`; anything else is a bare message.
             Err(ffi::FFIResult::Err(msg)) => Err(crate::error::PluginInvokeError::InvokeRejected(
-                crate::error::ErrorResponse {
-                    code: None,
-                    message: Some(msg),
-                    data: (),
-                },
+                serde_json::from_str::<crate::error::ErrorResponse>(&msg).map_or_else(
+                    |_| crate::error::ErrorResponse::new(None, msg.clone()),
+                    crate::error::ErrorResponse::with_error_code,
+                ),
             )
             .into()),
         }
     }
 }
 
+/// Awaits an FFI call, failing with [`crate::Error::Timeout`] once `limit`
+/// has passed. The Swift task keeps running; only the wait is abandoned.
+async fn with_timeout<T>(
+    limit: Option<Duration>,
+    call: impl Future<Output = T>,
+) -> crate::Result<T> {
+    match limit {
+        Some(limit) => tokio::time::timeout(limit, call)
+            .await
+            .map_err(|_| crate::Error::Timeout(limit)),
+        None => Ok(call.await),
+    }
+}
+
 // Signature matches the swift-bridge `extern "Rust"` declaration above, which
 // requires `String` (bridge ABI) — `&str` would change the FFI binding.
 /// Called by Swift via FFI when transaction updates occur.
 #[allow(clippy::needless_pass_by_value)]
 fn trigger(event: String, payload: String) -> Result<(), ffi::FFIResult> {
+    if event == TRANSACTION_UPDATED_EVENT
+        && let Some(cache) = PRODUCT_STATUS_CACHE.get()
+        && let Ok(update) = serde_json::from_str::<TransactionEvent>(&payload)
+    {
+        cache.invalidate(&update.product_id);
+    }
     crate::listeners::trigger(&event, &payload)
         .map_err(|e| ffi::FFIResult::Err(format!("Failed to trigger event '{event}': {e}")))
 }
 
-// `Result` matches the cross-platform `init` signature (mobile genuinely fails);
-// macOS body is infallible today but the contract is shared.
-#[allow(clippy::unnecessary_wraps)]
-pub fn init<R: Runtime, C: DeserializeOwned>(
+/// The [`Iap::product_status_cache`] that [`trigger`] invalidates, which has
+/// no other way to reach the plugin state.
+static PRODUCT_STATUS_CACHE: OnceLock<Arc<ProductStatusCache>> = OnceLock::new();
+
+pub fn init<R: Runtime>(
     app: &AppHandle<R>,
-    _api: &PluginApi<R, C>,
+    api: &PluginApi<R, Option<IapConfig>>,
 ) -> crate::Result<Iap<R>> {
+    let config = api.config().clone().unwrap_or_default();
+    if let Some(message) = validation::storekit_error() {
+        log::warn!("{message} IAP commands will reject with storeKitUnavailable.");
+    }
+    if config.require_app_bundle_at_startup {
+        validation::require_bundle()?;
+    }
+    let product_status_cache = PRODUCT_STATUS_CACHE
+        .get_or_init(|| {
+            Arc::new(ProductStatusCache::new(
+                config.product_status_cache_ttl_secs,
+            ))
+        })
+        .clone();
+    let entitlement_cache = config.offline_entitlement_max_age_secs.and_then(|max_age| {
+        match app.path().app_data_dir() {
+            Ok(dir) => Some(EntitlementCache::load(
+                dir.join(entitlement_cache::FILE_NAME),
+                Duration::from_secs(max_age),
+            )),
+            Err(e) => {
+                log::warn!("Offline entitlement cache disabled, no app data dir: {e}");
+                None
+            }
+        }
+    });
     Ok(Iap {
         _app: app.clone(),
-        plugin: ffi::IapPlugin::init_plugin(),
+        plugin: ffi::IapPlugin::init_plugin(
+            config.allow_unverified_transactions,
+            config.auto_finish_transaction_updates,
+        ),
+        products_cache: ProductsCache::new(config.products_cache_ttl()),
+        config,
+        product_status_cache,
+        entitlement_cache,
+        last_call_durations: Mutex::default(),
     })
 }
 
@@ -122,77 +377,1154 @@ pub fn init<R: Runtime, C: DeserializeOwned>(
 pub struct Iap<R: Runtime> {
     _app: AppHandle<R>,
     plugin: ffi::IapPlugin,
+    config: IapConfig,
+    product_status_cache: Arc<ProductStatusCache>,
+    products_cache: ProductsCache,
+    /// Set when [`IapConfig::offline_entitlement_max_age_secs`] is.
+    entitlement_cache: Option<EntitlementCache>,
+    /// How long the latest call to each Swift method took, for
+    /// [`Iap::get_diagnostics`].
+    last_call_durations: Mutex<HashMap<&'static str, Duration>>,
 }
 
 impl<R: Runtime> Iap<R> {
+    /// Runs before every store call: the OS must ship `StoreKit` 2, then the
+    /// `.app` bundle check applies.
+    fn check_environment(&self) -> crate::Result<()> {
+        validation::check_storekit()?;
+        validation::check_bundle(self.config.requires_app_bundle())
+    }
+
+    /// Awaits the FFI call `method`, recording how long `StoreKit` took. A
+    /// call abandoned by [`with_timeout`] isn't recorded.
+    async fn timed<T>(&self, method: &'static str, call: impl Future<Output = T>) -> T {
+        let start = Instant::now();
+        let output = call.await;
+        let elapsed = start.elapsed();
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            method,
+            elapsed_ms = elapsed.as_secs_f64() * 1000.0,
+            "StoreKit call finished"
+        );
+        self.last_call_durations
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(method, elapsed);
+        output
+    }
+
+    /// How long the most recent call to each `StoreKit` method took, e.g. to
+    /// show in a support screen or report alongside a slow purchase.
+    #[must_use]
+    pub fn get_diagnostics(&self) -> IapDiagnostics {
+        let durations = self
+            .last_call_durations
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        IapDiagnostics {
+            last_call_durations: durations
+                .iter()
+                .map(|(method, elapsed)| ((*method).to_string(), *elapsed))
+                .collect(),
+        }
+    }
+
+    /// The `plugins.iap` options from `tauri.conf.json`, with defaults for
+    /// anything left out.
+    #[must_use]
+    pub const fn config(&self) -> &IapConfig {
+        &self.config
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_products", skip(self), level = "debug", err)
+    )]
     pub async fn get_products(
         &self,
         product_ids: Vec<String>,
-        product_type: String,
+        product_type: ProductType,
     ) -> crate::Result<GetProductsResponse> {
-        validation::require_bundle()?;
+        self.check_environment()?;
+        crate::validation::validate_product_ids(&product_ids)?;
 
-        self.plugin
-            .getProducts(product_ids, product_type)
+        if let Some(response) = self.products_cache.get(&product_ids, product_type) {
+            response.log_success();
+            return Ok(response);
+        }
+        self.query_products(product_ids, product_type)
             .await
-            .parse()
+            .inspect(LogSuccess::log_success)
     }
 
+    /// [`Self::get_products`] without the products cache: always asks
+    /// `StoreKit`, then caches the fresh response.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::refresh_products", skip(self), level = "debug", err)
+    )]
+    pub async fn refresh_products(
+        &self,
+        product_ids: Vec<String>,
+        product_type: ProductType,
+    ) -> crate::Result<GetProductsResponse> {
+        self.check_environment()?;
+        crate::validation::validate_product_ids(&product_ids)?;
+
+        self.query_products(product_ids, product_type)
+            .await
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// Forgets every `get_products` response cached under
+    /// [`IapConfig::products_cache_ttl_secs`]. Called whenever an app
+    /// window gains focus.
+    pub fn clear_products_cache(&self) {
+        self.products_cache.clear();
+    }
+
+    async fn query_products(
+        &self,
+        product_ids: Vec<String>,
+        product_type: ProductType,
+    ) -> crate::Result<GetProductsResponse> {
+        let response: GetProductsResponse =
+            retry_with_backoff(self.config.retry_policy.as_ref(), || async {
+                with_timeout(
+                    self.config.product_query_timeout(),
+                    self.timed(
+                        "getProducts",
+                        self.plugin
+                            .getProducts(product_ids.clone(), product_type.into()),
+                    ),
+                )
+                .await?
+                .parse()
+            })
+            .await?;
+        self.products_cache
+            .insert(&product_ids, product_type, response.clone());
+        Ok(response)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::purchase",
+            skip(self, payload),
+            fields(product_id = %payload.product_id, product_type = %payload.product_type),
+            level = "debug",
+            err
+        )
+    )]
     pub async fn purchase(&self, payload: PurchaseRequest) -> crate::Result<Purchase> {
-        validation::require_bundle()?;
+        self.check_environment()?;
+        crate::validate_product_id(&payload.product_id)?;
 
-        self.plugin
-            .purchase(
+        if let Some(opts) = &payload.options {
+            opts.validate(payload.product_type)?;
+        }
+
+        let (
+            offer_token,
+            quantity,
+            auto_finish,
+            promotional_offer,
+            win_back_offer_id,
+            app_account_token,
+        ) = payload
+            .options
+            .map_or((None, 1, true, None, None, None), |opts| {
+                (
+                    opts.offer_token,
+                    opts.quantity.unwrap_or(1),
+                    opts.auto_finish.unwrap_or(true),
+                    opts.promotional_offer,
+                    opts.win_back_offer_id,
+                    opts.app_account_token,
+                )
+            });
+        // Crosses the bridge as JSON rather than five more parameters
+        let promotional_offer = promotional_offer
+            .map(|offer| serde_json::to_string(&offer))
+            .transpose()
+            .map_err(|e| {
+                crate::Error::from(crate::error::PluginInvokeError::CannotSerializePayload(e))
+            })?;
+
+        // Never timed out or retried: abandoning the wait wouldn't cancel the
+        // Swift task, and a failed attempt may already have charged the user,
+        // so either could lead to a second charge.
+        self.timed(
+            "purchase",
+            self.plugin.purchase(
                 payload.product_id,
-                payload.product_type,
-                payload.options.and_then(|opts| opts.offer_token),
-            )
-            .await
-            .parse()
+                payload.product_type.into(),
+                offer_token,
+                quantity,
+                auto_finish,
+                promotional_offer,
+                win_back_offer_id,
+                app_account_token,
+            ),
+        )
+        .await
+        .parse()
+        .inspect(LogSuccess::log_success)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::restore_purchases",
+            skip(self, request),
+            fields(product_type = %request.product_type, force_sync = request.force_sync),
+            level = "debug",
+            err
+        )
+    )]
     pub async fn restore_purchases(
         &self,
         request: RestorePurchasesRequest,
     ) -> crate::Result<RestorePurchasesResponse> {
-        validation::require_bundle()?;
+        self.check_environment()?;
 
-        // The Microsoft-only fields on `request` are ignored here;
-        // macOS gets only the cross-platform `product_type`.
-        self.plugin
-            .restorePurchases(request.product_type)
+        // A retry would run `AppStore.sync()` again, and with it the App Store
+        // sign-in prompt, so a forced sync is only attempted once.
+        let retry_policy = if request.force_sync {
+            None
+        } else {
+            self.config.retry_policy.as_ref()
+        };
+        // The Microsoft-only fields on `request` are ignored here.
+        let response: RestorePurchasesResponse = retry_with_backoff(retry_policy, || async {
+            self.timed(
+                "restorePurchases",
+                self.plugin
+                    .restorePurchases(request.product_type.into(), request.force_sync),
+            )
             .await
             .parse()
+        })
+        .await?;
+        if let Some(cache) = &self.entitlement_cache {
+            let now = entitlement_cache::now();
+            for purchase in &response.purchases {
+                cache.record(&purchase.product_id, purchase.expiration_date, now);
+            }
+        }
+        response.log_success();
+        Ok(response)
     }
 
-    /// No-op: macOS finishes transactions inside `purchase()` itself,
-    /// so there is nothing left to acknowledge here.
+    /// Every transaction from `Transaction.all`, including expired
+    /// subscriptions and finished consumables.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_purchase_history", skip(self), level = "debug", err)
+    )]
+    pub async fn get_purchase_history(&self) -> crate::Result<GetPurchaseHistoryResponse> {
+        self.check_environment()?;
+
+        self.timed("getPurchaseHistory", self.plugin.getPurchaseHistory())
+            .await
+            .parse()
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// [`Self::get_purchase_history`] filtered while iterating
+    /// `Transaction.all`, newest first.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_purchase_history_filtered",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_purchase_history_filtered(
+        &self,
+        request: PurchaseHistoryRequest,
+    ) -> crate::Result<GetPurchaseHistoryResponse> {
+        self.check_environment()?;
+
+        self.timed(
+            "getPurchaseHistoryFiltered",
+            self.plugin.getPurchaseHistoryFiltered(
+                request.product_type.map(String::from),
+                request.since_timestamp,
+                request.limit,
+            ),
+        )
+        .await
+        .parse()
+        .inspect(LogSuccess::log_success)
+    }
+
+    /// No-op: macOS finishes transactions inside `purchase()` itself (or via
+    /// `finish_transaction` when `auto_finish` is off), so there is nothing
+    /// left to acknowledge here.
     // `async` matches the cross-platform `Iap` contract — `commands.rs` `.await`s
     // this on every platform, including ones that genuinely yield (Android).
     #[allow(clippy::unused_async)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::acknowledge_purchase",
+            skip(self, _purchase_token),
+            level = "debug",
+            err
+        )
+    )]
     pub async fn acknowledge_purchase(&self, _purchase_token: String) -> crate::Result<()> {
-        validation::require_bundle()?;
+        self.check_environment()?;
+        ().log_success();
         Ok(())
     }
 
     /// No-op: macOS finishes transactions inside `purchase()` itself,
     /// and `StoreKit` auto-allows re-purchase of consumables once finished.
     #[allow(clippy::unused_async)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::consume_purchase",
+            skip(self, _purchase_token),
+            level = "debug",
+            err
+        )
+    )]
     pub async fn consume_purchase(&self, _purchase_token: String) -> crate::Result<()> {
-        validation::require_bundle()?;
+        self.check_environment()?;
+        ().log_success();
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_product_status", skip(self), level = "debug", err)
+    )]
     pub async fn get_product_status(
         &self,
         product_id: String,
-        product_type: String,
+        product_type: ProductType,
     ) -> crate::Result<ProductStatus> {
-        validation::require_bundle()?;
+        self.check_environment()?;
+        crate::validate_product_id(&product_id)?;
+
+        if let Some(status) = self.product_status_cache.get(&product_id, product_type) {
+            status.log_success();
+            return Ok(status);
+        }
+        let result: crate::Result<ProductStatus> =
+            retry_with_backoff(self.config.retry_policy.as_ref(), || async {
+                with_timeout(
+                    self.config.product_query_timeout(),
+                    self.timed(
+                        "getProductStatus",
+                        self.plugin
+                            .getProductStatus(product_id.clone(), product_type.into()),
+                    ),
+                )
+                .await?
+                .parse()
+            })
+            .await;
+        let status = match (result, &self.entitlement_cache) {
+            (Ok(status), cache) => {
+                if let Some(cache) = cache {
+                    if status.is_owned {
+                        cache.record(
+                            &product_id,
+                            status.expiration_time,
+                            entitlement_cache::now(),
+                        );
+                    } else {
+                        cache.remove(&product_id);
+                    }
+                }
+                status
+            }
+            (Err(err), Some(cache)) if entitlement_cache::is_network_error(&err) => {
+                let Some(status) = cache.product_status(&product_id, entitlement_cache::now())
+                else {
+                    return Err(err);
+                };
+                log::warn!("Store unreachable ({err}); using cached entitlement for {product_id}");
+                status.log_success();
+                return Ok(status);
+            }
+            (Err(err), _) => return Err(err),
+        };
+        self.product_status_cache
+            .insert(product_id, product_type, status.clone());
+        status.log_success();
+        Ok(status)
+    }
+
+    /// Forgets every `get_product_status` result cached under
+    /// [`IapConfig::product_status_cache_ttl_secs`], e.g. after the app
+    /// learns of a purchase from its own server.
+    pub fn clear_product_status_cache(&self) {
+        self.product_status_cache.clear();
+    }
+
+    /// Finishes a transaction that `purchase()` left open because
+    /// `auto_finish` was `false`, or one delivered through
+    /// `Transaction.updates`. Finishing a current entitlement again is a
+    /// no-op. Rejects with `transactionNotFound` when the id matches neither
+    /// an unfinished transaction nor a current entitlement.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::finish_transaction", skip(self), level = "debug", err)
+    )]
+    pub async fn finish_transaction(&self, transaction_id: String) -> crate::Result<()> {
+        self.check_environment()?;
+
+        self.timed(
+            "finishTransaction",
+            self.plugin.finishTransaction(transaction_id),
+        )
+        .await
+        .parse::<serde::de::IgnoredAny>()
+        .map(|_| ())
+        .inspect(LogSuccess::log_success)
+    }
+
+    /// Reads `AppTransaction.shared`, or `AppTransaction.refresh()` when
+    /// `force_refresh` is set. Rejects with `appTransactionUnavailable` when
+    /// `StoreKit` can't produce one (e.g. an unsigned development build) and
+    /// `verificationFailed` when its signature doesn't verify.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_app_transaction", skip(self), level = "debug", err)
+    )]
+    pub async fn get_app_transaction(
+        &self,
+        force_refresh: bool,
+    ) -> crate::Result<AppTransactionInfo> {
+        self.check_environment()?;
+
+        self.timed(
+            "getAppTransaction",
+            self.plugin.getAppTransaction(force_refresh),
+        )
+        .await
+        .parse()
+        .inspect(LogSuccess::log_success)
+    }
+
+    /// Reads `Transaction.currentEntitlements` from `StoreKit`'s local cache,
+    /// so it works offline and never prompts for App Store credentials.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_current_entitlements",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_current_entitlements(&self) -> crate::Result<GetCurrentEntitlementsResponse> {
+        self.check_environment()?;
+
+        let response: GetCurrentEntitlementsResponse = self
+            .timed(
+                "getCurrentEntitlements",
+                self.plugin.getCurrentEntitlements(),
+            )
+            .await
+            .parse()?;
+        if let Some(cache) = &self.entitlement_cache {
+            cache.replace_all(
+                response
+                    .purchases
+                    .iter()
+                    .map(|purchase| (purchase.product_id.clone(), purchase.expiration_date)),
+                entitlement_cache::now(),
+            );
+        }
+        response.log_success();
+        Ok(response)
+    }
+
+    /// Every status in the product's subscription group from
+    /// `Product.SubscriptionInfo.status`, with its verified `RenewalInfo`.
+    /// Family Sharing yields one entry per purchaser. Rejects with
+    /// `productNotFound` for unknown ids and `notSubscription` for products
+    /// that aren't auto-renewable subscriptions.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_subscription_status",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_subscription_status(
+        &self,
+        product_id: String,
+    ) -> crate::Result<GetSubscriptionStatusResponse> {
+        self.check_environment()?;
+        crate::validate_product_id(&product_id)?;
+
+        with_timeout(
+            self.config.product_query_timeout(),
+            self.timed(
+                "getSubscriptionStatus",
+                self.plugin.getSubscriptionStatus(product_id),
+            ),
+        )
+        .await?
+        .parse()
+        .inspect(LogSuccess::log_success)
+    }
 
-        self.plugin
-            .getProductStatus(product_id, product_type)
+    /// `StoreKit`'s manage subscriptions sheet is iOS-only, so this opens the
+    /// App Store's subscription management page and resolves once it has
+    /// been handed off. Rejects with `notSupported` if the App Store can't be
+    /// opened.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::show_manage_subscriptions",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn show_manage_subscriptions(&self) -> crate::Result<()> {
+        self.check_environment()?;
+
+        self.timed(
+            "showManageSubscriptions",
+            self.plugin.showManageSubscriptions(),
+        )
+        .await
+        .parse::<serde::de::IgnoredAny>()
+        .map(|_| ())
+        .inspect(LogSuccess::log_success)
+    }
+
+    /// Presents `StoreKit`'s offer code redemption sheet (macOS 15+). A
+    /// redeemed code produces a transaction that arrives through the usual
+    /// `purchaseUpdated` and `transactionUpdated` events. Rejects with
+    /// `notSupported` on older macOS versions.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::present_offer_code_redemption_sheet",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn present_offer_code_redemption_sheet(&self) -> crate::Result<()> {
+        self.check_environment()?;
+
+        self.timed(
+            "presentOfferCodeRedemptionSheet",
+            self.plugin.presentOfferCodeRedemptionSheet(),
+        )
+        .await
+        .parse::<serde::de::IgnoredAny>()
+        .map(|_| ())
+        .inspect(LogSuccess::log_success)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_storefront", skip(self), level = "debug", err)
+    )]
+    pub async fn get_storefront(&self) -> crate::Result<StorefrontInfo> {
+        self.check_environment()?;
+
+        self.timed("getStorefront", self.plugin.getStorefront())
             .await
             .parse()
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// Verified transactions from `Transaction.unfinished`: purchases that
+    /// completed but were never passed to `finish_transaction`, typically
+    /// because the app quit before handling them.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_unfinished_transactions",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_unfinished_transactions(&self) -> crate::Result<Vec<Purchase>> {
+        self.check_environment()?;
+
+        self.timed(
+            "getUnfinishedTransactions",
+            self.plugin.getUnfinishedTransactions(),
+        )
+        .await
+        .parse::<GetUnfinishedTransactionsResponse>()
+        .map(|response| response.purchases)
+        .inspect(LogSuccess::log_success)
+    }
+
+    /// Presents `StoreKit`'s refund request sheet for one of the user's
+    /// transactions. Rejects with `transactionNotFound` when the id isn't one
+    /// of the current user's transactions; a dismissed sheet resolves with
+    /// [`RefundRequestResult::UserCancelled`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::begin_refund_request", skip(self), level = "debug", err)
+    )]
+    pub async fn begin_refund_request(
+        &self,
+        transaction_id: String,
+    ) -> crate::Result<RefundRequestResult> {
+        self.check_environment()?;
+
+        self.timed(
+            "beginRefundRequest",
+            self.plugin.beginRefundRequest(transaction_id),
+        )
+        .await
+        .parse::<BeginRefundRequestResponse>()
+        .map(|response| response.result)
+        .inspect(LogSuccess::log_success)
+    }
+
+    /// [`Self::begin_refund_request`], reporting a failed submission as
+    /// [`RefundRequestStatus::Error`] instead of rejecting.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::request_refund", skip(self), level = "debug", err)
+    )]
+    pub async fn request_refund(
+        &self,
+        transaction_id: String,
+    ) -> crate::Result<RefundRequestStatus> {
+        RefundRequestStatus::from_outcome(self.begin_refund_request(transaction_id).await)
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// Win-back offers of a subscription with the user's eligibility for
+    /// each. Resolves with an empty list before macOS 15.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_win_back_offers", skip(self), level = "debug", err)
+    )]
+    pub async fn get_win_back_offers(
+        &self,
+        product_id: String,
+    ) -> crate::Result<GetWinBackOffersResponse> {
+        self.check_environment()?;
+        crate::validate_product_id(&product_id)?;
+
+        with_timeout(
+            self.config.product_query_timeout(),
+            self.timed("getWinBackOffers", self.plugin.getWinBackOffers(product_id)),
+        )
+        .await?
+        .parse()
+        .inspect(LogSuccess::log_success)
+    }
+
+    /// Whether the user can still redeem the introductory offer of the
+    /// product's subscription group.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::is_eligible_for_intro_offer",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn is_eligible_for_intro_offer(
+        &self,
+        product_id: String,
+    ) -> crate::Result<IsEligibleForIntroOfferResponse> {
+        self.check_environment()?;
+        crate::validate_product_id(&product_id)?;
+
+        with_timeout(
+            self.config.product_query_timeout(),
+            self.timed(
+                "isEligibleForIntroOffer",
+                self.plugin.isEligibleForIntroOffer(product_id),
+            ),
+        )
+        .await?
+        .parse()
+        .inspect(LogSuccess::log_success)
+    }
+
+    /// Store environment from `AppTransaction.environment`. macOS can't tell
+    /// TestFlight from other sandbox builds, so both report
+    /// [`IapEnvironment::Sandbox`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_environment", skip(self), level = "debug", err)
+    )]
+    pub async fn get_environment(&self) -> crate::Result<IapEnvironment> {
+        self.check_environment()?;
+
+        self.timed("getEnvironment", self.plugin.getEnvironment())
+            .await
+            .parse::<GetEnvironmentResponse>()
+            .map(|response| response.environment)
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// Base64 App Store receipt for `transaction_id`, for server-side
+    /// verification.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_receipt", skip(self), level = "debug", err)
+    )]
+    pub async fn get_receipt(&self, transaction_id: String) -> crate::Result<PurchaseReceipt> {
+        self.check_environment()?;
+
+        self.timed("getReceipt", self.plugin.getReceipt(transaction_id))
+            .await
+            .parse()
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// Re-downloads the App Store receipt with `SKReceiptRefreshRequest`, for
+    /// when [`Self::get_receipt`] rejects with `receiptUnavailable`. May show
+    /// the App Store sign-in prompt.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::refresh_receipt", skip(self), level = "debug", err)
+    )]
+    pub async fn refresh_receipt(&self) -> crate::Result<()> {
+        self.check_environment()?;
+
+        self.timed("refreshReceipt", self.plugin.refreshReceipt())
+            .await
+            .parse::<serde::de::IgnoredAny>()
+            .map(|_| ())
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// Typed counterpart of the `register_listener` command for Rust callers:
+    /// `handler` receives every `transactionUpdated` event until the returned
+    /// handle is dropped.
+    #[allow(clippy::unused_self)]
+    pub fn register_transaction_observer(
+        &self,
+        handler: Channel<TransactionEvent>,
+    ) -> crate::Result<ListenerHandle> {
+        crate::listeners::observe(TRANSACTION_UPDATED_EVENT, handler)
+    }
+
+    /// Every `transactionUpdated` event as a [`Stream`](futures_util::Stream),
+    /// for `while let Some(event) = updates.next().await` loops. Dropping the
+    /// stream removes its listener.
+    #[allow(clippy::unused_self)]
+    pub fn subscribe_to_transaction_updates(&self) -> crate::Result<EventStream<TransactionEvent>> {
+        crate::listeners::subscribe(TRANSACTION_UPDATED_EVENT)
+    }
+
+    /// Register `handler` for the next `event` only, e.g. to await a single
+    /// purchase confirmation. Returns the channel id for `remove_listener`.
+    #[allow(clippy::unused_self)]
+    pub fn register_one_shot_listener(
+        &self,
+        event: String,
+        handler: Channel<serde_json::Value>,
+    ) -> crate::Result<u32> {
+        crate::listeners::register_one_shot_listener(event, handler)
+    }
+
+    /// `AppStore.canMakePayments`, which is false when Screen Time, parental
+    /// controls or a device management profile blocks purchases.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::can_make_payments", skip(self), level = "debug", err)
+    )]
+    pub async fn can_make_payments(&self) -> crate::Result<CanMakePaymentsResponse> {
+        self.check_environment()?;
+
+        self.timed("canMakePayments", self.plugin.canMakePayments())
+            .await
+            .parse()
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// [`Self::is_eligible_for_intro_offer`] for several products at once,
+    /// keyed by product id. Unknown ids and non-subscriptions are left out.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::check_introductory_offer_eligibility",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn check_introductory_offer_eligibility(
+        &self,
+        product_ids: Vec<String>,
+    ) -> crate::Result<HashMap<String, bool>> {
+        self.check_environment()?;
+        crate::validation::validate_product_ids(&product_ids)?;
+
+        with_timeout(
+            self.config.product_query_timeout(),
+            self.timed(
+                "checkIntroductoryOfferEligibility",
+                self.plugin.checkIntroductoryOfferEligibility(product_ids),
+            ),
+        )
+        .await?
+        .parse::<CheckIntroductoryOfferEligibilityResponse>()
+        .map(|response| response.eligibility)
+        .inspect(LogSuccess::log_success)
+    }
+
+    /// How the running binary is signed (macOS only), e.g. to tell a
+    /// TestFlight build apart from the App Store one. Doesn't touch `StoreKit`,
+    /// so it works outside a `.app` bundle too.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_signing_info", skip(self), level = "debug", err)
+    )]
+    pub async fn get_signing_info(&self) -> crate::Result<SigningInfo> {
+        self.timed("getSigningInfo", self.plugin.getSigningInfo())
+            .await
+            .parse()
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// False when the running macOS is older than `StoreKit` 2 requires, in
+    /// which case every other method rejects with `storeKitUnavailable`.
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::is_supported", skip(self), level = "debug", err)
+    )]
+    pub async fn is_supported(&self) -> crate::Result<IsSupportedResponse> {
+        let is_supported = validation::storekit_error().is_none();
+        let response = IsSupportedResponse {
+            is_supported,
+            reason: (!is_supported).then(|| "storeKitUnavailable".to_string()),
+        };
+        response.log_success();
+        Ok(response)
+    }
+
+    /// Reads `Transaction.currentEntitlements` once and returns a status per
+    /// owned product of `product_type`, instead of a `get_product_status`
+    /// round-trip per product.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_all_product_statuses",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_all_product_statuses(
+        &self,
+        product_type: ProductType,
+    ) -> crate::Result<Vec<ProductStatus>> {
+        self.check_environment()?;
+
+        with_timeout(
+            self.config.product_query_timeout(),
+            self.timed(
+                "getAllProductStatuses",
+                self.plugin.getAllProductStatuses(product_type.into()),
+            ),
+        )
+        .await?
+        .parse::<GetAllProductStatusesResponse>()
+        .map(|response| response.statuses)
+        .inspect(LogSuccess::log_success)
+    }
+
+    /// `ExternalPurchaseLink.canOpen`, with the current storefront's country.
+    /// Rejects with `notSupported` before macOS 15.4.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::can_open_external_purchase_link",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn can_open_external_purchase_link(
+        &self,
+    ) -> crate::Result<ExternalPurchaseLinkAvailability> {
+        self.check_environment()?;
+
+        self.timed(
+            "canOpenExternalPurchaseLink",
+            self.plugin.canOpenExternalPurchaseLink(),
+        )
+        .await
+        .parse()
+        .inspect(LogSuccess::log_success)
+    }
+
+    /// Shows the App Store's external purchase notice and, once the user
+    /// continues, opens the link from the app's `SKExternalPurchaseLink`
+    /// Info.plist entry. Resolves when that flow completes.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::open_external_purchase_link",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn open_external_purchase_link(&self) -> crate::Result<()> {
+        self.check_environment()?;
+
+        self.timed(
+            "openExternalPurchaseLink",
+            self.plugin.openExternalPurchaseLink(),
+        )
+        .await
+        .parse::<serde::de::IgnoredAny>()
+        .map(|_| ())
+        .inspect(LogSuccess::log_success)
+    }
+
+    /// `StoreKit`'s `Message` API is iOS-only; macOS shows App Store messages
+    /// itself, so this always rejects with `notSupported`.
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::display_pending_store_messages",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn display_pending_store_messages(&self) -> crate::Result<u32> {
+        Err(crate::Error::PluginInvoke(
+            crate::error::PluginInvokeError::InvokeRejected(crate::error::ErrorResponse::new(
+                Some("notSupported"),
+                "display_pending_store_messages is only available on iOS",
+            )),
+        ))
+    }
+
+    /// `Transaction.currentEntitlement(for:)`: the user's current entitlement
+    /// to `product_id`, or `None`. Reads `StoreKit`'s local cache without
+    /// looking up product metadata, so it never touches the network and is
+    /// cheap enough to call whenever the app regains focus.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::current_entitlement", skip(self), level = "debug", err)
+    )]
+    pub async fn current_entitlement(&self, product_id: String) -> crate::Result<Option<Purchase>> {
+        self.check_environment()?;
+        crate::validate_product_id(&product_id)?;
+
+        let purchase = self
+            .timed(
+                "currentEntitlement",
+                self.plugin.currentEntitlement(product_id.clone()),
+            )
+            .await
+            .parse::<CurrentEntitlementResponse>()?
+            .purchase;
+        if let Some(cache) = &self.entitlement_cache {
+            match &purchase {
+                Some(purchase) => cache.record(
+                    &product_id,
+                    purchase.expiration_date,
+                    entitlement_cache::now(),
+                ),
+                None => cache.remove(&product_id),
+            }
+        }
+        purchase.log_success();
+        Ok(purchase)
+    }
+
+    /// The app's whole App Store receipt, base64-encoded, for legacy
+    /// `verifyReceipt` validation. With `refresh_if_missing` a missing
+    /// receipt is re-downloaded first, which may show the sign-in prompt;
+    /// rejects with [`IapErrorCode::ReceiptUnavailable`](crate::IapErrorCode::ReceiptUnavailable)
+    /// when there's still none.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_app_receipt", skip(self), level = "debug", err)
+    )]
+    pub async fn get_app_receipt(&self, refresh_if_missing: bool) -> crate::Result<String> {
+        self.check_environment()?;
+
+        self.timed(
+            "getAppReceipt",
+            self.plugin.getAppReceipt(refresh_if_missing),
+        )
+        .await
+        .parse::<GetAppReceiptResponse>()
+        .map(|response| response.receipt_data)
+        .inspect(LogSuccess::log_success)
+    }
+
+    /// Refunded or Family Sharing-removed transactions from `Transaction.all`,
+    /// newest revocation first, including revocations that happened while
+    /// the app wasn't running. Pass the time of the last check as `since` to
+    /// reconcile on launch without rescanning the whole history.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_revoked_purchases", skip(self), level = "debug", err)
+    )]
+    pub async fn get_revoked_purchases(
+        &self,
+        since: Option<Timestamp>,
+    ) -> crate::Result<Vec<Purchase>> {
+        self.check_environment()?;
+
+        self.timed(
+            "getRevokedPurchases",
+            self.plugin
+                .getRevokedPurchases(since.map(Timestamp::unix_millis)),
+        )
+        .await
+        .parse::<GetRevokedPurchasesResponse>()
+        .map(|response| response.purchases)
+        .inspect(LogSuccess::log_success)
+    }
+
+    /// Delivers every transaction in `Transaction.unfinished` once as
+    /// `purchaseCompleted`, then finishes it. Returns how many were
+    /// finished. The `initialize` command runs this when
+    /// [`IapConfig::auto_finish_unfinished`] is set.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::finish_unfinished_transactions",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn finish_unfinished_transactions(&self) -> crate::Result<u32> {
+        self.check_environment()?;
+
+        self.timed(
+            "finishUnfinishedTransactions",
+            self.plugin.finishUnfinishedTransactions(),
+        )
+        .await
+        .parse::<FinishUnfinishedTransactionsResponse>()
+        .map(|response| response.finished)
+        .inspect(LogSuccess::log_success)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::validation;
+
+    #[test]
+    fn test_check_bundle_rejects_unbundled_binary_when_required() {
+        // Test binaries run from target/, never from a .app bundle.
+        let err = validation::check_bundle(true).expect_err("Expected bundle check to fail");
+        assert!(err.to_string().contains(".app bundle"));
+    }
+
+    #[test]
+    fn test_is_bundled_executable() {
+        assert!(validation::is_bundled_executable(Path::new(
+            "/Applications/Example.app/Contents/MacOS/example"
+        )));
+        for exe in [
+            "/Users/dev/example/target/debug/example",
+            "/Applications/Example/Contents/MacOS/example",
+            "/Applications/Example.app/Contents/Resources/example",
+            "example",
+        ] {
+            assert!(!validation::is_bundled_executable(Path::new(exe)), "{exe}");
+        }
+    }
+
+    #[test]
+    fn test_bundle_error_message_names_the_executable() {
+        let message =
+            validation::bundle_error_message(Some(Path::new("/Users/dev/target/debug/example")));
+        assert!(message.contains("`/Users/dev/target/debug/example`"));
+        assert!(message.contains("requireAppBundle"));
+
+        let message = validation::bundle_error_message(None);
+        assert!(message.contains("an unknown location"));
+    }
+
+    #[test]
+    fn test_bundle_error() {
+        assert_eq!(
+            validation::bundle_error(Some(Path::new(
+                "/Applications/Example.app/Contents/MacOS/example"
+            ))),
+            None
+        );
+        let message = validation::bundle_error(Some(Path::new("/tmp/example")))
+            .expect("Expected an unbundled executable to fail");
+        assert!(message.contains("`/tmp/example`"));
+    }
+
+    #[test]
+    fn test_require_bundle_caches_the_failure_with_its_message() {
+        let first = validation::require_bundle().expect_err("Expected bundle check to fail");
+        let second = validation::require_bundle().expect_err("Cached check should still fail");
+        assert_eq!(first.to_string(), second.to_string());
+        assert!(second.to_string().contains("is running from"));
+    }
+
+    #[test]
+    fn test_check_bundle_skipped_when_not_required() {
+        validation::check_bundle(false).expect("Skipped bundle check should pass");
+        // The warning is logged once; later calls still pass.
+        validation::check_bundle(false).expect("Skipped bundle check should pass");
+    }
+
+    #[test]
+    fn test_parse_os_version() {
+        assert_eq!(validation::parse_os_version("12.7.4"), Some((12, 7)));
+        assert_eq!(validation::parse_os_version("14.4"), Some((14, 4)));
+        assert_eq!(validation::parse_os_version("15"), Some((15, 0)));
+        assert_eq!(validation::parse_os_version("15.x"), None);
+        assert_eq!(validation::parse_os_version(""), None);
+    }
+
+    #[test]
+    fn test_storekit_error_message() {
+        assert_eq!(validation::storekit_error_message(Some("13.0")), None);
+        assert_eq!(validation::storekit_error_message(Some("15.1.1")), None);
+        assert_eq!(validation::storekit_error_message(None), None);
+
+        let message = validation::storekit_error_message(Some("12.7.4"))
+            .expect("Expected macOS 12 to be rejected");
+        assert!(message.contains("macOS 13.0 or later"));
+        assert!(message.contains("macOS 12.7.4"));
+    }
+
+    #[test]
+    fn test_storekit_error_message_at_the_minimum_version() {
+        // Compares (major, minor) pairs, so a later minor never outranks a
+        // newer major, and a bare major is its .0 release.
+        assert_eq!(validation::storekit_error_message(Some("13")), None);
+        assert!(validation::storekit_error_message(Some("12.99")).is_some());
+        assert!(validation::storekit_error_message(Some("12")).is_some());
+        // Unparseable versions are let through.
+        assert_eq!(validation::storekit_error_message(Some("13.x")), None);
     }
 }
@@ -1,14 +1,30 @@
-use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+
 use tauri::{
     AppHandle, Runtime,
     plugin::{PluginApi, PluginHandle},
 };
 
 use crate::models::{
-    AcknowledgePurchaseRequest, ConsumePurchaseRequest, GetProductStatusRequest,
-    GetProductsRequest, GetProductsResponse, GetPurchaseHistoryResponse, ProductStatus, Purchase,
-    PurchaseRequest, RestorePurchasesRequest, RestorePurchasesResponse,
+    AcknowledgePurchaseRequest, AppTransactionInfo, BeginRefundRequestResponse,
+    CanMakePaymentsResponse, CheckIntroductoryOfferEligibilityRequest,
+    CheckIntroductoryOfferEligibilityResponse, ConsumePurchaseRequest, CurrentEntitlementRequest,
+    CurrentEntitlementResponse, DisplayPendingStoreMessagesResponse,
+    ExternalPurchaseLinkAvailability, FinishTransactionRequest,
+    FinishUnfinishedTransactionsResponse, GetAllProductStatusesRequest,
+    GetAllProductStatusesResponse, GetAppReceiptRequest, GetAppReceiptResponse,
+    GetAppTransactionRequest, GetCurrentEntitlementsResponse, GetEnvironmentResponse,
+    GetProductStatusRequest, GetProductsRequest, GetProductsResponse, GetPurchaseHistoryResponse,
+    GetReceiptRequest, GetRevokedPurchasesRequest, GetRevokedPurchasesResponse,
+    GetSubscriptionStatusRequest, GetSubscriptionStatusResponse, GetUnfinishedTransactionsResponse,
+    GetWinBackOffersRequest, GetWinBackOffersResponse, IapEnvironment,
+    IsEligibleForIntroOfferRequest, IsEligibleForIntroOfferResponse, IsSupportedResponse,
+    ProductStatus, ProductType, Purchase, PurchaseHistoryRequest, PurchaseReceipt, PurchaseRequest,
+    RefundRequest, RefundRequestResult, RefundRequestStatus, RestorePurchasesRequest,
+    RestorePurchasesResponse, StorefrontInfo,
 };
+use crate::trace::LogSuccess;
+use crate::{IapConfig, Timestamp};
 
 #[cfg(target_os = "android")]
 const PLUGIN_IDENTIFIER: &str = "app.tauri.iap";
@@ -17,46 +33,101 @@ const PLUGIN_IDENTIFIER: &str = "app.tauri.iap";
 tauri::ios_plugin_binding!(init_plugin_iap);
 
 // initializes the Kotlin or Swift plugin classes
-pub fn init<R: Runtime, C: DeserializeOwned>(
+pub fn init<R: Runtime>(
     _app: &AppHandle<R>,
-    api: &PluginApi<R, C>,
+    api: &PluginApi<R, Option<IapConfig>>,
 ) -> crate::Result<Iap<R>> {
     #[cfg(target_os = "android")]
     let handle = api.register_android_plugin(PLUGIN_IDENTIFIER, "IapPlugin")?;
     #[cfg(target_os = "ios")]
     let handle = api.register_ios_plugin(init_plugin_iap)?;
 
-    Ok(Iap(handle))
+    Ok(Iap(handle, api.config().clone().unwrap_or_default()))
 }
 
 /// Access to the iap APIs.
-pub struct Iap<R: Runtime>(PluginHandle<R>);
+pub struct Iap<R: Runtime>(PluginHandle<R>, IapConfig);
 
 impl<R: Runtime> Iap<R> {
+    /// The `plugins.iap` options from `tauri.conf.json`, with defaults for
+    /// anything left out.
+    #[must_use]
+    pub const fn config(&self) -> &IapConfig {
+        &self.1
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_products", skip(self), level = "debug", err)
+    )]
     pub async fn get_products(
         &self,
         product_ids: Vec<String>,
-        product_type: String,
+        product_type: ProductType,
     ) -> crate::Result<GetProductsResponse> {
+        crate::validation::validate_product_ids(&product_ids)?;
+
         self.0
             .run_mobile_plugin_async(
                 "getProducts",
                 GetProductsRequest {
                     product_ids,
                     product_type,
+                    force_refresh: false,
                 },
             )
             .await
             .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// Same as [`Self::get_products`]; only macOS caches products.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::refresh_products", skip(self), level = "debug", err)
+    )]
+    pub async fn refresh_products(
+        &self,
+        product_ids: Vec<String>,
+        product_type: ProductType,
+    ) -> crate::Result<GetProductsResponse> {
+        self.get_products(product_ids, product_type).await
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::purchase",
+            skip(self, payload),
+            fields(product_id = %payload.product_id, product_type = %payload.product_type),
+            level = "debug",
+            err
+        )
+    )]
     pub async fn purchase(&self, payload: PurchaseRequest) -> crate::Result<Purchase> {
+        crate::validate_product_id(&payload.product_id)?;
+
+        if let Some(opts) = &payload.options {
+            opts.validate(payload.product_type)?;
+        }
+
         self.0
             .run_mobile_plugin_async("purchase", payload)
             .await
             .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::restore_purchases",
+            skip(self, request),
+            fields(product_type = %request.product_type, force_sync = request.force_sync),
+            level = "debug",
+            err
+        )
+    )]
     pub async fn restore_purchases(
         &self,
         request: RestorePurchasesRequest,
@@ -67,14 +138,50 @@ impl<R: Runtime> Iap<R> {
             .run_mobile_plugin_async("restorePurchases", request)
             .await
             .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
     }
 
-    pub fn get_purchase_history(&self) -> crate::Result<GetPurchaseHistoryResponse> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_purchase_history", skip(self), level = "debug", err)
+    )]
+    pub async fn get_purchase_history(&self) -> crate::Result<GetPurchaseHistoryResponse> {
         self.0
-            .run_mobile_plugin("getPurchaseHistory", ())
+            .run_mobile_plugin_async("getPurchaseHistory", ())
+            .await
             .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_purchase_history_filtered",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_purchase_history_filtered(
+        &self,
+        request: PurchaseHistoryRequest,
+    ) -> crate::Result<GetPurchaseHistoryResponse> {
+        self.0
+            .run_mobile_plugin_async("getPurchaseHistoryFiltered", request)
+            .await
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::acknowledge_purchase",
+            skip(self, purchase_token),
+            level = "debug",
+            err
+        )
+    )]
     pub async fn acknowledge_purchase(&self, purchase_token: String) -> crate::Result<()> {
         self.0
             .run_mobile_plugin_async(
@@ -83,20 +190,37 @@ impl<R: Runtime> Iap<R> {
             )
             .await
             .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::consume_purchase",
+            skip(self, purchase_token),
+            level = "debug",
+            err
+        )
+    )]
     pub async fn consume_purchase(&self, purchase_token: String) -> crate::Result<()> {
         self.0
             .run_mobile_plugin_async("consumePurchase", ConsumePurchaseRequest { purchase_token })
             .await
             .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_product_status", skip(self), level = "debug", err)
+    )]
     pub async fn get_product_status(
         &self,
         product_id: String,
-        product_type: String,
+        product_type: ProductType,
     ) -> crate::Result<ProductStatus> {
+        crate::validate_product_id(&product_id)?;
+
         self.0
             .run_mobile_plugin_async(
                 "getProductStatus",
@@ -107,5 +231,494 @@ impl<R: Runtime> Iap<R> {
             )
             .await
             .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::finish_transaction", skip(self), level = "debug", err)
+    )]
+    pub async fn finish_transaction(&self, transaction_id: String) -> crate::Result<()> {
+        self.0
+            .run_mobile_plugin_async(
+                "finishTransaction",
+                FinishTransactionRequest { transaction_id },
+            )
+            .await
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_app_transaction", skip(self), level = "debug", err)
+    )]
+    pub async fn get_app_transaction(
+        &self,
+        force_refresh: bool,
+    ) -> crate::Result<AppTransactionInfo> {
+        self.0
+            .run_mobile_plugin_async(
+                "getAppTransaction",
+                GetAppTransactionRequest { force_refresh },
+            )
+            .await
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_current_entitlements",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_current_entitlements(&self) -> crate::Result<GetCurrentEntitlementsResponse> {
+        self.0
+            .run_mobile_plugin_async("getCurrentEntitlements", ())
+            .await
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_subscription_status",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_subscription_status(
+        &self,
+        product_id: String,
+    ) -> crate::Result<GetSubscriptionStatusResponse> {
+        crate::validate_product_id(&product_id)?;
+
+        self.0
+            .run_mobile_plugin_async(
+                "getSubscriptionStatus",
+                GetSubscriptionStatusRequest { product_id },
+            )
+            .await
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// On iOS resolves once the user dismisses the sheet; on Android once the
+    /// Play Store subscription center has been opened.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::show_manage_subscriptions",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn show_manage_subscriptions(&self) -> crate::Result<()> {
+        self.0
+            .run_mobile_plugin_async("showManageSubscriptions", ())
+            .await
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// Redeemed codes are delivered through the transaction update listeners.
+    /// On iOS 15 this falls back to `SKPaymentQueue.presentCodeRedemptionSheet()`,
+    /// which resolves as soon as the sheet is shown.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::present_offer_code_redemption_sheet",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn present_offer_code_redemption_sheet(&self) -> crate::Result<()> {
+        self.0
+            .run_mobile_plugin_async("presentOfferCodeRedemptionSheet", ())
+            .await
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// `Storefront.current` on iOS, the Play billing config's country on Android.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_storefront", skip(self), level = "debug", err)
+    )]
+    pub async fn get_storefront(&self) -> crate::Result<StorefrontInfo> {
+        self.0
+            .run_mobile_plugin_async("getStorefront", ())
+            .await
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// `Transaction.unfinished` on iOS; purchased but unacknowledged purchases
+    /// on Android.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_unfinished_transactions",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_unfinished_transactions(&self) -> crate::Result<Vec<Purchase>> {
+        self.0
+            .run_mobile_plugin_async::<GetUnfinishedTransactionsResponse>(
+                "getUnfinishedTransactions",
+                (),
+            )
+            .await
+            .map(|response| response.purchases)
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::begin_refund_request", skip(self), level = "debug", err)
+    )]
+    pub async fn begin_refund_request(
+        &self,
+        transaction_id: String,
+    ) -> crate::Result<RefundRequestResult> {
+        self.0
+            .run_mobile_plugin_async::<BeginRefundRequestResponse>(
+                "beginRefundRequest",
+                RefundRequest { transaction_id },
+            )
+            .await
+            .map(|response| response.result)
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// [`Self::begin_refund_request`], reporting a failed submission as
+    /// [`RefundRequestStatus::Error`] instead of rejecting.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::request_refund", skip(self), level = "debug", err)
+    )]
+    pub async fn request_refund(
+        &self,
+        transaction_id: String,
+    ) -> crate::Result<RefundRequestStatus> {
+        RefundRequestStatus::from_outcome(self.begin_refund_request(transaction_id).await)
+            .inspect(LogSuccess::log_success)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_win_back_offers", skip(self), level = "debug", err)
+    )]
+    pub async fn get_win_back_offers(
+        &self,
+        product_id: String,
+    ) -> crate::Result<GetWinBackOffersResponse> {
+        crate::validate_product_id(&product_id)?;
+
+        self.0
+            .run_mobile_plugin_async("getWinBackOffers", GetWinBackOffersRequest { product_id })
+            .await
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::is_eligible_for_intro_offer",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn is_eligible_for_intro_offer(
+        &self,
+        product_id: String,
+    ) -> crate::Result<IsEligibleForIntroOfferResponse> {
+        crate::validate_product_id(&product_id)?;
+
+        self.0
+            .run_mobile_plugin_async(
+                "isEligibleForIntroOffer",
+                IsEligibleForIntroOfferRequest { product_id },
+            )
+            .await
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_environment", skip(self), level = "debug", err)
+    )]
+    pub async fn get_environment(&self) -> crate::Result<IapEnvironment> {
+        self.0
+            .run_mobile_plugin_async::<GetEnvironmentResponse>("getEnvironment", ())
+            .await
+            .map(|response| response.environment)
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_receipt", skip(self), level = "debug", err)
+    )]
+    pub async fn get_receipt(&self, transaction_id: String) -> crate::Result<PurchaseReceipt> {
+        self.0
+            .run_mobile_plugin_async("getReceipt", GetReceiptRequest { transaction_id })
+            .await
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::refresh_receipt", skip(self), level = "debug", err)
+    )]
+    pub async fn refresh_receipt(&self) -> crate::Result<()> {
+        self.0
+            .run_mobile_plugin_async("refreshReceipt", ())
+            .await
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::can_make_payments", skip(self), level = "debug", err)
+    )]
+    pub async fn can_make_payments(&self) -> crate::Result<CanMakePaymentsResponse> {
+        self.0
+            .run_mobile_plugin_async("canMakePayments", ())
+            .await
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::check_introductory_offer_eligibility",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn check_introductory_offer_eligibility(
+        &self,
+        product_ids: Vec<String>,
+    ) -> crate::Result<HashMap<String, bool>> {
+        crate::validation::validate_product_ids(&product_ids)?;
+
+        self.0
+            .run_mobile_plugin_async::<CheckIntroductoryOfferEligibilityResponse>(
+                "checkIntroductoryOfferEligibility",
+                CheckIntroductoryOfferEligibilityRequest { product_ids },
+            )
+            .await
+            .map(|response| response.eligibility)
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// The Swift package targets iOS 15, where `StoreKit` 2 is always present,
+    /// and Play Billing ships with the app, so mobile builds are always supported.
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::is_supported", skip(self), level = "debug", err)
+    )]
+    pub async fn is_supported(&self) -> crate::Result<IsSupportedResponse> {
+        let response = IsSupportedResponse {
+            is_supported: true,
+            reason: None,
+        };
+        response.log_success();
+        Ok(response)
+    }
+
+    /// A status per owned product of `product_type`, from one pass over
+    /// `Transaction.currentEntitlements` on iOS and one `queryPurchasesAsync`
+    /// on Android.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_all_product_statuses",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_all_product_statuses(
+        &self,
+        product_type: ProductType,
+    ) -> crate::Result<Vec<ProductStatus>> {
+        self.0
+            .run_mobile_plugin_async::<GetAllProductStatusesResponse>(
+                "getAllProductStatuses",
+                GetAllProductStatusesRequest { product_type },
+            )
+            .await
+            .map(|response| response.statuses)
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// `ExternalPurchaseLink.canOpen` on iOS 17.4+. Rejects with
+    /// `notSupported` on older iOS and on Android.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::can_open_external_purchase_link",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn can_open_external_purchase_link(
+        &self,
+    ) -> crate::Result<ExternalPurchaseLinkAvailability> {
+        self.0
+            .run_mobile_plugin_async("canOpenExternalPurchaseLink", ())
+            .await
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// Shows the App Store's external purchase notice and, once the user
+    /// continues, opens the link from the app's `SKExternalPurchaseLink`
+    /// Info.plist entry. Resolves when that flow completes.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::open_external_purchase_link",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn open_external_purchase_link(&self) -> crate::Result<()> {
+        self.0
+            .run_mobile_plugin_async("openExternalPurchaseLink", ())
+            .await
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// Shows the `StoreKit` messages held back by
+    /// [`IapConfig::defer_store_messages`](crate::IapConfig::defer_store_messages),
+    /// oldest first, and returns how many were shown. Rejects with
+    /// `notSupported` before iOS 16 and on Android.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::display_pending_store_messages",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn display_pending_store_messages(&self) -> crate::Result<u32> {
+        self.0
+            .run_mobile_plugin_async::<DisplayPendingStoreMessagesResponse>(
+                "displayPendingStoreMessages",
+                (),
+            )
+            .await
+            .map(|response| response.displayed)
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    /// The user's current entitlement to `product_id`, or `None` if they have
+    /// none. Reads the store's local cache like
+    /// [`Self::get_current_entitlements`] without looking up product
+    /// metadata, so it is cheap enough to call whenever the app regains
+    /// focus.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::current_entitlement", skip(self), level = "debug", err)
+    )]
+    pub async fn current_entitlement(&self, product_id: String) -> crate::Result<Option<Purchase>> {
+        crate::validate_product_id(&product_id)?;
+
+        self.0
+            .run_mobile_plugin_async::<CurrentEntitlementResponse>(
+                "currentEntitlement",
+                CurrentEntitlementRequest { product_id },
+            )
+            .await
+            .map(|response| response.purchase)
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_app_receipt", skip(self), level = "debug", err)
+    )]
+    pub async fn get_app_receipt(&self, refresh_if_missing: bool) -> crate::Result<String> {
+        self.0
+            .run_mobile_plugin_async::<GetAppReceiptResponse>(
+                "getAppReceipt",
+                GetAppReceiptRequest { refresh_if_missing },
+            )
+            .await
+            .map(|response| response.receipt_data)
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_revoked_purchases", skip(self), level = "debug", err)
+    )]
+    pub async fn get_revoked_purchases(
+        &self,
+        since: Option<Timestamp>,
+    ) -> crate::Result<Vec<Purchase>> {
+        self.0
+            .run_mobile_plugin_async::<GetRevokedPurchasesResponse>(
+                "getRevokedPurchases",
+                GetRevokedPurchasesRequest { since },
+            )
+            .await
+            .map(|response| response.purchases)
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::finish_unfinished_transactions",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn finish_unfinished_transactions(&self) -> crate::Result<u32> {
+        self.0
+            .run_mobile_plugin_async::<FinishUnfinishedTransactionsResponse>(
+                "finishUnfinishedTransactions",
+                (),
+            )
+            .await
+            .map(|response| response.finished)
+            .map_err(Into::into)
+            .inspect(LogSuccess::log_success)
     }
 }
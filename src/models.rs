@@ -1,21 +1,116 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
+use crate::{IapErrorCode, Timestamp};
+
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct InitializeResponse {
     pub success: bool,
+    /// Unfinished transactions delivered and finished because
+    /// [`IapConfig::auto_finish_unfinished`](crate::IapConfig::auto_finish_unfinished)
+    /// is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_transactions: Option<u32>,
+}
+
+/// Kind of store product a request targets.
+///
+/// On the wire this stays the `"subs"` / `"inapp"` pair the Swift, Kotlin and
+/// Windows backends already understand, so the mapping is lossy:
+/// `Consumable` and `NonConsumable` both serialize as `"inapp"`,
+/// `AutoRenewableSubscription` and `NonRenewingSubscription` as `"subs"`.
+/// Deserializing `"inapp"` yields `NonConsumable` and `"subs"` yields
+/// `AutoRenewableSubscription`; the explicit variant names are accepted too.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProductType {
+    Consumable,
+    NonConsumable,
+    #[default]
+    AutoRenewableSubscription,
+    /// A fixed-length subscription the store never renews (`StoreKit`
+    /// `.nonRenewable`). Play Billing has no such type, so Android queries
+    /// it as `SUBS`. No store tracks when the period ends; compute it from
+    /// the purchase time.
+    NonRenewingSubscription,
+}
+
+impl ProductType {
+    /// Wire value sent to the platform backends.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        if self.is_subscription() {
+            "subs"
+        } else {
+            "inapp"
+        }
+    }
+
+    #[must_use]
+    pub const fn is_subscription(self) -> bool {
+        matches!(
+            self,
+            Self::AutoRenewableSubscription | Self::NonRenewingSubscription
+        )
+    }
+}
+
+impl std::fmt::Display for ProductType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<ProductType> for String {
+    fn from(product_type: ProductType) -> Self {
+        product_type.as_str().to_string()
+    }
+}
+
+impl std::str::FromStr for ProductType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "subs" | "autoRenewableSubscription" => Ok(Self::AutoRenewableSubscription),
+            "inapp" | "nonConsumable" => Ok(Self::NonConsumable),
+            "consumable" => Ok(Self::Consumable),
+            "nonRenewingSubscription" => Ok(Self::NonRenewingSubscription),
+            _ => Err(format!("Invalid product type: {s}")),
+        }
+    }
+}
+
+impl Serialize for ProductType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ProductType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetProductsRequest {
     pub product_ids: Vec<String>,
-    #[serde(default = "default_product_type")]
-    pub product_type: String,
-}
-
-fn default_product_type() -> String {
-    "subs".to_string()
+    #[serde(default)]
+    pub product_type: ProductType,
+    /// Skip the macOS products cache and query the store again.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub force_refresh: bool,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -29,6 +124,85 @@ pub struct PricingPhase {
     pub recurrence_mode: i32,
 }
 
+/// Unit of a [`SubscriptionPeriod`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PeriodUnit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+/// Billing period of a subscription, e.g. `{ unit: Month, value: 3 }` for a
+/// quarterly plan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionPeriod {
+    pub unit: PeriodUnit,
+    pub value: u32,
+}
+
+impl SubscriptionPeriod {
+    /// Parses a single-component ISO 8601 period such as `P1M` or `P2W` —
+    /// the format of [`PricingPhase::billing_period`]. Returns `None` for
+    /// compound (`P1Y2M`) or time-based (`PT1H`) periods.
+    #[must_use]
+    pub fn from_iso8601(period: &str) -> Option<Self> {
+        let rest = period.strip_prefix('P')?;
+        let unit = match rest.chars().last()? {
+            'D' => PeriodUnit::Day,
+            'W' => PeriodUnit::Week,
+            'M' => PeriodUnit::Month,
+            'Y' => PeriodUnit::Year,
+            _ => return None,
+        };
+        let value = rest[..rest.len() - 1].parse().ok()?;
+        Some(Self { unit, value })
+    }
+}
+
+/// Kind of discount a [`SubscriptionOfferInfo`] describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SubscriptionOfferType {
+    Introductory,
+    Promotional,
+    /// `StoreKit` win-back offer for lapsed subscribers (iOS 18+/macOS 15+).
+    WinBack,
+}
+
+/// How the customer pays during a discounted offer period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PaymentMode {
+    FreeTrial,
+    PayAsYouGo,
+    PayUpFront,
+}
+
+/// Introductory or promotional discount on a subscription.
+///
+/// Named `SubscriptionOfferInfo` because [`SubscriptionOffer`] already
+/// models the Google Play base plan/offer shape returned in
+/// `subscription_offer_details`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionOfferInfo {
+    /// Offer identifier; `None` for `StoreKit` introductory offers.
+    pub id: Option<String>,
+    pub offer_type: SubscriptionOfferType,
+    /// Localized price of one offer period.
+    pub price: String,
+    /// Locale (iOS/macOS) or ISO 4217 currency code (Android/Windows) the
+    /// price is formatted in.
+    pub price_locale: String,
+    pub period: SubscriptionPeriod,
+    /// Number of `period`s the offer lasts.
+    pub period_count: u32,
+    pub payment_mode: PaymentMode,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SubscriptionOffer {
@@ -45,14 +219,75 @@ pub struct Product {
     pub title: String,
     pub description: String,
     pub product_type: String,
+    /// Localized price for display; use `price_amount_micros` for math.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub formatted_price: Option<String>,
+    /// ISO 4217 currency code, e.g. `USD`.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub price_currency_code: Option<String>,
+    /// Price × 1,000,000 (`$4.99` is `4_990_000`, `¥120` is `120_000_000`);
+    /// the regular recurring price for subscriptions.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub price_amount_micros: Option<i64>,
+    /// Locale `formatted_price` is formatted in, e.g. `en_US`: `StoreKit`'s
+    /// `Product.priceFormatStyle.locale` on iOS/macOS, the device locale
+    /// on Android, where Play formats prices for it. `None` on Windows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_locale: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub subscription_offer_details: Option<Vec<SubscriptionOffer>>,
+    /// Regular (non-introductory) billing period; `None` for one-time products.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription_period: Option<SubscriptionPeriod>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub introductory_offer: Option<SubscriptionOfferInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub promotional_offers: Vec<SubscriptionOfferInfo>,
+    /// `StoreKit` subscription group; intro offer eligibility and upgrades
+    /// apply across the group. `None` on Android and Windows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription_group_id: Option<String>,
+    /// `StoreKit` `Product.isFamilyShareable`; `None` on Android and Windows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_family_shareable: Option<bool>,
+}
+
+impl Product {
+    /// The price as one structured value, for formatting with your own
+    /// locale-aware tools. `None` when the store reported no price.
+    /// `locale_identifier` is empty where the locale is unknown (Windows).
+    #[must_use]
+    pub fn price_info(&self) -> Option<PriceInfo> {
+        Some(PriceInfo {
+            amount: micros_to_amount(self.price_amount_micros?),
+            currency_code: self.price_currency_code.clone()?,
+            formatted_price: self.formatted_price.clone()?,
+            locale_identifier: self.price_locale.clone().unwrap_or_default(),
+        })
+    }
+}
+
+// Store prices are far below 2^53 micros, so the conversion is exact enough
+// for display.
+#[allow(clippy::cast_precision_loss)]
+fn micros_to_amount(micros: i64) -> f64 {
+    micros as f64 / 1_000_000.0
+}
+
+/// A product's price with the data needed to format it, from
+/// [`Product::price_info`].
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceInfo {
+    /// Price in major units, e.g. `4.99`. Use
+    /// [`Product::price_amount_micros`] for exact arithmetic.
+    pub amount: f64,
+    /// ISO 4217 currency code, e.g. `USD`.
+    pub currency_code: String,
+    /// The store's localized price string, e.g. `$4.99`.
+    pub formatted_price: String,
+    /// Locale the store formatted the price in, e.g. `en_US`.
+    pub locale_identifier: String,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -61,7 +296,7 @@ pub struct GetProductsResponse {
     pub products: Vec<Product>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PurchaseOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -70,8 +305,18 @@ pub struct PurchaseOptions {
     pub obfuscated_account_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub obfuscated_profile_id: Option<String>,
+    /// `StoreKit` (iOS/macOS only): UUID linking the transaction to an
+    /// account in your app, passed as `Product.PurchaseOption.appAccountToken`
+    /// and echoed in App Store Server Notifications.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub app_account_token: Option<String>,
+    /// `StoreKit` (iOS/macOS only): number of units to buy in a single
+    /// transaction, passed as `Product.PurchaseOption.quantity`. Defaults to
+    /// 1; at most [`MAX_PURCHASE_QUANTITY`], and only consumables accept more
+    /// than 1. Android and Windows reject quantities above 1 with
+    /// `notSupported`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<u32>,
     /// Product ID of the existing subscription to replace (Android only).
     /// When set, the purchase becomes a subscription upgrade/downgrade via the
     /// Billing Library 9.0+ `SubscriptionProductReplacementParams` API.
@@ -97,47 +342,354 @@ pub struct PurchaseOptions {
     /// the backend can identity-bind the purchase.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub publisher_user_id: Option<String>,
+    /// `StoreKit` (iOS/macOS only): whether `purchase()` finishes the
+    /// transaction before returning. Defaults to `true`. Set to `false` to
+    /// validate the purchase server-side first, then call
+    /// `finish_transaction` with the purchase's `purchase_token`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_finish: Option<bool>,
+    /// `StoreKit` (iOS 18+/macOS 15+ only): id of a win-back offer from
+    /// `get_win_back_offers` to apply, passed as
+    /// `Product.PurchaseOption.winBackOffer`. Ignored on other platforms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub win_back_offer_id: Option<String>,
+    /// `StoreKit` (iOS/macOS only): signed promotional offer to apply,
+    /// passed as `Product.PurchaseOption.promotionalOffer`. Ignored on other
+    /// platforms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub promotional_offer: Option<PromotionalOffer>,
+}
+
+/// Most units `StoreKit` sells in one transaction.
+pub const MAX_PURCHASE_QUANTITY: u32 = 10;
+
+impl PurchaseOptions {
+    /// Rejects options the store would refuse, before they cross the bridge.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error::InvalidArgument`] when `app_account_token` is
+    /// not a hyphenated UUID, `quantity` is outside 1 to
+    /// [`MAX_PURCHASE_QUANTITY`], or `quantity` is above 1 for a subscription.
+    /// One-time products are let through: `"inapp"` doesn't say whether the
+    /// product is consumable, so the store makes that call.
+    pub(crate) fn validate(&self, product_type: ProductType) -> crate::Result<()> {
+        if let Some(quantity) = self.quantity {
+            if !(1..=MAX_PURCHASE_QUANTITY).contains(&quantity) {
+                return Err(crate::Error::InvalidArgument(format!(
+                    "quantity must be between 1 and {MAX_PURCHASE_QUANTITY}, got {quantity}"
+                )));
+            }
+            if quantity > 1 && product_type.is_subscription() {
+                return Err(crate::Error::InvalidArgument(format!(
+                    "subscriptions can't be bought with quantity {quantity}"
+                )));
+            }
+        }
+        if let Some(token) = &self.app_account_token
+            && !is_uuid(token)
+        {
+            return Err(crate::Error::InvalidArgument(format!(
+                "app_account_token must be a UUID, got {token:?}"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Whether `value` is a hyphenated UUID (`8-4-4-4-12` hex digits), the only
+/// form `UUID(uuidString:)` accepts.
+fn is_uuid(value: &str) -> bool {
+    let groups: Vec<&str> = value.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(group, len)| group.len() == len && group.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// A `StoreKit` promotional offer signed by your server with an App Store
+/// Connect subscription key. Every field must match what was signed.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromotionalOffer {
+    /// Offer identifier from App Store Connect.
+    pub offer_id: String,
+    /// Identifier of the subscription key used to sign.
+    pub key_id: String,
+    /// Lowercase UUID included in the signature.
+    pub nonce: String,
+    /// Base64-encoded signature.
+    pub signature: String,
+    /// Epoch milliseconds included in the signature.
+    pub timestamp: i64,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PurchaseRequest {
     pub product_id: String,
-    #[serde(default = "default_product_type")]
-    pub product_type: String,
+    #[serde(default)]
+    pub product_type: ProductType,
     #[serde(flatten)]
     pub options: Option<PurchaseOptions>,
 }
 
+impl PurchaseRequest {
+    /// Starts a [`PurchaseRequestBuilder`] for constructing a request from Rust
+    /// without spelling out every optional field.
+    #[must_use]
+    pub fn builder() -> PurchaseRequestBuilder {
+        PurchaseRequestBuilder::default()
+    }
+}
+
+/// Builder for [`PurchaseRequest`]. Only `product_id` is required; every
+/// other field falls back to the same defaults IPC deserialization uses.
+#[derive(Debug, Clone, Default)]
+pub struct PurchaseRequestBuilder {
+    product_id: Option<String>,
+    product_type: ProductType,
+    options: PurchaseOptions,
+}
+
+impl PurchaseRequestBuilder {
+    /// Store product identifier. Required.
+    #[must_use]
+    pub fn product_id(mut self, product_id: impl Into<String>) -> Self {
+        self.product_id = Some(product_id.into());
+        self
+    }
+
+    /// Product type; defaults to [`ProductType::AutoRenewableSubscription`].
+    #[must_use]
+    pub const fn product_type(mut self, product_type: ProductType) -> Self {
+        self.product_type = product_type;
+        self
+    }
+
+    /// See [`PurchaseOptions::offer_token`].
+    #[must_use]
+    pub fn offer_token(mut self, offer_token: impl Into<String>) -> Self {
+        self.options.offer_token = Some(offer_token.into());
+        self
+    }
+
+    /// See [`PurchaseOptions::quantity`].
+    #[must_use]
+    pub const fn quantity(mut self, quantity: u32) -> Self {
+        self.options.quantity = Some(quantity);
+        self
+    }
+
+    /// See [`PurchaseOptions::app_account_token`].
+    #[must_use]
+    pub fn app_account_token(mut self, app_account_token: impl Into<String>) -> Self {
+        self.options.app_account_token = Some(app_account_token.into());
+        self
+    }
+
+    /// See [`PurchaseOptions::auto_finish`].
+    #[must_use]
+    pub const fn auto_finish(mut self, auto_finish: bool) -> Self {
+        self.options.auto_finish = Some(auto_finish);
+        self
+    }
+
+    /// See [`PurchaseOptions::win_back_offer_id`].
+    #[must_use]
+    pub fn win_back_offer_id(mut self, win_back_offer_id: impl Into<String>) -> Self {
+        self.options.win_back_offer_id = Some(win_back_offer_id.into());
+        self
+    }
+
+    /// See [`PurchaseOptions::promotional_offer`].
+    #[must_use]
+    pub fn promotional_offer(mut self, promotional_offer: PromotionalOffer) -> Self {
+        self.options.promotional_offer = Some(promotional_offer);
+        self
+    }
+
+    /// Validates the collected fields and produces the request.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error::InvalidArgument`] when `product_id` is missing
+    /// or empty, when `quantity` is zero, above [`MAX_PURCHASE_QUANTITY`] or
+    /// above 1 for a subscription, or when `app_account_token` is not a UUID.
+    pub fn build(self) -> crate::Result<PurchaseRequest> {
+        let product_id = self
+            .product_id
+            .filter(|id| !id.trim().is_empty())
+            .ok_or_else(|| crate::Error::InvalidArgument("product_id is required".into()))?;
+        self.options.validate(self.product_type)?;
+        Ok(PurchaseRequest {
+            product_id,
+            product_type: self.product_type,
+            options: Some(self.options),
+        })
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Purchase {
     pub order_id: Option<String>,
     pub package_name: String,
     pub product_id: String,
-    pub purchase_time: i64,
+    pub purchase_time: Timestamp,
     pub purchase_token: String,
-    pub purchase_state: PurchaseStateValue,
+    pub purchase_state: PurchaseState,
     pub is_auto_renewing: bool,
     pub is_acknowledged: bool,
     pub original_json: String,
     pub signature: String,
     pub original_id: Option<String>,
+    /// `StoreKit` `Transaction.originalID`, shared by every renewal of a
+    /// subscription, so servers can key subscriptions by it without decoding
+    /// the JWS. Same value as `original_id`. iOS/macOS only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_transaction_id: Option<String>,
+    /// `StoreKit` `Transaction.webOrderLineItemID`, which identifies one
+    /// subscription purchase or renewal across devices. iOS/macOS only, and
+    /// only for auto-renewable subscriptions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_order_line_item_id: Option<String>,
+    /// `StoreKit` `Transaction.subscriptionGroupID`, matching
+    /// [`Product::subscription_group_id`]. Only set for auto-renewable
+    /// subscriptions on iOS/macOS; Play has no subscription groups (a base
+    /// plan is a pricing option of one subscription, not a group), so
+    /// Android and Windows leave it `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription_group_id: Option<String>,
+    /// `StoreKit` signed transaction (`VerificationResult.jwsRepresentation`)
+    /// for server-side verification against Apple's certificate chain. On
+    /// Windows this carries the Store ID key when Microsoft credentials were
+    /// supplied. Android has no equivalent; verify `purchase_token` instead.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub jws_representation: Option<String>,
+    /// `StoreKit` `Transaction.ownershipType`; `None` on Android and Windows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ownership_type: Option<OwnershipType>,
+    /// `StoreKit` `Transaction.reason`: whether this is the user's own
+    /// purchase or an automatic subscription renewal. `None` before iOS 17 /
+    /// macOS 14, and on Android and Windows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_reason: Option<TransactionReason>,
+    /// When the App Store revoked the transaction (refund or Family Sharing
+    /// removal). iOS/macOS only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation_time: Option<Timestamp>,
+    /// `"developerIssue"` or `"other"` when revoked. iOS/macOS only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation_reason: Option<String>,
+    /// `StoreKit` `Transaction.environment`: `"production"`, `"sandbox"` or
+    /// `"xcode"`. `None` on Android, where license-test purchases can't be
+    /// told apart client-side, and on Windows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    /// [`PurchaseOptions::app_account_token`] echoed back from the
+    /// transaction, as a lowercase UUID. iOS/macOS only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_account_token: Option<String>,
+    /// `StoreKit` `Transaction.purchasedQuantity`. iOS/macOS only; see
+    /// [`Purchase::purchased_quantity`] for a value on every platform.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<u32>,
+    /// When a subscription's access ends, from
+    /// `StoreKit` `Transaction.expirationDate`. Renewals arrive as
+    /// `purchaseUpdated` events carrying the later date. Always `None` on
+    /// Android, where Play Billing doesn't expose expiry on-device (query
+    /// the Play Developer API server-side), and on Windows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration_date: Option<Timestamp>,
+    /// When the subscription next renews, best effort: set only for an
+    /// auto-renewable subscription that is still set to renew and whose
+    /// `RenewalInfo` could be verified. iOS/macOS only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_renewal_date: Option<Timestamp>,
+    /// Identifier of the redeemed offer: the `StoreKit` promotional, offer
+    /// code or win-back offer id, or the Play subscription offer id. Absent
+    /// for introductory offers on Apple platforms, which have no id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offer_id: Option<String>,
+    /// `"introductory"`, `"promotional"`, `"code"` or `"winBack"`. Taken from
+    /// `Transaction.offer` on iOS/macOS. On Android it is only set on the
+    /// purchase result and `purchaseUpdated` event of a purchase made in
+    /// this session, and only when the offer carries one of these names as
+    /// an offer tag; restored Android purchases never report an offer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offer_type: Option<String>,
+    /// Whether `StoreKit` verified the transaction's signature (iOS/macOS
+    /// only). Always `Verified` unless
+    /// [`IapConfig::allow_unverified_transactions`](crate::IapConfig) is set;
+    /// otherwise unverified purchases fail with
+    /// [`IapErrorCode::VerificationFailed`](crate::IapErrorCode) and are
+    /// left out of restores and `purchaseUpdated` events.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<TransactionVerification>,
+}
+
+impl Purchase {
+    /// Units bought in this transaction. Platforms that don't report
+    /// [`Self::quantity`] only sell one unit at a time, so this is 1 there.
+    #[must_use]
+    pub fn purchased_quantity(&self) -> u32 {
+        self.quantity.unwrap_or(1)
+    }
+}
+
+/// Outcome of `StoreKit`'s `VerificationResult` for a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransactionVerification {
+    Verified,
+    /// The JWS signature didn't check out, e.g. on a jailbroken device or a
+    /// patched `StoreKit`. Don't grant content without server validation.
+    Unverified,
+}
+
+/// Whether the user bought a `StoreKit` transaction or received it through
+/// Family Sharing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OwnershipType {
+    Purchased,
+    FamilyShared,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Why `StoreKit` created a transaction, from `Transaction.reason`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransactionReason {
+    /// The user bought the product, including a subscription upgrade,
+    /// downgrade or resubscribe.
+    Purchase,
+    /// The App Store renewed an auto-renewable subscription.
+    Renewal,
+    #[serde(other)]
+    Unknown,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RestorePurchasesRequest {
-    #[serde(default = "default_product_type")]
-    pub product_type: String,
+    #[serde(default)]
+    pub product_type: ProductType,
     /// See [`PurchaseOptions::service_ticket`].
     #[serde(skip_serializing_if = "Option::is_none")]
     pub service_ticket: Option<String>,
     /// See [`PurchaseOptions::publisher_user_id`].
     #[serde(skip_serializing_if = "Option::is_none")]
     pub publisher_user_id: Option<String>,
+    /// Call `AppStore.sync()` before reading entitlements, so purchases made
+    /// on another device minutes ago show up. It may prompt for App Store
+    /// credentials; dismissing the prompt rejects with `userCancelled`.
+    /// iOS and macOS only.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub force_sync: bool,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -146,15 +698,111 @@ pub struct RestorePurchasesResponse {
     pub purchases: Vec<Purchase>,
 }
 
+/// Everything the user currently owns: active subscriptions and
+/// non-consumables, excluding revoked and expired transactions.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetCurrentEntitlementsResponse {
+    pub purchases: Vec<Purchase>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentEntitlementRequest {
+    pub product_id: String,
+}
+
+/// The one entitlement `current_entitlement` found, if any.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentEntitlementResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purchase: Option<Purchase>,
+}
+
+/// Purchases the store has completed but the app never finished (`StoreKit`)
+/// or acknowledged (Play), e.g. because it crashed mid-purchase.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetUnfinishedTransactionsResponse {
+    pub purchases: Vec<Purchase>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRevokedPurchasesRequest {
+    /// Only transactions revoked at or after this time, so a launch-time
+    /// reconciliation doesn't rescan the whole history.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<Timestamp>,
+}
+
+/// How many transactions `finish_unfinished_transactions` delivered and
+/// finished.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FinishUnfinishedTransactionsResponse {
+    pub finished: u32,
+}
+
+/// Refunded or Family Sharing-removed transactions, newest revocation first,
+/// with `revocation_time` and `revocation_reason` set.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRevokedPurchasesResponse {
+    pub purchases: Vec<Purchase>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PurchaseHistoryRecord {
     pub product_id: String,
-    pub purchase_time: i64,
+    pub purchase_time: Timestamp,
     pub purchase_token: String,
     pub quantity: i32,
     pub original_json: String,
     pub signature: String,
+    /// `StoreKit` transaction id (iOS/macOS only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_id: Option<String>,
+    /// See [`Purchase::original_transaction_id`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_transaction_id: Option<String>,
+    /// See [`Purchase::web_order_line_item_id`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_order_line_item_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_type: Option<ProductType>,
+    /// See [`Purchase::transaction_reason`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_reason: Option<TransactionReason>,
+    /// When the App Store revoked the transaction (refund or Family Sharing
+    /// removal). iOS/macOS only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation_time: Option<Timestamp>,
+    /// `"developerIssue"` or `"other"` when revoked. iOS/macOS only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation_reason: Option<String>,
+    /// See [`Purchase::verification`]; unverified transactions are left out
+    /// of the history unless they are allowed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<TransactionVerification>,
+}
+
+/// Narrows `get_purchase_history_filtered` down from the full transaction
+/// history. Unset fields don't filter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurchaseHistoryRequest {
+    /// Matched as `"subs"` or `"inapp"`, like `restore_purchases`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_type: Option<ProductType>,
+    /// Only purchases made at or after this time, in epoch milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since_timestamp: Option<u64>,
+    /// Keep at most this many records, newest first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -175,237 +823,1958 @@ pub struct ConsumePurchaseRequest {
     pub purchase_token: String,
 }
 
-/// Keep in sync with `PurchaseState` in `guest-js/index.ts`
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum PurchaseStateValue {
-    Purchased = 0,
-    Canceled = 1,
-    Pending = 2,
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FinishTransactionRequest {
+    pub transaction_id: String,
 }
 
-impl Serialize for PurchaseStateValue {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        serializer.serialize_i32(*self as i32)
-    }
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefundRequest {
+    pub transaction_id: String,
 }
 
-impl<'de> Deserialize<'de> for PurchaseStateValue {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let value = i32::deserialize(deserializer)?;
-        match value {
-            0 => Ok(Self::Purchased),
-            1 => Ok(Self::Canceled),
-            2 => Ok(Self::Pending),
-            _ => Err(serde::de::Error::custom(format!(
-                "Invalid purchase state: {value}"
-            ))),
+/// How the user left `StoreKit`'s refund request sheet. Cancelling is an
+/// ordinary outcome, not an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RefundRequestResult {
+    /// The request was submitted to Apple; the decision arrives later as a
+    /// revoked transaction.
+    Success,
+    UserCancelled,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BeginRefundRequestResponse {
+    pub result: RefundRequestResult,
+}
+
+/// Outcome of `request_refund`.
+///
+/// Unlike [`RefundRequestResult`], a refund request `StoreKit` failed to
+/// submit (including a duplicate request) is reported as
+/// [`RefundRequestStatus::Error`] instead of rejecting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RefundRequestStatus {
+    Success,
+    UserCancelled,
+    Error,
+}
+
+impl RefundRequestStatus {
+    /// Folds a `begin_refund_request` outcome into a status. Errors other
+    /// than a failed submission (unknown transaction, no window, unsupported
+    /// platform) are passed through.
+    pub(crate) fn from_outcome(outcome: crate::Result<RefundRequestResult>) -> crate::Result<Self> {
+        match outcome {
+            Ok(RefundRequestResult::Success) => Ok(Self::Success),
+            Ok(RefundRequestResult::UserCancelled) => Ok(Self::UserCancelled),
+            Err(error)
+                if error.error_code().is_some_and(|code| {
+                    matches!(code.as_str(), "refundRequestFailed" | "duplicateRequest")
+                }) =>
+            {
+                Ok(Self::Error)
+            }
+            Err(error) => Err(error),
         }
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct GetProductStatusRequest {
-    pub product_id: String,
-    #[serde(default = "default_product_type")]
-    pub product_type: String,
+pub struct GetAppTransactionRequest {
+    /// Call `AppTransaction.refresh()` instead of reading the cached value.
+    /// Shows the App Store sign-in prompt, so only use it in response to an
+    /// explicit user action (e.g. a "Restore" button).
+    #[serde(default)]
+    pub force_refresh: bool,
 }
 
+/// Original app purchase details from `StoreKit`'s `AppTransaction`, for
+/// migrating users from a paid-app model to in-app purchases.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct ProductStatus {
-    pub product_id: String,
-    pub is_owned: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub purchase_state: Option<PurchaseStateValue>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub purchase_time: Option<i64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub expiration_time: Option<i64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub is_auto_renewing: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub is_acknowledged: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub purchase_token: Option<String>,
+pub struct AppTransactionInfo {
+    pub bundle_id: String,
+    /// `CFBundleVersion` (iOS) or `CFBundleShortVersionString` (macOS) of the
+    /// build the app transaction was signed for.
+    pub app_version: String,
+    /// Same as [`Self::app_version`], for the build the user originally
+    /// bought or downloaded.
+    pub original_app_version: String,
+    pub original_purchase_date: Timestamp,
+    /// [`IapEnvironment::Sandbox`] for both the App Store sandbox and Xcode
+    /// `StoreKit` testing.
+    pub environment: IapEnvironment,
+    /// Signed JWS of the app transaction for server-side verification.
+    pub jws_representation: String,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Store environment the app is running against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IapEnvironment {
+    Production,
+    /// App Store sandbox, including local `StoreKit` testing in Xcode and
+    /// `TestFlight` builds on macOS.
+    Sandbox,
+    /// iOS `TestFlight` build; purchases go through the sandbox for free.
+    TestFlight,
+    /// Debuggable or sideloaded Android build. Play has no sandbox, so test
+    /// purchases come from license testers on the production store.
+    AndroidTest,
+}
 
-    #[test]
-    fn test_default_product_type() {
-        assert_eq!(default_product_type(), "subs");
-    }
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetEnvironmentResponse {
+    pub environment: IapEnvironment,
+}
 
-    #[test]
-    fn test_purchase_state_value_serialize() {
-        assert_eq!(
-            serde_json::to_string(&PurchaseStateValue::Purchased)
-                .expect("Failed to serialize Purchased state"),
-            "0"
-        );
-        assert_eq!(
-            serde_json::to_string(&PurchaseStateValue::Canceled)
-                .expect("Failed to serialize Canceled state"),
-            "1"
-        );
-        assert_eq!(
-            serde_json::to_string(&PurchaseStateValue::Pending)
-                .expect("Failed to serialize Pending state"),
-            "2"
-        );
-    }
+/// Store platform a [`PurchaseReceipt`] came from, which tells the backend
+/// which verification API to call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    Ios,
+    MacOs,
+    Android,
+    Windows,
+}
 
-    #[test]
-    fn test_purchase_state_value_deserialize() {
-        assert_eq!(
-            serde_json::from_str::<PurchaseStateValue>("0")
-                .expect("Failed to deserialize Purchased state"),
-            PurchaseStateValue::Purchased
-        );
-        assert_eq!(
-            serde_json::from_str::<PurchaseStateValue>("1")
-                .expect("Failed to deserialize Canceled state"),
-            PurchaseStateValue::Canceled
-        );
-        assert_eq!(
-            serde_json::from_str::<PurchaseStateValue>("2")
-                .expect("Failed to deserialize Pending state"),
-            PurchaseStateValue::Pending
-        );
-    }
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetReceiptRequest {
+    pub transaction_id: String,
+}
 
-    #[test]
-    fn test_purchase_state_value_deserialize_invalid() {
-        let result = serde_json::from_str::<PurchaseStateValue>("3");
-        assert!(result.is_err());
-        let err = result
-            .expect_err("Expected error for invalid state")
-            .to_string();
-        assert!(err.contains("Invalid purchase state: 3"));
-    }
+/// What a backend needs to verify a purchase with the store.
+///
+/// On iOS/macOS `receipt_data` is the base64-encoded App Store receipt for
+/// `verifyReceipt`/the App Store Server API. On Android it is the purchase's
+/// `originalJson` and `purchase_token` carries the token for the Play
+/// Developer API.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurchaseReceipt {
+    pub platform: Platform,
+    pub receipt_data: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purchase_token: Option<String>,
+    pub product_id: String,
+    pub transaction_id: String,
+}
 
-    #[test]
-    fn test_purchase_state_value_roundtrip() {
-        for state in [
-            PurchaseStateValue::Purchased,
-            PurchaseStateValue::Canceled,
-            PurchaseStateValue::Pending,
-        ] {
-            let serialized =
-                serde_json::to_string(&state).expect("Failed to serialize PurchaseStateValue");
-            let deserialized: PurchaseStateValue = serde_json::from_str(&serialized)
-                .expect("Failed to deserialize PurchaseStateValue");
-            assert_eq!(state, deserialized);
-        }
-    }
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetAppReceiptRequest {
+    /// Run `SKReceiptRefreshRequest` when the app has no receipt yet.
+    #[serde(default)]
+    pub refresh_if_missing: bool,
+}
 
-    #[test]
-    fn test_initialize_response_default() {
+/// The whole base64-encoded App Store receipt, for legacy `verifyReceipt`
+/// validation.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetAppReceiptResponse {
+    pub receipt_data: String,
+}
+
+/// Whether this device can buy anything right now, so a paywall can explain
+/// a disabled purchase button instead of waiting for `purchase` to fail.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanMakePaymentsResponse {
+    pub can_make_payments: bool,
+    /// Why purchasing is unavailable; `None` when `can_make_payments` is
+    /// true. One of `"restricted"` (Screen Time, parental controls or device
+    /// management on iOS/macOS), `"billingUnavailable"` (the store connection
+    /// isn't up), `"billingUnsupported"` (the Play Store app is too old) or
+    /// `"notSupported"` (no store on this platform).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Whether the plugin can talk to a store on this platform at all, so the
+/// frontend can hide purchase UI up front.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IsSupportedResponse {
+    pub is_supported: bool,
+    /// Why the plugin is unusable; `None` when `is_supported` is true. One of
+    /// `"storeKitUnavailable"` (macOS older than `StoreKit` 2 requires) or
+    /// `"notSupported"` (no store on this platform).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// How the running macOS binary is signed, read with
+/// `SecCodeCopySigningInformation`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningInfo {
+    /// Apple Developer team identifier; `None` for unsigned or ad-hoc
+    /// signed builds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team_id: Option<String>,
+    /// Common name of the leaf signing certificate, e.g.
+    /// `"Apple Development: Jane Doe (ABCDE12345)"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject_cn: Option<String>,
+    pub distribution_type: DistributionType,
+}
+
+/// Distribution channel inferred from the signing certificate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DistributionType {
+    /// Signed with an Apple Development (or legacy Mac Developer) certificate.
+    Development,
+    /// App Store signed, running with a sandbox receipt.
+    TestFlight,
+    AppStore,
+    /// Unsigned, ad-hoc or Developer ID signed.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Name of the event carrying a [`Purchase`] that completes a deferred one.
+///
+/// Emitted on iOS and macOS, alongside the usual `purchaseUpdated`, once a
+/// purchase that returned [`PurchaseState::Deferred`] is approved, e.g. by
+/// the parent under Ask to Buy. With
+/// [`IapConfig::auto_finish_unfinished`](crate::IapConfig::auto_finish_unfinished)
+/// set, `initialize` also emits it, without `purchaseUpdated`, for every
+/// unfinished transaction it flushes.
+pub const PURCHASE_COMPLETED_EVENT: &str = "purchaseCompleted";
+
+/// Name of the event carrying [`PurchaseRevokedEvent`] payloads, emitted on
+/// iOS and macOS when `Transaction.updates` delivers a revoked transaction.
+pub const PURCHASE_REVOKED_EVENT: &str = "purchaseRevoked";
+
+/// A refund or Family Sharing removal took away a transaction's entitlement.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurchaseRevokedEvent {
+    pub product_id: String,
+    pub transaction_id: String,
+    pub revocation_time: Timestamp,
+    /// `"developerIssue"` or `"other"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation_reason: Option<String>,
+}
+
+/// Name of the event carrying [`PriceIncreasePendingEvent`] payloads.
+///
+/// Emitted on iOS and macOS when a transaction update or
+/// `get_subscription_status` finds a subscription waiting on price-increase
+/// consent.
+pub const PRICE_INCREASE_PENDING_EVENT: &str = "priceIncreasePending";
+
+/// A subscription will lapse at its next renewal unless the user agrees to
+/// the new price.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceIncreasePendingEvent {
+    pub product_id: String,
+    pub original_transaction_id: String,
+}
+
+/// Name of the event carrying [`StoreMessageEvent`] payloads, emitted on
+/// iOS 16+ for every `StoreKit` message the App Store wants to show.
+pub const STORE_MESSAGE_RECEIVED_EVENT: &str = "storeMessageReceived";
+
+/// `StoreKit` `Message.Reason`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StoreMessageReason {
+    Generic,
+    /// The user must agree to a subscription price increase.
+    PriceIncreaseConsent,
+    /// A renewal failed on a billing problem (iOS 16.4+).
+    BillingIssue,
+    /// A win-back offer for a lapsed subscription (iOS 18+).
+    WinBackOffer,
+    #[serde(other)]
+    Unknown,
+}
+
+/// An App Store message arrived.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreMessageEvent {
+    pub reason: StoreMessageReason,
+    /// Held for `display_pending_store_messages` because
+    /// [`IapConfig::defer_store_messages`](crate::IapConfig::defer_store_messages)
+    /// is on; otherwise it was shown right away.
+    pub deferred: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisplayPendingStoreMessagesResponse {
+    /// Deferred messages shown by this call.
+    pub displayed: u32,
+}
+
+/// Name of the event carrying [`RenewalPreferenceChangedEvent`] payloads.
+///
+/// Emitted on iOS and macOS when a subscription's auto-renew product or
+/// auto-renew setting differs from the last status the plugin saw.
+pub const RENEWAL_PREFERENCE_CHANGED_EVENT: &str = "renewalPreferenceChanged";
+
+/// The user scheduled a plan change or turned auto-renew on or off.
+///
+/// A downgrade keeps `product_id` active until renewal, so
+/// `auto_renew_product_id` names the plan that takes over at
+/// [`SubscriptionStatus::expiration_date`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenewalPreferenceChangedEvent {
+    pub product_id: String,
+    pub original_transaction_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_renew_product_id: Option<String>,
+    pub will_auto_renew: bool,
+}
+
+/// Name of the event carrying [`BillingIssueDetectedEvent`] payloads.
+///
+/// Emitted on iOS and macOS the first time the plugin sees a subscription
+/// enter the grace period or billing retry.
+pub const BILLING_ISSUE_DETECTED_EVENT: &str = "billingIssueDetected";
+
+/// A renewal charge failed, e.g. on an expired card. Prompt the user to
+/// update their payment method; during the grace period they keep access
+/// until `grace_period_expiration_date`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BillingIssueDetectedEvent {
+    pub product_id: String,
+    pub original_transaction_id: String,
+    /// `InGracePeriod` or `InBillingRetry`.
+    pub state: SubscriptionState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grace_period_expiration_date: Option<Timestamp>,
+}
+
+/// Name of the event carrying [`StorefrontInfo`] payloads, emitted on iOS and
+/// macOS when the user's storefront changes (Apple ID switch, region change).
+/// Prices fetched before the change are stale.
+pub const STOREFRONT_CHANGED_EVENT: &str = "storefrontChanged";
+
+/// The App Store storefront (or Play country) the user is buying from.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorefrontInfo {
+    /// `StoreKit`'s storefront identifier (e.g. `"143441"`). Play has no
+    /// separate identifier, so Android reports the country code here too.
+    pub id: String,
+    /// ISO 3166-1 alpha-3 on iOS/macOS (`"USA"`), alpha-2 on Android (`"US"`).
+    pub country_code: String,
+}
+
+/// Whether the app may send the user to its own website to buy, through
+/// `StoreKit`'s `ExternalPurchaseLink` (EU storefronts and other regions that
+/// allow it).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalPurchaseLinkAvailability {
+    /// `ExternalPurchaseLink.canOpen`: the app has the external purchase
+    /// link entitlement and the current storefront qualifies.
+    pub eligible: bool,
+    /// ISO 3166-1 alpha-3 code of the current storefront, when `StoreKit`
+    /// has loaded one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storefront_country: Option<String>,
+}
+
+/// Keep in sync with `PurchaseState` in `guest-js/index.ts`
+///
+/// Serialized as the integer code the Swift and Kotlin backends send.
+/// Codes this version doesn't know about deserialize into `Unknown` holding
+/// the raw value, so a newer backend can't break older Rust code.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PurchaseState {
+    Purchased,
+    Canceled,
+    /// Play pending payment, e.g. cash at a convenience store.
+    Pending,
+    Failed,
+    Refunded,
+    /// Waiting on approval (Ask to Buy) or a payment confirmation on
+    /// iOS/macOS. The purchase has no transaction yet; the approved one
+    /// arrives through [`PURCHASE_COMPLETED_EVENT`].
+    Deferred,
+    Unknown(String),
+}
+
+/// Former name of [`PurchaseState`], kept so existing imports still compile.
+pub type PurchaseStateValue = PurchaseState;
+
+impl PurchaseState {
+    /// Integer wire code, or `None` for `Unknown` values that weren't numeric.
+    #[must_use]
+    pub fn code(&self) -> Option<i64> {
+        match self {
+            Self::Purchased => Some(0),
+            Self::Canceled => Some(1),
+            Self::Pending => Some(2),
+            Self::Failed => Some(3),
+            Self::Refunded => Some(4),
+            Self::Deferred => Some(5),
+            Self::Unknown(raw) => raw.parse().ok(),
+        }
+    }
+
+    fn from_code(code: i64) -> Self {
+        match code {
+            0 => Self::Purchased,
+            1 => Self::Canceled,
+            2 => Self::Pending,
+            3 => Self::Failed,
+            4 => Self::Refunded,
+            5 => Self::Deferred,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for PurchaseState {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Unknown(raw) => match raw.parse::<i64>() {
+                Ok(code) => serializer.serialize_i64(code),
+                Err(_) => serializer.serialize_str(raw),
+            },
+            known => serializer.serialize_i64(known.code().unwrap_or_default()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PurchaseState {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct PurchaseStateVisitor;
+
+        impl serde::de::Visitor<'_> for PurchaseStateVisitor {
+            type Value = PurchaseState;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("an integer purchase state")
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Self::Value, E> {
+                Ok(PurchaseState::from_code(value))
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(i64::try_from(value).map_or_else(
+                    |_| PurchaseState::Unknown(value.to_string()),
+                    PurchaseState::from_code,
+                ))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                Ok(value.parse().map_or_else(
+                    |_| PurchaseState::Unknown(value.to_string()),
+                    PurchaseState::from_code,
+                ))
+            }
+        }
+
+        deserializer.deserialize_any(PurchaseStateVisitor)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetProductStatusRequest {
+    pub product_id: String,
+    #[serde(default)]
+    pub product_type: ProductType,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductStatus {
+    pub product_id: String,
+    /// Whether the user has access right now. A subscription in its billing
+    /// grace period counts as owned.
+    pub is_owned: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purchase_state: Option<PurchaseState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purchase_time: Option<Timestamp>,
+    /// Never set for a [`ProductType::NonRenewingSubscription`], which stays
+    /// owned until the app decides its period is over.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration_time: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_auto_renewing: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_acknowledged: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purchase_token: Option<String>,
+    /// Renewal details for owned auto-renewable subscriptions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renewal_info: Option<SubscriptionRenewalInfo>,
+    /// The product's most recent transaction, from `Product.latestTransaction`
+    /// on iOS/macOS. Kept when revoked or expired, with `revocation_time` or
+    /// `expiration_date` set, so the UI can say when access ended. Android
+    /// fills it only for an active purchase.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_transaction: Option<Purchase>,
+    /// Served from the offline entitlement cache because the store couldn't
+    /// be reached; see
+    /// [`IapConfig::offline_entitlement_max_age_secs`](crate::IapConfig::offline_entitlement_max_age_secs).
+    /// Only `is_owned` and `expiration_time` are filled in then.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub from_cache: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetAllProductStatusesRequest {
+    #[serde(default)]
+    pub product_type: ProductType,
+}
+
+/// A [`ProductStatus`] for every product of one type the user currently
+/// owns, read with a single store query.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetAllProductStatusesResponse {
+    pub statuses: Vec<ProductStatus>,
+}
+
+/// `StoreKit` `RenewalInfo.PriceIncreaseStatus`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PriceIncreaseStatus {
+    NoIncreasePending,
+    /// The user hasn't responded to a price increase that requires consent yet.
+    Pending,
+    Agreed,
+}
+
+/// Renewal state of an owned subscription, from `StoreKit`'s `RenewalInfo`
+/// on iOS/macOS. Google Play only reports `auto_renew_enabled` client-side;
+/// the other fields need the Play Developer API.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionRenewalInfo {
+    pub auto_renew_enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration_date: Option<Timestamp>,
+    /// `true` while the store retries a failed renewal charge.
+    #[serde(default)]
+    pub billing_retry_period: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grace_period_expires_date: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_increase_status: Option<PriceIncreaseStatus>,
+    /// Product the subscription renews into at the next billing date.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renewal_product_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetSubscriptionStatusRequest {
+    pub product_id: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetWinBackOffersRequest {
+    pub product_id: String,
+}
+
+/// A `StoreKit` win-back offer and whether the current user can redeem it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WinBackOffer {
+    #[serde(flatten)]
+    pub offer: SubscriptionOfferInfo,
+    /// Listed in the user's `RenewalInfo.eligibleWinBackOfferIDs`.
+    pub is_eligible: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetWinBackOffersResponse {
+    /// Empty on OS versions without win-back offers (before iOS 18/macOS 15).
+    pub offers: Vec<WinBackOffer>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IsEligibleForIntroOfferRequest {
+    pub product_id: String,
+}
+
+/// Whether the user can still redeem a subscription's introductory offer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IntroOfferEligibility {
+    Eligible,
+    Ineligible,
+    /// The store doesn't report eligibility, e.g. a Play subscription without
+    /// a free trial offer for this account.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IsEligibleForIntroOfferResponse {
+    pub eligibility: IntroOfferEligibility,
+    /// `StoreKit` subscription group the eligibility applies to; `None` on
+    /// Android.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subscription_group_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckIntroductoryOfferEligibilityRequest {
+    pub product_ids: Vec<String>,
+}
+
+/// Batch form of [`IsEligibleForIntroOfferResponse`] for paywalls listing
+/// several plans.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckIntroductoryOfferEligibilityResponse {
+    /// Keyed by product id. Unknown ids and products that aren't
+    /// auto-renewable subscriptions are left out. On Android `false` also
+    /// covers subscriptions without a free trial offer, since Play only
+    /// lists the offers an account may still redeem.
+    pub eligibility: HashMap<String, bool>,
+}
+
+/// `StoreKit` `Product.SubscriptionInfo.RenewalState`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SubscriptionState {
+    Subscribed,
+    Expired,
+    /// Renewal failed on a billing issue and the App Store is still retrying.
+    InBillingRetry,
+    /// Renewal failed but the user keeps access until the grace period ends.
+    InGracePeriod,
+    /// Refunded or removed from Family Sharing.
+    Revoked,
+    #[serde(other)]
+    Unknown,
+}
+
+/// `StoreKit` `Product.SubscriptionInfo.RenewalInfo.ExpirationReason`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExpirationReason {
+    AutoRenewDisabled,
+    BillingError,
+    DidNotConsentToPriceIncrease,
+    ProductUnavailable,
+    #[serde(other)]
+    Unknown,
+}
+
+/// One entry of a subscription group's `Product.SubscriptionInfo.status`.
+/// Family Sharing can give a user several, one per purchaser.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionStatus {
+    /// Product of the latest transaction; may differ from the queried product
+    /// when the user switched plans within the subscription group.
+    pub product_id: String,
+    pub state: SubscriptionState,
+    pub transaction_id: String,
+    pub original_transaction_id: String,
+    /// `true` when access comes from a family member's purchase.
+    #[serde(default)]
+    pub is_family_shared: bool,
+    /// Subscription group of `product_id`, matching
+    /// [`Product::subscription_group_id`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription_group_id: Option<String>,
+    pub will_auto_renew: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration_date: Option<Timestamp>,
+    /// Set while `state` is `InGracePeriod`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grace_period_expiration_date: Option<Timestamp>,
+    /// `RenewalInfo.isInBillingRetry`: the store is still retrying the
+    /// renewal charge. Stays `true` through the grace period, unlike
+    /// [`Self::is_in_billing_retry`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub billing_retry_period: bool,
+    /// Product the subscription renews into; differs from `product_id` after
+    /// a pending upgrade, downgrade or crossgrade.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_renew_product_id: Option<String>,
+    /// Why the subscription expired; only set once `state` is `Expired`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration_reason: Option<ExpirationReason>,
+    /// Whether the user still has to consent to a price increase.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_increase_status: Option<PriceIncreaseStatus>,
+    /// What the next renewal will charge, from `RenewalInfo.renewalPrice`,
+    /// in micros like [`Product::price_amount_micros`]. Reflects a price
+    /// increase once it applies to this user. iOS 17+ and macOS 14+ only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renewal_price_amount_micros: Option<i64>,
+    /// ISO 4217 currency of [`Self::renewal_price_amount_micros`], set
+    /// together with it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renewal_currency: Option<String>,
+}
+
+impl SubscriptionStatus {
+    /// Whether the user should have access right now: subscribed, or in the
+    /// grace period after a failed renewal.
+    #[must_use]
+    pub const fn is_active(&self) -> bool {
+        matches!(
+            self.state,
+            SubscriptionState::Subscribed | SubscriptionState::InGracePeriod
+        )
+    }
+
+    #[must_use]
+    pub const fn is_in_grace_period(&self) -> bool {
+        matches!(self.state, SubscriptionState::InGracePeriod)
+    }
+
+    /// Renewal failed and the store is still retrying; access has lapsed.
+    #[must_use]
+    pub const fn is_in_billing_retry(&self) -> bool {
+        matches!(self.state, SubscriptionState::InBillingRetry)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetSubscriptionStatusResponse {
+    pub statuses: Vec<SubscriptionStatus>,
+}
+
+/// Name of the event carrying [`TransactionEvent`] payloads.
+pub const TRANSACTION_UPDATED_EVENT: &str = "transactionUpdated";
+
+/// Lifecycle stage of a store transaction.
+///
+/// Serialized with a `type` tag, e.g.
+/// `{"type":"purchased","transactionId":"2000000123"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum TransactionState {
+    /// The purchase sheet is up; emitted before the store returns.
+    Purchasing,
+    Purchased {
+        transaction_id: String,
+    },
+    Failed {
+        error: IapErrorCode,
+    },
+    /// A previous purchase was re-delivered by a restore.
+    Restored {
+        original_transaction_id: String,
+    },
+    /// Waiting on external approval (Ask to Buy, Play pending payment).
+    Deferred,
+    /// Refunded or otherwise revoked by the store.
+    Revoked,
+    /// The plugin acknowledged the purchase itself because
+    /// [`IapConfig::auto_acknowledge`](crate::IapConfig::auto_acknowledge) is
+    /// on (Android).
+    AutoAcknowledged {
+        purchase_token: String,
+    },
+}
+
+/// Payload of the `transactionUpdated` event, identical on every platform.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionEvent {
+    pub product_id: String,
+    pub state: TransactionState,
+    /// Epoch milliseconds at which the platform observed the change.
+    pub timestamp: u64,
+    /// Set on iOS/macOS for `Purchased` and `Revoked` states, like
+    /// [`Purchase::verification`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<TransactionVerification>,
+}
+
+impl TransactionEvent {
+    /// Event for `product_id` stamped with the current time.
+    pub fn new(product_id: impl Into<String>, state: TransactionState) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| {
+                u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX)
+            });
+        Self {
+            product_id: product_id.into(),
+            state,
+            timestamp,
+            verification: None,
+        }
+    }
+}
+
+/// How macOS retries a store call that failed with a transient error, set
+/// through [`IapConfig::retry_policy`](crate::IapConfig::retry_policy).
+/// Fields left out of the config keep their [`Default`] values.
+///
+/// `purchase` is deliberately never retried, since the user may already have
+/// confirmed the payment when the error arrives, and neither is
+/// `restore_purchases` with `force_sync`, which would show the App Store
+/// sign-in prompt again.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetryPolicy {
+    /// Calls made in total, including the first; 1 disables retries.
+    pub max_attempts: u32,
+    /// Wait before the first retry.
+    pub initial_delay_ms: u64,
+    /// Factor applied to the wait after each retry.
+    pub backoff_multiplier: f64,
+    /// Errors worth retrying, matched against [`crate::Error::error_code`].
+    pub retryable_codes: Vec<IapErrorCode>,
+}
+
+impl Default for RetryPolicy {
+    /// 3 attempts, 500 ms apart and doubling, on
+    /// [`IapErrorCode::NetworkError`] and [`IapErrorCode::ServiceUnavailable`].
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay_ms: 500,
+            backoff_multiplier: 2.0,
+            retryable_codes: vec![IapErrorCode::NetworkError, IapErrorCode::ServiceUnavailable],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Whether `err` carries one of [`Self::retryable_codes`].
+    #[must_use]
+    pub fn is_retryable(&self, err: &crate::Error) -> bool {
+        err.error_code()
+            .is_some_and(|code| self.retryable_codes.contains(&code))
+    }
+}
+
+/// Timing of the most recent call to each native store method, from
+/// `get_diagnostics` on macOS.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IapDiagnostics {
+    /// Keyed by the Swift method name, e.g. `"getProducts"`.
+    pub last_call_durations: BTreeMap<String, Duration>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_product_type() {
+        assert_eq!(
+            ProductType::default(),
+            ProductType::AutoRenewableSubscription
+        );
+        assert_eq!(String::from(ProductType::default()), "subs");
+    }
+
+    #[test]
+    fn test_product_type_serialize() {
+        for (product_type, expected) in [
+            (ProductType::Consumable, r#""inapp""#),
+            (ProductType::NonConsumable, r#""inapp""#),
+            (ProductType::AutoRenewableSubscription, r#""subs""#),
+            (ProductType::NonRenewingSubscription, r#""subs""#),
+        ] {
+            assert_eq!(
+                serde_json::to_string(&product_type).expect("Failed to serialize ProductType"),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_product_type_deserialize() {
+        assert_eq!(
+            serde_json::from_str::<ProductType>(r#""subs""#).expect("Failed to deserialize subs"),
+            ProductType::AutoRenewableSubscription
+        );
+        assert_eq!(
+            serde_json::from_str::<ProductType>(r#""inapp""#).expect("Failed to deserialize inapp"),
+            ProductType::NonConsumable
+        );
+        assert_eq!(
+            serde_json::from_str::<ProductType>(r#""consumable""#)
+                .expect("Failed to deserialize consumable"),
+            ProductType::Consumable
+        );
+        // StoreKit reports a transaction's type by its variant name.
+        assert_eq!(
+            serde_json::from_str::<ProductType>(r#""nonRenewingSubscription""#)
+                .expect("Failed to deserialize nonRenewingSubscription"),
+            ProductType::NonRenewingSubscription
+        );
+        assert!(ProductType::NonRenewingSubscription.is_subscription());
+        let err = serde_json::from_str::<ProductType>(r#""sub""#)
+            .expect_err("Expected error for invalid product type")
+            .to_string();
+        assert!(err.contains("Invalid product type: sub"));
+    }
+
+    #[test]
+    fn test_purchase_state_serialize() {
+        assert_eq!(
+            serde_json::to_string(&PurchaseState::Purchased)
+                .expect("Failed to serialize Purchased state"),
+            "0"
+        );
+        assert_eq!(
+            serde_json::to_string(&PurchaseState::Canceled)
+                .expect("Failed to serialize Canceled state"),
+            "1"
+        );
+        assert_eq!(
+            serde_json::to_string(&PurchaseState::Pending)
+                .expect("Failed to serialize Pending state"),
+            "2"
+        );
+    }
+
+    #[test]
+    fn test_purchase_state_deserialize() {
+        assert_eq!(
+            serde_json::from_str::<PurchaseState>("0")
+                .expect("Failed to deserialize Purchased state"),
+            PurchaseState::Purchased
+        );
+        assert_eq!(
+            serde_json::from_str::<PurchaseState>("1")
+                .expect("Failed to deserialize Canceled state"),
+            PurchaseState::Canceled
+        );
+        assert_eq!(
+            serde_json::from_str::<PurchaseState>("2")
+                .expect("Failed to deserialize Pending state"),
+            PurchaseState::Pending
+        );
+    }
+
+    #[test]
+    fn test_purchase_state_deserialize_unknown() {
+        assert_eq!(
+            serde_json::from_str::<PurchaseState>("42")
+                .expect("Failed to deserialize unknown state"),
+            PurchaseState::Unknown("42".to_string())
+        );
+        assert_eq!(
+            serde_json::to_string(&PurchaseState::Unknown("42".to_string()))
+                .expect("Failed to serialize unknown state"),
+            "42"
+        );
+        assert!(serde_json::from_str::<PurchaseState>("true").is_err());
+    }
+
+    #[test]
+    fn test_purchase_state_roundtrip() {
+        for state in [
+            PurchaseState::Purchased,
+            PurchaseState::Canceled,
+            PurchaseState::Pending,
+            PurchaseState::Failed,
+            PurchaseState::Refunded,
+            PurchaseState::Deferred,
+        ] {
+            let serialized =
+                serde_json::to_string(&state).expect("Failed to serialize PurchaseState");
+            let deserialized: PurchaseState =
+                serde_json::from_str(&serialized).expect("Failed to deserialize PurchaseState");
+            assert_eq!(state, deserialized);
+        }
+    }
+
+    #[test]
+    fn test_initialize_response_default() {
         let response = InitializeResponse::default();
         assert!(!response.success);
     }
 
     #[test]
-    fn test_initialize_response_serde() {
-        let response = InitializeResponse { success: true };
-        let json =
-            serde_json::to_string(&response).expect("Failed to serialize InitializeResponse");
-        assert_eq!(json, r#"{"success":true}"#);
+    fn test_initialize_response_serde() {
+        let response = InitializeResponse {
+            success: true,
+            finished_transactions: None,
+        };
+        let json =
+            serde_json::to_string(&response).expect("Failed to serialize InitializeResponse");
+        assert_eq!(json, r#"{"success":true}"#);
+
+        let deserialized: InitializeResponse =
+            serde_json::from_str(&json).expect("Failed to deserialize InitializeResponse");
+        assert!(deserialized.success);
+
+        let response = InitializeResponse {
+            success: true,
+            finished_transactions: Some(2),
+        };
+        let json =
+            serde_json::to_string(&response).expect("Failed to serialize InitializeResponse");
+        assert_eq!(json, r#"{"success":true,"finishedTransactions":2}"#);
+    }
+
+    #[test]
+    fn test_finish_unfinished_transactions_response_serde() {
+        let response: FinishUnfinishedTransactionsResponse =
+            serde_json::from_str(r#"{"finished":3}"#)
+                .expect("Failed to deserialize FinishUnfinishedTransactionsResponse");
+        assert_eq!(response.finished, 3);
+    }
+
+    #[test]
+    fn test_get_products_request_default_product_type() {
+        let json = r#"{"productIds":["product1","product2"]}"#;
+        let request: GetProductsRequest =
+            serde_json::from_str(json).expect("Failed to deserialize GetProductsRequest");
+        assert_eq!(request.product_ids, vec!["product1", "product2"]);
+        assert_eq!(request.product_type, ProductType::AutoRenewableSubscription);
+    }
+
+    #[test]
+    fn test_get_products_request_explicit_product_type() {
+        let json = r#"{"productIds":["product1"],"productType":"inapp"}"#;
+        let request: GetProductsRequest =
+            serde_json::from_str(json).expect("Failed to deserialize GetProductsRequest");
+        assert_eq!(request.product_type, ProductType::NonConsumable);
+        assert!(!request.force_refresh);
+    }
+
+    #[test]
+    fn test_get_products_request_force_refresh() {
+        let json = r#"{"productIds":["product1"],"productType":"inapp","forceRefresh":true}"#;
+        let request: GetProductsRequest =
+            serde_json::from_str(json).expect("Failed to deserialize GetProductsRequest");
+        assert!(request.force_refresh);
+    }
+
+    #[test]
+    fn test_product_optional_fields_skip_serializing() {
+        let product = Product {
+            product_id: "test".to_string(),
+            title: "Test Product".to_string(),
+            description: "A test product".to_string(),
+            product_type: "inapp".to_string(),
+            formatted_price: None,
+            price_currency_code: None,
+            price_amount_micros: None,
+            price_locale: None,
+            subscription_offer_details: None,
+            subscription_period: None,
+            introductory_offer: None,
+            promotional_offers: Vec::new(),
+            subscription_group_id: None,
+            is_family_shareable: None,
+        };
+        let json = serde_json::to_string(&product).expect("Failed to serialize Product");
+        assert!(!json.contains("formattedPrice"));
+        assert!(!json.contains("priceCurrencyCode"));
+        assert!(!json.contains("priceAmountMicros"));
+        assert!(!json.contains("subscriptionOfferDetails"));
+        assert!(!json.contains("subscriptionPeriod"));
+        assert!(!json.contains("introductoryOffer"));
+        assert!(!json.contains("promotionalOffers"));
+        assert!(!json.contains("subscriptionGroupId"));
+    }
+
+    #[test]
+    fn test_product_from_storekit_subscription_fixture() {
+        // Shape produced by getProducts in macos/Sources/IapPlugin.swift
+        let json = r#"{
+            "productId": "com.example.premium.monthly",
+            "title": "Premium",
+            "description": "All features",
+            "productType": "autoRenewable",
+            "formattedPrice": "$4.99",
+            "priceCurrencyCode": "USD",
+            "subscriptionOfferDetails": [
+                {"offerToken": "", "basePlanId": "", "offerId": "", "pricingPhases": [
+                    {"formattedPrice": "$0.00", "priceCurrencyCode": "USD", "priceAmountMicros": 0,
+                     "billingPeriod": "P1W", "billingCycleCount": 1, "recurrenceMode": 0}]},
+                {"offerToken": "", "basePlanId": "", "offerId": "", "pricingPhases": [
+                    {"formattedPrice": "$4.99", "priceCurrencyCode": "USD", "priceAmountMicros": 4990000,
+                     "billingPeriod": "P1M", "billingCycleCount": 0, "recurrenceMode": 1}]}
+            ],
+            "subscriptionPeriod": {"unit": "month", "value": 1},
+            "introductoryOffer": {"offerType": "introductory", "price": "$0.00", "priceLocale": "en_US",
+                "period": {"unit": "week", "value": 1}, "periodCount": 1, "paymentMode": "freeTrial"},
+            "promotionalOffers": [
+                {"id": "loyal_50", "offerType": "promotional", "price": "$2.49", "priceLocale": "en_US",
+                 "period": {"unit": "month", "value": 1}, "periodCount": 3, "paymentMode": "payAsYouGo"}
+            ],
+            "subscriptionGroupId": "21345678"
+        }"#;
+        let product: Product =
+            serde_json::from_str(json).expect("Failed to deserialize StoreKit product");
+
+        assert_eq!(
+            product.subscription_period,
+            Some(SubscriptionPeriod {
+                unit: PeriodUnit::Month,
+                value: 1
+            })
+        );
+        let intro = product
+            .introductory_offer
+            .expect("Expected introductory offer");
+        assert!(intro.id.is_none());
+        assert_eq!(intro.payment_mode, PaymentMode::FreeTrial);
+        assert_eq!(intro.period.unit, PeriodUnit::Week);
+        assert_eq!(intro.period_count, 1);
+        assert_eq!(
+            product.promotional_offers[0].id.as_deref(),
+            Some("loyal_50")
+        );
+        assert_eq!(product.promotional_offers[0].price, "$2.49");
+        assert_eq!(product.subscription_group_id.as_deref(), Some("21345678"));
+    }
+
+    #[test]
+    fn test_product_from_storekit_non_subscription_fixture() {
+        let json = r#"{"productId":"coins_100","title":"100 Coins","description":"","productType":"consumable","formattedPrice":"$0.99","priceCurrencyCode":"USD","priceAmountMicros":990000}"#;
+        let product: Product =
+            serde_json::from_str(json).expect("Failed to deserialize StoreKit product");
+        assert!(product.subscription_period.is_none());
+        assert!(product.introductory_offer.is_none());
+        assert!(product.promotional_offers.is_empty());
+        assert!(product.subscription_group_id.is_none());
+    }
+
+    #[test]
+    fn test_product_price_without_decimals_round_trip() {
+        let json = r#"{"productId":"coins_jp","title":"Coins","description":"","productType":"consumable","formattedPrice":"¥120","priceCurrencyCode":"JPY","priceAmountMicros":120000000}"#;
+        let product: Product = serde_json::from_str(json).expect("Failed to deserialize Product");
+        assert_eq!(product.price_currency_code.as_deref(), Some("JPY"));
+        assert_eq!(product.price_amount_micros, Some(120_000_000));
+
+        let serialized = serde_json::to_string(&product).expect("Failed to serialize Product");
+        assert_eq!(serialized, json);
+    }
+
+    #[test]
+    fn test_product_price_info() {
+        let json = r#"{"productId":"coins_jp","title":"Coins","description":"","productType":"consumable","formattedPrice":"¥120","priceCurrencyCode":"JPY","priceAmountMicros":120000000,"priceLocale":"ja_JP"}"#;
+        let product: Product = serde_json::from_str(json).expect("Failed to deserialize Product");
+
+        assert_eq!(
+            product.price_info(),
+            Some(PriceInfo {
+                amount: 120.0,
+                currency_code: "JPY".to_string(),
+                formatted_price: "¥120".to_string(),
+                locale_identifier: "ja_JP".to_string(),
+            })
+        );
+        let serialized = serde_json::to_string(&product).expect("Failed to serialize Product");
+        assert_eq!(serialized, json);
+
+        // Windows reports no locale; a product without a price has no info.
+        let windows: Product = serde_json::from_str(&json.replace(r#","priceLocale":"ja_JP""#, ""))
+            .expect("Failed to deserialize Product");
+        let info = windows.price_info().expect("Expected price info");
+        assert!(info.locale_identifier.is_empty());
+        let unpriced: Product = serde_json::from_str(
+            r#"{"productId":"premium","title":"Premium","description":"","productType":"subs"}"#,
+        )
+        .expect("Failed to deserialize Product");
+        assert_eq!(unpriced.price_info(), None);
+    }
+
+    #[test]
+    fn test_product_price_above_i32_micros_round_trip() {
+        // ¥12,000 is 12 billion micros, well past i32::MAX
+        let json = r#"{"productId":"lifetime_jp","title":"Lifetime","description":"","productType":"nonConsumable","formattedPrice":"¥12,000","priceCurrencyCode":"JPY","priceAmountMicros":12000000000}"#;
+        let product: Product = serde_json::from_str(json).expect("Failed to deserialize Product");
+        let micros = product.price_amount_micros.expect("Expected price");
+        assert!(micros > i64::from(i32::MAX));
+        assert_eq!(micros, 12_000_000_000);
+
+        let serialized = serde_json::to_string(&product).expect("Failed to serialize Product");
+        assert_eq!(serialized, json);
+    }
+
+    #[test]
+    fn test_subscription_period_serde() {
+        let json = r#"{"productId":"premium","title":"Premium","description":"","productType":"subs","subscriptionPeriod":{"unit":"month","value":3}}"#;
+        let product: Product = serde_json::from_str(json).expect("Failed to deserialize Product");
+        assert_eq!(
+            product.subscription_period,
+            Some(SubscriptionPeriod {
+                unit: PeriodUnit::Month,
+                value: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn test_product_subscription_offers_serde() {
+        let json = r#"{"productId":"premium","title":"Premium","description":"","productType":"subs","introductoryOffer":{"id":null,"offerType":"introductory","price":"$0.00","priceLocale":"en_US","period":{"unit":"week","value":1},"periodCount":1,"paymentMode":"freeTrial"},"promotionalOffers":[{"id":"winback","offerType":"promotional","price":"$1.99","priceLocale":"en_US","period":{"unit":"month","value":1},"periodCount":3,"paymentMode":"payAsYouGo"}]}"#;
+        let product: Product = serde_json::from_str(json).expect("Failed to deserialize Product");
+
+        let intro = product
+            .introductory_offer
+            .expect("Expected introductory offer");
+        assert_eq!(intro.offer_type, SubscriptionOfferType::Introductory);
+        assert_eq!(intro.payment_mode, PaymentMode::FreeTrial);
+        assert_eq!(intro.period.unit, PeriodUnit::Week);
+        assert_eq!(product.promotional_offers.len(), 1);
+        assert_eq!(product.promotional_offers[0].id.as_deref(), Some("winback"));
+        assert_eq!(
+            product.promotional_offers[0].payment_mode,
+            PaymentMode::PayAsYouGo
+        );
+    }
+
+    #[test]
+    fn test_subscription_period_from_iso8601() {
+        assert_eq!(
+            SubscriptionPeriod::from_iso8601("P1W"),
+            Some(SubscriptionPeriod {
+                unit: PeriodUnit::Week,
+                value: 1,
+            })
+        );
+        assert_eq!(
+            SubscriptionPeriod::from_iso8601("P12M"),
+            Some(SubscriptionPeriod {
+                unit: PeriodUnit::Month,
+                value: 12,
+            })
+        );
+        assert_eq!(SubscriptionPeriod::from_iso8601("PT1H"), None);
+        assert_eq!(SubscriptionPeriod::from_iso8601("P1Y2M"), None);
+        assert_eq!(SubscriptionPeriod::from_iso8601("1M"), None);
+    }
+
+    #[test]
+    fn test_product_with_optional_fields() {
+        let product = Product {
+            product_id: "test".to_string(),
+            title: "Test Product".to_string(),
+            description: "A test product".to_string(),
+            product_type: "inapp".to_string(),
+            formatted_price: Some("$9.99".to_string()),
+            price_currency_code: Some("USD".to_string()),
+            price_amount_micros: Some(9_990_000),
+            price_locale: None,
+            subscription_offer_details: None,
+            subscription_period: None,
+            introductory_offer: None,
+            promotional_offers: Vec::new(),
+            subscription_group_id: None,
+            is_family_shareable: None,
+        };
+        let json = serde_json::to_string(&product).expect("Failed to serialize Product");
+        assert!(json.contains(r#""formattedPrice":"$9.99""#));
+        assert!(json.contains(r#""priceCurrencyCode":"USD""#));
+        assert!(json.contains(r#""priceAmountMicros":9990000"#));
+    }
+
+    #[test]
+    fn test_purchase_serde_roundtrip() {
+        let purchase = Purchase {
+            order_id: Some("order123".to_string()),
+            package_name: "com.example.app".to_string(),
+            product_id: "product1".to_string(),
+            purchase_time: Timestamp::from_unix_millis(1_700_000_000_000),
+            purchase_token: "token123".to_string(),
+            purchase_state: PurchaseState::Purchased,
+            is_auto_renewing: true,
+            is_acknowledged: false,
+            original_json: "{}".to_string(),
+            signature: "sig".to_string(),
+            original_id: None,
+            original_transaction_id: None,
+            web_order_line_item_id: None,
+            subscription_group_id: Some("21345678".to_string()),
+            jws_representation: Some("test_jws".to_string()),
+            ownership_type: Some(OwnershipType::FamilyShared),
+            transaction_reason: Some(TransactionReason::Renewal),
+            revocation_time: None,
+            revocation_reason: None,
+            environment: Some("sandbox".to_string()),
+            app_account_token: None,
+            quantity: None,
+            expiration_date: Some(Timestamp::from_unix_millis(1_702_592_000_000)),
+            next_renewal_date: None,
+            offer_id: Some("loyal_50".to_string()),
+            offer_type: Some("promotional".to_string()),
+            verification: Some(TransactionVerification::Verified),
+        };
+
+        let json = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
+        let deserialized: Purchase =
+            serde_json::from_str(&json).expect("Failed to deserialize Purchase");
+
+        assert_eq!(deserialized.order_id, purchase.order_id);
+        assert_eq!(deserialized.product_id, purchase.product_id);
+        assert_eq!(deserialized.purchase_time, purchase.purchase_time);
+        assert_eq!(deserialized.purchase_state, purchase.purchase_state);
+        assert_eq!(deserialized.is_auto_renewing, purchase.is_auto_renewing);
+        assert_eq!(
+            deserialized.ownership_type,
+            Some(OwnershipType::FamilyShared)
+        );
+        assert_eq!(deserialized.environment.as_deref(), Some("sandbox"));
+        assert_eq!(deserialized.expiration_date, purchase.expiration_date);
+        assert_eq!(deserialized.offer_id.as_deref(), Some("loyal_50"));
+        assert_eq!(deserialized.offer_type.as_deref(), Some("promotional"));
+        assert_eq!(
+            deserialized.subscription_group_id.as_deref(),
+            Some("21345678")
+        );
+    }
+
+    #[test]
+    fn test_purchase_ownership_type_serde() {
+        let json = r#"{"orderId":"1","packageName":"com.example.app","productId":"premium","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"1","purchaseState":0,"isAutoRenewing":true,"isAcknowledged":true,"originalJson":"","signature":"","originalId":"1","ownershipType":"familyShared"}"#;
+        let purchase: Purchase =
+            serde_json::from_str(json).expect("Failed to deserialize Purchase");
+        assert_eq!(purchase.ownership_type, Some(OwnershipType::FamilyShared));
+
+        let serialized = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
+        assert!(serialized.contains(r#""ownershipType":"familyShared""#));
+
+        let android = json.replace(r#","ownershipType":"familyShared""#, "");
+        let purchase: Purchase =
+            serde_json::from_str(&android).expect("Failed to deserialize Purchase");
+        assert!(purchase.ownership_type.is_none());
+        let serialized = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
+        assert!(!serialized.contains("ownershipType"));
+    }
+
+    #[test]
+    fn test_purchase_revocation_fields_serde() {
+        let json = r#"{"orderId":"1","packageName":"com.example.app","productId":"premium","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"1","purchaseState":1,"isAutoRenewing":false,"isAcknowledged":true,"originalJson":"","signature":"","originalId":"1","ownershipType":"familyShared","revocationTime":"2023-11-14T22:21:40.000Z","revocationReason":"other"}"#;
+        let purchase: Purchase =
+            serde_json::from_str(json).expect("Failed to deserialize Purchase");
+        assert_eq!(
+            purchase.revocation_time,
+            Some(Timestamp::from_unix_millis(1_700_000_500_000))
+        );
+        assert_eq!(purchase.revocation_reason.as_deref(), Some("other"));
+
+        let serialized = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
+        assert_eq!(serialized, json);
+    }
+
+    #[test]
+    fn test_purchase_environment_serde() {
+        let json = r#"{"orderId":"1","packageName":"com.example.app","productId":"premium","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"1","purchaseState":0,"isAutoRenewing":false,"isAcknowledged":true,"originalJson":"","signature":"","originalId":"1","environment":"xcode"}"#;
+        let purchase: Purchase =
+            serde_json::from_str(json).expect("Failed to deserialize Purchase");
+        assert_eq!(purchase.environment.as_deref(), Some("xcode"));
+
+        let serialized = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
+        assert_eq!(serialized, json);
+
+        let android = json.replace(r#","environment":"xcode""#, "");
+        let purchase: Purchase =
+            serde_json::from_str(&android).expect("Failed to deserialize Purchase");
+        assert!(purchase.environment.is_none());
+        let serialized = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
+        assert!(!serialized.contains("environment"));
+    }
+
+    #[test]
+    fn test_purchase_original_transaction_fields_serde() {
+        // A StoreKit renewal: new transaction id, same original id.
+        let json = r#"{"orderId":"2000000456","packageName":"com.example.app","productId":"monthly","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"2000000456","purchaseState":0,"isAutoRenewing":true,"isAcknowledged":true,"originalJson":"","signature":"","originalId":"2000000123","originalTransactionId":"2000000123","webOrderLineItemId":"2000000012345678"}"#;
+        let purchase: Purchase =
+            serde_json::from_str(json).expect("Failed to deserialize Purchase");
+        assert_eq!(
+            purchase.original_transaction_id.as_deref(),
+            Some("2000000123")
+        );
+        assert_eq!(
+            purchase.web_order_line_item_id.as_deref(),
+            Some("2000000012345678")
+        );
+        let serialized = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
+        assert_eq!(serialized, json);
+
+        // Payloads from before these fields, and from Android.
+        let without = json.replace(
+            r#","originalTransactionId":"2000000123","webOrderLineItemId":"2000000012345678""#,
+            "",
+        );
+        let purchase: Purchase =
+            serde_json::from_str(&without).expect("Failed to deserialize Purchase");
+        assert!(purchase.original_transaction_id.is_none());
+        assert!(purchase.web_order_line_item_id.is_none());
+        let serialized = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
+        assert_eq!(serialized, without);
+    }
+
+    #[test]
+    fn test_current_entitlement_response_without_purchase() {
+        let response = CurrentEntitlementResponse::default();
+        let json = serde_json::to_string(&response)
+            .expect("Failed to serialize CurrentEntitlementResponse");
+        assert_eq!(json, "{}");
+
+        for json in ["{}", r#"{"purchase":null}"#] {
+            let response: CurrentEntitlementResponse = serde_json::from_str(json)
+                .expect("Failed to deserialize CurrentEntitlementResponse");
+            assert!(response.purchase.is_none());
+        }
+        // The command's `Option<Purchase>` reaches JavaScript as `null`.
+        assert_eq!(
+            serde_json::to_string(&response.purchase).expect("Failed to serialize Option"),
+            "null"
+        );
+    }
+
+    #[test]
+    fn test_current_entitlement_response_with_purchase() {
+        let json = r#"{"purchase":{"orderId":"2000000456","packageName":"com.example.app","productId":"premium","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"2000000456","purchaseState":0,"isAutoRenewing":false,"isAcknowledged":true,"originalJson":"","signature":""}}"#;
+        let response: CurrentEntitlementResponse =
+            serde_json::from_str(json).expect("Failed to deserialize CurrentEntitlementResponse");
+        let purchase = response.purchase.expect("Expected a purchase");
+        assert_eq!(purchase.product_id, "premium");
+        assert_eq!(purchase.purchase_state, PurchaseState::Purchased);
+    }
+
+    #[test]
+    fn test_iap_diagnostics_serde() {
+        let diagnostics = IapDiagnostics {
+            last_call_durations: BTreeMap::from([(
+                "getProducts".to_string(),
+                Duration::from_millis(1500),
+            )]),
+        };
+        let json = serde_json::to_string(&diagnostics).expect("Failed to serialize IapDiagnostics");
+        assert_eq!(
+            json,
+            r#"{"lastCallDurations":{"getProducts":{"secs":1,"nanos":500000000}}}"#
+        );
+        assert!(IapDiagnostics::default().last_call_durations.is_empty());
+    }
+
+    #[test]
+    fn test_retry_policy_serde_fills_in_defaults() {
+        let policy: RetryPolicy = serde_json::from_str(r#"{"maxAttempts":5}"#)
+            .expect("Failed to deserialize RetryPolicy");
+        assert_eq!(
+            policy,
+            RetryPolicy {
+                max_attempts: 5,
+                ..RetryPolicy::default()
+            }
+        );
+
+        let policy: RetryPolicy =
+            serde_json::from_str(r#"{"retryableCodes":["networkError","serviceTimeout"]}"#)
+                .expect("Failed to deserialize RetryPolicy");
+        assert_eq!(
+            policy.retryable_codes,
+            vec![IapErrorCode::NetworkError, IapErrorCode::ServiceTimeout]
+        );
+        assert!(policy.is_retryable(&crate::Error::Timeout(std::time::Duration::from_secs(1))));
+        assert!(!policy.is_retryable(&crate::Error::InvalidArgument("bad".to_string())));
+    }
+
+    #[test]
+    fn test_purchase_subscription_group_id_serde() {
+        let json = r#"{"orderId":"2000000456","packageName":"com.example.app","productId":"monthly","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"2000000456","purchaseState":0,"isAutoRenewing":true,"isAcknowledged":true,"originalJson":"","signature":"","originalId":null,"subscriptionGroupId":"21345678"}"#;
+        let purchase: Purchase =
+            serde_json::from_str(json).expect("Failed to deserialize Purchase");
+        assert_eq!(purchase.subscription_group_id.as_deref(), Some("21345678"));
+        let serialized = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
+        assert_eq!(serialized, json);
+
+        // Android never sends one, not even for subscriptions with several
+        // base plans.
+        let android = r#"{"orderId":"GPA.1234","packageName":"com.example.app","productId":"premium","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"token","purchaseState":0,"isAutoRenewing":true,"isAcknowledged":true,"originalJson":"{}","signature":"sig"}"#;
+        let purchase: Purchase =
+            serde_json::from_str(android).expect("Failed to deserialize Purchase");
+        assert!(purchase.subscription_group_id.is_none());
+        let serialized = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
+        assert!(!serialized.contains("subscriptionGroupId"));
+    }
+
+    #[test]
+    fn test_purchase_history_record_original_transaction_fields_serde() {
+        let json = r#"{"productId":"monthly","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"2000000456","quantity":1,"originalJson":"","signature":"","transactionId":"2000000456","originalTransactionId":"2000000123","webOrderLineItemId":"2000000012345678","productType":"subs"}"#;
+        let record: PurchaseHistoryRecord =
+            serde_json::from_str(json).expect("Failed to deserialize PurchaseHistoryRecord");
+        assert_eq!(
+            record.original_transaction_id.as_deref(),
+            Some("2000000123")
+        );
+        assert_eq!(
+            record.web_order_line_item_id.as_deref(),
+            Some("2000000012345678")
+        );
+        let serialized =
+            serde_json::to_string(&record).expect("Failed to serialize PurchaseHistoryRecord");
+        assert_eq!(serialized, json);
+    }
+
+    #[test]
+    fn test_purchase_app_account_token_serde() {
+        let json = r#"{"orderId":"1","packageName":"com.example.app","productId":"premium","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"1","purchaseState":0,"isAutoRenewing":false,"isAcknowledged":true,"originalJson":"","signature":"","originalId":"1","appAccountToken":"550e8400-e29b-41d4-a716-446655440000"}"#;
+        let purchase: Purchase =
+            serde_json::from_str(json).expect("Failed to deserialize Purchase");
+        assert_eq!(
+            purchase.app_account_token.as_deref(),
+            Some("550e8400-e29b-41d4-a716-446655440000")
+        );
+
+        let serialized = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
+        assert_eq!(serialized, json);
+
+        let without = json.replace(
+            r#","appAccountToken":"550e8400-e29b-41d4-a716-446655440000""#,
+            "",
+        );
+        let purchase: Purchase =
+            serde_json::from_str(&without).expect("Failed to deserialize Purchase");
+        assert!(purchase.app_account_token.is_none());
+    }
+
+    #[test]
+    fn test_purchase_expiration_dates_serde() {
+        let json = r#"{"orderId":"2","packageName":"com.example.app","productId":"monthly","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"2","purchaseState":0,"isAutoRenewing":true,"isAcknowledged":true,"originalJson":"","signature":"","originalId":"1","expirationDate":"2023-12-14T22:13:20.000Z","nextRenewalDate":"2023-12-14T22:13:20.000Z"}"#;
+        let purchase: Purchase =
+            serde_json::from_str(json).expect("Failed to deserialize Purchase");
+        let expiration = Some(Timestamp::from_unix_millis(1_702_592_000_000));
+        assert_eq!(purchase.expiration_date, expiration);
+        assert_eq!(purchase.next_renewal_date, expiration);
 
-        let deserialized: InitializeResponse =
-            serde_json::from_str(&json).expect("Failed to deserialize InitializeResponse");
-        assert!(deserialized.success);
+        let serialized = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
+        assert_eq!(serialized, json);
     }
 
     #[test]
-    fn test_get_products_request_default_product_type() {
-        let json = r#"{"productIds":["product1","product2"]}"#;
-        let request: GetProductsRequest =
-            serde_json::from_str(json).expect("Failed to deserialize GetProductsRequest");
-        assert_eq!(request.product_ids, vec!["product1", "product2"]);
-        assert_eq!(request.product_type, "subs");
+    fn test_purchase_dates_accept_epoch_millis_and_second_precision() {
+        // Native layers before the switch to RFC 3339 sent epoch milliseconds
+        // for purchaseTime and whole-second dates for expirationDate.
+        let json = r#"{"orderId":"2","packageName":"com.example.app","productId":"monthly","purchaseTime":1700000000000,"purchaseToken":"2","purchaseState":0,"isAutoRenewing":true,"isAcknowledged":true,"originalJson":"","signature":"","originalId":"1","expirationDate":"2023-12-14T22:13:20Z","revocationTime":1700000500000}"#;
+        let purchase: Purchase =
+            serde_json::from_str(json).expect("Failed to deserialize Purchase");
+        assert_eq!(
+            purchase.purchase_time,
+            Timestamp::from_unix_millis(1_700_000_000_000)
+        );
+        assert_eq!(
+            purchase.expiration_date,
+            Some(Timestamp::from_unix_millis(1_702_592_000_000))
+        );
+
+        let serialized = serde_json::to_value(&purchase).expect("Failed to serialize Purchase");
+        assert_eq!(serialized["purchaseTime"], "2023-11-14T22:13:20.000Z");
+        assert_eq!(serialized["expirationDate"], "2023-12-14T22:13:20.000Z");
+        assert_eq!(serialized["revocationTime"], "2023-11-14T22:21:40.000Z");
     }
 
     #[test]
-    fn test_get_products_request_explicit_product_type() {
-        let json = r#"{"productIds":["product1"],"productType":"inapp"}"#;
-        let request: GetProductsRequest =
-            serde_json::from_str(json).expect("Failed to deserialize GetProductsRequest");
-        assert_eq!(request.product_type, "inapp");
+    fn test_android_subscription_purchase_has_no_expiration_dates() {
+        // Play Billing's on-device Purchase has no expiry, so the Android
+        // bridge never sends these fields.
+        let json = r#"{"orderId":"GPA.1234","packageName":"com.example.app","productId":"monthly","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"token","purchaseState":0,"isAutoRenewing":true,"isAcknowledged":true,"originalJson":"{}","signature":"sig","originalId":null}"#;
+        let purchase: Purchase =
+            serde_json::from_str(json).expect("Failed to deserialize Purchase");
+        assert!(purchase.expiration_date.is_none());
+        assert!(purchase.next_renewal_date.is_none());
+
+        let serialized = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
+        assert!(!serialized.contains("expirationDate"));
+        assert!(!serialized.contains("nextRenewalDate"));
     }
 
     #[test]
-    fn test_product_optional_fields_skip_serializing() {
-        let product = Product {
-            product_id: "test".to_string(),
-            title: "Test Product".to_string(),
-            description: "A test product".to_string(),
-            product_type: "inapp".to_string(),
-            formatted_price: None,
-            price_currency_code: None,
-            price_amount_micros: None,
-            subscription_offer_details: None,
-        };
-        let json = serde_json::to_string(&product).expect("Failed to serialize Product");
-        assert!(!json.contains("formattedPrice"));
-        assert!(!json.contains("priceCurrencyCode"));
-        assert!(!json.contains("priceAmountMicros"));
-        assert!(!json.contains("subscriptionOfferDetails"));
+    fn test_purchase_result_offer_fields_serde() {
+        let json = r#"{"orderId":"2000000789","packageName":"com.example.app","productId":"monthly","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"2000000789","purchaseState":0,"isAutoRenewing":true,"isAcknowledged":true,"originalJson":"","signature":"","originalId":"2000000789","offerId":"SPRING24","offerType":"code"}"#;
+        let purchase: Purchase =
+            serde_json::from_str(json).expect("Failed to deserialize Purchase");
+        assert_eq!(purchase.offer_id.as_deref(), Some("SPRING24"));
+        assert_eq!(purchase.offer_type.as_deref(), Some("code"));
+
+        let serialized = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
+        assert_eq!(serialized, json);
     }
 
     #[test]
-    fn test_product_with_optional_fields() {
-        let product = Product {
-            product_id: "test".to_string(),
-            title: "Test Product".to_string(),
-            description: "A test product".to_string(),
-            product_type: "inapp".to_string(),
-            formatted_price: Some("$9.99".to_string()),
-            price_currency_code: Some("USD".to_string()),
-            price_amount_micros: Some(9_990_000),
-            subscription_offer_details: None,
-        };
-        let json = serde_json::to_string(&product).expect("Failed to serialize Product");
-        assert!(json.contains(r#""formattedPrice":"$9.99""#));
-        assert!(json.contains(r#""priceCurrencyCode":"USD""#));
-        assert!(json.contains(r#""priceAmountMicros":9990000"#));
+    fn test_restore_response_offer_fields_serde() {
+        // An introductory offer has no id on Apple platforms; a plain
+        // restore on Android has neither field.
+        let json = r#"{"purchases":[{"orderId":"1","packageName":"com.example.app","productId":"monthly","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"1","purchaseState":0,"isAutoRenewing":true,"isAcknowledged":true,"originalJson":"","signature":"","originalId":"1","offerType":"introductory"},{"orderId":"GPA.1234","packageName":"com.example.app","productId":"yearly","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"token","purchaseState":0,"isAutoRenewing":true,"isAcknowledged":true,"originalJson":"{}","signature":"sig","originalId":null}]}"#;
+        let response: RestorePurchasesResponse =
+            serde_json::from_str(json).expect("Failed to deserialize RestorePurchasesResponse");
+        assert!(response.purchases[0].offer_id.is_none());
+        assert_eq!(
+            response.purchases[0].offer_type.as_deref(),
+            Some("introductory")
+        );
+        assert!(response.purchases[1].offer_id.is_none());
+        assert!(response.purchases[1].offer_type.is_none());
+
+        let serialized =
+            serde_json::to_string(&response).expect("Failed to serialize RestorePurchasesResponse");
+        assert_eq!(serialized.matches("offerType").count(), 1);
+        assert!(!serialized.contains("offerId"));
     }
 
     #[test]
-    fn test_purchase_serde_roundtrip() {
-        let purchase = Purchase {
-            order_id: Some("order123".to_string()),
-            package_name: "com.example.app".to_string(),
-            product_id: "product1".to_string(),
-            purchase_time: 1_700_000_000_000,
-            purchase_token: "token123".to_string(),
-            purchase_state: PurchaseStateValue::Purchased,
-            is_auto_renewing: true,
-            is_acknowledged: false,
-            original_json: "{}".to_string(),
-            signature: "sig".to_string(),
-            original_id: None,
-            jws_representation: Some("test_jws".to_string()),
-        };
+    fn test_purchase_updated_event_offer_fields_serde() {
+        // Android purchaseUpdated payload for an untagged offer: the id is
+        // known from the offer token but the type is not.
+        let json = r#"{"orderId":"GPA.5678","packageName":"com.example.app","productId":"monthly","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"token","purchaseState":0,"isAutoRenewing":true,"isAcknowledged":false,"originalJson":"{}","signature":"sig","offerId":"winback-50"}"#;
+        let purchase: Purchase =
+            serde_json::from_str(json).expect("Failed to deserialize Purchase");
+        assert_eq!(purchase.offer_id.as_deref(), Some("winback-50"));
+        assert!(purchase.offer_type.is_none());
+
+        let serialized = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
+        assert!(serialized.contains(r#""offerId":"winback-50""#));
+        assert!(!serialized.contains("offerType"));
+    }
+
+    #[test]
+    fn test_platform_serde() {
+        for (platform, wire) in [
+            (Platform::Ios, r#""ios""#),
+            (Platform::MacOs, r#""macos""#),
+            (Platform::Android, r#""android""#),
+            (Platform::Windows, r#""windows""#),
+        ] {
+            assert_eq!(
+                serde_json::to_string(&platform).expect("Failed to serialize Platform"),
+                wire
+            );
+            let parsed: Platform =
+                serde_json::from_str(wire).expect("Failed to deserialize Platform");
+            assert_eq!(parsed, platform);
+        }
+    }
+
+    #[test]
+    fn test_purchase_receipt_serde() {
+        let json = r#"{"platform":"ios","receiptData":"TUlJVDBR","productId":"premium","transactionId":"2000000123"}"#;
+        let receipt: PurchaseReceipt =
+            serde_json::from_str(json).expect("Failed to deserialize PurchaseReceipt");
+        assert_eq!(receipt.platform, Platform::Ios);
+        assert!(receipt.purchase_token.is_none());
+        assert_eq!(
+            serde_json::to_string(&receipt).expect("Failed to serialize PurchaseReceipt"),
+            json
+        );
+
+        let json = r#"{"platform":"android","receiptData":"{\"orderId\":\"GPA.1234\"}","purchaseToken":"token123","productId":"coins","transactionId":"GPA.1234"}"#;
+        let receipt: PurchaseReceipt =
+            serde_json::from_str(json).expect("Failed to deserialize PurchaseReceipt");
+        assert_eq!(receipt.purchase_token.as_deref(), Some("token123"));
+        assert_eq!(receipt.receipt_data, r#"{"orderId":"GPA.1234"}"#);
+        assert_eq!(
+            serde_json::to_string(&receipt).expect("Failed to serialize PurchaseReceipt"),
+            json
+        );
+    }
+
+    #[test]
+    fn test_get_revoked_purchases_request_serde() {
+        let request: GetRevokedPurchasesRequest =
+            serde_json::from_str("{}").expect("Failed to deserialize GetRevokedPurchasesRequest");
+        assert!(request.since.is_none());
+        assert_eq!(
+            serde_json::to_string(&request)
+                .expect("Failed to serialize GetRevokedPurchasesRequest"),
+            "{}"
+        );
+
+        let json = r#"{"since":"2023-11-14T22:13:20.000Z"}"#;
+        let request: GetRevokedPurchasesRequest =
+            serde_json::from_str(json).expect("Failed to deserialize GetRevokedPurchasesRequest");
+        assert_eq!(
+            request.since,
+            Some(Timestamp::from_unix_millis(1_700_000_000_000))
+        );
+        assert_eq!(
+            serde_json::to_string(&request)
+                .expect("Failed to serialize GetRevokedPurchasesRequest"),
+            json
+        );
+    }
+
+    #[test]
+    fn test_get_app_receipt_serde() {
+        let request: GetAppReceiptRequest =
+            serde_json::from_str("{}").expect("Failed to deserialize GetAppReceiptRequest");
+        assert!(!request.refresh_if_missing);
+        let request: GetAppReceiptRequest = serde_json::from_str(r#"{"refreshIfMissing":true}"#)
+            .expect("Failed to deserialize GetAppReceiptRequest");
+        assert!(request.refresh_if_missing);
+
+        let response: GetAppReceiptResponse = serde_json::from_str(r#"{"receiptData":"TUlJVDBR"}"#)
+            .expect("Failed to deserialize GetAppReceiptResponse");
+        assert_eq!(response.receipt_data, "TUlJVDBR");
+    }
+
+    #[test]
+    fn test_purchase_revoked_event_serde() {
+        let json = r#"{"productId":"premium","transactionId":"2000000123","revocationTime":"2023-11-14T22:21:40.000Z","revocationReason":"developerIssue"}"#;
+        let event: PurchaseRevokedEvent =
+            serde_json::from_str(json).expect("Failed to deserialize PurchaseRevokedEvent");
+        assert_eq!(event.transaction_id, "2000000123");
+        assert_eq!(event.revocation_reason.as_deref(), Some("developerIssue"));
+
+        let serialized =
+            serde_json::to_string(&event).expect("Failed to serialize PurchaseRevokedEvent");
+        assert_eq!(serialized, json);
+    }
+
+    #[test]
+    fn test_subscription_status_price_increase_serde() {
+        let json = r#"{"productId":"premium","state":"subscribed","transactionId":"2","originalTransactionId":"1","isFamilyShared":false,"willAutoRenew":true,"priceIncreaseStatus":"pending"}"#;
+        let status: SubscriptionStatus =
+            serde_json::from_str(json).expect("Failed to deserialize SubscriptionStatus");
+        assert_eq!(
+            status.price_increase_status,
+            Some(PriceIncreaseStatus::Pending)
+        );
+
+        let serialized =
+            serde_json::to_string(&status).expect("Failed to serialize SubscriptionStatus");
+        assert_eq!(serialized, json);
+
+        let agreed: SubscriptionStatus = serde_json::from_str(&json.replace("pending", "agreed"))
+            .expect("Failed to deserialize SubscriptionStatus");
+        assert_eq!(
+            agreed.price_increase_status,
+            Some(PriceIncreaseStatus::Agreed)
+        );
+    }
+
+    #[test]
+    fn test_subscription_status_renewal_price_serde() {
+        let json = r#"{"productId":"premium","state":"subscribed","transactionId":"2","originalTransactionId":"1","isFamilyShared":false,"willAutoRenew":true,"priceIncreaseStatus":"agreed","renewalPriceAmountMicros":5990000,"renewalCurrency":"USD"}"#;
+        let status: SubscriptionStatus =
+            serde_json::from_str(json).expect("Failed to deserialize SubscriptionStatus");
+        assert_eq!(status.renewal_price_amount_micros, Some(5_990_000));
+        assert_eq!(status.renewal_currency.as_deref(), Some("USD"));
+
+        let serialized =
+            serde_json::to_string(&status).expect("Failed to serialize SubscriptionStatus");
+        assert_eq!(serialized, json);
+
+        // Older OS versions leave both out.
+        let status: SubscriptionStatus = serde_json::from_str(
+            r#"{"productId":"premium","state":"subscribed","transactionId":"2","originalTransactionId":"1","willAutoRenew":true}"#,
+        )
+        .expect("Failed to deserialize SubscriptionStatus");
+        assert_eq!(status.renewal_price_amount_micros, None);
+        assert_eq!(status.renewal_currency, None);
+    }
+
+    #[test]
+    fn test_renewal_preference_changed_event_serde() {
+        let json = r#"{"productId":"premium_yearly","originalTransactionId":"1000000001","autoRenewProductId":"basic_yearly","willAutoRenew":true}"#;
+        let event: RenewalPreferenceChangedEvent = serde_json::from_str(json)
+            .expect("Failed to deserialize RenewalPreferenceChangedEvent");
+        assert_eq!(event.auto_renew_product_id.as_deref(), Some("basic_yearly"));
+        assert!(event.will_auto_renew);
+
+        let serialized = serde_json::to_string(&event)
+            .expect("Failed to serialize RenewalPreferenceChangedEvent");
+        assert_eq!(serialized, json);
+
+        // Turning auto-renew off can leave no preferred product.
+        let cancelled: RenewalPreferenceChangedEvent = serde_json::from_str(
+            r#"{"productId":"premium_yearly","originalTransactionId":"1000000001","willAutoRenew":false}"#,
+        )
+        .expect("Failed to deserialize RenewalPreferenceChangedEvent");
+        assert!(cancelled.auto_renew_product_id.is_none());
+        assert!(!cancelled.will_auto_renew);
+    }
+
+    #[test]
+    fn test_purchase_history_request_serde() {
+        let request: PurchaseHistoryRequest = serde_json::from_str(
+            r#"{"productType":"subs","sinceTimestamp":1700000000000,"limit":20}"#,
+        )
+        .expect("Failed to deserialize PurchaseHistoryRequest");
+        assert_eq!(
+            request.product_type,
+            Some(ProductType::AutoRenewableSubscription)
+        );
+        assert_eq!(request.since_timestamp, Some(1_700_000_000_000));
+        assert_eq!(request.limit, Some(20));
+
+        // An empty request matches the whole history.
+        let empty: PurchaseHistoryRequest =
+            serde_json::from_str("{}").expect("Failed to deserialize PurchaseHistoryRequest");
+        assert_eq!(empty, PurchaseHistoryRequest::default());
+        assert_eq!(
+            serde_json::to_string(&empty).expect("Failed to serialize PurchaseHistoryRequest"),
+            "{}"
+        );
+    }
+
+    #[test]
+    fn test_billing_issue_detected_event_serde() {
+        let json = r#"{"productId":"premium","originalTransactionId":"1000000001","state":"inGracePeriod","gracePeriodExpirationDate":"2023-11-20T17:06:40.000Z"}"#;
+        let event: BillingIssueDetectedEvent =
+            serde_json::from_str(json).expect("Failed to deserialize BillingIssueDetectedEvent");
+        assert_eq!(event.state, SubscriptionState::InGracePeriod);
+        assert_eq!(
+            event.grace_period_expiration_date,
+            Some(Timestamp::from_unix_millis(1_700_500_000_000))
+        );
+
+        let serialized =
+            serde_json::to_string(&event).expect("Failed to serialize BillingIssueDetectedEvent");
+        assert_eq!(serialized, json);
+
+        // Billing retry without a grace period: access has already lapsed.
+        let retry: BillingIssueDetectedEvent = serde_json::from_str(
+            r#"{"productId":"premium","originalTransactionId":"1000000001","state":"inBillingRetry"}"#,
+        )
+        .expect("Failed to deserialize BillingIssueDetectedEvent");
+        assert_eq!(retry.state, SubscriptionState::InBillingRetry);
+        assert!(retry.grace_period_expiration_date.is_none());
+    }
+
+    #[test]
+    fn test_price_increase_pending_event_serde() {
+        let json = r#"{"productId":"premium","originalTransactionId":"1000000001"}"#;
+        let event: PriceIncreasePendingEvent =
+            serde_json::from_str(json).expect("Failed to deserialize PriceIncreasePendingEvent");
+        assert_eq!(event.original_transaction_id, "1000000001");
+
+        let serialized =
+            serde_json::to_string(&event).expect("Failed to serialize PriceIncreasePendingEvent");
+        assert_eq!(serialized, json);
+    }
+
+    #[test]
+    fn test_ownership_type_serde() {
+        assert_eq!(
+            serde_json::to_string(&OwnershipType::Purchased)
+                .expect("Failed to serialize OwnershipType"),
+            r#""purchased""#
+        );
+        let future: OwnershipType =
+            serde_json::from_str(r#""borrowed""#).expect("Failed to deserialize OwnershipType");
+        assert_eq!(future, OwnershipType::Unknown);
+    }
+
+    #[test]
+    fn test_transaction_reason_serde() {
+        let json = r#"{"productId":"monthly","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"2000000456","quantity":1,"originalJson":"","signature":"","transactionReason":"renewal"}"#;
+        let record: PurchaseHistoryRecord =
+            serde_json::from_str(json).expect("Failed to deserialize PurchaseHistoryRecord");
+        assert_eq!(record.transaction_reason, Some(TransactionReason::Renewal));
+        let serialized =
+            serde_json::to_string(&record).expect("Failed to serialize PurchaseHistoryRecord");
+        assert_eq!(serialized, json);
+
+        // Older OS versions and other stores send no reason.
+        let json = r#"{"orderId":"GPA.1234","packageName":"com.example.app","productId":"premium","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"token","purchaseState":0,"isAutoRenewing":true,"isAcknowledged":true,"originalJson":"{}","signature":"sig"}"#;
+        let purchase: Purchase =
+            serde_json::from_str(json).expect("Failed to deserialize Purchase");
+        assert!(purchase.transaction_reason.is_none());
+
+        let future: TransactionReason =
+            serde_json::from_str(r#""refund""#).expect("Failed to deserialize TransactionReason");
+        assert_eq!(future, TransactionReason::Unknown);
+    }
+
+    #[test]
+    fn test_product_is_family_shareable_serde() {
+        let json = r#"{"productId":"premium","title":"Premium","description":"","productType":"autoRenewable","isFamilyShareable":true}"#;
+        let product: Product = serde_json::from_str(json).expect("Failed to deserialize Product");
+        assert_eq!(product.is_family_shareable, Some(true));
+
+        let serialized = serde_json::to_string(&product).expect("Failed to serialize Product");
+        assert_eq!(serialized, json);
+    }
+
+    #[test]
+    fn test_purchase_without_jws_representation() {
+        // Android payloads never carry `jwsRepresentation`.
+        let json = r#"{"orderId":"GPA.1234","packageName":"com.example.app","productId":"product1","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"token123","purchaseState":0,"isAutoRenewing":false,"isAcknowledged":true,"originalJson":"{}","signature":"sig"}"#;
+        let purchase: Purchase =
+            serde_json::from_str(json).expect("Failed to deserialize Purchase");
+        assert_eq!(purchase.jws_representation, None);
+        assert_eq!(purchase.original_id, None);
 
         let json = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
-        let deserialized: Purchase =
-            serde_json::from_str(&json).expect("Failed to deserialize Purchase");
+        assert!(!json.contains("jwsRepresentation"));
+    }
 
-        assert_eq!(deserialized.order_id, purchase.order_id);
-        assert_eq!(deserialized.product_id, purchase.product_id);
-        assert_eq!(deserialized.purchase_time, purchase.purchase_time);
-        assert_eq!(deserialized.purchase_state, purchase.purchase_state);
-        assert_eq!(deserialized.is_auto_renewing, purchase.is_auto_renewing);
+    #[test]
+    fn test_pending_purchase_serde() {
+        // Shape the Swift side resolves for an Ask to Buy purchase.
+        let json = r#"{"packageName":"com.example.app","productId":"premium","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"","purchaseState":5,"isAutoRenewing":false,"isAcknowledged":false,"originalJson":"","signature":""}"#;
+        let purchase: Purchase =
+            serde_json::from_str(json).expect("Failed to deserialize pending Purchase");
+
+        assert_eq!(purchase.purchase_state, PurchaseState::Deferred);
+        assert_eq!(purchase.order_id, None);
+        assert_eq!(purchase.original_id, None);
+        assert!(purchase.purchase_token.is_empty());
+
+        let serialized =
+            serde_json::to_string(&purchase).expect("Failed to serialize pending Purchase");
+        assert!(serialized.contains(r#""purchaseState":5"#));
+        assert!(!serialized.contains("verification"));
+    }
+
+    #[test]
+    fn test_purchase_verification_serde() {
+        let json = r#"{"orderId":"2000000123","packageName":"com.example.app","productId":"premium","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"2000000123","purchaseState":0,"isAutoRenewing":false,"isAcknowledged":true,"originalJson":"","signature":"","verification":"unverified"}"#;
+        let purchase: Purchase =
+            serde_json::from_str(json).expect("Failed to deserialize Purchase");
+        assert_eq!(
+            purchase.verification,
+            Some(TransactionVerification::Unverified)
+        );
+
+        let serialized = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
+        assert!(serialized.ends_with(r#""verification":"unverified"}"#));
+
+        // Android and Windows never report verification.
+        let android: Purchase = serde_json::from_str(
+            r#"{"orderId":"GPA.1234","packageName":"com.example.app","productId":"premium","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"token","purchaseState":0,"isAutoRenewing":false,"isAcknowledged":true,"originalJson":"{}","signature":"sig"}"#,
+        )
+        .expect("Failed to deserialize Purchase");
+        assert_eq!(android.verification, None);
     }
 
     #[test]
@@ -452,35 +2821,240 @@ mod tests {
     }
 
     #[test]
-    fn test_purchase_options_flatten() {
-        let json = r#"{"productId":"prod1","offerToken":"token","obfuscatedAccountId":"acc123"}"#;
+    fn test_purchase_options_flatten() {
+        let json = r#"{"productId":"prod1","offerToken":"token","obfuscatedAccountId":"acc123"}"#;
+        let request: PurchaseRequest =
+            serde_json::from_str(json).expect("Failed to deserialize PurchaseRequest");
+
+        assert_eq!(request.product_id, "prod1");
+        assert_eq!(request.product_type, ProductType::AutoRenewableSubscription); // default
+        let opts = request
+            .options
+            .expect("Expected PurchaseOptions to be present");
+        assert_eq!(opts.offer_token, Some("token".to_string()));
+        assert_eq!(opts.obfuscated_account_id, Some("acc123".to_string()));
+        assert_eq!(opts.old_product_id, None);
+        assert_eq!(opts.subscription_replacement_mode, None);
+        assert_eq!(opts.auto_finish, None);
+    }
+
+    #[test]
+    fn test_purchase_options_auto_finish() {
+        let json = r#"{"productId":"prod1","productType":"inapp","autoFinish":false}"#;
+        let request: PurchaseRequest =
+            serde_json::from_str(json).expect("Failed to deserialize PurchaseRequest");
+
+        let opts = request
+            .options
+            .expect("Expected PurchaseOptions to be present");
+        assert_eq!(opts.auto_finish, Some(false));
+    }
+
+    #[test]
+    fn test_purchase_options_with_subscription_update() {
+        let json = r#"{"productId":"prod1","offerToken":"token","oldProductId":"basic_monthly","subscriptionReplacementMode":2}"#;
+        let request: PurchaseRequest =
+            serde_json::from_str(json).expect("Failed to deserialize PurchaseRequest");
+
+        assert_eq!(request.product_id, "prod1");
+        let opts = request
+            .options
+            .expect("Expected PurchaseOptions to be present");
+        assert_eq!(opts.offer_token, Some("token".to_string()));
+        assert_eq!(opts.old_product_id, Some("basic_monthly".to_string()));
+        assert_eq!(opts.subscription_replacement_mode, Some(2));
+    }
+
+    #[test]
+    fn test_purchase_request_builder() {
+        let request = PurchaseRequest::builder()
+            .product_id("com.example.coins")
+            .product_type(ProductType::Consumable)
+            .quantity(3)
+            .app_account_token("2f1b3c4d-0000-4000-8000-000000000000")
+            .build()
+            .expect("Failed to build PurchaseRequest");
+
+        assert_eq!(request.product_id, "com.example.coins");
+        assert_eq!(request.product_type, ProductType::Consumable);
+        let opts = request
+            .options
+            .expect("Expected PurchaseOptions to be present");
+        assert_eq!(opts.quantity, Some(3));
+        assert_eq!(
+            opts.app_account_token,
+            Some("2f1b3c4d-0000-4000-8000-000000000000".to_string())
+        );
+        assert_eq!(opts.offer_token, None);
+    }
+
+    #[test]
+    fn test_purchase_request_builder_rejects_malformed_app_account_token() {
+        for token in [
+            "",
+            "not-a-uuid",
+            "2f1b3c4d00004000800000000000000",
+            "2f1b3c4d-0000-4000-8000-00000000000",
+            "2f1b3c4d-0000-4000-8000-00000000000g",
+            "{2f1b3c4d-0000-4000-8000-000000000000}",
+        ] {
+            let result = PurchaseRequest::builder()
+                .product_id("premium")
+                .app_account_token(token)
+                .build();
+            assert!(
+                matches!(result, Err(crate::Error::InvalidArgument(_))),
+                "{token:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_purchase_options_validate_app_account_token() {
+        let mut options = PurchaseOptions::default();
+        assert!(options.validate(ProductType::Consumable).is_ok());
+
+        options.app_account_token = Some("2F1B3C4D-0000-4000-8000-00000000ABCD".to_string());
+        assert!(options.validate(ProductType::Consumable).is_ok());
+
+        options.app_account_token = Some("user-42".to_string());
+        let error = options
+            .validate(ProductType::Consumable)
+            .expect_err("Expected malformed token to fail");
+        assert_eq!(
+            error.error_code(),
+            Some(crate::error::IapErrorCode::DeveloperError)
+        );
+    }
+
+    #[test]
+    fn test_purchase_options_validate_quantity() {
+        let mut options = PurchaseOptions {
+            quantity: Some(MAX_PURCHASE_QUANTITY),
+            ..PurchaseOptions::default()
+        };
+        assert!(options.validate(ProductType::Consumable).is_ok());
+
+        for quantity in [0, MAX_PURCHASE_QUANTITY + 1] {
+            options.quantity = Some(quantity);
+            let error = options
+                .validate(ProductType::Consumable)
+                .expect_err("Expected out-of-range quantity to fail");
+            assert_eq!(
+                error.error_code(),
+                Some(crate::error::IapErrorCode::DeveloperError)
+            );
+        }
+    }
+
+    #[test]
+    fn test_purchase_options_validate_quantity_rejects_subscriptions() {
+        let options = PurchaseOptions {
+            quantity: Some(2),
+            ..PurchaseOptions::default()
+        };
+        // "inapp" can't tell consumables apart, so StoreKit decides.
+        assert!(options.validate(ProductType::Consumable).is_ok());
+        assert!(options.validate(ProductType::NonConsumable).is_ok());
+
+        for product_type in [
+            ProductType::AutoRenewableSubscription,
+            ProductType::NonRenewingSubscription,
+        ] {
+            let error = options
+                .validate(product_type)
+                .expect_err("Expected quantity > 1 on a subscription to fail");
+            assert!(matches!(error, crate::Error::InvalidArgument(_)));
+        }
+
+        // A single unit is fine for every product type.
+        let single = PurchaseOptions {
+            quantity: Some(1),
+            ..PurchaseOptions::default()
+        };
+        assert!(
+            single
+                .validate(ProductType::AutoRenewableSubscription)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_purchase_request_inapp_with_quantity_is_valid() {
+        // The documented frontend call: purchase('coins_100', 'inapp', { quantity: 3 }).
+        let json = r#"{"productId":"coins_100","productType":"inapp","quantity":3}"#;
         let request: PurchaseRequest =
             serde_json::from_str(json).expect("Failed to deserialize PurchaseRequest");
-
-        assert_eq!(request.product_id, "prod1");
-        assert_eq!(request.product_type, "subs"); // default
-        let opts = request
+        let options = request
             .options
+            .as_ref()
             .expect("Expected PurchaseOptions to be present");
-        assert_eq!(opts.offer_token, Some("token".to_string()));
-        assert_eq!(opts.obfuscated_account_id, Some("acc123".to_string()));
-        assert_eq!(opts.old_product_id, None);
-        assert_eq!(opts.subscription_replacement_mode, None);
+        assert_eq!(options.quantity, Some(3));
+        assert!(options.validate(request.product_type).is_ok());
     }
 
     #[test]
-    fn test_purchase_options_with_subscription_update() {
-        let json = r#"{"productId":"prod1","offerToken":"token","oldProductId":"basic_monthly","subscriptionReplacementMode":2}"#;
-        let request: PurchaseRequest =
-            serde_json::from_str(json).expect("Failed to deserialize PurchaseRequest");
+    fn test_purchase_quantity_serde() {
+        let json = r#"{"orderId":"1","packageName":"com.example.app","productId":"coins_100","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"1","purchaseState":0,"isAutoRenewing":false,"isAcknowledged":true,"originalJson":"","signature":"","originalId":"1","quantity":3}"#;
+        let purchase: Purchase =
+            serde_json::from_str(json).expect("Failed to deserialize Purchase");
+        assert_eq!(purchase.quantity, Some(3));
+        assert_eq!(purchase.purchased_quantity(), 3);
 
-        assert_eq!(request.product_id, "prod1");
-        let opts = request
-            .options
-            .expect("Expected PurchaseOptions to be present");
-        assert_eq!(opts.offer_token, Some("token".to_string()));
-        assert_eq!(opts.old_product_id, Some("basic_monthly".to_string()));
-        assert_eq!(opts.subscription_replacement_mode, Some(2));
+        let serialized = serde_json::to_string(&purchase).expect("Failed to serialize Purchase");
+        assert_eq!(serialized, json);
+
+        // Android and Windows leave the quantity out.
+        let single: Purchase = serde_json::from_str(&json.replace(r#","quantity":3"#, ""))
+            .expect("Failed to deserialize Purchase");
+        assert_eq!(single.quantity, None);
+        assert_eq!(single.purchased_quantity(), 1);
+    }
+
+    #[test]
+    fn test_purchase_request_builder_round_trip() {
+        let request = PurchaseRequest::builder()
+            .product_id("premium")
+            .offer_token("token")
+            .build()
+            .expect("Failed to build PurchaseRequest");
+
+        let json = serde_json::to_string(&request).expect("Failed to serialize PurchaseRequest");
+        assert_eq!(
+            json,
+            r#"{"productId":"premium","productType":"subs","offerToken":"token"}"#
+        );
+        let deserialized: PurchaseRequest =
+            serde_json::from_str(&json).expect("Failed to deserialize PurchaseRequest");
+        assert_eq!(deserialized.product_id, "premium");
+    }
+
+    #[test]
+    fn test_purchase_request_builder_rejects_missing_fields() {
+        assert!(PurchaseRequest::builder().build().is_err());
+        assert!(PurchaseRequest::builder().product_id("  ").build().is_err());
+        assert!(
+            PurchaseRequest::builder()
+                .product_id("coins")
+                .quantity(0)
+                .build()
+                .is_err()
+        );
+        assert!(
+            PurchaseRequest::builder()
+                .product_id("coins")
+                .quantity(MAX_PURCHASE_QUANTITY + 1)
+                .build()
+                .is_err()
+        );
+        assert!(
+            PurchaseRequest::builder()
+                .product_id("premium")
+                .product_type(ProductType::AutoRenewableSubscription)
+                .quantity(2)
+                .build()
+                .is_err()
+        );
     }
 
     #[test]
@@ -488,7 +3062,24 @@ mod tests {
         let json = "{}";
         let request: RestorePurchasesRequest =
             serde_json::from_str(json).expect("Failed to deserialize RestorePurchasesRequest");
-        assert_eq!(request.product_type, "subs");
+        assert_eq!(request.product_type, ProductType::AutoRenewableSubscription);
+        assert!(!request.force_sync);
+        assert_eq!(
+            serde_json::to_string(&request).expect("Failed to serialize RestorePurchasesRequest"),
+            r#"{"productType":"subs"}"#
+        );
+    }
+
+    #[test]
+    fn test_restore_purchases_request_force_sync() {
+        let json = r#"{"productType":"inapp","forceSync":true}"#;
+        let request: RestorePurchasesRequest =
+            serde_json::from_str(json).expect("Failed to deserialize RestorePurchasesRequest");
+        assert!(request.force_sync);
+        assert_eq!(
+            serde_json::to_string(&request).expect("Failed to serialize RestorePurchasesRequest"),
+            json
+        );
     }
 
     #[test]
@@ -502,6 +3093,9 @@ mod tests {
             is_auto_renewing: None,
             is_acknowledged: None,
             purchase_token: None,
+            renewal_info: None,
+            latest_transaction: None,
+            from_cache: false,
         };
 
         let json = serde_json::to_string(&status).expect("Failed to serialize ProductStatus");
@@ -509,6 +3103,7 @@ mod tests {
         assert!(!json.contains("purchaseState"));
         assert!(!json.contains("purchaseTime"));
         assert!(!json.contains("expirationTime"));
+        assert!(!json.contains("renewalInfo"));
     }
 
     #[test]
@@ -516,12 +3111,15 @@ mod tests {
         let status = ProductStatus {
             product_id: "prod1".to_string(),
             is_owned: true,
-            purchase_state: Some(PurchaseStateValue::Purchased),
-            purchase_time: Some(1_700_000_000_000),
-            expiration_time: Some(1_703_000_000_000),
+            purchase_state: Some(PurchaseState::Purchased),
+            purchase_time: Some(Timestamp::from_unix_millis(1_700_000_000_000)),
+            expiration_time: Some(Timestamp::from_unix_millis(1_703_000_000_000)),
             is_auto_renewing: Some(true),
             is_acknowledged: Some(true),
             purchase_token: Some("token123".to_string()),
+            renewal_info: None,
+            latest_transaction: None,
+            from_cache: false,
         };
 
         let json = serde_json::to_string(&status).expect("Failed to serialize ProductStatus");
@@ -540,30 +3138,785 @@ mod tests {
         assert_eq!(json, r#"{"purchaseToken":"token123"}"#);
     }
 
+    #[test]
+    fn test_finish_transaction_request_serde() {
+        let json = r#"{"transactionId":"2000000123456789"}"#;
+        let request: FinishTransactionRequest =
+            serde_json::from_str(json).expect("Failed to deserialize FinishTransactionRequest");
+        assert_eq!(request.transaction_id, "2000000123456789");
+    }
+
+    #[test]
+    fn test_get_app_transaction_request_default() {
+        let request: GetAppTransactionRequest =
+            serde_json::from_str("{}").expect("Failed to deserialize GetAppTransactionRequest");
+        assert!(!request.force_refresh);
+
+        let request: GetAppTransactionRequest = serde_json::from_str(r#"{"forceRefresh":true}"#)
+            .expect("Failed to deserialize GetAppTransactionRequest");
+        assert!(request.force_refresh);
+    }
+
+    #[test]
+    fn test_app_transaction_info_serde() {
+        let json = r#"{"bundleId":"com.example.app","appVersion":"2.3","originalAppVersion":"1.0","originalPurchaseDate":"2020-09-13T12:26:40.000Z","environment":"production","jwsRepresentation":"eyJ..."}"#;
+        let info: AppTransactionInfo =
+            serde_json::from_str(json).expect("Failed to deserialize AppTransactionInfo");
+        assert_eq!(info.bundle_id, "com.example.app");
+        assert_eq!(info.app_version, "2.3");
+        assert_eq!(info.original_app_version, "1.0");
+        assert_eq!(
+            info.original_purchase_date,
+            Timestamp::from_unix_millis(1_600_000_000_000)
+        );
+        assert_eq!(info.environment, IapEnvironment::Production);
+        assert_eq!(info.jws_representation, "eyJ...");
+
+        let sandbox: AppTransactionInfo = serde_json::from_str(&json.replace(
+            r#""environment":"production""#,
+            r#""environment":"sandbox""#,
+        ))
+        .expect("Failed to deserialize AppTransactionInfo");
+        assert_eq!(sandbox.environment, IapEnvironment::Sandbox);
+    }
+
+    #[test]
+    fn test_get_unfinished_transactions_response_serde() {
+        let json = r#"{"purchases":[{"orderId":null,"packageName":"com.example.app","productId":"coins_100","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"2000000123","purchaseState":0,"isAutoRenewing":false,"isAcknowledged":false,"originalJson":"","signature":""}]}"#;
+        let response: GetUnfinishedTransactionsResponse = serde_json::from_str(json)
+            .expect("Failed to deserialize GetUnfinishedTransactionsResponse");
+        assert_eq!(response.purchases.len(), 1);
+        assert_eq!(response.purchases[0].product_id, "coins_100");
+        assert!(!response.purchases[0].is_acknowledged);
+
+        // Nothing left unfinished is a normal, empty result.
+        let empty: GetUnfinishedTransactionsResponse = serde_json::from_str(r#"{"purchases":[]}"#)
+            .expect("Failed to deserialize GetUnfinishedTransactionsResponse");
+        assert!(empty.purchases.is_empty());
+    }
+
+    #[test]
+    fn test_get_all_product_statuses_serde() {
+        let request: GetAllProductStatusesRequest =
+            serde_json::from_str(r#"{"productType":"inapp"}"#)
+                .expect("Failed to deserialize GetAllProductStatusesRequest");
+        assert_eq!(request.product_type, ProductType::NonConsumable);
+
+        let json = r#"{"statuses":[{"productId":"premium","isOwned":true,"purchaseState":0,"purchaseToken":"2000000123"},{"productId":"remove_ads","isOwned":true}]}"#;
+        let response: GetAllProductStatusesResponse = serde_json::from_str(json)
+            .expect("Failed to deserialize GetAllProductStatusesResponse");
+        let ids: Vec<_> = response
+            .statuses
+            .iter()
+            .map(|status| status.product_id.as_str())
+            .collect();
+        assert_eq!(ids, ["premium", "remove_ads"]);
+        assert!(response.statuses.iter().all(|status| status.is_owned));
+    }
+
+    #[test]
+    fn test_refund_request_serde() {
+        let request: RefundRequest = serde_json::from_str(r#"{"transactionId":"2000000123"}"#)
+            .expect("Failed to deserialize RefundRequest");
+        assert_eq!(request.transaction_id, "2000000123");
+    }
+
+    #[test]
+    fn test_begin_refund_request_response_serde() {
+        let response: BeginRefundRequestResponse =
+            serde_json::from_str(r#"{"result":"userCancelled"}"#)
+                .expect("Failed to deserialize BeginRefundRequestResponse");
+        assert_eq!(response.result, RefundRequestResult::UserCancelled);
+
+        assert_eq!(
+            serde_json::to_string(&RefundRequestResult::Success)
+                .expect("Failed to serialize RefundRequestResult"),
+            r#""success""#
+        );
+    }
+
+    #[test]
+    fn test_refund_request_status_serde() {
+        assert_eq!(
+            serde_json::to_string(&RefundRequestStatus::UserCancelled)
+                .expect("Failed to serialize RefundRequestStatus"),
+            r#""userCancelled""#
+        );
+        let status: RefundRequestStatus =
+            serde_json::from_str(r#""error""#).expect("Failed to deserialize RefundRequestStatus");
+        assert_eq!(status, RefundRequestStatus::Error);
+    }
+
+    #[cfg(desktop)]
+    #[test]
+    fn test_refund_request_status_from_outcome() {
+        use crate::error::{ErrorResponse, PluginInvokeError};
+
+        let rejected = |code: &str| {
+            Err(crate::Error::from(PluginInvokeError::InvokeRejected(
+                ErrorResponse::new(Some(code), "refund"),
+            )))
+        };
+
+        assert_eq!(
+            RefundRequestStatus::from_outcome(Ok(RefundRequestResult::Success))
+                .expect("Expected a status"),
+            RefundRequestStatus::Success
+        );
+        assert_eq!(
+            RefundRequestStatus::from_outcome(Ok(RefundRequestResult::UserCancelled))
+                .expect("Expected a status"),
+            RefundRequestStatus::UserCancelled
+        );
+        assert_eq!(
+            RefundRequestStatus::from_outcome(rejected("duplicateRequest"))
+                .expect("Expected a status"),
+            RefundRequestStatus::Error
+        );
+        assert_eq!(
+            RefundRequestStatus::from_outcome(rejected("refundRequestFailed"))
+                .expect("Expected a status"),
+            RefundRequestStatus::Error
+        );
+
+        let error = RefundRequestStatus::from_outcome(rejected("transactionNotFound"))
+            .expect_err("Expected transactionNotFound to stay an error");
+        assert_eq!(
+            error.error_code(),
+            Some(crate::IapErrorCode::TransactionNotFound)
+        );
+    }
+
+    #[test]
+    fn test_purchase_request_with_promotional_offer() {
+        let json = r#"{"productId":"premium","productType":"subs","promotionalOffer":{"offerId":"winback","keyId":"ABC123","nonce":"4f0a7c1e-2b3d-4e5f-8a9b-0c1d2e3f4a5b","signature":"MEUCIQ==","timestamp":1700000000000}}"#;
+        let request: PurchaseRequest =
+            serde_json::from_str(json).expect("Failed to deserialize PurchaseRequest");
+        let offer = request
+            .options
+            .and_then(|opts| opts.promotional_offer)
+            .expect("Expected promotional offer");
+        assert_eq!(offer.offer_id, "winback");
+        assert_eq!(offer.key_id, "ABC123");
+        assert_eq!(offer.nonce, "4f0a7c1e-2b3d-4e5f-8a9b-0c1d2e3f4a5b");
+        assert_eq!(offer.signature, "MEUCIQ==");
+        assert_eq!(offer.timestamp, 1_700_000_000_000);
+    }
+
+    #[test]
+    fn test_purchase_request_builder_promotional_offer() {
+        let offer = PromotionalOffer {
+            offer_id: "winback".to_string(),
+            key_id: "ABC123".to_string(),
+            nonce: "4f0a7c1e-2b3d-4e5f-8a9b-0c1d2e3f4a5b".to_string(),
+            signature: "MEUCIQ==".to_string(),
+            timestamp: 1_700_000_000_000,
+        };
+        let request = PurchaseRequest::builder()
+            .product_id("premium")
+            .promotional_offer(offer.clone())
+            .build()
+            .expect("Failed to build PurchaseRequest");
+
+        let json = serde_json::to_value(&request).expect("Failed to serialize PurchaseRequest");
+        assert_eq!(json["promotionalOffer"]["offerId"], "winback");
+        assert_eq!(
+            request.options.and_then(|opts| opts.promotional_offer),
+            Some(offer)
+        );
+    }
+
+    #[test]
+    fn test_get_win_back_offers_response_serde() {
+        let json = r#"{"offers":[{"id":"winback_50","offerType":"winBack","price":"$4.99","priceLocale":"en_US","period":{"unit":"month","value":1},"periodCount":3,"paymentMode":"payAsYouGo","isEligible":true}]}"#;
+        let response: GetWinBackOffersResponse =
+            serde_json::from_str(json).expect("Failed to deserialize GetWinBackOffersResponse");
+        let offer = &response.offers[0];
+        assert_eq!(offer.offer.id.as_deref(), Some("winback_50"));
+        assert_eq!(offer.offer.offer_type, SubscriptionOfferType::WinBack);
+        assert_eq!(offer.offer.period_count, 3);
+        assert!(offer.is_eligible);
+
+        let serialized =
+            serde_json::to_string(&response).expect("Failed to serialize GetWinBackOffersResponse");
+        assert_eq!(serialized, json);
+    }
+
+    #[test]
+    fn test_purchase_request_with_win_back_offer_id() {
+        let request = PurchaseRequest::builder()
+            .product_id("premium")
+            .win_back_offer_id("winback_50")
+            .build()
+            .expect("Failed to build PurchaseRequest");
+        let json = serde_json::to_value(&request).expect("Failed to serialize PurchaseRequest");
+        assert_eq!(json["winBackOfferId"], "winback_50");
+    }
+
+    #[test]
+    fn test_signing_info_serde() {
+        let json = r#"{"teamId":"ABCDE12345","subjectCn":"Apple Distribution: Example Inc (ABCDE12345)","distributionType":"testFlight"}"#;
+        let info: SigningInfo =
+            serde_json::from_str(json).expect("Failed to deserialize SigningInfo");
+        assert_eq!(info.team_id.as_deref(), Some("ABCDE12345"));
+        assert_eq!(info.distribution_type, DistributionType::TestFlight);
+        let serialized = serde_json::to_string(&info).expect("Failed to serialize SigningInfo");
+        assert_eq!(serialized, json);
+
+        let unsigned: SigningInfo = serde_json::from_str(r#"{"distributionType":"unknown"}"#)
+            .expect("Failed to deserialize SigningInfo");
+        assert_eq!(unsigned.team_id, None);
+        assert_eq!(unsigned.subject_cn, None);
+        assert_eq!(unsigned.distribution_type, DistributionType::Unknown);
+
+        // Channels added later fall back to Unknown.
+        let future: DistributionType = serde_json::from_str(r#""enterprise""#)
+            .expect("Failed to deserialize DistributionType");
+        assert_eq!(future, DistributionType::Unknown);
+    }
+
+    #[test]
+    fn test_can_make_payments_response_serde() {
+        let json = r#"{"canMakePayments":false,"reason":"restricted"}"#;
+        let response: CanMakePaymentsResponse =
+            serde_json::from_str(json).expect("Failed to deserialize CanMakePaymentsResponse");
+        assert!(!response.can_make_payments);
+        assert_eq!(response.reason.as_deref(), Some("restricted"));
+        let serialized =
+            serde_json::to_string(&response).expect("Failed to serialize CanMakePaymentsResponse");
+        assert_eq!(serialized, json);
+
+        let allowed: CanMakePaymentsResponse = serde_json::from_str(r#"{"canMakePayments":true}"#)
+            .expect("Failed to deserialize CanMakePaymentsResponse");
+        assert!(allowed.can_make_payments);
+        assert!(allowed.reason.is_none());
+    }
+
+    #[test]
+    fn test_is_supported_response_serde() {
+        let json = r#"{"isSupported":false,"reason":"storeKitUnavailable"}"#;
+        let response: IsSupportedResponse =
+            serde_json::from_str(json).expect("Failed to deserialize IsSupportedResponse");
+        assert!(!response.is_supported);
+        assert_eq!(response.reason.as_deref(), Some("storeKitUnavailable"));
+        let serialized =
+            serde_json::to_string(&response).expect("Failed to serialize IsSupportedResponse");
+        assert_eq!(serialized, json);
+
+        let supported = IsSupportedResponse {
+            is_supported: true,
+            reason: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&supported).expect("Failed to serialize IsSupportedResponse"),
+            r#"{"isSupported":true}"#
+        );
+    }
+
+    #[test]
+    fn test_check_introductory_offer_eligibility_serde() {
+        let request: CheckIntroductoryOfferEligibilityRequest =
+            serde_json::from_str(r#"{"productIds":["monthly","yearly"]}"#)
+                .expect("Failed to deserialize CheckIntroductoryOfferEligibilityRequest");
+        assert_eq!(request.product_ids, ["monthly", "yearly"]);
+
+        let response: CheckIntroductoryOfferEligibilityResponse =
+            serde_json::from_str(r#"{"eligibility":{"monthly":true,"yearly":false}}"#)
+                .expect("Failed to deserialize CheckIntroductoryOfferEligibilityResponse");
+        assert_eq!(response.eligibility.get("monthly"), Some(&true));
+        assert_eq!(response.eligibility.get("yearly"), Some(&false));
+        assert!(!response.eligibility.contains_key("coins"));
+    }
+
+    #[test]
+    fn test_is_eligible_for_intro_offer_response_serde() {
+        let json = r#"{"eligibility":"eligible","subscriptionGroupId":"21345678"}"#;
+        let response: IsEligibleForIntroOfferResponse = serde_json::from_str(json)
+            .expect("Failed to deserialize IsEligibleForIntroOfferResponse");
+        assert_eq!(response.eligibility, IntroOfferEligibility::Eligible);
+        assert_eq!(response.subscription_group_id.as_deref(), Some("21345678"));
+
+        let serialized = serde_json::to_string(&response)
+            .expect("Failed to serialize IsEligibleForIntroOfferResponse");
+        assert_eq!(serialized, json);
+    }
+
+    #[test]
+    fn test_is_eligible_for_intro_offer_response_without_group() {
+        let response: IsEligibleForIntroOfferResponse =
+            serde_json::from_str(r#"{"eligibility":"unknown"}"#)
+                .expect("Failed to deserialize IsEligibleForIntroOfferResponse");
+        assert_eq!(response.eligibility, IntroOfferEligibility::Unknown);
+        assert!(response.subscription_group_id.is_none());
+
+        let serialized = serde_json::to_string(&response)
+            .expect("Failed to serialize IsEligibleForIntroOfferResponse");
+        assert!(!serialized.contains("subscriptionGroupId"));
+    }
+
+    #[test]
+    fn test_intro_offer_eligibility_serde() {
+        for (eligibility, expected) in [
+            (IntroOfferEligibility::Eligible, r#""eligible""#),
+            (IntroOfferEligibility::Ineligible, r#""ineligible""#),
+            (IntroOfferEligibility::Unknown, r#""unknown""#),
+        ] {
+            let json = serde_json::to_string(&eligibility)
+                .expect("Failed to serialize IntroOfferEligibility");
+            assert_eq!(json, expected);
+        }
+
+        let future: IntroOfferEligibility = serde_json::from_str(r#""pending""#)
+            .expect("Failed to deserialize IntroOfferEligibility");
+        assert_eq!(future, IntroOfferEligibility::Unknown);
+    }
+
+    #[test]
+    fn test_get_environment_response_serde() {
+        for (environment, name) in [
+            (IapEnvironment::Production, "production"),
+            (IapEnvironment::Sandbox, "sandbox"),
+            (IapEnvironment::TestFlight, "testFlight"),
+            (IapEnvironment::AndroidTest, "androidTest"),
+        ] {
+            let json = format!(r#"{{"environment":"{name}"}}"#);
+            let response: GetEnvironmentResponse =
+                serde_json::from_str(&json).expect("Failed to deserialize GetEnvironmentResponse");
+            assert_eq!(response.environment, environment);
+
+            let serialized = serde_json::to_string(&response)
+                .expect("Failed to serialize GetEnvironmentResponse");
+            assert_eq!(serialized, json);
+        }
+    }
+
+    #[test]
+    fn test_storefront_info_serde() {
+        let json = r#"{"id":"143441","countryCode":"USA"}"#;
+        let storefront: StorefrontInfo =
+            serde_json::from_str(json).expect("Failed to deserialize StorefrontInfo");
+        assert_eq!(storefront.id, "143441");
+        assert_eq!(storefront.country_code, "USA");
+
+        let serialized =
+            serde_json::to_string(&storefront).expect("Failed to serialize StorefrontInfo");
+        assert_eq!(serialized, json);
+    }
+
+    #[test]
+    fn test_store_message_event_serde() {
+        let json = r#"{"reason":"priceIncreaseConsent","deferred":true}"#;
+        let event: StoreMessageEvent =
+            serde_json::from_str(json).expect("Failed to deserialize StoreMessageEvent");
+        assert_eq!(event.reason, StoreMessageReason::PriceIncreaseConsent);
+        assert!(event.deferred);
+        assert_eq!(
+            serde_json::to_string(&event).expect("Failed to serialize StoreMessageEvent"),
+            json
+        );
+
+        // Reasons added in later iOS versions don't break older Rust code.
+        let event: StoreMessageEvent =
+            serde_json::from_str(r#"{"reason":"somethingNew","deferred":false}"#)
+                .expect("Failed to deserialize StoreMessageEvent");
+        assert_eq!(event.reason, StoreMessageReason::Unknown);
+
+        let response: DisplayPendingStoreMessagesResponse =
+            serde_json::from_str(r#"{"displayed":2}"#)
+                .expect("Failed to deserialize DisplayPendingStoreMessagesResponse");
+        assert_eq!(response.displayed, 2);
+    }
+
+    #[test]
+    fn test_external_purchase_link_availability_serde() {
+        let json = r#"{"eligible":true,"storefrontCountry":"NLD"}"#;
+        let availability: ExternalPurchaseLinkAvailability = serde_json::from_str(json)
+            .expect("Failed to deserialize ExternalPurchaseLinkAvailability");
+        assert!(availability.eligible);
+        assert_eq!(availability.storefront_country.as_deref(), Some("NLD"));
+        assert_eq!(
+            serde_json::to_string(&availability)
+                .expect("Failed to serialize ExternalPurchaseLinkAvailability"),
+            json
+        );
+
+        // No storefront loaded yet.
+        let availability: ExternalPurchaseLinkAvailability =
+            serde_json::from_str(r#"{"eligible":false}"#)
+                .expect("Failed to deserialize ExternalPurchaseLinkAvailability");
+        assert!(!availability.eligible);
+        assert_eq!(availability.storefront_country, None);
+    }
+
+    #[test]
+    fn test_product_status_with_renewal_info() {
+        let json = r#"{"productId":"premium","isOwned":true,"purchaseState":0,"isAutoRenewing":false,"renewalInfo":{"autoRenewEnabled":false,"expirationDate":1700000000000,"billingRetryPeriod":true,"gracePeriodExpiresDate":1700500000000,"priceIncreaseStatus":"pending","renewalProductId":"premium_yearly"}}"#;
+        let status: ProductStatus =
+            serde_json::from_str(json).expect("Failed to deserialize ProductStatus");
+        let renewal = status
+            .renewal_info
+            .expect("Expected renewal info to be present");
+        assert!(!renewal.auto_renew_enabled);
+        assert!(renewal.billing_retry_period);
+        assert_eq!(
+            renewal.expiration_date,
+            Some(Timestamp::from_unix_millis(1_700_000_000_000))
+        );
+        assert_eq!(
+            renewal.grace_period_expires_date,
+            Some(Timestamp::from_unix_millis(1_700_500_000_000))
+        );
+        assert_eq!(
+            renewal.price_increase_status,
+            Some(PriceIncreaseStatus::Pending)
+        );
+        assert_eq!(
+            renewal.renewal_product_id,
+            Some("premium_yearly".to_string())
+        );
+    }
+
+    #[test]
+    fn test_product_status_renewal_info_android_subset() {
+        // Play Billing only knows whether the subscription auto-renews.
+        let json =
+            r#"{"productId":"premium","isOwned":true,"renewalInfo":{"autoRenewEnabled":true}}"#;
+        let status: ProductStatus =
+            serde_json::from_str(json).expect("Failed to deserialize ProductStatus");
+        assert_eq!(
+            status.renewal_info,
+            Some(SubscriptionRenewalInfo {
+                auto_renew_enabled: true,
+                ..SubscriptionRenewalInfo::default()
+            })
+        );
+
+        let status: ProductStatus =
+            serde_json::from_str(r#"{"productId":"coins","isOwned":false}"#)
+                .expect("Failed to deserialize ProductStatus");
+        assert_eq!(status.renewal_info, None);
+    }
+
+    #[test]
+    fn test_product_status_latest_transaction() {
+        let json = r#"{
+            "productId": "premium",
+            "isOwned": false,
+            "latestTransaction": {
+                "orderId": null,
+                "packageName": "com.example.app",
+                "productId": "premium",
+                "purchaseTime": 1700000000000,
+                "purchaseToken": "2000000123",
+                "purchaseState": 1,
+                "isAutoRenewing": false,
+                "isAcknowledged": true,
+                "originalJson": "",
+                "signature": "",
+                "originalId": "2000000001",
+                "revocationTime": 1700100000000,
+                "revocationReason": "other"
+            }
+        }"#;
+        let status: ProductStatus =
+            serde_json::from_str(json).expect("Failed to deserialize ProductStatus");
+        let latest = status
+            .latest_transaction
+            .expect("Expected latest transaction to be present");
+        assert_eq!(latest.purchase_token, "2000000123");
+        assert_eq!(
+            latest.revocation_time,
+            Some(Timestamp::from_unix_millis(1_700_100_000_000))
+        );
+
+        // Older payloads without the field still parse, and it isn't sent back.
+        let status: ProductStatus =
+            serde_json::from_str(r#"{"productId":"coins","isOwned":false}"#)
+                .expect("Failed to deserialize ProductStatus");
+        assert!(status.latest_transaction.is_none());
+        let serialized = serde_json::to_string(&status).expect("Failed to serialize ProductStatus");
+        assert!(!serialized.contains("latestTransaction"));
+    }
+
+    #[test]
+    fn test_transaction_event_serialize() {
+        let event = TransactionEvent {
+            product_id: "premium".to_string(),
+            state: TransactionState::Purchased {
+                transaction_id: "2000000123".to_string(),
+            },
+            timestamp: 1_700_000_000_000,
+            verification: None,
+        };
+        let json = serde_json::to_string(&event).expect("Failed to serialize TransactionEvent");
+        assert_eq!(
+            json,
+            r#"{"productId":"premium","state":{"type":"purchased","transactionId":"2000000123"},"timestamp":1700000000000}"#
+        );
+    }
+
+    #[test]
+    fn test_transaction_state_deserialize() {
+        for (json, expected) in [
+            (r#"{"type":"purchasing"}"#, TransactionState::Purchasing),
+            (r#"{"type":"deferred"}"#, TransactionState::Deferred),
+            (r#"{"type":"revoked"}"#, TransactionState::Revoked),
+            (
+                r#"{"type":"restored","originalTransactionId":"2000000001"}"#,
+                TransactionState::Restored {
+                    original_transaction_id: "2000000001".to_string(),
+                },
+            ),
+            (
+                r#"{"type":"failed","error":"userCancelled"}"#,
+                TransactionState::Failed {
+                    error: IapErrorCode::UserCancelled,
+                },
+            ),
+            (
+                r#"{"type":"autoAcknowledged","purchaseToken":"token-1"}"#,
+                TransactionState::AutoAcknowledged {
+                    purchase_token: "token-1".to_string(),
+                },
+            ),
+            (
+                r#"{"type":"failed","error":"storeQueryFailed"}"#,
+                TransactionState::Failed {
+                    error: IapErrorCode::PlatformSpecific("storeQueryFailed".to_string()),
+                },
+            ),
+        ] {
+            let state: TransactionState =
+                serde_json::from_str(json).expect("Failed to deserialize TransactionState");
+            assert_eq!(state, expected);
+        }
+    }
+
+    #[test]
+    fn test_transaction_event_verification_serde() {
+        let json = r#"{"productId":"premium","state":{"type":"purchased","transactionId":"2000000123"},"timestamp":1700000000000,"verification":"verified"}"#;
+        let event: TransactionEvent =
+            serde_json::from_str(json).expect("Failed to deserialize TransactionEvent");
+        assert_eq!(event.verification, Some(TransactionVerification::Verified));
+
+        let serialized =
+            serde_json::to_string(&event).expect("Failed to serialize TransactionEvent");
+        assert_eq!(serialized, json);
+    }
+
+    #[test]
+    fn test_transaction_event_new_stamps_time() {
+        let event = TransactionEvent::new("premium", TransactionState::Purchasing);
+        assert_eq!(event.product_id, "premium");
+        assert!(event.timestamp > 1_600_000_000_000);
+    }
+
+    #[test]
+    fn test_get_subscription_status_response_deserialize() {
+        // Shape produced by `subscriptionStatusObject` in the Swift backends.
+        let json = r#"{
+            "statuses": [
+                {
+                    "productId": "premium_monthly",
+                    "state": "subscribed",
+                    "transactionId": "2000000123456789",
+                    "originalTransactionId": "2000000000000001",
+                    "isFamilyShared": false,
+                    "willAutoRenew": true,
+                    "expirationDate": 1700000000000,
+                    "autoRenewProductId": "premium_yearly"
+                },
+                {
+                    "productId": "premium_monthly",
+                    "state": "inGracePeriod",
+                    "transactionId": "2000000123456790",
+                    "originalTransactionId": "2000000000000002",
+                    "isFamilyShared": true,
+                    "willAutoRenew": true,
+                    "expirationDate": 1690000000000,
+                    "gracePeriodExpirationDate": 1691000000000,
+                    "billingRetryPeriod": true,
+                    "autoRenewProductId": "premium_monthly"
+                },
+                {
+                    "productId": "premium_monthly",
+                    "state": "expired",
+                    "transactionId": "2000000123456791",
+                    "originalTransactionId": "2000000000000003",
+                    "willAutoRenew": false,
+                    "expirationDate": 1680000000000,
+                    "expirationReason": "billingError"
+                }
+            ]
+        }"#;
+        let response: GetSubscriptionStatusResponse = serde_json::from_str(json)
+            .expect("Failed to deserialize GetSubscriptionStatusResponse");
+        assert_eq!(response.statuses.len(), 3);
+
+        let subscribed = &response.statuses[0];
+        assert_eq!(subscribed.state, SubscriptionState::Subscribed);
+        assert!(subscribed.will_auto_renew);
+        assert!(!subscribed.is_family_shared);
+        assert_eq!(
+            subscribed.expiration_date,
+            Some(Timestamp::from_unix_millis(1_700_000_000_000))
+        );
+        assert_eq!(
+            subscribed.auto_renew_product_id,
+            Some("premium_yearly".to_string())
+        );
+        assert_eq!(subscribed.expiration_reason, None);
+
+        let shared = &response.statuses[1];
+        assert_eq!(shared.state, SubscriptionState::InGracePeriod);
+        assert!(shared.is_family_shared);
+        assert_eq!(
+            shared.grace_period_expiration_date,
+            Some(Timestamp::from_unix_millis(1_691_000_000_000))
+        );
+        assert!(shared.billing_retry_period);
+        assert!(!subscribed.billing_retry_period);
+
+        let expired = &response.statuses[2];
+        assert_eq!(expired.state, SubscriptionState::Expired);
+        assert!(!expired.is_family_shared);
+        assert_eq!(
+            expired.expiration_reason,
+            Some(ExpirationReason::BillingError)
+        );
+        assert_eq!(expired.auto_renew_product_id, None);
+    }
+
+    #[test]
+    fn test_subscription_state_serde() {
+        for (state, expected) in [
+            (SubscriptionState::Subscribed, r#""subscribed""#),
+            (SubscriptionState::Expired, r#""expired""#),
+            (SubscriptionState::InBillingRetry, r#""inBillingRetry""#),
+            (SubscriptionState::InGracePeriod, r#""inGracePeriod""#),
+            (SubscriptionState::Revoked, r#""revoked""#),
+        ] {
+            assert_eq!(
+                serde_json::to_string(&state).expect("Failed to serialize SubscriptionState"),
+                expected
+            );
+        }
+        let state: SubscriptionState = serde_json::from_str(r#""somethingNew""#)
+            .expect("Failed to deserialize SubscriptionState");
+        assert_eq!(state, SubscriptionState::Unknown);
+    }
+
+    #[test]
+    fn test_expiration_reason_serde() {
+        let reason: ExpirationReason = serde_json::from_str(r#""didNotConsentToPriceIncrease""#)
+            .expect("Failed to deserialize ExpirationReason");
+        assert_eq!(reason, ExpirationReason::DidNotConsentToPriceIncrease);
+        let reason: ExpirationReason =
+            serde_json::from_str(r#""unknown""#).expect("Failed to deserialize ExpirationReason");
+        assert_eq!(reason, ExpirationReason::Unknown);
+    }
+
+    #[test]
+    fn test_subscription_status_skips_absent_fields() {
+        let status = SubscriptionStatus {
+            product_id: "premium".to_string(),
+            state: SubscriptionState::Subscribed,
+            transaction_id: "1".to_string(),
+            original_transaction_id: "1".to_string(),
+            is_family_shared: false,
+            subscription_group_id: None,
+            will_auto_renew: true,
+            expiration_date: None,
+            grace_period_expiration_date: None,
+            billing_retry_period: false,
+            auto_renew_product_id: None,
+            expiration_reason: None,
+            price_increase_status: None,
+            renewal_price_amount_micros: None,
+            renewal_currency: None,
+        };
+        let json = serde_json::to_string(&status).expect("Failed to serialize SubscriptionStatus");
+        assert_eq!(
+            json,
+            r#"{"productId":"premium","state":"subscribed","transactionId":"1","originalTransactionId":"1","isFamilyShared":false,"willAutoRenew":true}"#
+        );
+    }
+
+    #[test]
+    fn test_subscription_status_state_helpers() {
+        // Shape the Android bridge sends for a subscription Play still reports.
+        let json = r#"{"productId":"premium","state":"subscribed","transactionId":"GPA.1","originalTransactionId":"token","isFamilyShared":false,"willAutoRenew":false}"#;
+        let mut status: SubscriptionStatus =
+            serde_json::from_str(json).expect("Failed to deserialize SubscriptionStatus");
+        assert!(status.is_active());
+        assert!(!status.is_in_grace_period());
+        assert!(!status.is_in_billing_retry());
+
+        status.state = SubscriptionState::InGracePeriod;
+        assert!(status.is_active());
+        assert!(status.is_in_grace_period());
+
+        status.state = SubscriptionState::InBillingRetry;
+        assert!(!status.is_active());
+        assert!(status.is_in_billing_retry());
+
+        for state in [
+            SubscriptionState::Expired,
+            SubscriptionState::Revoked,
+            SubscriptionState::Unknown,
+        ] {
+            status.state = state;
+            assert!(!status.is_active());
+        }
+    }
+
     #[test]
     fn test_get_product_status_request_serde() {
         let json = r#"{"productId":"prod1"}"#;
         let request: GetProductStatusRequest =
             serde_json::from_str(json).expect("Failed to deserialize GetProductStatusRequest");
         assert_eq!(request.product_id, "prod1");
-        assert_eq!(request.product_type, "subs"); // default
+        assert_eq!(request.product_type, ProductType::AutoRenewableSubscription); // default
     }
 
     #[test]
     fn test_purchase_history_record_serde() {
         let record = PurchaseHistoryRecord {
             product_id: "prod1".to_string(),
-            purchase_time: 1_700_000_000_000,
+            purchase_time: Timestamp::from_unix_millis(1_700_000_000_000),
             purchase_token: "token".to_string(),
             quantity: 1,
             original_json: "{}".to_string(),
             signature: "sig".to_string(),
+            transaction_id: None,
+            original_transaction_id: None,
+            web_order_line_item_id: None,
+            product_type: None,
+            transaction_reason: None,
+            revocation_time: None,
+            revocation_reason: None,
+            verification: None,
         };
 
         let json =
             serde_json::to_string(&record).expect("Failed to serialize PurchaseHistoryRecord");
+        assert!(!json.contains("revocationTime"));
         let deserialized: PurchaseHistoryRecord =
             serde_json::from_str(&json).expect("Failed to deserialize PurchaseHistoryRecord");
         assert_eq!(deserialized.quantity, 1);
     }
+
+    #[test]
+    fn test_purchase_history_record_storekit_fields() {
+        let json = r#"{"productId":"coins_100","purchaseTime":"2023-11-14T22:13:20.000Z","purchaseToken":"2000000123","quantity":1,"originalJson":"","signature":"","transactionId":"2000000123","productType":"consumable","revocationTime":"2023-11-14T22:21:40.000Z","revocationReason":"other"}"#;
+        let record: PurchaseHistoryRecord =
+            serde_json::from_str(json).expect("Failed to deserialize PurchaseHistoryRecord");
+        assert_eq!(record.transaction_id.as_deref(), Some("2000000123"));
+        assert_eq!(record.product_type, Some(ProductType::Consumable));
+        assert_eq!(
+            record.revocation_time,
+            Some(Timestamp::from_unix_millis(1_700_000_500_000))
+        );
+        assert_eq!(record.revocation_reason.as_deref(), Some("other"));
+    }
 }
@@ -0,0 +1,110 @@
+use std::future::Future;
+use std::time::Duration;
+
+use crate::RetryPolicy;
+
+/// Runs `call` until it succeeds, fails with an error `policy` doesn't retry,
+/// or [`RetryPolicy::max_attempts`] is reached, sleeping with exponential
+/// backoff in between. Without a policy `call` runs once.
+///
+/// # Errors
+///
+/// Returns the last error `call` produced.
+pub async fn retry_with_backoff<T, F, Fut>(
+    policy: Option<&RetryPolicy>,
+    mut call: F,
+) -> crate::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = crate::Result<T>>,
+{
+    let Some(policy) = policy else {
+        return call().await;
+    };
+    let mut delay = Duration::from_millis(policy.initial_delay_ms);
+    let mut attempt = 1;
+    loop {
+        match call().await {
+            Err(err) if attempt < policy.max_attempts && policy.is_retryable(&err) => {
+                log::debug!(
+                    "Retrying store call after {err} (attempt {attempt}, waiting {delay:?})"
+                );
+                tokio::time::sleep(delay).await;
+                // A negative, NaN or overflowing factor keeps the last delay.
+                delay =
+                    Duration::try_from_secs_f64(delay.as_secs_f64() * policy.backoff_multiplier)
+                        .unwrap_or(delay);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IapErrorCode;
+    use std::cell::Cell;
+
+    fn network_error() -> crate::Error {
+        crate::error::PluginInvokeError::InvokeRejected(crate::error::ErrorResponse::new(
+            Some("networkError"),
+            "The Internet connection appears to be offline.",
+        ))
+        .into()
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            initial_delay_ms: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[test]
+    fn test_retry_with_backoff_retries_transient_errors() {
+        let calls = Cell::new(0);
+        let result = tauri::async_runtime::block_on(retry_with_backoff(Some(&policy()), || {
+            calls.set(calls.get() + 1);
+            let attempt = calls.get();
+            async move {
+                if attempt < 3 {
+                    Err(network_error())
+                } else {
+                    Ok(attempt)
+                }
+            }
+        }));
+        assert_eq!(result.ok(), Some(3));
+    }
+
+    #[test]
+    fn test_retry_with_backoff_gives_up_after_max_attempts() {
+        let calls = Cell::new(0);
+        let result: crate::Result<()> =
+            tauri::async_runtime::block_on(retry_with_backoff(Some(&policy()), || {
+                calls.set(calls.get() + 1);
+                async { Err(network_error()) }
+            }));
+        assert_eq!(
+            result.err().and_then(|e| e.error_code()),
+            Some(IapErrorCode::NetworkError)
+        );
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    fn test_retry_with_backoff_does_not_retry_other_errors() {
+        for policy in [Some(policy()), None] {
+            let calls = Cell::new(0);
+            let result: crate::Result<()> =
+                tauri::async_runtime::block_on(retry_with_backoff(policy.as_ref(), || {
+                    calls.set(calls.get() + 1);
+                    async { Err(crate::Error::InvalidArgument("bad".to_string())) }
+                }));
+            assert!(result.is_err());
+            assert_eq!(calls.get(), 1);
+        }
+    }
+}
@@ -0,0 +1,424 @@
+//! Payloads of the store-to-backend notifications: App Store Server
+//! Notifications V2 and Google Play Real-Time Developer Notifications (RTDN).
+//!
+//! These are for app backends; the plugin itself never receives them. Apple
+//! posts `{"signedPayload": "<JWS>"}`, whose decoded payload is an
+//! [`AppStoreNotification`]. Google publishes to Cloud Pub/Sub, and the
+//! base64 `message.data` decodes to a [`GooglePlayNotification`].
+
+use serde::{Deserialize, Serialize};
+
+use crate::Timestamp;
+
+/// Decoded `signedPayload` of an App Store Server Notification V2, after
+/// Apple's `ResponseBodyV2DecodedPayload`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppStoreNotification {
+    pub notification_type: AppStoreNotificationType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subtype: Option<AppStoreNotificationSubtype>,
+    /// Unique per notification; Apple retries with the same value, so use it
+    /// to drop duplicates.
+    #[serde(rename = "notificationUUID")]
+    pub notification_uuid: String,
+    /// Absent on `RENEWAL_EXTENSION` summaries and `EXTERNAL_PURCHASE_TOKEN`
+    /// notifications, which carry `summary` or `externalPurchaseToken`
+    /// instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<AppStoreNotificationData>,
+    /// `"2.0"`.
+    pub version: String,
+    pub signed_date: Timestamp,
+}
+
+/// App Store `notificationType`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AppStoreNotificationType {
+    ConsumptionRequest,
+    DidChangeRenewalPref,
+    DidChangeRenewalStatus,
+    DidFailToRenew,
+    DidRenew,
+    Expired,
+    ExternalPurchaseToken,
+    GracePeriodExpired,
+    OfferRedeemed,
+    OneTimeCharge,
+    PriceIncrease,
+    Refund,
+    RefundDeclined,
+    RefundReversed,
+    RenewalExtended,
+    RenewalExtension,
+    Revoke,
+    Subscribed,
+    /// Sent on request through the App Store Server API.
+    Test,
+    #[serde(other)]
+    Unknown,
+}
+
+/// App Store notification `subtype`, refining the
+/// [`AppStoreNotificationType`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AppStoreNotificationSubtype {
+    InitialBuy,
+    Resubscribe,
+    Downgrade,
+    Upgrade,
+    AutoRenewEnabled,
+    AutoRenewDisabled,
+    Voluntary,
+    BillingRetry,
+    PriceIncrease,
+    GracePeriod,
+    Pending,
+    Accepted,
+    BillingRecovery,
+    ProductNotForSale,
+    Summary,
+    Failure,
+    Unreported,
+    #[serde(other)]
+    Unknown,
+}
+
+/// The app and signed transaction a notification is about.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppStoreNotificationData {
+    /// Apple ID of the app; absent in the sandbox.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_apple_id: Option<u64>,
+    pub bundle_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bundle_version: Option<String>,
+    /// `"Production"` or `"Sandbox"`.
+    pub environment: String,
+    /// JWS transaction; decode it with `jws::decode_jws_transaction` (the
+    /// `jws` feature).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signed_transaction_info: Option<String>,
+    /// JWS renewal info, for auto-renewable subscriptions only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signed_renewal_info: Option<String>,
+    /// Subscription status: 1 active, 2 expired, 3 billing retry, 4 grace
+    /// period, 5 revoked. Auto-renewable subscriptions only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u8>,
+}
+
+/// Google Play `DeveloperNotification`, the decoded Pub/Sub `message.data`.
+/// Exactly one of the notification fields is set; none is for a test
+/// notification sent from the Play Console.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GooglePlayNotification {
+    pub version: String,
+    pub package_name: String,
+    /// Sent as a decimal string; numbers are accepted too.
+    #[serde(with = "millis_string")]
+    pub event_time_millis: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription_notification: Option<SubscriptionNotification>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub one_time_product_notification: Option<OneTimeProductNotification>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voided_purchase_notification: Option<VoidedPurchaseNotification>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionNotification {
+    pub version: String,
+    pub notification_type: SubscriptionNotificationType,
+    /// Look the subscription up with `purchases.subscriptionsv2.get`.
+    pub purchase_token: String,
+    /// Deprecated by Google, which no longer sends it on new notifications.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription_id: Option<String>,
+}
+
+/// Play `SubscriptionNotification.notificationType`, sent as an integer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(from = "u32", into = "u32")]
+pub enum SubscriptionNotificationType {
+    Recovered,
+    Renewed,
+    Canceled,
+    Purchased,
+    OnHold,
+    InGracePeriod,
+    Restarted,
+    PriceChangeConfirmed,
+    Deferred,
+    Paused,
+    PauseScheduleChanged,
+    Revoked,
+    Expired,
+    PriceChangeUpdated,
+    PendingPurchaseCanceled,
+    /// A code this version doesn't know about, kept verbatim.
+    Unknown(u32),
+}
+
+impl From<u32> for SubscriptionNotificationType {
+    fn from(code: u32) -> Self {
+        match code {
+            1 => Self::Recovered,
+            2 => Self::Renewed,
+            3 => Self::Canceled,
+            4 => Self::Purchased,
+            5 => Self::OnHold,
+            6 => Self::InGracePeriod,
+            7 => Self::Restarted,
+            8 => Self::PriceChangeConfirmed,
+            9 => Self::Deferred,
+            10 => Self::Paused,
+            11 => Self::PauseScheduleChanged,
+            12 => Self::Revoked,
+            13 => Self::Expired,
+            19 => Self::PriceChangeUpdated,
+            20 => Self::PendingPurchaseCanceled,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl From<SubscriptionNotificationType> for u32 {
+    fn from(kind: SubscriptionNotificationType) -> Self {
+        match kind {
+            SubscriptionNotificationType::Recovered => 1,
+            SubscriptionNotificationType::Renewed => 2,
+            SubscriptionNotificationType::Canceled => 3,
+            SubscriptionNotificationType::Purchased => 4,
+            SubscriptionNotificationType::OnHold => 5,
+            SubscriptionNotificationType::InGracePeriod => 6,
+            SubscriptionNotificationType::Restarted => 7,
+            SubscriptionNotificationType::PriceChangeConfirmed => 8,
+            SubscriptionNotificationType::Deferred => 9,
+            SubscriptionNotificationType::Paused => 10,
+            SubscriptionNotificationType::PauseScheduleChanged => 11,
+            SubscriptionNotificationType::Revoked => 12,
+            SubscriptionNotificationType::Expired => 13,
+            SubscriptionNotificationType::PriceChangeUpdated => 19,
+            SubscriptionNotificationType::PendingPurchaseCanceled => 20,
+            SubscriptionNotificationType::Unknown(code) => code,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OneTimeProductNotification {
+    pub version: String,
+    pub notification_type: OneTimeProductNotificationType,
+    pub purchase_token: String,
+    /// Product id of the one-time product.
+    pub sku: String,
+}
+
+/// Play `OneTimeProductNotification.notificationType`, sent as an integer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(from = "u32", into = "u32")]
+pub enum OneTimeProductNotificationType {
+    Purchased,
+    /// A pending purchase was canceled before the payment completed.
+    Canceled,
+    /// A code this version doesn't know about, kept verbatim.
+    Unknown(u32),
+}
+
+impl From<u32> for OneTimeProductNotificationType {
+    fn from(code: u32) -> Self {
+        match code {
+            1 => Self::Purchased,
+            2 => Self::Canceled,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl From<OneTimeProductNotificationType> for u32 {
+    fn from(kind: OneTimeProductNotificationType) -> Self {
+        match kind {
+            OneTimeProductNotificationType::Purchased => 1,
+            OneTimeProductNotificationType::Canceled => 2,
+            OneTimeProductNotificationType::Unknown(code) => code,
+        }
+    }
+}
+
+/// A refunded, charged back or canceled purchase; revoke what it granted.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoidedPurchaseNotification {
+    pub purchase_token: String,
+    pub order_id: String,
+    /// 1 for a subscription, 2 for a one-time product.
+    pub product_type: u32,
+    /// 1 for a full refund, 2 for a quantity-based partial refund.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refund_type: Option<u32>,
+}
+
+/// Protobuf JSON encodes 64-bit integers as strings.
+mod millis_string {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Millis {
+        Number(u64),
+        String(String),
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(millis: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(millis)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        match Millis::deserialize(deserializer)? {
+            Millis::Number(millis) => Ok(millis),
+            Millis::String(millis) => millis.parse().map_err(D::Error::custom),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_store_notification_serde() {
+        let json = r#"{
+            "notificationType": "DID_RENEW",
+            "subtype": "BILLING_RECOVERY",
+            "notificationUUID": "002e14d5-51f5-4503-b5a8-c3a1af68eb20",
+            "data": {
+                "appAppleId": 1234567890,
+                "bundleId": "com.example.app",
+                "bundleVersion": "42",
+                "environment": "Production",
+                "signedTransactionInfo": "eyJhbGciOiJFUzI1NiJ9.e30.sig",
+                "signedRenewalInfo": "eyJhbGciOiJFUzI1NiJ9.e30.sig",
+                "status": 1
+            },
+            "version": "2.0",
+            "signedDate": 1700000000000
+        }"#;
+        let notification: AppStoreNotification =
+            serde_json::from_str(json).expect("Failed to deserialize AppStoreNotification");
+
+        assert_eq!(
+            notification.notification_type,
+            AppStoreNotificationType::DidRenew
+        );
+        assert_eq!(
+            notification.subtype,
+            Some(AppStoreNotificationSubtype::BillingRecovery)
+        );
+        assert_eq!(
+            notification.notification_uuid,
+            "002e14d5-51f5-4503-b5a8-c3a1af68eb20"
+        );
+        assert_eq!(
+            notification.signed_date,
+            Timestamp::from_unix_millis(1_700_000_000_000)
+        );
+        let data = notification.data.expect("Expected notification data");
+        assert_eq!(data.app_apple_id, Some(1_234_567_890));
+        assert_eq!(data.bundle_id, "com.example.app");
+        assert_eq!(data.environment, "Production");
+        assert_eq!(data.status, Some(1));
+    }
+
+    #[test]
+    fn test_app_store_notification_without_data_or_known_type() {
+        let json = r#"{"notificationType":"SOMETHING_NEW","notificationUUID":"uuid","version":"2.0","signedDate":1700000000000}"#;
+        let notification: AppStoreNotification =
+            serde_json::from_str(json).expect("Failed to deserialize AppStoreNotification");
+
+        assert_eq!(
+            notification.notification_type,
+            AppStoreNotificationType::Unknown
+        );
+        assert_eq!(notification.subtype, None);
+        assert_eq!(notification.data, None);
+    }
+
+    #[test]
+    fn test_google_play_subscription_notification_serde() {
+        let json = r#"{"version":"1.0","packageName":"com.example.app","eventTimeMillis":"1700000000000","subscriptionNotification":{"version":"1.0","notificationType":4,"purchaseToken":"token-1","subscriptionId":"premium_monthly"}}"#;
+        let notification: GooglePlayNotification =
+            serde_json::from_str(json).expect("Failed to deserialize GooglePlayNotification");
+
+        assert_eq!(notification.package_name, "com.example.app");
+        assert_eq!(notification.event_time_millis, 1_700_000_000_000);
+        let subscription = notification
+            .subscription_notification
+            .as_ref()
+            .expect("Expected a subscription notification");
+        assert_eq!(
+            subscription.notification_type,
+            SubscriptionNotificationType::Purchased
+        );
+        assert_eq!(subscription.purchase_token, "token-1");
+        assert!(notification.one_time_product_notification.is_none());
+
+        // Round-trips to Google's wire format.
+        assert_eq!(
+            serde_json::to_string(&notification)
+                .expect("Failed to serialize GooglePlayNotification"),
+            json
+        );
+    }
+
+    #[test]
+    fn test_google_play_one_time_product_and_voided_notifications() {
+        let json = r#"{"version":"1.0","packageName":"com.example.app","eventTimeMillis":1700000000000,"oneTimeProductNotification":{"version":"1.0","notificationType":2,"purchaseToken":"token-2","sku":"coins_100"}}"#;
+        let notification: GooglePlayNotification =
+            serde_json::from_str(json).expect("Failed to deserialize GooglePlayNotification");
+        let one_time = notification
+            .one_time_product_notification
+            .expect("Expected a one-time product notification");
+        assert_eq!(
+            one_time.notification_type,
+            OneTimeProductNotificationType::Canceled
+        );
+        assert_eq!(one_time.sku, "coins_100");
+
+        let json = r#"{"version":"1.0","packageName":"com.example.app","eventTimeMillis":"1700000000000","voidedPurchaseNotification":{"purchaseToken":"token-3","orderId":"GPA.1234","productType":2,"refundType":1}}"#;
+        let notification: GooglePlayNotification =
+            serde_json::from_str(json).expect("Failed to deserialize GooglePlayNotification");
+        let voided = notification
+            .voided_purchase_notification
+            .expect("Expected a voided purchase notification");
+        assert_eq!(voided.order_id, "GPA.1234");
+        assert_eq!(voided.refund_type, Some(1));
+    }
+
+    #[test]
+    fn test_play_notification_types_keep_unknown_codes() {
+        assert_eq!(
+            SubscriptionNotificationType::from(99),
+            SubscriptionNotificationType::Unknown(99)
+        );
+        for code in (1..=13).chain([19, 20, 99]) {
+            assert_eq!(u32::from(SubscriptionNotificationType::from(code)), code);
+        }
+        for code in [1, 2, 7] {
+            assert_eq!(u32::from(OneTimeProductNotificationType::from(code)), code);
+        }
+        assert!(
+            serde_json::from_str::<GooglePlayNotification>(
+                r#"{"version":"1.0","packageName":"p","eventTimeMillis":"soon"}"#
+            )
+            .is_err()
+        );
+    }
+}
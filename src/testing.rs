@@ -0,0 +1,416 @@
+//! In-memory [`IapBackend`] for unit tests. Enable the `testing` feature in
+//! `[dev-dependencies]` to use it from application tests.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::backend::IapBackend;
+use crate::models::{
+    GetProductsResponse, GetPurchaseHistoryResponse, Product, ProductStatus, ProductType, Purchase,
+    PurchaseHistoryRecord, PurchaseRequest, RestorePurchasesRequest, RestorePurchasesResponse,
+};
+
+/// A fake store whose answers are configured up front.
+///
+/// Products, restorable purchases, history and product statuses are returned
+/// on every call. Purchase results and injected failures are queued and
+/// consumed one call at a time, since [`crate::Error`] is not `Clone`.
+/// Acknowledged, consumed and finished identifiers are recorded for
+/// assertions.
+#[derive(Debug, Default)]
+pub struct MockIap {
+    products: Vec<Product>,
+    restored: Vec<Purchase>,
+    history: Vec<PurchaseHistoryRecord>,
+    statuses: HashMap<String, ProductStatus>,
+    get_products_errors: Mutex<VecDeque<crate::Error>>,
+    purchase_results: Mutex<VecDeque<crate::Result<Purchase>>>,
+    acknowledged: Mutex<Vec<String>>,
+    consumed: Mutex<Vec<String>>,
+    finished: Mutex<Vec<String>>,
+}
+
+impl MockIap {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Products served by `get_products`, filtered by the requested ids and type.
+    #[must_use]
+    pub fn with_products(mut self, products: Vec<Product>) -> Self {
+        self.products = products;
+        self
+    }
+
+    /// Queue the result of the next `purchase` call. Without a queued result,
+    /// `purchase` fails with an [`std::io::Error`].
+    #[must_use]
+    pub fn with_purchase_result(self, result: crate::Result<Purchase>) -> Self {
+        lock(&self.purchase_results).push_back(result);
+        self
+    }
+
+    /// Make the next `get_products` call fail with `err`.
+    #[must_use]
+    pub fn fail_get_products_with(self, err: crate::Error) -> Self {
+        lock(&self.get_products_errors).push_back(err);
+        self
+    }
+
+    /// Purchases returned by `restore_purchases`, filtered by product type.
+    #[must_use]
+    pub fn with_restored_purchases(mut self, purchases: Vec<Purchase>) -> Self {
+        self.restored = purchases;
+        self
+    }
+
+    /// Records returned by `get_purchase_history`.
+    #[must_use]
+    pub fn with_purchase_history(mut self, history: Vec<PurchaseHistoryRecord>) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Status returned by `get_product_status` for `status.product_id`. Unknown
+    /// products are reported as not owned.
+    #[must_use]
+    pub fn with_product_status(mut self, status: ProductStatus) -> Self {
+        self.statuses.insert(status.product_id.clone(), status);
+        self
+    }
+
+    /// Purchase tokens passed to `acknowledge_purchase`, in call order.
+    #[must_use]
+    pub fn acknowledged(&self) -> Vec<String> {
+        lock(&self.acknowledged).clone()
+    }
+
+    /// Purchase tokens passed to `consume_purchase`, in call order.
+    #[must_use]
+    pub fn consumed(&self) -> Vec<String> {
+        lock(&self.consumed).clone()
+    }
+
+    /// Transaction ids passed to `finish_transaction`, in call order.
+    #[must_use]
+    pub fn finished(&self) -> Vec<String> {
+        lock(&self.finished).clone()
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+impl IapBackend for MockIap {
+    async fn get_products(
+        &self,
+        product_ids: Vec<String>,
+        product_type: ProductType,
+    ) -> crate::Result<GetProductsResponse> {
+        crate::validation::validate_product_ids(&product_ids)?;
+        let injected = lock(&self.get_products_errors).pop_front();
+        if let Some(err) = injected {
+            return Err(err);
+        }
+        let products = self
+            .products
+            .iter()
+            .filter(|p| product_ids.contains(&p.product_id))
+            .filter(|p| p.product_type == product_type.as_str())
+            .cloned()
+            .collect();
+        Ok(GetProductsResponse { products })
+    }
+
+    async fn purchase(&self, payload: PurchaseRequest) -> crate::Result<Purchase> {
+        crate::validate_product_id(&payload.product_id)?;
+        if let Some(opts) = &payload.options {
+            opts.validate(payload.product_type)?;
+        }
+        lock(&self.purchase_results).pop_front().unwrap_or_else(|| {
+            Err(crate::Error::from(std::io::Error::other(format!(
+                "MockIap has no purchase result queued for {}",
+                payload.product_id
+            ))))
+        })
+    }
+
+    async fn restore_purchases(
+        &self,
+        request: RestorePurchasesRequest,
+    ) -> crate::Result<RestorePurchasesResponse> {
+        let purchases = self
+            .restored
+            .iter()
+            .filter(|purchase| {
+                self.products
+                    .iter()
+                    .find(|p| p.product_id == purchase.product_id)
+                    .is_none_or(|p| p.product_type == request.product_type.as_str())
+            })
+            .cloned()
+            .collect();
+        Ok(RestorePurchasesResponse { purchases })
+    }
+
+    async fn get_purchase_history(&self) -> crate::Result<GetPurchaseHistoryResponse> {
+        Ok(GetPurchaseHistoryResponse {
+            history: self.history.clone(),
+        })
+    }
+
+    async fn acknowledge_purchase(&self, purchase_token: String) -> crate::Result<()> {
+        lock(&self.acknowledged).push(purchase_token);
+        Ok(())
+    }
+
+    async fn consume_purchase(&self, purchase_token: String) -> crate::Result<()> {
+        lock(&self.consumed).push(purchase_token);
+        Ok(())
+    }
+
+    async fn get_product_status(
+        &self,
+        product_id: String,
+        _product_type: ProductType,
+    ) -> crate::Result<ProductStatus> {
+        Ok(self
+            .statuses
+            .get(&product_id)
+            .cloned()
+            .unwrap_or(ProductStatus {
+                product_id,
+                is_owned: false,
+                purchase_state: None,
+                purchase_time: None,
+                expiration_time: None,
+                is_auto_renewing: None,
+                is_acknowledged: None,
+                purchase_token: None,
+                renewal_info: None,
+                latest_transaction: None,
+                from_cache: false,
+            }))
+    }
+
+    async fn finish_transaction(&self, transaction_id: String) -> crate::Result<()> {
+        lock(&self.finished).push(transaction_id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{MAX_PURCHASE_QUANTITY, PurchaseOptions};
+    use tauri::async_runtime::block_on;
+
+    fn product(product_id: &str, product_type: &str) -> Product {
+        serde_json::from_value(serde_json::json!({
+            "productId": product_id,
+            "title": product_id,
+            "description": "",
+            "productType": product_type,
+        }))
+        .expect("Failed to build product")
+    }
+
+    fn purchase(product_id: &str, token: &str) -> Purchase {
+        serde_json::from_value(serde_json::json!({
+            "orderId": null,
+            "packageName": "com.example.app",
+            "productId": product_id,
+            "purchaseTime": 1_700_000_000_000_i64,
+            "purchaseToken": token,
+            "purchaseState": 0,
+            "isAutoRenewing": false,
+            "isAcknowledged": false,
+            "originalJson": "{}",
+            "signature": "",
+            "originalId": null,
+        }))
+        .expect("Failed to build purchase")
+    }
+
+    #[test]
+    fn test_mock_get_products_filters_by_id_and_type() {
+        let store = MockIap::new().with_products(vec![
+            product("premium", "inapp"),
+            product("monthly", "subs"),
+            product("coins", "inapp"),
+        ]);
+
+        let response = block_on(store.get_products(
+            vec!["premium".to_string(), "monthly".to_string()],
+            ProductType::NonConsumable,
+        ))
+        .expect("Failed to get products");
+
+        let ids: Vec<_> = response
+            .products
+            .iter()
+            .map(|p| p.product_id.as_str())
+            .collect();
+        assert_eq!(ids, ["premium"]);
+    }
+
+    #[test]
+    fn test_mock_fail_get_products_with_is_one_shot() {
+        let store = MockIap::new()
+            .with_products(vec![product("premium", "inapp")])
+            .fail_get_products_with(crate::Error::InvalidArgument("boom".to_string()));
+
+        let err =
+            block_on(store.get_products(vec!["premium".to_string()], ProductType::NonConsumable))
+                .expect_err("First call should fail");
+        assert_eq!(err.to_string(), "invalid argument: boom");
+
+        let response =
+            block_on(store.get_products(vec!["premium".to_string()], ProductType::NonConsumable))
+                .expect("Second call should succeed");
+        assert_eq!(response.products.len(), 1);
+    }
+
+    #[test]
+    fn test_get_products_batch_keeps_successful_chunks() {
+        let store = MockIap::new()
+            .with_products(vec![
+                product("a", "inapp"),
+                product("b", "inapp"),
+                product("c", "inapp"),
+            ])
+            .fail_get_products_with(crate::Error::InvalidArgument("boom".to_string()));
+        let ids = ["a", "b", "c"].map(String::from).to_vec();
+
+        let batch = block_on(store.get_products_batch(ids, ProductType::NonConsumable, 2))
+            .expect("Batch should run");
+
+        assert!(!batch.is_complete());
+        let ids: Vec<_> = batch
+            .products
+            .iter()
+            .map(|p| p.product_id.as_str())
+            .collect();
+        assert_eq!(ids, ["c"]);
+        assert_eq!(batch.errors.len(), 1);
+        assert_eq!(batch.errors[0].0, ["a", "b"]);
+        assert!(batch.into_result().is_err());
+    }
+
+    #[test]
+    fn test_get_products_batch_rejects_zero_chunk_size() {
+        let err = block_on(MockIap::new().get_products_batch(
+            vec!["a".to_string()],
+            ProductType::NonConsumable,
+            0,
+        ))
+        .expect_err("Expected chunk_size 0 to fail");
+        assert!(matches!(err, crate::Error::InvalidArgument(_)));
+    }
+
+    #[test]
+    fn test_mock_purchase_results_are_queued() {
+        let store = MockIap::new()
+            .with_purchase_result(Ok(purchase("premium", "token-1")))
+            .with_purchase_result(Err(crate::Error::InvalidArgument("declined".to_string())));
+        let request = || {
+            PurchaseRequest::builder()
+                .product_id("premium")
+                .product_type(ProductType::NonConsumable)
+                .build()
+                .expect("Failed to build request")
+        };
+
+        let first = block_on(store.purchase(request())).expect("First purchase should succeed");
+        assert_eq!(first.purchase_token, "token-1");
+        assert!(block_on(store.purchase(request())).is_err());
+        // The queue is drained.
+        assert!(block_on(store.purchase(request())).is_err());
+    }
+
+    #[test]
+    fn test_mock_purchase_rejects_out_of_range_quantity_before_the_store() {
+        let store = MockIap::new().with_purchase_result(Ok(purchase("coins_100", "token-1")));
+        let request = |options: PurchaseOptions| PurchaseRequest {
+            product_id: "coins_100".to_string(),
+            product_type: ProductType::Consumable,
+            options: Some(options),
+        };
+
+        for quantity in [0, MAX_PURCHASE_QUANTITY + 1] {
+            let err = block_on(store.purchase(request(PurchaseOptions {
+                quantity: Some(quantity),
+                ..PurchaseOptions::default()
+            })))
+            .expect_err("Expected out-of-range quantity to fail");
+            assert!(matches!(err, crate::Error::InvalidArgument(_)));
+        }
+
+        // The queued store result was never consumed.
+        let purchase = block_on(store.purchase(request(PurchaseOptions::default())))
+            .expect("Queued purchase should still be there");
+        assert_eq!(purchase.purchase_token, "token-1");
+    }
+
+    #[test]
+    fn test_mock_rejects_invalid_product_ids_before_the_store() {
+        let store = MockIap::new().with_purchase_result(Ok(purchase("premium", "token-1")));
+
+        let err = block_on(store.get_products(
+            vec!["premium".to_string(), "premium monthly".to_string()],
+            ProductType::AutoRenewableSubscription,
+        ))
+        .expect_err("Expected an id with a space to fail");
+        assert_eq!(
+            err.error_code(),
+            Some(crate::IapErrorCode::InvalidProductId)
+        );
+
+        let err = block_on(store.purchase(PurchaseRequest {
+            product_id: String::new(),
+            product_type: ProductType::NonConsumable,
+            options: None,
+        }))
+        .expect_err("Expected an empty id to fail");
+        assert_eq!(err.error_code(), Some(crate::IapErrorCode::EmptyProductId));
+
+        // The queued store result was never consumed.
+        assert!(
+            block_on(store.purchase(PurchaseRequest {
+                product_id: "premium".to_string(),
+                product_type: ProductType::NonConsumable,
+                options: None,
+            }))
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_mock_records_acknowledge_consume_and_finish() {
+        let store = MockIap::new();
+
+        block_on(store.acknowledge_purchase("ack".to_string())).expect("Failed to acknowledge");
+        block_on(store.consume_purchase("consume".to_string())).expect("Failed to consume");
+        block_on(store.finish_transaction("2000000123".to_string())).expect("Failed to finish");
+
+        assert_eq!(store.acknowledged(), ["ack"]);
+        assert_eq!(store.consumed(), ["consume"]);
+        assert_eq!(store.finished(), ["2000000123"]);
+    }
+
+    #[test]
+    fn test_mock_product_status_defaults_to_not_owned() {
+        let store = MockIap::new();
+
+        let status =
+            block_on(store.get_product_status("premium".to_string(), ProductType::NonConsumable))
+                .expect("Failed to get product status");
+
+        assert_eq!(status.product_id, "premium");
+        assert!(!status.is_owned);
+    }
+}
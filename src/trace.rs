@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+
+use crate::models::{
+    AppTransactionInfo, CanMakePaymentsResponse, ExternalPurchaseLinkAvailability,
+    GetCurrentEntitlementsResponse, GetProductsResponse, GetPurchaseHistoryResponse,
+    GetSubscriptionStatusResponse, GetWinBackOffersResponse, IapEnvironment,
+    IsEligibleForIntroOfferResponse, IsSupportedResponse, ProductStatus, Purchase, PurchaseReceipt,
+    RefundRequestResult, RefundRequestStatus, RestorePurchasesResponse, SigningInfo,
+    StorefrontInfo,
+};
+
+/// What a successful `Iap` call logs at info level under the `tracing`
+/// feature: ids, states and counts, never tokens, signatures, receipts or
+/// JWS payloads. Without the feature `log_success` does nothing.
+pub trait LogSuccess {
+    fn log_success(&self) {}
+}
+
+impl LogSuccess for () {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!("succeeded");
+    }
+}
+
+impl LogSuccess for u32 {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(count = *self, "succeeded");
+    }
+}
+
+/// An app receipt: only its length is logged.
+impl LogSuccess for String {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(len = self.len(), "succeeded");
+    }
+}
+
+impl LogSuccess for Purchase {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(
+            product_id = %self.product_id,
+            order_id = ?self.order_id,
+            purchase_state = ?self.purchase_state,
+            "succeeded"
+        );
+    }
+}
+
+impl LogSuccess for Option<Purchase> {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        match self {
+            Some(purchase) => purchase.log_success(),
+            None => tracing::info!(found = false, "succeeded"),
+        }
+    }
+}
+
+impl LogSuccess for Vec<Purchase> {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(count = self.len(), "succeeded");
+    }
+}
+
+impl LogSuccess for ProductStatus {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(
+            product_id = %self.product_id,
+            is_owned = self.is_owned,
+            purchase_state = ?self.purchase_state,
+            from_cache = self.from_cache,
+            "succeeded"
+        );
+    }
+}
+
+impl LogSuccess for Vec<ProductStatus> {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(
+            count = self.len(),
+            owned = self.iter().filter(|status| status.is_owned).count(),
+            "succeeded"
+        );
+    }
+}
+
+impl LogSuccess for HashMap<String, bool> {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(
+            count = self.len(),
+            eligible = self.values().filter(|eligible| **eligible).count(),
+            "succeeded"
+        );
+    }
+}
+
+impl LogSuccess for GetProductsResponse {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(count = self.products.len(), "succeeded");
+    }
+}
+
+impl LogSuccess for RestorePurchasesResponse {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(count = self.purchases.len(), "succeeded");
+    }
+}
+
+impl LogSuccess for GetCurrentEntitlementsResponse {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(count = self.purchases.len(), "succeeded");
+    }
+}
+
+impl LogSuccess for GetPurchaseHistoryResponse {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(count = self.history.len(), "succeeded");
+    }
+}
+
+impl LogSuccess for GetSubscriptionStatusResponse {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(count = self.statuses.len(), "succeeded");
+    }
+}
+
+impl LogSuccess for GetWinBackOffersResponse {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(count = self.offers.len(), "succeeded");
+    }
+}
+
+impl LogSuccess for AppTransactionInfo {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(
+            bundle_id = %self.bundle_id,
+            environment = ?self.environment,
+            "succeeded"
+        );
+    }
+}
+
+impl LogSuccess for PurchaseReceipt {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(
+            product_id = %self.product_id,
+            transaction_id = %self.transaction_id,
+            "succeeded"
+        );
+    }
+}
+
+impl LogSuccess for StorefrontInfo {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(country_code = %self.country_code, "succeeded");
+    }
+}
+
+impl LogSuccess for IapEnvironment {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(environment = ?self, "succeeded");
+    }
+}
+
+impl LogSuccess for RefundRequestResult {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(result = ?self, "succeeded");
+    }
+}
+
+impl LogSuccess for RefundRequestStatus {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(status = ?self, "succeeded");
+    }
+}
+
+impl LogSuccess for IsEligibleForIntroOfferResponse {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(eligibility = ?self.eligibility, "succeeded");
+    }
+}
+
+impl LogSuccess for CanMakePaymentsResponse {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(
+            can_make_payments = self.can_make_payments,
+            reason = ?self.reason,
+            "succeeded"
+        );
+    }
+}
+
+impl LogSuccess for IsSupportedResponse {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(
+            is_supported = self.is_supported,
+            reason = ?self.reason,
+            "succeeded"
+        );
+    }
+}
+
+impl LogSuccess for ExternalPurchaseLinkAvailability {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(
+            eligible = self.eligible,
+            storefront_country = ?self.storefront_country,
+            "succeeded"
+        );
+    }
+}
+
+impl LogSuccess for SigningInfo {
+    #[cfg(feature = "tracing")]
+    fn log_success(&self) {
+        tracing::info!(
+            distribution_type = ?self.distribution_type,
+            "succeeded"
+        );
+    }
+}
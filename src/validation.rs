@@ -0,0 +1,102 @@
+use crate::IapErrorCode;
+
+/// Longest product id [`validate_product_id`] accepts. App Store Connect
+/// and Play Console both cap product ids well below this.
+pub const MAX_PRODUCT_ID_LEN: usize = 255;
+
+/// Rejects a product id no store would accept, so a typo fails with a clear
+/// error instead of an opaque one from `StoreKit` or Play Billing.
+///
+/// # Errors
+///
+/// Returns [`crate::Error::InvalidProductId`] with
+/// [`IapErrorCode::EmptyProductId`] for an empty id,
+/// [`IapErrorCode::ProductIdTooLong`] for one longer than
+/// [`MAX_PRODUCT_ID_LEN`] bytes, and [`IapErrorCode::InvalidProductId`] for
+/// one containing whitespace, control or non-ASCII characters.
+pub fn validate_product_id(id: &str) -> crate::Result<()> {
+    let invalid = |code, message| Err(crate::Error::InvalidProductId { code, message });
+
+    if id.is_empty() {
+        return invalid(
+            IapErrorCode::EmptyProductId,
+            "product id must not be empty".to_string(),
+        );
+    }
+    if id.len() > MAX_PRODUCT_ID_LEN {
+        return invalid(
+            IapErrorCode::ProductIdTooLong,
+            format!(
+                "product id is {} bytes long, the limit is {MAX_PRODUCT_ID_LEN}",
+                id.len()
+            ),
+        );
+    }
+    if let Some(c) = id.chars().find(|c| !c.is_ascii_graphic()) {
+        return invalid(
+            IapErrorCode::InvalidProductId,
+            format!("product id {id:?} contains {c:?}"),
+        );
+    }
+    Ok(())
+}
+
+/// [`validate_product_id`] for every id in `ids`.
+pub(crate) fn validate_product_ids(ids: &[String]) -> crate::Result<()> {
+    ids.iter().try_for_each(|id| validate_product_id(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rejection(id: &str) -> Option<IapErrorCode> {
+        validate_product_id(id).err().and_then(|e| e.error_code())
+    }
+
+    #[test]
+    fn test_validate_product_id_accepts_store_ids() {
+        for id in [
+            "premium",
+            "com.example.app.coins_100",
+            "premium-monthly:base-plan",
+            "9NBLGGH4R315",
+            &"a".repeat(MAX_PRODUCT_ID_LEN),
+        ] {
+            assert!(validate_product_id(id).is_ok(), "{id:?} should be valid");
+        }
+    }
+
+    #[test]
+    fn test_validate_product_id_rejections() {
+        assert_eq!(rejection(""), Some(IapErrorCode::EmptyProductId));
+        assert_eq!(
+            rejection(&"a".repeat(MAX_PRODUCT_ID_LEN + 1)),
+            Some(IapErrorCode::ProductIdTooLong)
+        );
+        for id in [
+            "premium monthly",
+            " premium",
+            "premium\n",
+            "pre\0mium",
+            "prémium",
+        ] {
+            assert_eq!(
+                rejection(id),
+                Some(IapErrorCode::InvalidProductId),
+                "{id:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_product_ids_stops_at_first_invalid_id() {
+        let ids = vec!["premium".to_string(), String::new(), "bad id".to_string()];
+        let err = validate_product_ids(&ids).expect_err("An empty id should be rejected");
+        assert_eq!(err.error_code(), Some(IapErrorCode::EmptyProductId));
+        assert_eq!(
+            err.to_string(),
+            "invalid product id: product id must not be empty"
+        );
+    }
+}
@@ -1,35 +1,59 @@
 use base64::Engine;
 use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use nt_time::FileTime;
-use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use tauri::Emitter;
 use tauri::Manager;
-use tauri::{AppHandle, Runtime, plugin::PluginApi};
+use tauri::{AppHandle, Runtime, ipc::Channel, plugin::PluginApi};
 use windows::core::{HSTRING, Interface};
 use windows::{
     Foundation::DateTime,
     Services::Store::{
-        StoreConsumableStatus, StoreContext, StoreDurationUnit, StoreLicense, StorePrice,
-        StoreProduct, StorePurchaseProperties, StorePurchaseStatus,
+        StoreAppLicense, StoreConsumableStatus, StoreContext, StoreDurationUnit, StoreLicense,
+        StorePrice, StoreProduct, StorePurchaseProperties, StorePurchaseStatus,
     },
     Win32::UI::Shell::IInitializeWithWindow,
 };
 use windows_collections::IIterable;
 
-use crate::error::{ErrorResponse, PluginInvokeError};
+use crate::error::{ErrorResponse, IapErrorCode, PluginInvokeError};
+use crate::listeners::{EventStream, ListenerHandle};
 use crate::models::{
-    GetProductsResponse, PricingPhase, Product, ProductStatus, Purchase, PurchaseRequest,
-    PurchaseStateValue, RestorePurchasesRequest, RestorePurchasesResponse, SubscriptionOffer,
+    AppTransactionInfo, CanMakePaymentsResponse, ExternalPurchaseLinkAvailability,
+    GetCurrentEntitlementsResponse, GetProductsResponse, GetPurchaseHistoryResponse,
+    GetSubscriptionStatusResponse, GetWinBackOffersResponse, IapEnvironment,
+    IsEligibleForIntroOfferResponse, IsSupportedResponse, PaymentMode, PricingPhase, Product,
+    ProductStatus, ProductType, Purchase, PurchaseHistoryRequest, PurchaseReceipt, PurchaseRequest,
+    PurchaseState, RefundRequestResult, RefundRequestStatus, RestorePurchasesRequest,
+    RestorePurchasesResponse, StorefrontInfo, SubscriptionOffer, SubscriptionOfferInfo,
+    SubscriptionOfferType, SubscriptionPeriod, SubscriptionRenewalInfo, TRANSACTION_UPDATED_EVENT,
+    TransactionEvent, TransactionState,
 };
+use crate::trace::LogSuccess;
+use crate::{IapConfig, Timestamp};
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 
 fn reject(code: &str, message: impl Into<String>) -> crate::Error {
-    crate::Error::PluginInvoke(PluginInvokeError::InvokeRejected(ErrorResponse {
-        code: Some(code.to_string()),
-        message: Some(message.into()),
-        data: (),
-    }))
+    crate::Error::PluginInvoke(PluginInvokeError::InvokeRejected(ErrorResponse::new(
+        Some(code),
+        message,
+    )))
+}
+
+/// Run a Store call that waits on a WinRT async operation on Tauri's
+/// blocking pool. `get()` parks the calling thread until the Store answers
+/// (for purchases, as long as the dialog stays open), which must not happen
+/// on an async runtime worker.
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> crate::Result<T> + Send + 'static,
+) -> crate::Result<T> {
+    tauri::async_runtime::spawn_blocking(f).await.map_err(|e| {
+        reject(
+            "internalError",
+            format!("Store call failed to complete: {e}"),
+        )
+    })?
 }
 
 /// Parse a Microsoft Store formatted price string (e.g. `"$4.99"`, `"4,99 €"`)
@@ -134,13 +158,14 @@ impl WindowsPurchaseTokenV1 {
 }
 
 #[allow(clippy::unnecessary_wraps)]
-pub fn init<R: Runtime, C: DeserializeOwned>(
+pub fn init<R: Runtime>(
     app: &AppHandle<R>,
-    _api: &PluginApi<R, C>,
+    api: &PluginApi<R, Option<IapConfig>>,
 ) -> crate::Result<Iap<R>> {
     Ok(Iap {
         app_handle: app.clone(),
         store_context: Arc::new(RwLock::new(None)),
+        config: api.config().clone().unwrap_or_default(),
     })
 }
 
@@ -148,9 +173,17 @@ pub fn init<R: Runtime, C: DeserializeOwned>(
 pub struct Iap<R: Runtime> {
     app_handle: AppHandle<R>,
     store_context: Arc<RwLock<Option<StoreContext>>>,
+    config: IapConfig,
 }
 
 impl<R: Runtime> Iap<R> {
+    /// The `plugins.iap` options from `tauri.conf.json`, with defaults for
+    /// anything left out.
+    #[must_use]
+    pub const fn config(&self) -> &IapConfig {
+        &self.config
+    }
+
     /// Get or create the `StoreContext` instance
     fn get_store_context(&self) -> crate::Result<StoreContext> {
         let mut context_guard = self.store_context.write().map_err(|e| {
@@ -201,6 +234,14 @@ impl<R: Runtime> Iap<R> {
         let _ = self.app_handle.emit(event, payload);
     }
 
+    /// Deliver a [`TransactionEvent`] to `transactionUpdated` plugin listeners.
+    fn emit_transaction_event(product_id: &str, state: TransactionState) {
+        let _ = crate::listeners::emit(
+            TRANSACTION_UPDATED_EVENT,
+            &TransactionEvent::new(product_id, state),
+        );
+    }
+
     /// Mint a Microsoft Store ID key (JWT) bound to the current
     /// Microsoft account signed into the device. Backends use the key
     /// as `b2bKey` / `beneficiaries[].identityValue` when calling the
@@ -219,34 +260,40 @@ impl<R: Runtime> Iap<R> {
     /// picks the right mint method from the existing
     /// `product_type` field already on every purchase / restore
     /// payload:
-    ///   * `"subs"`  → `GetCustomerPurchaseIdAsync`
-    ///   * `"inapp"` → `GetCustomerCollectionsIdAsync`
+    ///   * subscriptions → `GetCustomerPurchaseIdAsync`
+    ///   * one-time products → `GetCustomerCollectionsIdAsync`
     ///
     /// `service_ticket` is an Entra ID access token with audience
     /// `https://onestore.microsoft.com`. `publisher_user_id` is
     /// embedded verbatim in the key as the `userId` claim so the
     /// backend can identity-bind the purchase.
-    fn mint_store_id_key(
+    async fn mint_store_id_key(
         &self,
-        product_type: &str,
+        product_type: ProductType,
         service_ticket: &str,
         publisher_user_id: &str,
     ) -> crate::Result<String> {
         let context = self.get_store_context()?;
         let ticket = HSTRING::from(service_ticket);
         let user_id = HSTRING::from(publisher_user_id);
-        let key = if product_type == "subs" {
-            context
-                .GetCustomerPurchaseIdAsync(&ticket, &user_id)
-                .and_then(|op| op.get())?
-        } else {
-            context
-                .GetCustomerCollectionsIdAsync(&ticket, &user_id)
-                .and_then(|op| op.get())?
-        };
+        let key = blocking(move || {
+            let op = if product_type.is_subscription() {
+                context.GetCustomerPurchaseIdAsync(&ticket, &user_id)?
+            } else {
+                context.GetCustomerCollectionsIdAsync(&ticket, &user_id)?
+            };
+            op.get().map_err(Into::into)
+        })
+        .await?;
         Ok(key.to_string())
     }
 
+    /// The app's license, including add-on licenses keyed by SKU `StoreId`.
+    async fn app_license(&self) -> crate::Result<StoreAppLicense> {
+        let context = self.get_store_context()?;
+        blocking(move || context.GetAppLicenseAsync()?.get().map_err(Into::into)).await
+    }
+
     /// Developer-defined product id exposed by Microsoft Store as `InAppOfferToken`.
     /// This is the identifier callers see across all platforms — Microsoft-generated
     /// `StoreId` and `SkuStoreId` values stay internal to this module.
@@ -273,34 +320,48 @@ impl<R: Runtime> Iap<R> {
             .map_or(sku_store_id, |(prefix, _)| prefix)
     }
 
+    /// Whether any SKU of `store_product` is a subscription SKU.
+    /// `SubscriptionInfo` is null (→ `Err`) on non-subscription SKUs.
+    fn has_subscription_sku(store_product: &StoreProduct) -> crate::Result<bool> {
+        let skus = store_product.Skus()?;
+        for i in 0..skus.Size()? {
+            if skus.GetAt(i)?.SubscriptionInfo().is_ok() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
     /// Query all add-ons associated with this app. We cannot use
     /// `GetStoreProductsAsync` with developer product ids because Microsoft
     /// expects Microsoft-generated `StoreIds` there.
-    fn query_associated_products(&self, product_type: &str) -> crate::Result<Vec<StoreProduct>> {
+    async fn query_associated_products(
+        &self,
+        product_type: ProductType,
+    ) -> crate::Result<Vec<StoreProduct>> {
         let context = self.get_store_context()?;
 
-        let product_kinds: Vec<HSTRING> = match product_type {
-            "inapp" => vec![
-                HSTRING::from("Consumable"),
-                HSTRING::from("UnmanagedConsumable"),
-                HSTRING::from("Durable"),
-            ],
+        let product_kinds: Vec<HSTRING> = if product_type.is_subscription() {
             // Microsoft Store surfaces subscription add-ons under the
             // `Durable` kind in practice, even when Partner Center
             // categorizes them as Subscription (see #232). Query both.
-            "subs" => vec![HSTRING::from("Subscription"), HSTRING::from("Durable")],
-            _ => vec![
+            vec![HSTRING::from("Subscription"), HSTRING::from("Durable")]
+        } else {
+            vec![
                 HSTRING::from("Consumable"),
                 HSTRING::from("UnmanagedConsumable"),
                 HSTRING::from("Durable"),
-                HSTRING::from("Subscription"),
-            ],
+            ]
         };
-        let product_kinds: IIterable<HSTRING> = product_kinds.into();
 
-        let query_result = context
-            .GetAssociatedStoreProductsAsync(&product_kinds)
-            .and_then(|async_op| async_op.get())?;
+        let query_result = blocking(move || {
+            let product_kinds: IIterable<HSTRING> = product_kinds.into();
+            context
+                .GetAssociatedStoreProductsAsync(&product_kinds)?
+                .get()
+                .map_err(Into::into)
+        })
+        .await?;
 
         let extended_error = query_result.ExtendedError()?;
         if extended_error.is_err() {
@@ -321,13 +382,18 @@ impl<R: Runtime> Iap<R> {
         Ok(products)
     }
 
-    #[allow(clippy::unused_async)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_products", skip(self), level = "debug", err)
+    )]
     pub async fn get_products(
         &self,
         product_ids: Vec<String>,
-        product_type: String,
+        product_type: ProductType,
     ) -> crate::Result<GetProductsResponse> {
-        let store_products = self.query_associated_products(&product_type)?;
+        crate::validation::validate_product_ids(&product_ids)?;
+
+        let store_products = self.query_associated_products(product_type).await?;
         let mut products = Vec::new();
 
         for requested_id in product_ids {
@@ -339,16 +405,31 @@ impl<R: Runtime> Iap<R> {
             };
             products.push(Self::convert_store_product_to_product(
                 store_product,
-                &product_type,
+                product_type,
             )?);
         }
 
-        Ok(GetProductsResponse { products })
+        let response = GetProductsResponse { products };
+        response.log_success();
+        Ok(response)
+    }
+
+    /// Same as [`Self::get_products`]; only macOS caches products.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::refresh_products", skip(self), level = "debug", err)
+    )]
+    pub async fn refresh_products(
+        &self,
+        product_ids: Vec<String>,
+        product_type: ProductType,
+    ) -> crate::Result<GetProductsResponse> {
+        self.get_products(product_ids, product_type).await
     }
 
     fn convert_store_product_to_product(
         store_product: &StoreProduct,
-        product_type: &str,
+        product_type: ProductType,
     ) -> crate::Result<Product> {
         let product_id = Self::app_product_id(store_product)?;
 
@@ -364,7 +445,7 @@ impl<R: Runtime> Iap<R> {
         // products `FormattedRecurrencePrice` is not meaningful and the
         // Store can return a non-empty `$0`-style placeholder there, so
         // stick with `FormattedBasePrice`.
-        let product_formatted_price = if product_type == "subs" {
+        let product_formatted_price = if product_type.is_subscription() {
             recurring_formatted_price(&price)?
         } else {
             price.FormattedBasePrice()?.to_string()
@@ -376,7 +457,7 @@ impl<R: Runtime> Iap<R> {
         // StoreSubscriptionInfo.HasTrialPeriod), emit a finite free phase
         // followed by the recurring phase — mirroring StoreKit's intro+regular
         // shape on macOS and Google Play's free-trial offer on Android.
-        let subscription_offer_details = if product_type == "subs" {
+        let subscription_offer_details = if product_type.is_subscription() {
             let mut offers = Vec::new();
             let skus = store_product.Skus()?;
             let sku_count = skus.Size()?;
@@ -464,24 +545,82 @@ impl<R: Runtime> Iap<R> {
             })
             .unwrap_or((product_formatted_price, product_price_micros));
 
+        // Sub-day Store durations (minutes/hours) have no `PeriodUnit`
+        // equivalent and leave this `None`.
+        let subscription_period = subscription_offer_details.as_ref().and_then(|offers| {
+            offers.iter().find_map(|offer| {
+                offer
+                    .pricing_phases
+                    .iter()
+                    .rfind(|p| p.recurrence_mode == 1)
+                    .and_then(|p| SubscriptionPeriod::from_iso8601(&p.billing_period))
+            })
+        });
+
+        // Microsoft Store only models free trials, so the first trial phase
+        // (if the customer is eligible) becomes the introductory offer.
+        let introductory_offer = subscription_offer_details.as_ref().and_then(|offers| {
+            let trial = offers
+                .iter()
+                .flat_map(|offer| &offer.pricing_phases)
+                .find(|p| p.recurrence_mode == 2)?;
+            Some(SubscriptionOfferInfo {
+                id: None,
+                offer_type: SubscriptionOfferType::Introductory,
+                price: trial.formatted_price.clone(),
+                price_locale: trial.price_currency_code.clone(),
+                period: SubscriptionPeriod::from_iso8601(&trial.billing_period)?,
+                period_count: 1,
+                payment_mode: PaymentMode::FreeTrial,
+            })
+        });
+
         Ok(Product {
             product_id,
             title,
             description,
-            product_type: product_type.to_string(),
+            product_type: product_type.into(),
             formatted_price: Some(formatted_price),
             price_currency_code: Some(currency_code),
             price_amount_micros: Some(price_amount_micros),
+            price_locale: None,
             subscription_offer_details,
+            subscription_period,
+            introductory_offer,
+            promotional_offers: Vec::new(),
+            subscription_group_id: None,
+            is_family_shareable: None,
         })
     }
 
-    #[allow(clippy::unused_async)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::purchase",
+            skip(self, payload),
+            fields(product_id = %payload.product_id, product_type = %payload.product_type),
+            level = "debug",
+            err
+        )
+    )]
     pub async fn purchase(&self, payload: PurchaseRequest) -> crate::Result<Purchase> {
+        crate::validate_product_id(&payload.product_id)?;
+
+        if payload
+            .options
+            .as_ref()
+            .and_then(|opts| opts.quantity)
+            .is_some_and(|quantity| quantity > 1)
+        {
+            return Err(reject(
+                "notSupported",
+                "Purchase quantities above 1 are not supported by the Microsoft Store",
+            ));
+        }
         let context = self.get_store_context()?;
 
         // Resolve the developer product id to the matching Windows StoreProduct.
-        let store_products = self.query_associated_products(&payload.product_type)?;
+        let store_products = self.query_associated_products(payload.product_type).await?;
         let store_product = store_products
             .into_iter()
             .find(|sp| Self::app_product_id(sp).is_ok_and(|id| id == payload.product_id))
@@ -491,8 +630,7 @@ impl<R: Runtime> Iap<R> {
                     format!("Product not found: {}", payload.product_id),
                 )
             })?;
-        let product =
-            Self::convert_store_product_to_product(&store_product, &payload.product_type)?;
+        let product = Self::convert_store_product_to_product(&store_product, payload.product_type)?;
         let store_id = store_product.StoreId()?.to_string();
 
         // Create purchase properties if we have an offer token (for subscriptions).
@@ -504,37 +642,46 @@ impl<R: Runtime> Iap<R> {
             .options
             .as_ref()
             .and_then(|opts| opts.offer_token.clone());
-        let purchase_result = if let Some(token) = offer_token {
-            let properties = StorePurchaseProperties::Create(&HSTRING::from(store_id.as_str()))?;
-            properties.SetExtendedJsonData(&HSTRING::from(format!(r#"{{"skuId":"{token}"}}"#)))?;
-            context
-                .RequestPurchaseWithPurchasePropertiesAsync(
-                    &HSTRING::from(store_id.as_str()),
-                    &properties,
-                )
-                .and_then(|async_op| async_op.get())?
-        } else {
-            context
-                .RequestPurchaseAsync(&HSTRING::from(store_id.as_str()))
-                .and_then(|async_op| async_op.get())?
-        };
+        Self::emit_transaction_event(&payload.product_id, TransactionState::Purchasing);
+        let store_hstring = HSTRING::from(store_id.as_str());
+        let purchase_result = blocking(move || {
+            let op = if let Some(token) = offer_token {
+                let properties = StorePurchaseProperties::Create(&store_hstring)?;
+                properties
+                    .SetExtendedJsonData(&HSTRING::from(format!(r#"{{"skuId":"{token}"}}"#)))?;
+                context.RequestPurchaseWithPurchasePropertiesAsync(&store_hstring, &properties)?
+            } else {
+                context.RequestPurchaseAsync(&store_hstring)?
+            };
+            op.get().map_err(Into::into)
+        })
+        .await?;
 
         let status = purchase_result.Status()?;
         let purchase_state = match status {
             StorePurchaseStatus::Succeeded | StorePurchaseStatus::AlreadyPurchased => {
-                PurchaseStateValue::Purchased
-            }
-            StorePurchaseStatus::NotPurchased => {
-                return Err(reject("purchaseNotCompleted", "Purchase was not completed"));
-            }
-            StorePurchaseStatus::NetworkError => {
-                return Err(reject("networkError", "Network error during purchase"));
+                PurchaseState::Purchased
             }
-            StorePurchaseStatus::ServerError => {
-                return Err(reject("serverError", "Server error during purchase"));
-            }
-            _ => {
-                return Err(reject("purchaseFailed", "Purchase failed"));
+            failed => {
+                let error = match failed {
+                    StorePurchaseStatus::NotPurchased => {
+                        reject("purchaseNotCompleted", "Purchase was not completed")
+                    }
+                    StorePurchaseStatus::NetworkError => {
+                        reject("networkError", "Network error during purchase")
+                    }
+                    StorePurchaseStatus::ServerError => {
+                        reject("serverError", "Server error during purchase")
+                    }
+                    _ => reject("purchaseFailed", "Purchase failed"),
+                };
+                Self::emit_transaction_event(
+                    &payload.product_id,
+                    TransactionState::Failed {
+                        error: error.error_code().unwrap_or(IapErrorCode::Unknown),
+                    },
+                );
+                return Err(error);
             }
         };
 
@@ -562,7 +709,10 @@ impl<R: Runtime> Iap<R> {
                 .as_ref()
                 .and_then(|o| o.publisher_user_id.as_deref()),
         ) {
-            Some(self.mint_store_id_key(&payload.product_type, ticket, user_id)?)
+            Some(
+                self.mint_store_id_key(payload.product_type, ticket, user_id)
+                    .await?,
+            )
         } else {
             None
         };
@@ -571,10 +721,10 @@ impl<R: Runtime> Iap<R> {
             order_id: Some(purchase_token.clone()),
             package_name: product.title.clone(),
             product_id: product.product_id.clone(),
-            purchase_time,
+            purchase_time: Timestamp::from_unix_millis(purchase_time),
             purchase_token,
             purchase_state,
-            is_auto_renewing: product.product_type == "subs",
+            is_auto_renewing: payload.product_type.is_subscription(),
             is_acknowledged: true, // Windows Store handles acknowledgment
             original_json: format!(
                 r#"{{"status":{},"message":"{}","productId":"{}"}}"#,
@@ -582,24 +732,50 @@ impl<R: Runtime> Iap<R> {
             ),
             signature: String::new(), // Windows doesn't provide signatures like Android
             original_id: None, // Windows doesn't have original transaction IDs like iOS/macOS
+            original_transaction_id: None,
+            web_order_line_item_id: None,
+            subscription_group_id: None,
             jws_representation,
+            ownership_type: None,
+            transaction_reason: None,
+            revocation_time: None,
+            revocation_reason: None,
+            environment: None,
+            app_account_token: None,
+            quantity: None,
+            expiration_date: None,
+            next_renewal_date: None,
+            offer_id: None,
+            offer_type: None,
+            verification: None,
         };
 
+        Self::emit_transaction_event(
+            &purchase.product_id,
+            TransactionState::Purchased {
+                transaction_id: purchase.purchase_token.clone(),
+            },
+        );
         self.trigger("purchaseUpdated", purchase.clone());
+        purchase.log_success();
         Ok(purchase)
     }
 
-    #[allow(clippy::unused_async)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::restore_purchases",
+            skip(self, request),
+            fields(product_type = %request.product_type, force_sync = request.force_sync),
+            level = "debug",
+            err
+        )
+    )]
     pub async fn restore_purchases(
         &self,
         request: RestorePurchasesRequest,
     ) -> crate::Result<RestorePurchasesResponse> {
-        let context = self.get_store_context()?;
-
-        // Get app license info
-        let app_license = context
-            .GetAppLicenseAsync()
-            .and_then(|async_op| async_op.get())?;
+        let app_license = self.app_license().await?;
 
         // Microsoft issues one Store ID key per user that covers every
         // subscription / IAP, so mint it once and stamp it onto every
@@ -610,7 +786,10 @@ impl<R: Runtime> Iap<R> {
             request.service_ticket.as_deref(),
             request.publisher_user_id.as_deref(),
         ) {
-            Some(self.mint_store_id_key(&request.product_type, ticket, user_id)?)
+            Some(
+                self.mint_store_id_key(request.product_type, ticket, user_id)
+                    .await?,
+            )
         } else {
             None
         };
@@ -622,21 +801,73 @@ impl<R: Runtime> Iap<R> {
 
         for kv in addon_licenses {
             let license = kv.Value()?;
-            let mut purchase = self.convert_license_to_purchase(&license, &request.product_type)?;
+            let mut purchase = self.convert_license_to_purchase(&license, request.product_type)?;
             purchase.jws_representation.clone_from(&jws_representation);
 
-            if purchase.purchase_state == PurchaseStateValue::Purchased {
+            if purchase.purchase_state == PurchaseState::Purchased {
+                Self::emit_transaction_event(
+                    &purchase.product_id,
+                    TransactionState::Restored {
+                        original_transaction_id: purchase.purchase_token.clone(),
+                    },
+                );
                 purchases.push(purchase);
             }
         }
 
-        Ok(RestorePurchasesResponse { purchases })
+        let response = RestorePurchasesResponse { purchases };
+        response.log_success();
+        Ok(response)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_current_entitlements",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_current_entitlements(&self) -> crate::Result<GetCurrentEntitlementsResponse> {
+        let app_license = self.app_license().await?;
+
+        // Licenses don't say whether they belong to a subscription; the
+        // product's SKUs do.
+        let mut subscription_ids = HashSet::new();
+        for store_product in self
+            .query_associated_products(ProductType::AutoRenewableSubscription)
+            .await?
+        {
+            if Self::has_subscription_sku(&store_product)? {
+                subscription_ids.insert(Self::app_product_id(&store_product)?);
+            }
+        }
+
+        let mut purchases = Vec::new();
+        for kv in app_license.AddOnLicenses()? {
+            let license = kv.Value()?;
+            if !license.IsActive()? {
+                continue;
+            }
+            let product_type = if subscription_ids.contains(&license.InAppOfferToken()?.to_string())
+            {
+                ProductType::AutoRenewableSubscription
+            } else {
+                ProductType::NonConsumable
+            };
+            purchases.push(self.convert_license_to_purchase(&license, product_type)?);
+        }
+
+        let response = GetCurrentEntitlementsResponse { purchases };
+        response.log_success();
+        Ok(response)
     }
 
     fn convert_license_to_purchase(
         &self,
         license: &StoreLicense,
-        product_type: &str,
+        product_type: ProductType,
     ) -> crate::Result<Purchase> {
         let product_id = license.InAppOfferToken()?.to_string();
         let sku_store_id = license.SkuStoreId()?.to_string();
@@ -647,7 +878,7 @@ impl<R: Runtime> Iap<R> {
         let expiration_millis = Self::datetime_to_unix_millis(license.ExpirationDate()?);
 
         // Estimate purchase time (30 days before expiration for monthly subs)
-        let purchase_time = if product_type == "subs" && expiration_millis > 0 {
+        let purchase_time = if product_type.is_subscription() && expiration_millis > 0 {
             expiration_millis - (30 * 24 * 60 * 60 * 1000)
         } else {
             FileTime::now().to_unix_time_millis()
@@ -656,48 +887,121 @@ impl<R: Runtime> Iap<R> {
         let purchase_token = WindowsPurchaseTokenV1::new(store_id, purchase_time)?.encode()?;
 
         let purchase_state = if is_active {
-            PurchaseStateValue::Purchased
+            PurchaseState::Purchased
         } else {
-            PurchaseStateValue::Canceled
+            PurchaseState::Canceled
         };
 
         Ok(Purchase {
             order_id: Some(purchase_token.clone()),
             package_name: self.app_handle.package_info().name.clone(),
             product_id,
-            purchase_time,
+            purchase_time: Timestamp::from_unix_millis(purchase_time),
             purchase_token,
             purchase_state,
-            is_auto_renewing: product_type == "subs" && is_active,
+            is_auto_renewing: product_type.is_subscription() && is_active,
             is_acknowledged: true,
             original_json: format!(
                 r#"{{"isActive":{is_active},"expirationDate":{expiration_millis}}}"#
             ),
             signature: String::new(),
             original_id: None,
+            original_transaction_id: None,
+            web_order_line_item_id: None,
+            subscription_group_id: None,
             jws_representation: None, // Windows doesn't have JWS like iOS/macOS
+            ownership_type: None,
+            transaction_reason: None,
+            revocation_time: None,
+            revocation_reason: None,
+            environment: None,
+            app_account_token: None,
+            quantity: None,
+            expiration_date: None,
+            next_renewal_date: None,
+            offer_id: None,
+            offer_type: None,
+            verification: None,
         })
     }
 
+    /// Microsoft Store only exposes current licenses, not past transactions.
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_purchase_history", skip(self), level = "debug", err)
+    )]
+    pub async fn get_purchase_history(&self) -> crate::Result<GetPurchaseHistoryResponse> {
+        Err(reject(
+            "notSupported",
+            "Purchase history is not supported by the Microsoft Store",
+        ))
+    }
+
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_purchase_history_filtered",
+            skip(self, _request),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_purchase_history_filtered(
+        &self,
+        _request: PurchaseHistoryRequest,
+    ) -> crate::Result<GetPurchaseHistoryResponse> {
+        Err(reject(
+            "notSupported",
+            "Purchase history is not supported by the Microsoft Store",
+        ))
+    }
+
     /// No-op: Microsoft Store auto-acknowledges purchases. Method exists for API parity.
     #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::acknowledge_purchase",
+            skip(self, _purchase_token),
+            level = "debug",
+            err
+        )
+    )]
     pub async fn acknowledge_purchase(&self, _purchase_token: String) -> crate::Result<()> {
+        ().log_success();
         Ok(())
     }
 
-    #[allow(clippy::unused_async)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::consume_purchase",
+            skip(self, purchase_token),
+            level = "debug",
+            err
+        )
+    )]
     pub async fn consume_purchase(&self, purchase_token: String) -> crate::Result<()> {
         let envelope = WindowsPurchaseTokenV1::decode(&purchase_token)?;
         let context = self.get_store_context()?;
         let store_id = HSTRING::from(&envelope.store_id);
         let tracking_id = windows::core::GUID::new()?;
 
-        let result = context
-            .ReportConsumableFulfillmentAsync(&store_id, 1u32, tracking_id)
-            .and_then(|async_op| async_op.get())?;
+        let result = blocking(move || {
+            context
+                .ReportConsumableFulfillmentAsync(&store_id, 1u32, tracking_id)?
+                .get()
+                .map_err(Into::into)
+        })
+        .await?;
 
         match result.Status()? {
-            StoreConsumableStatus::Succeeded => Ok(()),
+            StoreConsumableStatus::Succeeded => {
+                ().log_success();
+                Ok(())
+            }
             StoreConsumableStatus::InsufficentQuantity => Err(reject(
                 "insufficientQuantity",
                 "Not enough balance remaining to consume",
@@ -712,18 +1016,19 @@ impl<R: Runtime> Iap<R> {
         }
     }
 
-    #[allow(clippy::unused_async)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_product_status", skip(self), level = "debug", err)
+    )]
     pub async fn get_product_status(
         &self,
         product_id: String,
-        product_type: String,
+        product_type: ProductType,
     ) -> crate::Result<ProductStatus> {
-        let context = self.get_store_context()?;
+        crate::validate_product_id(&product_id)?;
 
         // Get app license to check ownership
-        let app_license = context
-            .GetAppLicenseAsync()
-            .and_then(|async_op| async_op.get())?;
+        let app_license = self.app_license().await?;
 
         let addon_licenses = app_license.AddOnLicenses()?;
 
@@ -741,7 +1046,7 @@ impl<R: Runtime> Iap<R> {
             let sku_store_id = license.SkuStoreId()?.to_string();
             let store_id = Self::store_id_from_sku_store_id(&sku_store_id).to_string();
 
-            let purchase_time = if product_type == "subs" && expiration_time > 0 {
+            let purchase_time = if product_type.is_subscription() && expiration_time > 0 {
                 expiration_time - (30 * 24 * 60 * 60 * 1000)
             } else {
                 expiration_time
@@ -750,28 +1055,39 @@ impl<R: Runtime> Iap<R> {
             let purchase_token = WindowsPurchaseTokenV1::new(store_id, purchase_time)?.encode()?;
 
             let purchase_state = if is_active {
-                Some(PurchaseStateValue::Purchased)
+                Some(PurchaseState::Purchased)
             } else {
-                Some(PurchaseStateValue::Canceled)
+                Some(PurchaseState::Canceled)
             };
 
-            return Ok(ProductStatus {
+            let status = ProductStatus {
                 product_id,
                 is_owned: is_active,
                 purchase_state,
-                purchase_time: Some(purchase_time),
-                expiration_time: if expiration_time > 0 {
-                    Some(expiration_time)
-                } else {
-                    None
-                },
-                is_auto_renewing: Some(product_type == "subs" && is_active),
+                purchase_time: Some(Timestamp::from_unix_millis(purchase_time)),
+                expiration_time: (expiration_time > 0)
+                    .then(|| Timestamp::from_unix_millis(expiration_time)),
+                is_auto_renewing: Some(product_type.is_subscription() && is_active),
                 is_acknowledged: Some(true),
                 purchase_token: Some(purchase_token),
-            });
+                // The Store exposes no renewal preference; an active
+                // subscription license is assumed to renew.
+                renewal_info: (product_type.is_subscription() && is_active).then(|| {
+                    SubscriptionRenewalInfo {
+                        auto_renew_enabled: true,
+                        expiration_date: (expiration_time > 0)
+                            .then(|| Timestamp::from_unix_millis(expiration_time)),
+                        ..SubscriptionRenewalInfo::default()
+                    }
+                }),
+                latest_transaction: None,
+                from_cache: false,
+            };
+            status.log_success();
+            return Ok(status);
         }
 
-        Ok(ProductStatus {
+        let status = ProductStatus {
             product_id,
             is_owned: false,
             purchase_state: None,
@@ -780,7 +1096,480 @@ impl<R: Runtime> Iap<R> {
             is_auto_renewing: None,
             is_acknowledged: None,
             purchase_token: None,
-        })
+            renewal_info: None,
+            latest_transaction: None,
+            from_cache: false,
+        };
+        status.log_success();
+        Ok(status)
+    }
+
+    /// Microsoft Store has no per-transaction finish step: purchases are
+    /// settled by the Store itself and consumables go through
+    /// `consume_purchase`.
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::finish_transaction",
+            skip(self, _transaction_id),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn finish_transaction(&self, _transaction_id: String) -> crate::Result<()> {
+        Err(reject(
+            "notSupported",
+            "finish_transaction is only available on iOS and macOS",
+        ))
+    }
+
+    /// `AppTransaction` is `StoreKit`-only; the Microsoft Store equivalent is
+    /// the app license returned by `GetAppLicenseAsync`.
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_app_transaction",
+            skip(self, _force_refresh),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_app_transaction(
+        &self,
+        _force_refresh: bool,
+    ) -> crate::Result<AppTransactionInfo> {
+        Err(reject(
+            "notSupported",
+            "get_app_transaction is only available on iOS and macOS",
+        ))
+    }
+
+    /// Renewal state comes from `StoreKit`'s `RenewalInfo`; the Microsoft
+    /// Store only exposes `is_auto_renewing` via `get_product_status`.
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_subscription_status",
+            skip(self, _product_id),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_subscription_status(
+        &self,
+        _product_id: String,
+    ) -> crate::Result<GetSubscriptionStatusResponse> {
+        Err(reject(
+            "notSupported",
+            "get_subscription_status is only available on iOS and macOS",
+        ))
+    }
+
+    /// The Microsoft Store manages subscriptions on the user's Microsoft
+    /// account page; there is no in-app sheet to present.
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::show_manage_subscriptions",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn show_manage_subscriptions(&self) -> crate::Result<()> {
+        Err(reject(
+            "notSupported",
+            "show_manage_subscriptions is only available on iOS, macOS and Android",
+        ))
+    }
+
+    /// Microsoft Store codes are redeemed in the Store app, not in-app.
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::present_offer_code_redemption_sheet",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn present_offer_code_redemption_sheet(&self) -> crate::Result<()> {
+        Err(reject(
+            "notSupported",
+            "present_offer_code_redemption_sheet is only available on iOS and macOS",
+        ))
+    }
+
+    /// The Microsoft Store doesn't expose the account's market to apps.
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_storefront", skip(self), level = "debug", err)
+    )]
+    pub async fn get_storefront(&self) -> crate::Result<StorefrontInfo> {
+        Err(reject(
+            "notSupported",
+            "get_storefront is only available on iOS, macOS and Android",
+        ))
+    }
+
+    /// The Microsoft Store has no equivalent of `Transaction.unfinished`.
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_unfinished_transactions",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_unfinished_transactions(&self) -> crate::Result<Vec<Purchase>> {
+        Err(reject(
+            "notSupported",
+            "get_unfinished_transactions is only available on iOS, macOS and Android",
+        ))
+    }
+
+    /// Microsoft Store refunds go through the user's Microsoft account.
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::begin_refund_request",
+            skip(self, _transaction_id),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn begin_refund_request(
+        &self,
+        _transaction_id: String,
+    ) -> crate::Result<RefundRequestResult> {
+        Err(reject(
+            "notSupported",
+            "begin_refund_request is only available on iOS and macOS",
+        ))
+    }
+
+    /// [`Self::begin_refund_request`], reporting a failed submission as
+    /// [`RefundRequestStatus::Error`] instead of rejecting.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::request_refund", skip(self), level = "debug", err)
+    )]
+    pub async fn request_refund(
+        &self,
+        transaction_id: String,
+    ) -> crate::Result<RefundRequestStatus> {
+        RefundRequestStatus::from_outcome(self.begin_refund_request(transaction_id).await)
+            .inspect(LogSuccess::log_success)
+    }
+
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_win_back_offers",
+            skip(self, _product_id),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_win_back_offers(
+        &self,
+        _product_id: String,
+    ) -> crate::Result<GetWinBackOffersResponse> {
+        Err(reject(
+            "notSupported",
+            "get_win_back_offers is only available on iOS and macOS",
+        ))
+    }
+
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::is_eligible_for_intro_offer",
+            skip(self, _product_id),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn is_eligible_for_intro_offer(
+        &self,
+        _product_id: String,
+    ) -> crate::Result<IsEligibleForIntroOfferResponse> {
+        Err(reject(
+            "notSupported",
+            "is_eligible_for_intro_offer is only available on iOS, macOS and Android",
+        ))
+    }
+
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_environment", skip(self), level = "debug", err)
+    )]
+    pub async fn get_environment(&self) -> crate::Result<IapEnvironment> {
+        Err(reject(
+            "notSupported",
+            "get_environment is only available on iOS, macOS and Android",
+        ))
+    }
+
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_receipt",
+            skip(self, _transaction_id),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_receipt(&self, _transaction_id: String) -> crate::Result<PurchaseReceipt> {
+        Err(reject(
+            "notSupported",
+            "get_receipt is only available on iOS, macOS and Android",
+        ))
+    }
+
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::refresh_receipt", skip(self), level = "debug", err)
+    )]
+    pub async fn refresh_receipt(&self) -> crate::Result<()> {
+        Err(reject(
+            "notSupported",
+            "refresh_receipt is only available on iOS and macOS",
+        ))
+    }
+
+    /// Typed counterpart of the `register_listener` command for Rust callers:
+    /// `handler` receives every `transactionUpdated` event until the returned
+    /// handle is dropped.
+    #[allow(clippy::unused_self)]
+    pub fn register_transaction_observer(
+        &self,
+        handler: Channel<TransactionEvent>,
+    ) -> crate::Result<ListenerHandle> {
+        crate::listeners::observe(TRANSACTION_UPDATED_EVENT, handler)
+    }
+
+    /// Every `transactionUpdated` event as a [`Stream`](futures_util::Stream),
+    /// for `while let Some(event) = updates.next().await` loops. Dropping the
+    /// stream removes its listener.
+    #[allow(clippy::unused_self)]
+    pub fn subscribe_to_transaction_updates(&self) -> crate::Result<EventStream<TransactionEvent>> {
+        crate::listeners::subscribe(TRANSACTION_UPDATED_EVENT)
+    }
+
+    /// Register `handler` for the next `event` only, e.g. to await a single
+    /// purchase confirmation. Returns the channel id for `remove_listener`.
+    #[allow(clippy::unused_self)]
+    pub fn register_one_shot_listener(
+        &self,
+        event: String,
+        handler: Channel<serde_json::Value>,
+    ) -> crate::Result<u32> {
+        crate::listeners::register_one_shot_listener(event, handler)
+    }
+
+    /// True once a `StoreContext` can be created for the main window; the
+    /// Store has no equivalent of `StoreKit`'s purchase restrictions.
+    #[allow(clippy::unused_async)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::can_make_payments", skip(self), level = "debug", err)
+    )]
+    pub async fn can_make_payments(&self) -> crate::Result<CanMakePaymentsResponse> {
+        let can_make_payments = self.get_store_context().is_ok();
+        let response = CanMakePaymentsResponse {
+            can_make_payments,
+            reason: (!can_make_payments).then(|| "billingUnavailable".to_string()),
+        };
+        response.log_success();
+        Ok(response)
+    }
+
+    /// The Microsoft Store has no introductory offer eligibility API.
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::check_introductory_offer_eligibility",
+            skip(self, _product_ids),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn check_introductory_offer_eligibility(
+        &self,
+        _product_ids: Vec<String>,
+    ) -> crate::Result<HashMap<String, bool>> {
+        Err(reject(
+            "notSupported",
+            "check_introductory_offer_eligibility is only available on iOS, macOS and Android",
+        ))
+    }
+
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::is_supported", skip(self), level = "debug", err)
+    )]
+    pub async fn is_supported(&self) -> crate::Result<IsSupportedResponse> {
+        let response = IsSupportedResponse {
+            is_supported: true,
+            reason: None,
+        };
+        response.log_success();
+        Ok(response)
+    }
+
+    /// Store licenses don't say which kind of product they belong to; call
+    /// `get_product_status` per product instead.
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::get_all_product_statuses",
+            skip(self, _product_type),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn get_all_product_statuses(
+        &self,
+        _product_type: ProductType,
+    ) -> crate::Result<Vec<ProductStatus>> {
+        Err(reject(
+            "notSupported",
+            "get_all_product_statuses is only available on iOS, macOS and Android",
+        ))
+    }
+
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::can_open_external_purchase_link",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn can_open_external_purchase_link(
+        &self,
+    ) -> crate::Result<ExternalPurchaseLinkAvailability> {
+        Err(reject(
+            "notSupported",
+            "can_open_external_purchase_link is only available on iOS and macOS",
+        ))
+    }
+
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::open_external_purchase_link",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn open_external_purchase_link(&self) -> crate::Result<()> {
+        Err(reject(
+            "notSupported",
+            "open_external_purchase_link is only available on iOS and macOS",
+        ))
+    }
+
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::display_pending_store_messages",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn display_pending_store_messages(&self) -> crate::Result<u32> {
+        Err(reject(
+            "notSupported",
+            "display_pending_store_messages is only available on iOS",
+        ))
+    }
+
+    /// [`Self::get_current_entitlements`] narrowed to `product_id`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::current_entitlement", skip(self), level = "debug", err)
+    )]
+    pub async fn current_entitlement(&self, product_id: String) -> crate::Result<Option<Purchase>> {
+        crate::validate_product_id(&product_id)?;
+
+        let purchase = self
+            .get_current_entitlements()
+            .await?
+            .purchases
+            .into_iter()
+            .find(|purchase| purchase.product_id == product_id);
+        purchase.log_success();
+        Ok(purchase)
+    }
+
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_app_receipt", skip(self), level = "debug", err)
+    )]
+    pub async fn get_app_receipt(&self, _refresh_if_missing: bool) -> crate::Result<String> {
+        Err(reject(
+            "notSupported",
+            "get_app_receipt is only available on iOS and macOS",
+        ))
+    }
+
+    /// Microsoft Store refunds only reach the app as a license that's gone.
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "iap::get_revoked_purchases", skip(self), level = "debug", err)
+    )]
+    pub async fn get_revoked_purchases(
+        &self,
+        _since: Option<Timestamp>,
+    ) -> crate::Result<Vec<Purchase>> {
+        Err(reject(
+            "notSupported",
+            "get_revoked_purchases is only available on iOS and macOS",
+        ))
+    }
+
+    #[allow(clippy::unused_async, clippy::unused_self)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "iap::finish_unfinished_transactions",
+            skip(self),
+            level = "debug",
+            err
+        )
+    )]
+    pub async fn finish_unfinished_transactions(&self) -> crate::Result<u32> {
+        Err(reject(
+            "notSupported",
+            "finish_unfinished_transactions is only available on iOS and macOS",
+        ))
     }
 }
 